@@ -0,0 +1,262 @@
+use crate::context::Context;
+use crate::segment::Segment;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// The directory rendered modules are cached in
+pub fn modules_dir() -> PathBuf {
+    utils::cache_dir().join("modules")
+}
+
+/// Removes every cached module
+pub fn clear() {
+    let dir = modules_dir();
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => println!("Cleared the module cache in {:?}", dir),
+        Err(error) if error.kind() == ErrorKind::NotFound => {}
+        Err(error) => {
+            eprintln!("Unable to clear the module cache in {:?}: {}", dir, error);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// Seconds since the Unix epoch at which the module was rendered
+    created: u64,
+    /// The rendered segments, or `None` if the module wasn't shown
+    segments: Option<Vec<Segment>>,
+}
+
+/// The cached rendering of a module in the current directory
+///
+/// Modules are only cached if they have a `cache_ttl`. The entry is specific
+/// to the directory, `PATH` and the module's config, and is invalidated when
+/// any file in the directory is added, removed or modified.
+pub struct ModuleCache {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl ModuleCache {
+    /// The cache entry of `module`, or `None` if it isn't cached
+    pub fn new(context: &Context, module: &str) -> Option<Self> {
+        let dir = context.cache_dir.as_ref()?;
+        let ttl = match context.root_config.cache_ttl.get(module) {
+            None | Some(0) => return None,
+            Some(ttl) => Duration::from_secs(*ttl),
+        };
+
+        let mut hasher = DefaultHasher::new();
+        context.current_dir.hash(&mut hasher);
+        context.get_env("PATH").hash(&mut hasher);
+        context
+            .config
+            .get_module_config(module)
+            .map(toml::Value::to_string)
+            .hash(&mut hasher);
+        fingerprint(&context.current_dir, &mut hasher);
+
+        Some(Self {
+            path: dir.join(format!("{}-{:016x}.json", module, hasher.finish())),
+            ttl,
+        })
+    }
+
+    /// The cached segments, if they were rendered less than `cache_ttl` ago
+    ///
+    /// The inner `None` means the module was cached as not shown.
+    pub fn load(&self) -> Option<Option<Vec<Segment>>> {
+        let entry: CacheEntry = serde_json::from_str(&fs::read_to_string(&self.path).ok()?)
+            .map_err(|error| log::debug!("Invalid cache entry {:?}: {}", self.path, error))
+            .ok()?;
+        let age = now().checked_sub(Duration::from_secs(entry.created))?;
+        if age >= self.ttl {
+            log::trace!("Cache entry {:?} expired", self.path);
+            return None;
+        }
+        Some(entry.segments)
+    }
+
+    /// Caches the rendered segments, or that the module isn't shown
+    pub fn store(&self, segments: Option<&[Segment]>) {
+        let entry = CacheEntry {
+            created: now().as_secs(),
+            segments: segments.map(<[Segment]>::to_vec),
+        };
+        // Write to a temporary file first, so no other prompt reads a partial entry
+        let temp_path = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        let result = self
+            .path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&temp_path, serde_json::to_string(&entry)?))
+            .and_then(|_| fs::rename(&temp_path, &self.path));
+        if let Err(error) = result {
+            log::debug!("Unable to write cache entry {:?}: {}", self.path, error);
+        }
+    }
+}
+
+/// Hashes the modification times of `dir` and the entries in it
+fn fingerprint(dir: &Path, hasher: &mut DefaultHasher) {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    modified(dir).hash(hasher);
+
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect();
+    entries.sort();
+    for entry in entries {
+        entry.hash(hasher);
+        modified(&entry).hash(hasher);
+    }
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::{default_context, ModuleRenderer};
+    use std::fs::File;
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Renders the nodejs module and returns its output with the number of
+    /// commands it ran
+    fn render(dir: &Path, cache_dir: Option<&Path>, ttl: i64) -> (Option<String>, usize) {
+        let cmd_calls = AtomicUsize::new(0);
+        let mut renderer = ModuleRenderer::new("nodejs")
+            .path(dir)
+            .count_cmds(&cmd_calls)
+            .config(toml::toml! {
+                [cache_ttl]
+                nodejs = ttl
+            });
+        if let Some(cache_dir) = cache_dir {
+            renderer = renderer.cache_dir(cache_dir);
+        }
+        let actual = renderer.collect();
+        (actual, cmd_calls.load(Ordering::Relaxed))
+    }
+
+    #[test]
+    fn second_render_within_ttl_runs_no_commands() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+
+        let (first, first_calls) = render(dir.path(), Some(cache_dir.path()), 60);
+        let (second, second_calls) = render(dir.path(), Some(cache_dir.path()), 60);
+
+        assert!(first.is_some());
+        assert!(first_calls > 0);
+        assert_eq!(first, second);
+        assert_eq!(0, second_calls);
+        dir.close()?;
+        cache_dir.close()
+    }
+
+    #[test]
+    fn modules_that_are_not_shown_stay_hidden() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+
+        let (first, _) = render(dir.path(), Some(cache_dir.path()), 60);
+        let (second, _) = render(dir.path(), Some(cache_dir.path()), 60);
+        assert_eq!(None, first);
+        assert_eq!(None, second);
+
+        // The cached entry doesn't turn into an empty module either
+        let mut context = default_context();
+        context.current_dir = dir.path().to_path_buf();
+        context.cache_dir = Some(cache_dir.path().to_path_buf());
+        context
+            .root_config
+            .cache_ttl
+            .insert("nodejs".to_string(), 60);
+        let cached = ModuleCache::new(&context, "nodejs").unwrap().load();
+        assert!(matches!(cached, Some(None)));
+        assert!(crate::modules::handle("nodejs", &context).is_none());
+        dir.close()?;
+        cache_dir.close()
+    }
+
+    #[test]
+    fn cache_is_opt_in() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+
+        // Without a cache directory, as in all other tests
+        render(dir.path(), None, 60);
+        assert!(render(dir.path(), None, 60).1 > 0);
+
+        // Without a ttl for the module
+        render(dir.path(), Some(cache_dir.path()), 0);
+        assert!(render(dir.path(), Some(cache_dir.path()), 0).1 > 0);
+        assert_eq!(0, fs::read_dir(cache_dir.path())?.count());
+        dir.close()?;
+        cache_dir.close()
+    }
+
+    #[test]
+    fn directory_changes_invalidate_the_cache() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+        File::create(dir.path().join("package.json"))?.sync_all()?;
+
+        render(dir.path(), Some(cache_dir.path()), 60);
+        File::create(dir.path().join("index.js"))?.sync_all()?;
+        let (actual, calls) = render(dir.path(), Some(cache_dir.path()), 60);
+
+        assert!(actual.is_some());
+        assert!(calls > 0);
+        dir.close()?;
+        cache_dir.close()
+    }
+
+    #[test]
+    fn expired_entries_are_ignored() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cache_dir = tempfile::tempdir()?;
+        let mut context = default_context();
+        context.current_dir = dir.path().to_path_buf();
+        context.cache_dir = Some(cache_dir.path().to_path_buf());
+        context
+            .root_config
+            .cache_ttl
+            .insert("nodejs".to_string(), 60);
+
+        let cache = ModuleCache::new(&context, "nodejs").unwrap();
+        cache.store(Some(&Segment::from_text(None, "cached")));
+        assert_eq!(
+            1,
+            cache.load().flatten().map_or(0, |segments| segments.len())
+        );
+
+        let entry = fs::read_to_string(&cache.path)?;
+        let created = format!("\"created\":{}", now().as_secs());
+        fs::write(&cache.path, entry.replace(&created, "\"created\":0"))?;
+        assert!(cache.load().is_none());
+        dir.close()?;
+        cache_dir.close()
+    }
+}
@@ -11,6 +11,7 @@ use std::borrow::Cow;
 use std::clone::Clone;
 use std::collections::HashMap;
 use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
 use std::env;
 use toml::Value;
@@ -52,7 +53,7 @@ impl<'a, T: Deserialize<'a> + Default> ModuleConfig<'a, ValueError> for T {
     }
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "config-schema",
     derive(schemars::JsonSchema),
@@ -124,16 +125,19 @@ pub fn get_config_path() -> Option<String> {
 }
 
 impl StarshipConfig {
-    /// Initialize the Config struct
+    /// Initialize the Config struct, applying the profile in `STARSHIP_PROFILE`
     pub fn initialize() -> Self {
-        if let Some(file_data) = Self::config_from_file() {
-            Self {
-                config: Some(file_data),
-            }
-        } else {
-            Self {
-                config: Some(Value::Table(toml::value::Table::new())),
-            }
+        Self::initialize_with_profile(env::var("STARSHIP_PROFILE").ok().as_deref())
+    }
+
+    /// Initialize the Config struct with the given profile applied
+    pub fn initialize_with_profile(profile: Option<&str>) -> Self {
+        let mut config =
+            Self::config_from_file().unwrap_or_else(|| Value::Table(toml::value::Table::new()));
+        apply_profile(&mut config, profile);
+        interpolate_env(&mut config, &|name| env::var(name).ok());
+        Self {
+            config: Some(config),
         }
     }
 
@@ -158,13 +162,21 @@ impl StarshipConfig {
             }
         }?;
 
-        match toml::from_str(&toml_content) {
+        let parsed = match toml::from_str(&toml_content) {
             Ok(parsed) => {
                 log::debug!("Config parsed: {:?}", &parsed);
-                Some(parsed)
+                parsed
             }
             Err(error) => {
                 log::error!("Unable to parse the config file: {}", error);
+                return None;
+            }
+        };
+
+        match resolve_includes(parsed, Path::new(&file_path), &mut Vec::new()) {
+            Ok(config) => Some(config),
+            Err(error) => {
+                log::error!("Unable to load the config file: {}", error);
                 None
             }
         }
@@ -437,10 +449,182 @@ fn get_palette<'a>(
     }
 }
 
+/// Merges the files listed in the `include` array of a config into it
+///
+/// Later includes override earlier ones and `config` overrides all of them.
+/// Relative paths are resolved against `path`, the file `config` was read
+/// from, and `chain` holds the files that (transitively) included it.
+pub fn resolve_includes(
+    mut config: Value,
+    path: &Path,
+    chain: &mut Vec<PathBuf>,
+) -> Result<Value, String> {
+    let includes = match config
+        .as_table_mut()
+        .and_then(|table| table.remove("include"))
+    {
+        Some(Value::Array(includes)) => includes,
+        Some(include) => {
+            log::warn!("Invalid include {}, expected an array of paths", include);
+            return Ok(config);
+        }
+        None => return Ok(config),
+    };
+
+    let path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    chain.push(path);
+
+    let mut merged = Value::Table(toml::value::Table::new());
+    for include in includes {
+        let include = match include.as_str() {
+            Some(include) => dir.join(Context::expand_tilde(PathBuf::from(include))),
+            None => {
+                log::warn!("Invalid include {}, expected a path", include);
+                continue;
+            }
+        };
+        let include = match dunce::canonicalize(&include) {
+            Ok(include) => include,
+            Err(error) => {
+                log::warn!(
+                    "Unable to read included config file {:?}: {}",
+                    include,
+                    error
+                );
+                continue;
+            }
+        };
+
+        if chain.contains(&include) {
+            let cycle: Vec<String> = chain
+                .iter()
+                .chain(std::iter::once(&include))
+                .map(|path| format!("{:?}", path))
+                .collect();
+            return Err(format!("Include cycle {}", cycle.join(" -> ")));
+        }
+
+        let content = utils::read_file(&include).map_err(|error| {
+            format!(
+                "Unable to read included config file {:?}: {}",
+                include, error
+            )
+        })?;
+        let parsed = toml::from_str(&content).map_err(|error| {
+            format!(
+                "Unable to parse included config file {:?}: {}",
+                include, error
+            )
+        })?;
+        merge_toml(&mut merged, resolve_includes(parsed, &include, chain)?);
+    }
+
+    chain.pop();
+    merge_toml(&mut merged, config);
+    Ok(merged)
+}
+
+/// Merges the `[profiles.<name>]` table of a config into its root
+///
+/// The `profiles` table is removed from the config either way. Unknown
+/// profiles are ignored with a warning.
+pub fn apply_profile(config: &mut Value, profile: Option<&str>) {
+    let profiles = config
+        .as_table_mut()
+        .and_then(|table| table.remove("profiles"));
+    let name = match profile {
+        Some(name) if !name.is_empty() => name,
+        _ => return,
+    };
+
+    match profiles.and_then(|mut profiles| profiles.as_table_mut()?.remove(name)) {
+        Some(overrides) => {
+            log::debug!("Applying profile \"{}\"", name);
+            merge_toml(config, overrides);
+        }
+        None => log::warn!("Unknown profile \"{}\", using the base config", name),
+    }
+}
+
+/// Substitutes `${env:VAR}` and `${env:VAR:-default}` in the string values of a config
+///
+/// Unset variables expand to the default, or to an empty string with a
+/// warning. `$${env:VAR}` is left as the literal `${env:VAR}`.
+pub fn interpolate_env(config: &mut Value, get_env: &dyn Fn(&str) -> Option<String>) {
+    match config {
+        Value::String(value) if value.contains("${env:") => {
+            *value = interpolate_str(value, get_env);
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| interpolate_env(value, get_env)),
+        Value::Table(table) => table
+            .iter_mut()
+            .for_each(|(_, value)| interpolate_env(value, get_env)),
+        _ => {}
+    }
+}
+
+fn interpolate_str(value: &str, get_env: &dyn Fn(&str) -> Option<String>) -> String {
+    const START: &str = "${env:";
+
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(START) {
+        let (before, after) = rest.split_at(start);
+        let after = &after[START.len()..];
+        if let Some(escaped) = before.strip_suffix('$') {
+            result.push_str(escaped);
+            result.push_str(START);
+            rest = after;
+            continue;
+        }
+        let end = match after.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+
+        result.push_str(before);
+        let (name, default) = match after[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&after[..end], None),
+        };
+        match (get_env(name).filter(|value| !value.is_empty()), default) {
+            (Some(value), _) => result.push_str(&value),
+            (None, Some(default)) => result.push_str(default),
+            (None, None) => log::warn!("Environment variable {} in the config is not set", name),
+        }
+        rest = &after[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Deep merges `overrides` into `base`
+///
+/// Tables are merged key by key, any other value is replaced.
+pub fn merge_toml(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Table(base), Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use nu_ansi_term::Style;
+    use std::fs;
 
     // Small wrapper to allow deserializing Style without a struct with #[serde(deserialize_with=)]
     #[derive(Default, Clone, Debug, PartialEq)]
@@ -895,4 +1079,248 @@ mod tests {
         // Test default behavior
         assert!(get_palette(&palettes, None).is_none());
     }
+
+    #[test]
+    fn test_merge_toml_merges_nested_tables() {
+        let mut base = toml::toml! {
+            format = "$all"
+            [directory]
+            style = "blue"
+            truncation_length = 3
+        };
+        merge_toml(
+            &mut base,
+            toml::toml! {
+                [directory]
+                style = "red"
+                [character]
+                success_symbol = ">"
+            },
+        );
+
+        assert_eq!(
+            base,
+            toml::toml! {
+                format = "$all"
+                [directory]
+                style = "red"
+                truncation_length = 3
+                [character]
+                success_symbol = ">"
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_toml_replaces_other_values() {
+        let mut base = toml::toml! {
+            format = "$all"
+            list = ["a", "b"]
+            [directory]
+            style = "blue"
+        };
+        merge_toml(
+            &mut base,
+            toml::toml! {
+                list = ["c"]
+                directory = "none"
+            },
+        );
+
+        assert_eq!(
+            base,
+            toml::toml! {
+                format = "$all"
+                list = ["c"]
+                directory = "none"
+            }
+        );
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let config = toml::toml! {
+            add_newline = false
+            [username]
+            style_user = "yellow"
+            show_always = true
+            [profiles.demo]
+            add_newline = true
+            [profiles.demo.username]
+            style_user = "red"
+        };
+
+        let mut demo = config.clone();
+        apply_profile(&mut demo, Some("demo"));
+        assert_eq!(
+            demo,
+            toml::toml! {
+                add_newline = true
+                [username]
+                style_user = "red"
+                show_always = true
+            }
+        );
+
+        let mut base = config.clone();
+        apply_profile(&mut base, None);
+        let mut unknown = config;
+        apply_profile(&mut unknown, Some("unknown"));
+        let expected = toml::toml! {
+            add_newline = false
+            [username]
+            style_user = "yellow"
+            show_always = true
+        };
+        assert_eq!(base, expected);
+        assert_eq!(unknown, expected);
+    }
+
+    #[test]
+    fn test_profile_from_env() {
+        let config = toml::toml! {
+            [character]
+            success_symbol = "[>](green)"
+            [profiles.demo.character]
+            success_symbol = "[❯](red)"
+        };
+
+        let base = crate::test::ModuleRenderer::new("character")
+            .config(config.clone())
+            .collect();
+        let demo = crate::test::ModuleRenderer::new("character")
+            .config(config)
+            .env("STARSHIP_PROFILE", "demo")
+            .collect();
+
+        assert_eq!(base, Some(format!("{} ", Color::Green.paint(">"))));
+        assert_eq!(demo, Some(format!("{} ", Color::Red.paint("❯"))));
+    }
+
+    fn load_with_includes(path: &Path) -> Result<Value, String> {
+        let config = toml::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        resolve_includes(config, path, &mut Vec::new())
+    }
+
+    #[test]
+    fn test_nested_includes() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("sub"))?;
+        fs::write(
+            dir.path().join("starship.toml"),
+            "include = ['a.toml', 'sub/b.toml']\nformat = 'main'\n",
+        )?;
+        fs::write(
+            dir.path().join("a.toml"),
+            "format = 'a'\nadd_newline = false\n[directory]\nstyle = 'a'\ntruncation_length = 1\n",
+        )?;
+        fs::write(
+            dir.path().join("sub/b.toml"),
+            "include = ['c.toml']\n[directory]\nstyle = 'b'\n",
+        )?;
+        fs::write(
+            dir.path().join("sub/c.toml"),
+            "scan_timeout = 5\n[directory]\nstyle = 'c'\ntruncation_length = 2\n",
+        )?;
+
+        let config = load_with_includes(&dir.path().join("starship.toml"));
+        assert_eq!(
+            config,
+            Ok(toml::toml! {
+                format = "main"
+                add_newline = false
+                scan_timeout = 5
+                [directory]
+                style = "b"
+                truncation_length = 2
+            })
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn test_missing_include_is_skipped() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("starship.toml"),
+            "include = ['missing.toml', 'a.toml']\n",
+        )?;
+        fs::write(dir.path().join("a.toml"), "add_newline = false\n")?;
+
+        let config = load_with_includes(&dir.path().join("starship.toml"));
+        assert_eq!(config, Ok(toml::toml! { add_newline = false }));
+        dir.close()
+    }
+
+    #[test]
+    fn test_include_cycle() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("starship.toml"), "include = ['a.toml']\n")?;
+        fs::write(dir.path().join("a.toml"), "include = ['b.toml']\n")?;
+        fs::write(dir.path().join("b.toml"), "include = ['a.toml']\n")?;
+
+        let error = load_with_includes(&dir.path().join("starship.toml")).unwrap_err();
+        let dir_path = dunce::canonicalize(dir.path())?;
+        let chain: Vec<String> = ["starship.toml", "a.toml", "b.toml", "a.toml"]
+            .iter()
+            .map(|file| format!("{:?}", dir_path.join(file)))
+            .collect();
+        assert_eq!(error, format!("Include cycle {}", chain.join(" -> ")));
+        dir.close()
+    }
+
+    #[test]
+    fn test_interpolate_env() {
+        let get_env = |name: &str| match name {
+            "PROMPT_PREFIX" => Some("> ".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        let mut config = toml::toml! {
+            format = "${env:PROMPT_PREFIX}$all"
+            scan_timeout = 10
+            [custom.tool]
+            command = "${env:TOOL_DIR:-/opt/tool}/bin/tool"
+            when = ["${env:EMPTY:-fallback}", "${env:MISSING}done"]
+        };
+        interpolate_env(&mut config, &get_env);
+
+        assert_eq!(
+            config,
+            toml::toml! {
+                format = "> $all"
+                scan_timeout = 10
+                [custom.tool]
+                command = "/opt/tool/bin/tool"
+                when = ["fallback", "done"]
+            }
+        );
+    }
+
+    #[test]
+    fn test_interpolate_env_escaping() {
+        let get_env = |_: &str| Some("value".to_string());
+
+        assert_eq!(
+            interpolate_str("$${env:HOME} ${env:HOME}", &get_env),
+            "${env:HOME} value"
+        );
+        assert_eq!(
+            interpolate_str("$$ $all ${env:HOME", &get_env),
+            "$$ $all ${env:HOME"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_env_from_context() {
+        let actual = crate::test::ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character]
+                success_symbol = "[${env:SYMBOL}](green)"
+            })
+            .env("SYMBOL", "λ")
+            .collect();
+
+        assert_eq!(actual, Some(format!("{} ", Color::Green.paint("λ"))));
+    }
 }
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct AgentsConfig<'a> {
+    pub format: &'a str,
+    pub style: &'a str,
+    pub ssh_loaded_symbol: &'a str,
+    pub ssh_empty_symbol: &'a str,
+    pub ssh_no_agent_symbol: &'a str,
+    pub gpg_keygrip: &'a str,
+    pub gpg_cached_symbol: &'a str,
+    pub gpg_uncached_symbol: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> Default for AgentsConfig<'a> {
+    fn default() -> Self {
+        AgentsConfig {
+            format: "[$ssh_symbol($ssh_keys )$gpg_symbol]($style)",
+            style: "bold green",
+            ssh_loaded_symbol: "🔑 ",
+            ssh_empty_symbol: "🔒 ",
+            ssh_no_agent_symbol: "",
+            gpg_keygrip: "",
+            gpg_cached_symbol: "🔏 ",
+            gpg_uncached_symbol: "",
+            disabled: true,
+        }
+    }
+}
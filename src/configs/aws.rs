@@ -13,7 +13,7 @@ use std::collections::HashMap;
 /// The `aws` module shows the current AWS region and profile and an expiration timer when using temporary credentials.
 /// The output of the module uses the `AWS_REGION`, `AWS_DEFAULT_REGION`, and `AWS_PROFILE` env vars and the `~/.aws/config` and `~/.aws/credentials` files as required.
 ///
-/// The module will display a profile only if its credentials are present in `~/.aws/credentials` or if a `credential_process` or `sso_start_url` are defined in `~/.aws/config`. Alternatively, having any of the `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, or `AWS_SESSION_TOKEN` env vars defined will also suffice.
+/// The module will display a profile only if its credentials are present in `~/.aws/credentials` or if a `credential_process`, `sso_start_url`, `sso_session` or `role_arn` are defined in `~/.aws/config`. Alternatively, having any of the `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, or `AWS_SESSION_TOKEN` env vars defined will also suffice.
 /// If the option `force_display` is set to `true`, all available information will be displayed even if no credentials per the conditions above are detected.
 ///
 /// When using [aws-vault](https://github.com/99designs/aws-vault) the profile
@@ -26,6 +26,10 @@ use std::collections::HashMap;
 /// When using [AWSume](https://awsu.me) the profile
 /// is read from the `AWSUME_PROFILE` env var and the credentials expiration
 /// date is read from the `AWSUME_EXPIRATION` env var.
+///
+/// Otherwise the expiration is read from the `expiration` of the profile in
+/// `~/.aws/credentials`, or from the credentials of assumed roles and the SSO
+/// tokens the AWS CLI cached in `~/.aws/cli/cache` and `~/.aws/sso/cache`.
 pub struct AwsConfig<'a> {
     /// The format for the module.
     pub format: &'a str,
@@ -41,6 +45,8 @@ pub struct AwsConfig<'a> {
     pub profile_aliases: HashMap<String, &'a str>,
     /// The symbol displayed when the temporary credentials have expired.
     pub expiration_symbol: &'a str,
+    /// Pairs of a number of seconds and the style used when the credentials expire within them.
+    pub expiration_styles: Vec<(i64, &'a str)>,
     /// If true displays info even if `credentials`, `credential_process` or `sso_start_url` have not been setup.
     pub force_display: bool,
 }
@@ -55,6 +61,7 @@ impl<'a> Default for AwsConfig<'a> {
             region_aliases: HashMap::new(),
             profile_aliases: HashMap::new(),
             expiration_symbol: "X",
+            expiration_styles: vec![],
             force_display: false,
         }
     }
@@ -1,3 +1,4 @@
+use crate::config::VecOr;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -17,6 +18,10 @@ pub struct BatteryConfig<'a> {
     pub display: Vec<BatteryDisplayConfig<'a>>,
     pub disabled: bool,
     pub format: &'a str,
+    pub display_time: bool,
+    pub use_gradient: bool,
+    #[serde(borrow)]
+    pub gradient: VecOr<&'a str>,
 }
 
 impl<'a> Default for BatteryConfig<'a> {
@@ -30,6 +35,9 @@ impl<'a> Default for BatteryConfig<'a> {
             format: "[$symbol$percentage]($style) ",
             display: vec![BatteryDisplayConfig::default()],
             disabled: false,
+            display_time: true,
+            use_gradient: false,
+            gradient: VecOr(vec!["#ff0000", "#ffff00", "#00ff00"]),
         }
     }
 }
@@ -12,9 +12,23 @@ pub struct CmdDurationConfig<'a> {
     pub format: &'a str,
     pub style: &'a str,
     pub show_milliseconds: bool,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "format_style_schema")
+    )]
+    pub format_style: &'a str,
+    pub max_units: usize,
+    pub history_size: usize,
     pub disabled: bool,
     pub show_notifications: bool,
     pub min_time_to_notify: i64,
+    pub notify: bool,
+    pub notify_min_time: i64,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "notify_protocol_schema")
+    )]
+    pub notify_protocol: &'a str,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notification_timeout: Option<u32>,
@@ -26,11 +40,27 @@ impl<'a> Default for CmdDurationConfig<'a> {
             min_time: 2_000,
             format: "took [$duration]($style) ",
             show_milliseconds: false,
+            format_style: "compact",
+            max_units: 0,
+            history_size: 20,
             style: "yellow bold",
             disabled: false,
             show_notifications: false,
             min_time_to_notify: 45_000,
+            notify: false,
+            notify_min_time: 45_000,
+            notify_protocol: "auto",
             notification_timeout: None,
         }
     }
 }
+
+#[cfg(feature = "config-schema")]
+fn format_style_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    crate::configs::gradient::string_enum(&["compact", "precise", "digital"])
+}
+
+#[cfg(feature = "config-schema")]
+fn notify_protocol_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    crate::configs::gradient::string_enum(&["auto", "osc9", "osc777"])
+}
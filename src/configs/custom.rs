@@ -29,6 +29,9 @@ pub struct CustomConfig<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub use_stdin: Option<bool>,
     pub ignore_timeout: bool,
+    pub when_exit_codes: Vec<i64>,
+    pub ignore_stderr: bool,
+    pub output_format: &'a str,
 }
 
 impl<'a> Default for CustomConfig<'a> {
@@ -48,6 +51,9 @@ impl<'a> Default for CustomConfig<'a> {
             os: None,
             use_stdin: None,
             ignore_timeout: false,
+            when_exit_codes: vec![0],
+            ignore_stderr: true,
+            output_format: "text",
         }
     }
 }
@@ -12,7 +12,15 @@ use serde::{Deserialize, Serialize};
 pub struct DirectoryConfig<'a> {
     pub truncation_length: i64,
     pub truncate_to_repo: bool,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "truncation_style_schema")
+    )]
+    pub truncation_style: &'a str,
+    pub keep_first: usize,
+    pub keep_last: usize,
     pub substitutions: IndexMap<String, &'a str>,
+    pub regex_substitutions: IndexMap<String, &'a str>,
     pub fish_style_pwd_dir_length: i64,
     pub use_logical_path: bool,
     pub format: &'a str,
@@ -22,6 +30,11 @@ pub struct DirectoryConfig<'a> {
     pub disabled: bool,
     pub read_only: &'a str,
     pub read_only_style: &'a str,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "read_only_detection_schema")
+    )]
+    pub read_only_detection: &'a str,
     pub truncation_symbol: &'a str,
     pub home_symbol: &'a str,
     pub use_os_path_sep: bool,
@@ -32,9 +45,13 @@ impl<'a> Default for DirectoryConfig<'a> {
         DirectoryConfig {
             truncation_length: 3,
             truncate_to_repo: true,
+            truncation_style: "leading",
+            keep_first: 1,
+            keep_last: 3,
             fish_style_pwd_dir_length: 0,
             use_logical_path: true,
             substitutions: IndexMap::new(),
+            regex_substitutions: IndexMap::new(),
             format: "[$path]($style)[$read_only]($read_only_style) ",
             repo_root_format: "[$before_root_path]($style)[$repo_root]($repo_root_style)[$path]($style)[$read_only]($read_only_style) ",
             style: "cyan bold",
@@ -42,9 +59,20 @@ impl<'a> Default for DirectoryConfig<'a> {
             disabled: false,
             read_only: "🔒",
             read_only_style: "red",
+            read_only_detection: "mode",
             truncation_symbol: "",
             home_symbol: "~",
             use_os_path_sep: true,
         }
     }
 }
+
+#[cfg(feature = "config-schema")]
+fn read_only_detection_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    crate::configs::gradient::string_enum(&["mode", "access"])
+}
+
+#[cfg(feature = "config-schema")]
+fn truncation_style_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    crate::configs::gradient::string_enum(&["leading", "middle"])
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct DirenvConfig<'a> {
+    pub format: &'a str,
+    pub symbol: &'a str,
+    pub style: &'a str,
+    pub disabled: bool,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
+    pub allowed_symbol: &'a str,
+    pub not_allowed_symbol: &'a str,
+    pub denied_symbol: &'a str,
+    pub loaded_symbol: &'a str,
+    pub unloaded_symbol: &'a str,
+}
+
+impl<'a> Default for DirenvConfig<'a> {
+    fn default() -> Self {
+        DirenvConfig {
+            format: "[$symbol$loaded/$allowed]($style) ",
+            symbol: "direnv ",
+            style: "bold bright-yellow",
+            disabled: true,
+            detect_extensions: vec![],
+            detect_files: vec![".envrc", ".env"],
+            detect_folders: vec![],
+            allowed_symbol: "allowed",
+            not_allowed_symbol: "not allowed",
+            denied_symbol: "denied",
+            loaded_symbol: "loaded",
+            unloaded_symbol: "not loaded",
+        }
+    }
+}
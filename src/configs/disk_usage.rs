@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct DiskUsageConfig<'a> {
+    pub threshold: i64,
+    pub format: &'a str,
+    pub symbol: &'a str,
+    pub style: &'a str,
+    #[serde(borrow)]
+    pub display: Vec<DiskUsageDisplayConfig<'a>>,
+    pub excluded_fs_types: Vec<&'a str>,
+    pub disabled: bool,
+}
+
+impl<'a> Default for DiskUsageConfig<'a> {
+    fn default() -> Self {
+        DiskUsageConfig {
+            threshold: 85,
+            format: "[$symbol${used_pct}%( \\($free free\\))]($style) ",
+            symbol: "💾 ",
+            style: "bold yellow",
+            display: vec![DiskUsageDisplayConfig::default()],
+            excluded_fs_types: vec![
+                "nfs",
+                "nfs4",
+                "cifs",
+                "smbfs",
+                "smb3",
+                "sshfs",
+                "fuse.sshfs",
+                "afpfs",
+                "davfs",
+                "9p",
+                "remote",
+            ],
+            disabled: true,
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct DiskUsageDisplayConfig<'a> {
+    pub threshold: i64,
+    pub style: &'a str,
+}
+
+impl<'a> Default for DiskUsageDisplayConfig<'a> {
+    fn default() -> Self {
+        DiskUsageDisplayConfig {
+            threshold: 95,
+            style: "bold red",
+        }
+    }
+}
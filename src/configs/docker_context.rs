@@ -9,9 +9,11 @@ use serde::{Deserialize, Serialize};
 #[serde(default)]
 pub struct DockerContextConfig<'a> {
     pub symbol: &'a str,
+    pub podman_symbol: &'a str,
     pub style: &'a str,
     pub format: &'a str,
     pub only_with_files: bool,
+    pub detect_engines: Vec<&'a str>,
     pub disabled: bool,
     pub detect_extensions: Vec<&'a str>,
     pub detect_files: Vec<&'a str>,
@@ -22,9 +24,11 @@ impl<'a> Default for DockerContextConfig<'a> {
     fn default() -> Self {
         DockerContextConfig {
             symbol: "🐳 ",
+            podman_symbol: "🦭 ",
             style: "blue bold",
             format: "via [$symbol$context]($style) ",
             only_with_files: true,
+            detect_engines: vec!["docker"],
             disabled: false,
             detect_extensions: vec![],
             detect_files: vec!["docker-compose.yml", "docker-compose.yaml", "Dockerfile"],
@@ -14,6 +14,13 @@ pub struct EnvVarConfig<'a> {
     pub variable: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regex: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replace: Option<[&'a str; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    pub truncation_symbol: &'a str,
     pub format: &'a str,
     pub disabled: bool,
 }
@@ -25,6 +32,10 @@ impl<'a> Default for EnvVarConfig<'a> {
             style: "black bold dimmed",
             variable: None,
             default: None,
+            regex: None,
+            replace: None,
+            max_length: None,
+            truncation_symbol: "…",
             format: "with [$env_value]($style) ",
             disabled: false,
         }
@@ -1,16 +1,17 @@
 use serde::{Deserialize, Serialize};
 
+use super::gradient::GradientConfig;
+
 #[derive(Clone, Deserialize, Serialize)]
-#[cfg_attr(
-    feature = "config-schema",
-    derive(schemars::JsonSchema),
-    schemars(deny_unknown_fields)
-)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
 #[serde(default)]
 pub struct FillConfig<'a> {
     pub style: &'a str,
     pub symbol: &'a str,
     pub disabled: bool,
+    /// Only used if the `gradient` option is set
+    #[serde(flatten, borrow)]
+    pub gradient: GradientConfig<'a>,
 }
 
 impl<'a> Default for FillConfig<'a> {
@@ -19,6 +20,7 @@ impl<'a> Default for FillConfig<'a> {
             style: "bold black",
             symbol: ".",
             disabled: false,
+            gradient: GradientConfig::default(),
         }
     }
 }
@@ -16,6 +16,9 @@ pub struct GitBranchConfig<'a> {
     pub only_attached: bool,
     pub always_show_remote: bool,
     pub ignore_branches: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_to: Option<&'a str>,
+    pub hash_palette: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -30,6 +33,8 @@ impl<'a> Default for GitBranchConfig<'a> {
             only_attached: false,
             always_show_remote: false,
             ignore_branches: vec![],
+            compare_to: None,
+            hash_palette: vec![],
             disabled: false,
         }
     }
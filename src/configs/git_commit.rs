@@ -16,6 +16,10 @@ pub struct GitCommitConfig<'a> {
     pub tag_symbol: &'a str,
     pub tag_disabled: bool,
     pub tag_max_candidates: usize,
+    pub signed_symbol: &'a str,
+    pub unverified_symbol: &'a str,
+    pub unsigned_symbol: &'a str,
+    pub verify: bool,
 }
 
 impl<'a> Default for GitCommitConfig<'a> {
@@ -30,6 +34,10 @@ impl<'a> Default for GitCommitConfig<'a> {
             tag_symbol: " 🏷  ",
             tag_disabled: true,
             tag_max_candidates: 0,
+            signed_symbol: "🔏",
+            unverified_symbol: "❓",
+            unsigned_symbol: "",
+            verify: false,
         }
     }
 }
@@ -11,6 +11,8 @@ pub struct GitMetricsConfig<'a> {
     pub added_style: &'a str,
     pub deleted_style: &'a str,
     pub only_nonzero_diffs: bool,
+    pub breakdown: bool,
+    pub breakdown_limit: usize,
     pub format: &'a str,
     pub disabled: bool,
 }
@@ -21,6 +23,8 @@ impl<'a> Default for GitMetricsConfig<'a> {
             added_style: "bold green",
             deleted_style: "bold red",
             only_nonzero_diffs: true,
+            breakdown: false,
+            breakdown_limit: 3,
             format: "([+$added]($added_style) )([-$deleted]($deleted_style) )",
             disabled: true,
         }
@@ -16,6 +16,9 @@ pub struct GitStateConfig<'a> {
     pub am: &'a str,
     pub am_or_rebase: &'a str,
     pub style: &'a str,
+    pub progress_bar_width: usize,
+    pub progress_bar_filled: &'a str,
+    pub progress_bar_empty: &'a str,
     pub format: &'a str,
     pub disabled: bool,
 }
@@ -31,6 +34,9 @@ impl<'a> Default for GitStateConfig<'a> {
             am: "AM",
             am_or_rebase: "AM/REBASE",
             style: "bold yellow",
+            progress_bar_width: 8,
+            progress_bar_filled: "▰",
+            progress_bar_empty: "▱",
             format: "\\([$state( $progress_current/$progress_total)]($style)\\) ",
             disabled: false,
         }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
@@ -21,7 +22,12 @@ pub struct GitStatusConfig<'a> {
     pub modified: &'a str,
     pub staged: &'a str,
     pub untracked: &'a str,
+    pub count_limit: usize,
+    pub count_limits: HashMap<String, usize>,
+    pub count_overflow_suffix: &'a str,
     pub ignore_submodules: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_to: Option<&'a str>,
     pub disabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub windows_starship: Option<&'a str>,
@@ -43,7 +49,11 @@ impl<'a> Default for GitStatusConfig<'a> {
             modified: "!",
             staged: "+",
             untracked: "?",
+            count_limit: 0,
+            count_limits: HashMap::new(),
+            count_overflow_suffix: "+",
             ignore_submodules: false,
+            compare_to: None,
             disabled: false,
             windows_starship: None,
         }
@@ -12,6 +12,7 @@ pub struct GoConfig<'a> {
     pub version_format: &'a str,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub prefer_mod_version: bool,
     pub disabled: bool,
     pub detect_extensions: Vec<&'a str>,
     pub detect_files: Vec<&'a str>,
@@ -25,6 +26,7 @@ impl<'a> Default for GoConfig<'a> {
             version_format: "v${raw}",
             symbol: "🐹 ",
             style: "bold cyan",
+            prefer_mod_version: true,
             disabled: false,
             detect_extensions: vec!["go"],
             detect_files: vec![
@@ -1,5 +1,10 @@
+use crate::config::VecOr;
 use serde::{Deserialize, Serialize};
 
+/// Options shared by every module that renders its output with a gradient.
+///
+/// Gradient modules flatten this into their own config, so the keys live
+/// directly in the module's table (e.g. `[gradient_username]`).
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
     feature = "config-schema",
@@ -8,19 +13,119 @@ use serde::{Deserialize, Serialize};
 )]
 #[serde(default)]
 pub struct GradientConfig<'a> {
-    pub format: &'a str,
-    pub gradient: &'a str,
-    pub show_always: bool,
-    pub disabled: bool,
+    /// The gradient colors, or the name of a preset.
+    #[serde(borrow)]
+    pub gradient: VecOr<&'a str>,
+    /// Positions of the colors along the gradient, from 0 to 1.
+    pub domain: Vec<f64>,
+    /// The direction the gradient is laid out in.
+    #[cfg_attr(feature = "config-schema", schemars(schema_with = "direction_schema"))]
+    pub direction: &'a str,
+    /// Which part of the text gets colored.
+    #[cfg_attr(feature = "config-schema", schemars(schema_with = "target_schema"))]
+    pub target: &'a str,
+    /// The color depth to render with. `auto` detects it from the terminal.
+    #[cfg_attr(feature = "config-schema", schemars(schema_with = "color_mode_schema"))]
+    pub color_mode: &'a str,
+    /// How the starting point of the gradient shifts between prompts.
+    #[cfg_attr(feature = "config-schema", schemars(schema_with = "phase_schema"))]
+    pub phase: &'a str,
+    /// How colors are blended between the gradient stops.
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "interpolation_schema")
+    )]
+    pub interpolation: &'a str,
+    /// In the right prompt, lays the gradient out as if it spanned the whole
+    /// line, so that it ends at the right edge of the terminal.
+    pub right_align_gradient: bool,
 }
 
 impl<'a> Default for GradientConfig<'a> {
     fn default() -> Self {
         GradientConfig {
-            format: "$module",
-            gradient: "",
-            show_always: false,
+            gradient: VecOr(vec!["#C7D2FE", "#FECACA", "#FEF9C3"]),
+            domain: vec![],
+            direction: "forward",
+            target: "foreground",
+            color_mode: "auto",
+            phase: "static",
+            interpolation: "linear-rgb",
+            right_align_gradient: false,
+        }
+    }
+}
+
+/// The top-level `[gradient]` table
+///
+/// It configures the `gradient` module, which colors the modules referenced in
+/// its `format`. With `scope = "line"` a single gradient is laid over the whole
+/// prompt after all modules have been rendered.
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct GradientPromptConfig<'a> {
+    /// The modules to color with the gradient.
+    pub format: &'a str,
+    /// Whether the gradient covers the `format` modules or the whole line.
+    #[cfg_attr(feature = "config-schema", schemars(schema_with = "scope_schema"))]
+    pub scope: &'a str,
+    pub disabled: bool,
+    #[serde(flatten, borrow)]
+    pub gradient: GradientConfig<'a>,
+}
+
+impl<'a> Default for GradientPromptConfig<'a> {
+    fn default() -> Self {
+        GradientPromptConfig {
+            format: "",
+            scope: "module",
             disabled: false,
+            gradient: GradientConfig::default(),
         }
     }
 }
+
+#[cfg(feature = "config-schema")]
+pub(crate) fn string_enum(variants: &[&str]) -> schemars::schema::Schema {
+    schemars::schema::SchemaObject {
+        instance_type: Some(schemars::schema::InstanceType::String.into()),
+        enum_values: Some(variants.iter().map(|&variant| variant.into()).collect()),
+        ..Default::default()
+    }
+    .into()
+}
+
+#[cfg(feature = "config-schema")]
+fn direction_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    string_enum(&["forward", "reverse", "mirror"])
+}
+
+#[cfg(feature = "config-schema")]
+fn target_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    string_enum(&["foreground", "background", "both"])
+}
+
+#[cfg(feature = "config-schema")]
+fn color_mode_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    string_enum(&["auto", "truecolor", "ansi256", "none"])
+}
+
+#[cfg(feature = "config-schema")]
+fn phase_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    string_enum(&["static", "time", "random"])
+}
+
+#[cfg(feature = "config-schema")]
+fn interpolation_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    string_enum(&["linear-rgb", "oklab", "catmull-rom"])
+}
+
+#[cfg(feature = "config-schema")]
+fn scope_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    string_enum(&["module", "line"])
+}
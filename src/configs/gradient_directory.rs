@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+use super::directory::DirectoryConfig;
+use super::gradient::GradientConfig;
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct GradientDirectoryConfig<'a> {
+    #[serde(flatten, borrow)]
+    pub directory: DirectoryConfig<'a>,
+    #[serde(flatten, borrow)]
+    pub gradient: GradientConfig<'a>,
+}
@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use super::gradient::GradientConfig;
+use super::hostname::HostnameConfig;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct GradientHostnameConfig<'a> {
+    #[serde(flatten, borrow)]
+    pub hostname: HostnameConfig<'a>,
+    #[serde(flatten, borrow)]
+    pub gradient: GradientConfig<'a>,
+    pub seed_from_hostname: bool,
+}
+
+impl<'a> Default for GradientHostnameConfig<'a> {
+    fn default() -> Self {
+        GradientHostnameConfig {
+            hostname: HostnameConfig::default(),
+            gradient: GradientConfig::default(),
+            seed_from_hostname: true,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::config::VecOr;
+use serde::{Deserialize, Serialize};
+
+use super::gradient::GradientConfig;
+use super::username::UsernameConfig;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "config-schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct GradientUsernameConfig<'a> {
+    #[serde(flatten, borrow)]
+    pub username: UsernameConfig<'a>,
+    #[serde(flatten, borrow)]
+    pub gradient: GradientConfig<'a>,
+    pub gradient_variables: Vec<&'a str>,
+    #[serde(borrow)]
+    pub gradient_root: VecOr<&'a str>,
+    #[serde(borrow)]
+    pub gradient_user: VecOr<&'a str>,
+    #[serde(borrow)]
+    pub gradient_success: VecOr<&'a str>,
+    #[serde(borrow)]
+    pub gradient_error: VecOr<&'a str>,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "palette_seed_schema")
+    )]
+    pub palette_seed: &'a str,
+    pub min_lightness: f64,
+    pub max_lightness: f64,
+}
+
+impl<'a> Default for GradientUsernameConfig<'a> {
+    fn default() -> Self {
+        GradientUsernameConfig {
+            username: UsernameConfig::default(),
+            gradient: GradientConfig::default(),
+            gradient_variables: vec![],
+            gradient_root: VecOr::default(),
+            gradient_user: VecOr::default(),
+            gradient_success: VecOr::default(),
+            gradient_error: VecOr::default(),
+            palette_seed: "",
+            min_lightness: 0.55,
+            max_lightness: 0.75,
+        }
+    }
+}
+
+#[cfg(feature = "config-schema")]
+fn palette_seed_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    super::gradient::string_enum(&["", "username"])
+}
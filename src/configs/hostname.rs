@@ -13,6 +13,13 @@ pub struct HostnameConfig<'a> {
     pub trim_at: &'a str,
     pub format: &'a str,
     pub style: &'a str,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "super::username::ssh_detection_schema")
+    )]
+    pub ssh_detection: &'a str,
+    pub color_by_hash: bool,
+    pub hash_palette: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -24,6 +31,9 @@ impl<'a> Default for HostnameConfig<'a> {
             trim_at: ".",
             format: "[$ssh_symbol$hostname]($style) in ",
             style: "green dimmed bold",
+            ssh_detection: "env",
+            color_by_hash: false,
+            hash_palette: vec![],
             disabled: false,
         }
     }
@@ -14,6 +14,8 @@ pub struct JobsConfig<'a> {
     pub format: &'a str,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub names_separator: &'a str,
+    pub max_names: usize,
     pub disabled: bool,
 }
 
@@ -26,6 +28,8 @@ impl<'a> Default for JobsConfig<'a> {
             format: "[$symbol$number]($style) ",
             symbol: "✦",
             style: "bold blue",
+            names_separator: ", ",
+            max_names: 3,
             disabled: false,
         }
     }
@@ -12,6 +12,9 @@ pub struct KubernetesConfig<'a> {
     pub symbol: &'a str,
     pub format: &'a str,
     pub style: &'a str,
+    pub dangerous_contexts: Vec<&'a str>,
+    pub style_dangerous: &'a str,
+    pub danger_symbol: &'a str,
     pub disabled: bool,
     pub context_aliases: HashMap<String, &'a str>,
     pub user_aliases: HashMap<String, &'a str>,
@@ -24,8 +27,11 @@ impl<'a> Default for KubernetesConfig<'a> {
     fn default() -> Self {
         KubernetesConfig {
             symbol: "☸ ",
-            format: "[$symbol$context( \\($namespace\\))]($style) in ",
+            format: "[$danger_symbol$symbol$context( \\($namespace\\))]($style) in ",
             style: "cyan bold",
+            dangerous_contexts: vec![],
+            style_dangerous: "red bold",
+            danger_symbol: "⚠️ ",
             disabled: true,
             context_aliases: HashMap::new(),
             user_aliases: HashMap::new(),
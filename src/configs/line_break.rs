@@ -8,5 +8,7 @@ use serde::{Deserialize, Serialize};
 )]
 #[serde(default)]
 pub struct LineBreakConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_width: Option<usize>,
     pub disabled: bool,
 }
@@ -1,6 +1,7 @@
 use indexmap::IndexMap;
 use serde::{self, Deserialize, Serialize};
 
+pub mod agents;
 pub mod aws;
 pub mod azure;
 pub mod battery;
@@ -19,6 +20,8 @@ pub mod daml;
 pub mod dart;
 pub mod deno;
 pub mod directory;
+pub mod direnv;
+pub mod disk_usage;
 pub mod docker_context;
 pub mod dotnet;
 pub mod elixir;
@@ -33,6 +36,10 @@ pub mod git_metrics;
 pub mod git_state;
 pub mod git_status;
 pub mod go;
+pub mod gradient;
+pub mod gradient_directory;
+pub mod gradient_hostname;
+pub mod gradient_username;
 pub mod haskell;
 pub mod helm;
 pub mod hg_branch;
@@ -46,6 +53,7 @@ pub mod line_break;
 pub mod localip;
 pub mod lua;
 pub mod memory_usage;
+pub mod network;
 pub mod nim;
 pub mod nix_shell;
 pub mod nodejs;
@@ -54,6 +62,7 @@ pub mod openstack;
 pub mod package;
 pub mod perl;
 pub mod php;
+pub mod proxy;
 pub mod pulumi;
 pub mod purescript;
 pub mod python;
@@ -63,6 +72,7 @@ pub mod rlang;
 pub mod ruby;
 pub mod rust;
 pub mod scala;
+pub mod secrets;
 pub mod shell;
 pub mod shlvl;
 pub mod singularity;
@@ -73,6 +83,7 @@ pub mod sudo;
 pub mod swift;
 pub mod terraform;
 pub mod time;
+pub mod todos;
 pub mod username;
 pub mod v;
 pub mod vagrant;
@@ -97,6 +108,8 @@ pub struct FullConfig<'a> {
     root: StarshipRootConfig,
     // modules
     #[serde(borrow)]
+    agents: agents::AgentsConfig<'a>,
+    #[serde(borrow)]
     aws: aws::AwsConfig<'a>,
     #[serde(borrow)]
     azure: azure::AzureConfig<'a>,
@@ -131,6 +144,10 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     directory: directory::DirectoryConfig<'a>,
     #[serde(borrow)]
+    direnv: direnv::DirenvConfig<'a>,
+    #[serde(borrow)]
+    disk_usage: disk_usage::DiskUsageConfig<'a>,
+    #[serde(borrow)]
     docker_context: docker_context::DockerContextConfig<'a>,
     #[serde(borrow)]
     dotnet: dotnet::DotnetConfig<'a>,
@@ -159,6 +176,14 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     golang: go::GoConfig<'a>,
     #[serde(borrow)]
+    gradient: gradient::GradientPromptConfig<'a>,
+    #[serde(borrow)]
+    gradient_directory: gradient_directory::GradientDirectoryConfig<'a>,
+    #[serde(borrow)]
+    gradient_hostname: gradient_hostname::GradientHostnameConfig<'a>,
+    #[serde(borrow)]
+    gradient_username: gradient_username::GradientUsernameConfig<'a>,
+    #[serde(borrow)]
     haskell: haskell::HaskellConfig<'a>,
     #[serde(borrow)]
     helm: helm::HelmConfig<'a>,
@@ -184,6 +209,8 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     memory_usage: memory_usage::MemoryConfig<'a>,
     #[serde(borrow)]
+    network: network::NetworkConfig<'a>,
+    #[serde(borrow)]
     nim: nim::NimConfig<'a>,
     #[serde(borrow)]
     nix_shell: nix_shell::NixShellConfig<'a>,
@@ -200,6 +227,8 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     php: php::PhpConfig<'a>,
     #[serde(borrow)]
+    proxy: proxy::ProxyConfig<'a>,
+    #[serde(borrow)]
     pulumi: pulumi::PulumiConfig<'a>,
     #[serde(borrow)]
     purescript: purescript::PureScriptConfig<'a>,
@@ -218,6 +247,8 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     scala: scala::ScalaConfig<'a>,
     #[serde(borrow)]
+    secrets: secrets::SecretsConfig<'a>,
+    #[serde(borrow)]
     shell: shell::ShellConfig<'a>,
     #[serde(borrow)]
     shlvl: shlvl::ShLvlConfig<'a>,
@@ -236,6 +267,8 @@ pub struct FullConfig<'a> {
     #[serde(borrow)]
     time: time::TimeConfig<'a>,
     #[serde(borrow)]
+    todos: todos::TodosConfig<'a>,
+    #[serde(borrow)]
     username: username::UsernameConfig<'a>,
     #[serde(borrow)]
     vagrant: vagrant::VagrantConfig<'a>,
@@ -259,8 +292,37 @@ mod test {
     fn test_all_modules_in_full_config() {
         let full_cfg = Value::try_from(FullConfig::default()).unwrap();
         let cfg_table = full_cfg.as_table().unwrap();
-        // for module in ALL_MODULES {
-        //     assert!(cfg_table.contains_key(*module));
-        // }
+        for module in ALL_MODULES {
+            assert!(cfg_table.contains_key(*module), "{} missing", module);
+        }
+    }
+
+    #[test]
+    fn test_gradient_defaults_in_full_config() {
+        let full_cfg = Value::try_from(FullConfig::default()).unwrap();
+
+        let gradient = full_cfg["gradient"].as_table().unwrap();
+        assert_eq!(gradient["scope"].as_str(), Some("module"));
+        assert_eq!(gradient["interpolation"].as_str(), Some("linear-rgb"));
+
+        for module in [
+            "gradient_directory",
+            "gradient_hostname",
+            "gradient_username",
+        ] {
+            let table = full_cfg[module].as_table().unwrap();
+            for key in [
+                "gradient",
+                "domain",
+                "direction",
+                "target",
+                "color_mode",
+                "phase",
+                "interpolation",
+            ] {
+                assert!(table.contains_key(key), "{}.{} missing", module, key);
+            }
+            assert!(table.contains_key("format"), "{}.format missing", module);
+        }
     }
 }
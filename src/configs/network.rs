@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct NetworkConfig<'a> {
+    pub format: &'a str,
+    pub symbol: &'a str,
+    pub style: &'a str,
+    pub detect_interfaces: Vec<&'a str>,
+    pub disabled: bool,
+}
+
+impl<'a> Default for NetworkConfig<'a> {
+    fn default() -> Self {
+        NetworkConfig {
+            format: "via [$symbol$interface]($style) ",
+            symbol: "🛡 ",
+            style: "bold green",
+            detect_interfaces: vec!["tun*", "wg*", "tailscale*"],
+            disabled: true,
+        }
+    }
+}
@@ -12,6 +12,7 @@ pub struct PackageConfig<'a> {
     pub symbol: &'a str,
     pub style: &'a str,
     pub display_private: bool,
+    pub allow_dynamic_versions: bool,
     pub disabled: bool,
     pub version_format: &'a str,
 }
@@ -23,6 +24,7 @@ impl<'a> Default for PackageConfig<'a> {
             symbol: "📦 ",
             style: "208 bold",
             display_private: false,
+            allow_dynamic_versions: false,
             disabled: false,
             version_format: "v${raw}",
         }
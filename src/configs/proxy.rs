@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct ProxyConfig<'a> {
+    pub format: &'a str,
+    pub symbol: &'a str,
+    pub style: &'a str,
+    pub env_vars: Vec<&'a str>,
+    pub no_proxy_domain: &'a str,
+    pub no_proxy_symbol: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> Default for ProxyConfig<'a> {
+    fn default() -> Self {
+        ProxyConfig {
+            format: "via [$symbol($host )$no_proxy_symbol]($style)",
+            symbol: "🔀 ",
+            style: "bold yellow",
+            env_vars: vec![
+                "HTTPS_PROXY",
+                "https_proxy",
+                "HTTP_PROXY",
+                "http_proxy",
+                "ALL_PROXY",
+                "all_proxy",
+            ],
+            no_proxy_domain: "",
+            no_proxy_symbol: "🚫 ",
+            disabled: true,
+        }
+    }
+}
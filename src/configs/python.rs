@@ -17,6 +17,8 @@ pub struct PythonConfig<'a> {
     pub version_format: &'a str,
     pub style: &'a str,
     pub symbol: &'a str,
+    pub detect_order: Vec<&'a str>,
+    pub ignore_conda_base: bool,
     pub disabled: bool,
     pub detect_extensions: Vec<&'a str>,
     pub detect_files: Vec<&'a str>,
@@ -33,6 +35,8 @@ impl<'a> Default for PythonConfig<'a> {
             version_format: "v${raw}",
             style: "yellow bold",
             symbol: "🐍 ",
+            detect_order: vec!["virtualenv"],
+            ignore_conda_base: true,
             disabled: false,
             detect_extensions: vec!["py"],
             detect_files: vec![
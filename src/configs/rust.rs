@@ -12,6 +12,7 @@ pub struct RustConfig<'a> {
     pub version_format: &'a str,
     pub symbol: &'a str,
     pub style: &'a str,
+    pub components_symbol: &'a str,
     pub disabled: bool,
     pub detect_extensions: Vec<&'a str>,
     pub detect_files: Vec<&'a str>,
@@ -25,6 +26,7 @@ impl<'a> Default for RustConfig<'a> {
             version_format: "v${raw}",
             symbol: "🦀 ",
             style: "bold red",
+            components_symbol: "🔧 ",
             disabled: false,
             detect_extensions: vec!["rs"],
             detect_files: vec!["Cargo.toml"],
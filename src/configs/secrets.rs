@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct SecretsConfig<'a> {
+    pub format: &'a str,
+    pub symbol: &'a str,
+    pub style: &'a str,
+    pub globs: Vec<&'a str>,
+    pub files_limit: usize,
+    pub truncation_symbol: &'a str,
+    pub disabled: bool,
+}
+
+impl<'a> Default for SecretsConfig<'a> {
+    fn default() -> Self {
+        SecretsConfig {
+            format: "[$symbol$count( \\($files\\))]($style) ",
+            symbol: "🚨 ",
+            style: "bold red",
+            globs: vec![".env", "*.pem", "id_rsa"],
+            files_limit: 3,
+            truncation_symbol: "…",
+            disabled: true,
+        }
+    }
+}
@@ -21,6 +21,9 @@ pub struct ShellConfig<'a> {
     pub cmd_indicator: &'a str,
     pub unknown_indicator: &'a str,
     pub style: &'a str,
+    pub show_level: bool,
+    pub level_offset: i64,
+    pub repeat_symbol: bool,
     pub disabled: bool,
 }
 
@@ -40,6 +43,9 @@ impl<'a> Default for ShellConfig<'a> {
             cmd_indicator: "cmd",
             unknown_indicator: "",
             style: "white bold",
+            show_level: false,
+            level_offset: 1,
+            repeat_symbol: false,
             disabled: true,
         }
     }
@@ -1,3 +1,4 @@
+use crate::config::Either;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -11,15 +12,38 @@ use std::collections::HashMap;
 pub struct StarshipRootConfig {
     #[serde(rename = "$schema")]
     schema: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<String>,
     pub format: String,
     pub right_format: String,
     pub continuation_prompt: String,
+    pub transient_format: String,
+    pub transient_right_format: String,
     pub scan_timeout: u64,
     pub command_timeout: u64,
+    pub parallelism: usize,
+    pub custom_parallelism: usize,
+    pub module_timeout_ms: u64,
+    pub module_timeouts: HashMap<String, u64>,
+    pub timeout_symbol: String,
+    pub cache_ttl: HashMap<String, u64>,
     pub add_newline: bool,
+    pub semantic_prompt: bool,
+    pub osc7: bool,
+    pub set_title: bool,
+    pub title_format: String,
+    pub title_format_ssh: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_prompt_width: Option<Either<usize, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub palette: Option<String>,
     pub palettes: HashMap<String, Palette>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(with = "HashMap<String, serde_json::Map<String, serde_json::Value>>")
+    )]
+    pub profiles: HashMap<String, toml::value::Table>,
 }
 
 pub type Palette = HashMap<String, String>;
@@ -41,6 +65,7 @@ pub const PROMPT_ORDER: &[&str] = &[
     "git_state",
     "git_metrics",
     "git_status",
+    "todos",
     "hg_branch",
     "docker_context",
     "package",
@@ -90,10 +115,16 @@ pub const PROMPT_ORDER: &[&str] = &[
     "conda",
     "spack",
     "memory_usage",
+    "disk_usage",
+    "agents",
     "aws",
     "gcloud",
     "openstack",
     "azure",
+    "direnv",
+    "proxy",
+    "network",
+    "secrets",
     "env_var",
     "crystal",
     "custom",
@@ -115,14 +146,30 @@ impl Default for StarshipRootConfig {
     fn default() -> Self {
         Self {
             schema: "https://starship.rs/config-schema.json".to_string(),
+            include: Vec::default(),
             format: "$all".to_string(),
             right_format: "".to_string(),
             continuation_prompt: "[∙](bright-black) ".to_string(),
+            transient_format: "$character".to_string(),
+            transient_right_format: "".to_string(),
             scan_timeout: 30,
             command_timeout: 500,
+            parallelism: 0,
+            custom_parallelism: 0,
+            module_timeout_ms: 0,
+            module_timeouts: HashMap::default(),
+            timeout_symbol: "".to_string(),
+            cache_ttl: HashMap::default(),
             add_newline: true,
+            semantic_prompt: false,
+            osc7: false,
+            set_title: false,
+            title_format: "$directory".to_string(),
+            title_format_ssh: "".to_string(),
+            max_prompt_width: None,
             palette: None,
             palettes: HashMap::default(),
+            profiles: HashMap::default(),
         }
     }
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Clone, Deserialize, Serialize)]
 #[cfg_attr(
@@ -17,6 +18,7 @@ pub struct StatusConfig<'a> {
     pub signal_symbol: &'a str,
     pub style: &'a str,
     pub map_symbol: bool,
+    pub symbol_map: HashMap<String, &'a str>,
     pub recognize_signal_code: bool,
     pub pipestatus: bool,
     pub pipestatus_separator: &'a str,
@@ -38,6 +40,7 @@ impl<'a> Default for StatusConfig<'a> {
             signal_symbol: "⚡",
             style: "bold red",
             map_symbol: false,
+            symbol_map: HashMap::new(),
             recognize_signal_code: true,
             pipestatus: false,
             pipestatus_separator: "|",
@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -16,6 +17,7 @@ pub struct TimeConfig<'a> {
     pub disabled: bool,
     pub utc_time_offset: &'a str,
     pub time_range: &'a str,
+    pub additional_timezones: IndexMap<String, &'a str>,
 }
 
 impl<'a> Default for TimeConfig<'a> {
@@ -28,6 +30,7 @@ impl<'a> Default for TimeConfig<'a> {
             disabled: true,
             utc_time_offset: "local",
             time_range: "-",
+            additional_timezones: IndexMap::new(),
         }
     }
 }
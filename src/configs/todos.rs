@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "config-schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(default)]
+pub struct TodosConfig<'a> {
+    pub format: &'a str,
+    pub todo_symbol: &'a str,
+    pub fixme_symbol: &'a str,
+    pub style: &'a str,
+    pub todo_patterns: Vec<&'a str>,
+    pub fixme_patterns: Vec<&'a str>,
+    pub max_files: usize,
+    pub disabled: bool,
+}
+
+impl<'a> Default for TodosConfig<'a> {
+    fn default() -> Self {
+        TodosConfig {
+            format: "[($todo_symbol$todo_count )($fixme_symbol$fixme_count )]($style)",
+            todo_symbol: "📝 ",
+            fixme_symbol: "🔥 ",
+            style: "bold purple",
+            todo_patterns: vec!["TODO"],
+            fixme_patterns: vec!["FIXME"],
+            max_files: 5000,
+            disabled: true,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -9,9 +10,26 @@ use serde::{Deserialize, Serialize};
 #[serde(default)]
 pub struct UsernameConfig<'a> {
     pub format: &'a str,
+    pub format_root: Option<&'a str>,
     pub style_root: &'a str,
     pub style_user: &'a str,
     pub show_always: bool,
+    pub truncation_length: i64,
+    pub truncation_symbol: &'a str,
+    pub aliases: IndexMap<String, &'a str>,
+    pub root_detection: Vec<&'a str>,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "show_elevated_as_schema")
+    )]
+    pub show_elevated_as: &'a str,
+    pub elevation_symbol: &'a str,
+    #[cfg_attr(
+        feature = "config-schema",
+        schemars(schema_with = "ssh_detection_schema")
+    )]
+    pub ssh_detection: &'a str,
+    pub detect_env_vars: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -19,10 +37,31 @@ impl<'a> Default for UsernameConfig<'a> {
     fn default() -> Self {
         UsernameConfig {
             format: "[$user]($style) in ",
+            format_root: None,
             style_root: "red bold",
             style_user: "yellow bold",
             show_always: false,
+            truncation_length: std::i64::MAX,
+            truncation_symbol: "…",
+            aliases: IndexMap::new(),
+            root_detection: vec!["euid"],
+            show_elevated_as: "administrator",
+            elevation_symbol: "⚡",
+            ssh_detection: "env",
+            detect_env_vars: vec![],
             disabled: false,
         }
     }
 }
+
+#[cfg(feature = "config-schema")]
+fn show_elevated_as_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    super::gradient::string_enum(&["administrator", "username", "both"])
+}
+
+#[cfg(feature = "config-schema")]
+pub(crate) fn ssh_detection_schema(
+    _: &mut schemars::gen::SchemaGenerator,
+) -> schemars::schema::Schema {
+    super::gradient::string_enum(&["env", "process-tree", "both"])
+}
@@ -7,12 +7,21 @@ use std::str::FromStr;
 
 use crate::config::ModuleConfig;
 use crate::config::StarshipConfig;
-use crate::configs::PROMPT_ORDER;
+use crate::configs::gradient::GradientPromptConfig;
+use crate::configs::gradient_directory::GradientDirectoryConfig;
+use crate::configs::gradient_hostname::GradientHostnameConfig;
+use crate::configs::gradient_username::GradientUsernameConfig;
+use crate::configs::{StarshipRootConfig, PROMPT_ORDER};
+use crate::formatter::{gradient, StringFormatter, VariableHolder};
+use crate::module::ALL_MODULES;
+use crate::modules::gradient::GRADIENT_MODULES;
+use crate::print::{unsupported_continuation_modules, CONTINUATION_MODULES};
 use crate::utils;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use toml::Value;
-use toml_edit::Document;
+use toml_edit::{Document, TableLike};
 
 #[cfg(not(windows))]
 const STD_EDITOR: &str = "vi";
@@ -22,6 +31,10 @@ const STD_EDITOR: &str = "notepad.exe";
 pub fn update_configuration(name: &str, value: &str) {
     let mut doc = get_configuration_edit();
 
+    if let Some(module) = unknown_module(name) {
+        log::warn!("Setting '{}' of the unknown module '{}'", name, module);
+    }
+
     match handle_update_configuration(&mut doc, name, value) {
         Err(e) => {
             eprintln!("{}", e);
@@ -31,6 +44,84 @@ pub fn update_configuration(name: &str, value: &str) {
     }
 }
 
+/// The first key of `name` if it indexes into a table that isn't a module or a
+/// table of the root config
+fn unknown_module(name: &str) -> Option<&str> {
+    let (module, _) = name.split_once('.')?;
+    if ALL_MODULES.contains(&module) || ["custom", "env_var", "profiles"].contains(&module) {
+        return None;
+    }
+
+    let root_config = Value::try_from(StarshipRootConfig::default()).ok()?;
+    match root_config.get(module) {
+        Some(value) if value.is_table() => None,
+        _ => Some(module),
+    }
+}
+
+pub fn print_configuration_value(name: &str) {
+    let doc = get_configuration_edit();
+
+    match handle_get_configuration(&doc, name) {
+        Ok(value) => println!("{}", value),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+fn handle_get_configuration(doc: &Document, name: &str) -> Result<String, String> {
+    let mut current_item = doc.as_item();
+
+    for key in name.split('.') {
+        if key.is_empty() {
+            return Err("Empty table keys are not supported".to_owned());
+        }
+
+        current_item = current_item
+            .as_table_like()
+            .and_then(|table| table.get(key))
+            .ok_or_else(|| format!("Given config key '{}' not found in config file", name))?;
+    }
+
+    Ok(match current_item.as_value() {
+        Some(value) => value.clone().decorated("", "").to_string(),
+        None => current_item.to_string().trim().to_owned(),
+    })
+}
+
+pub fn unset_configuration(name: &str) {
+    let mut doc = get_configuration_edit();
+
+    match handle_unset_configuration(&mut doc, name) {
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        _ => write_configuration(&doc),
+    }
+}
+
+fn handle_unset_configuration(doc: &mut Document, name: &str) -> Result<(), String> {
+    let keys: Vec<_> = name.split('.').collect();
+    if keys.iter().any(|key| key.is_empty()) {
+        return Err("Empty table keys are not supported".to_owned());
+    }
+    let not_found = || format!("Given config key '{}' not found in config file", name);
+
+    let (last_key, parents) = keys.split_last().unwrap();
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for key in parents {
+        table = table
+            .get_mut(key)
+            .and_then(toml_edit::Item::as_table_like_mut)
+            .ok_or_else(not_found)?;
+    }
+
+    table.remove(last_key).map(|_| ()).ok_or_else(not_found)
+}
+
 fn handle_update_configuration(doc: &mut Document, name: &str, value: &str) -> Result<(), String> {
     let mut keys = name.split('.');
 
@@ -72,7 +163,7 @@ fn handle_update_configuration(doc: &mut Document, name: &str, value: &str) -> R
     Ok(())
 }
 
-pub fn print_configuration(use_default: bool, paths: &[String]) {
+pub fn print_configuration(use_default: bool, profile: Option<&str>, paths: &[String]) {
     let config = if use_default {
         // Get default config
         let default_config = crate::configs::FullConfig::default();
@@ -80,7 +171,10 @@ pub fn print_configuration(use_default: bool, paths: &[String]) {
         toml::value::Value::try_from(default_config).unwrap()
     } else {
         // Get config as toml::Value
-        let user_config = get_configuration();
+        let user_config = match profile {
+            Some(profile) => get_configuration_with_profile(Some(profile)),
+            None => get_configuration(),
+        };
         // Convert into FullConfig and fill in default values
         let user_config = crate::configs::FullConfig::load(&user_config);
         // Convert back to Value because toml can't serialize FullConfig directly
@@ -222,6 +316,54 @@ fn handle_toggle_configuration(doc: &mut Document, name: &str, key: &str) -> Res
     Ok(())
 }
 
+/// Prints the problems found in the configuration, and exits with an error if
+/// there are any
+pub fn validate_configuration() {
+    let config = get_configuration();
+    let problems = find_configuration_problems(&config);
+
+    if problems.is_empty() {
+        println!("No problems found in the configuration");
+        return;
+    }
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    process::exit(1);
+}
+
+fn find_configuration_problems(config: &Value) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for module in GRADIENT_MODULES {
+        let table = match config.get(module) {
+            Some(table) => table,
+            None => continue,
+        };
+        let gradient_config = match *module {
+            "gradient" => GradientPromptConfig::load(table).gradient,
+            "gradient_directory" => GradientDirectoryConfig::load(table).gradient,
+            "gradient_hostname" => GradientHostnameConfig::load(table).gradient,
+            _ => GradientUsernameConfig::load(table).gradient,
+        };
+        if let Err(errors) = gradient::validate_gradient(&gradient_config) {
+            problems.extend(errors.iter().map(|error| format!("[{}] {}", module, error)));
+        }
+    }
+
+    let root_config = StarshipRootConfig::load(config);
+    if let Ok(formatter) = StringFormatter::new(&root_config.continuation_prompt) {
+        for module in unsupported_continuation_modules(&formatter.get_variables()) {
+            problems.push(format!(
+                "[continuation_prompt] The module `{}` is not supported, only {:?} are",
+                module, CONTINUATION_MODULES
+            ));
+        }
+    }
+
+    problems
+}
+
 pub fn get_configuration() -> Value {
     let starship_config = StarshipConfig::initialize();
 
@@ -230,6 +372,14 @@ pub fn get_configuration() -> Value {
         .expect("Failed to load starship config")
 }
 
+pub fn get_configuration_with_profile(profile: Option<&str>) -> Value {
+    let starship_config = StarshipConfig::initialize_with_profile(profile);
+
+    starship_config
+        .config
+        .expect("Failed to load starship config")
+}
+
 pub fn get_configuration_edit() -> Document {
     let file_path = get_config_path();
     let toml_content = match utils::read_file(&file_path) {
@@ -256,13 +406,21 @@ pub fn get_configuration_edit() -> Document {
 }
 
 pub fn write_configuration(doc: &Document) {
-    let config_path = get_config_path();
+    let config_path = PathBuf::from(get_config_path());
+    // Write through symlinks instead of replacing them
+    let config_path = dunce::canonicalize(&config_path).unwrap_or(config_path);
+
+    write_configuration_to(&config_path, doc).expect("Error writing starship config");
+}
 
-    let config_str = doc.to_string();
+/// Writes to a temporary file first, so the config is never partially written
+fn write_configuration_to(config_path: &Path, doc: &Document) -> std::io::Result<()> {
+    let mut temp_path = config_path.as_os_str().to_owned();
+    temp_path.push(format!(".{}.tmp", process::id()));
 
-    File::create(&config_path)
-        .and_then(|mut file| file.write_all(config_str.as_ref()))
-        .expect("Error writing starship config");
+    File::create(&temp_path)
+        .and_then(|mut file| file.write_all(doc.to_string().as_ref()))
+        .and_then(|_| fs::rename(&temp_path, config_path))
 }
 
 pub fn edit_configuration(editor_override: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
@@ -586,4 +744,165 @@ mod tests {
             .as_bool()
             .unwrap())
     }
+
+    const COMMENTED_CONFIG: &str = concat!(
+        "# Prompt settings\n",
+        "format = \"$all\" # the default\n",
+        "\n",
+        "[directory] # where am I\n",
+        "truncation_length = 3 # keep it short\n",
+        "style = 'bold cyan'\n",
+        "\n",
+        "# Languages\n",
+        "[nodejs]\n",
+        "disabled = false\n",
+    );
+
+    #[test]
+    fn test_update_config_changes_only_the_target_line() {
+        let mut doc = COMMENTED_CONFIG.parse::<Document>().unwrap();
+
+        handle_update_configuration(&mut doc, "directory.truncation_length", "5").unwrap();
+
+        let new_config = doc.to_string();
+        let changed_lines: Vec<_> = COMMENTED_CONFIG
+            .lines()
+            .zip(new_config.lines())
+            .filter(|(old, new)| old != new)
+            .collect();
+        assert_eq!(COMMENTED_CONFIG.lines().count(), new_config.lines().count());
+        assert_eq!(
+            changed_lines,
+            vec![(
+                "truncation_length = 3 # keep it short",
+                "truncation_length = 5 # keep it short"
+            )]
+        );
+    }
+
+    #[test]
+    fn test_update_config_infers_arrays() {
+        let mut doc = create_doc();
+
+        handle_update_configuration(&mut doc, "status.pipestatus_format", "[1, 2]").unwrap();
+
+        let array = doc["status"]["pipestatus_format"].as_array().unwrap();
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.get(0).and_then(toml_edit::Value::as_integer), Some(1));
+    }
+
+    #[test]
+    fn test_get_config() {
+        let doc = COMMENTED_CONFIG.parse::<Document>().unwrap();
+
+        assert_eq!(
+            handle_get_configuration(&doc, "format"),
+            Ok("\"$all\"".to_owned())
+        );
+        assert_eq!(
+            handle_get_configuration(&doc, "directory.truncation_length"),
+            Ok("3".to_owned())
+        );
+        assert_eq!(
+            handle_get_configuration(&doc, "nodejs"),
+            Ok("disabled = false".to_owned())
+        );
+        assert!(handle_get_configuration(&doc, "directory.missing").is_err());
+        assert!(handle_get_configuration(&doc, "format.missing").is_err());
+        assert!(handle_get_configuration(&doc, "directory.").is_err());
+    }
+
+    #[test]
+    fn test_unset_config() {
+        let mut doc = COMMENTED_CONFIG.parse::<Document>().unwrap();
+
+        handle_unset_configuration(&mut doc, "directory.truncation_length").unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            COMMENTED_CONFIG.replace("truncation_length = 3 # keep it short\n", "")
+        );
+        assert!(handle_unset_configuration(&mut doc, "directory.truncation_length").is_err());
+        assert!(handle_unset_configuration(&mut doc, "missing.disabled").is_err());
+        assert!(handle_unset_configuration(&mut doc, "").is_err());
+
+        handle_unset_configuration(&mut doc, "nodejs").unwrap();
+        assert!(doc.get("nodejs").is_none());
+    }
+
+    #[test]
+    fn test_unknown_module() {
+        assert_eq!(unknown_module("directory.style"), None);
+        assert_eq!(unknown_module("custom.foo.command"), None);
+        assert_eq!(unknown_module("palettes.mine.red"), None);
+        assert_eq!(unknown_module("profiles.demo.format"), None);
+        assert_eq!(unknown_module("format"), None);
+        assert_eq!(unknown_module("directoy.style"), Some("directoy"));
+    }
+
+    #[test]
+    fn test_write_configuration_replaces_the_file() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let config_path = dir.path().join("starship.toml");
+        fs::write(&config_path, "format = \"$all\"\n")?;
+
+        let doc = COMMENTED_CONFIG.parse::<Document>().unwrap();
+        write_configuration_to(&config_path, &doc)?;
+
+        assert_eq!(fs::read_to_string(&config_path)?, COMMENTED_CONFIG);
+        assert_eq!(fs::read_dir(dir.path())?.count(), 1);
+        dir.close()
+    }
+
+    #[test]
+    fn valid_configuration_has_no_problems() {
+        let config = toml::toml! {
+            [gradient]
+            format = "$username"
+            gradient = "#ff0000,#00ff00,#0000ff"
+            domain = [0.0, 0.2, 1.0]
+
+            [gradient_username]
+            gradient = "viridis"
+            domain = [1.0, 0.0]
+        };
+
+        assert!(find_configuration_problems(&config).is_empty());
+    }
+
+    #[test]
+    fn gradient_problems_name_the_module() {
+        let config = toml::toml! {
+            [gradient_directory]
+            gradient = "#ff0000,#00ff00,#0000ff"
+            domain = [0.0, 200.0, 100.0]
+
+            [gradient_hostname]
+            gradient = ["#ff0000", "#00ff00", "#0000ff"]
+            domain = [0.0, 0.5, 0.7, 1.0]
+        };
+
+        assert_eq!(
+            vec![
+                "[gradient_directory] `domain = [0.0, 200.0, 100.0]` is not in increasing order",
+                "[gradient_hostname] `domain = [0.0, 0.5, 0.7, 1.0]` has 4 stops, expected 2 or one for each of the 3 colors",
+            ],
+            find_configuration_problems(&config)
+        );
+    }
+
+    #[test]
+    fn continuation_prompt_problems() {
+        let config = toml::toml! {
+            continuation_prompt = "$directory$character$time"
+        };
+
+        assert_eq!(
+            vec![
+                "[continuation_prompt] The module `directory` is not supported, only [\"character\"] are",
+                "[continuation_prompt] The module `time` is not supported, only [\"character\"] are",
+            ],
+            find_configuration_problems(&config)
+        );
+    }
 }
@@ -1,10 +1,12 @@
 use crate::config::{ModuleConfig, StarshipConfig};
 use crate::configs::StarshipRootConfig;
 use crate::module::Module;
-use crate::utils::{create_command, exec_timeout, read_file, CommandOutput};
+use crate::session_state::SessionState;
+use crate::utils::{create_command, exec_timeout_with_input, read_file, CommandOutput};
 
 use crate::modules;
 use crate::utils::{self, home_dir};
+use chrono::{DateTime, Local};
 use clap::Parser;
 use git_repository::{
     self as git,
@@ -51,12 +53,30 @@ pub struct Context<'a> {
     /// Private field to store Git information for modules who need it
     repo: OnceCell<Repo>,
 
+    /// Random starting point shared by the gradients with a random phase
+    random_gradient_phase: OnceCell<f64>,
+
+    /// Whether an `sshd` process is among the ancestors of this one
+    sshd_ancestor: OnceCell<bool>,
+
+    /// Where rendered modules are cached between prompts, `None` disables the cache
+    pub cache_dir: Option<PathBuf>,
+
+    /// Where the state of shell sessions is kept, `None` disables the session state
+    pub session_dir: Option<PathBuf>,
+
+    /// The state of the current shell session
+    session_state: OnceCell<Option<SessionState>>,
+
     /// The shell the user is assumed to be running
     pub shell: Shell,
 
     /// Which prompt to print (main, right, ...)
     pub target: Target,
 
+    /// Whether the prompt replaces an earlier one after its command was run
+    pub transient: bool,
+
     /// Width of terminal, or zero if width cannot be detected.
     pub width: usize,
 
@@ -72,9 +92,32 @@ pub struct Context<'a> {
     #[cfg(test)]
     pub root_dir: tempfile::TempDir,
 
+    /// A mock of the current time
+    #[cfg(test)]
+    pub now: Option<DateTime<Local>>,
+
+    /// A mock of whether the current user is root
+    #[cfg(test)]
+    pub root_user: bool,
+
+    /// A mock of whether the process runs elevated on Windows
+    #[cfg(test)]
+    pub elevated: bool,
+
+    /// Counts the commands executed through `exec_cmd`
+    #[cfg(test)]
+    pub cmd_calls: Option<&'a std::sync::atomic::AtomicUsize>,
+
     #[cfg(feature = "battery")]
     pub battery_info_provider: &'a (dyn crate::modules::BatteryInfoProvider + Send + Sync),
 
+    pub process_tree_provider: &'a (dyn crate::modules::ProcessTreeProvider + Send + Sync),
+
+    pub network_interface_provider:
+        &'a (dyn crate::modules::NetworkInterfaceProvider + Send + Sync),
+
+    pub filesystem_stats_provider: &'a (dyn crate::modules::FilesystemStatsProvider + Send + Sync),
+
     /// Starship root config
     pub root_config: StarshipRootConfig,
 
@@ -160,8 +203,22 @@ impl<'a> Context<'a> {
             logical_dir,
             dir_contents: OnceCell::new(),
             repo: OnceCell::new(),
+            random_gradient_phase: OnceCell::new(),
+            sshd_ancestor: OnceCell::new(),
+            cache_dir: if cfg!(test) {
+                None
+            } else {
+                Some(crate::cache::modules_dir())
+            },
+            session_dir: if cfg!(test) {
+                None
+            } else {
+                Some(crate::session_state::sessions_dir())
+            },
+            session_state: OnceCell::new(),
             shell,
             target,
+            transient: false,
             width,
             #[cfg(test)]
             root_dir: tempfile::TempDir::new().unwrap(),
@@ -169,13 +226,37 @@ impl<'a> Context<'a> {
             env: HashMap::new(),
             #[cfg(test)]
             cmd: HashMap::new(),
+            #[cfg(test)]
+            now: None,
+            #[cfg(test)]
+            root_user: false,
+            #[cfg(test)]
+            elevated: false,
+            #[cfg(test)]
+            cmd_calls: None,
             #[cfg(feature = "battery")]
             battery_info_provider: &crate::modules::BatteryInfoProviderImpl,
+            process_tree_provider: &crate::modules::ProcessTreeProviderImpl,
+            network_interface_provider: &crate::modules::NetworkInterfaceProviderImpl,
+            filesystem_stats_provider: &crate::modules::FilesystemStatsProviderImpl,
             root_config,
             _marker: PhantomData,
         }
     }
 
+    /// The state of the current shell session, or `None` outside of a session
+    pub fn session_state(&self) -> Option<&SessionState> {
+        self.session_state
+            .get_or_init(|| {
+                let dir = self.session_dir.as_ref()?;
+                let key = self
+                    .get_env("STARSHIP_SESSION_KEY")
+                    .filter(|key| !key.is_empty())?;
+                Some(SessionState::open(dir, &key))
+            })
+            .as_ref()
+    }
+
     // Tries to retrieve home directory from a table in testing mode or else retrieves it from the os
     pub fn get_home(&self) -> Option<PathBuf> {
         if cfg!(test) {
@@ -211,6 +292,34 @@ impl<'a> Context<'a> {
         env::var_os(key.as_ref())
     }
 
+    /// Whether any of the environment variables is set, while none of the ones
+    /// negated with a leading `!` are
+    pub fn detect_env_vars(&self, env_vars: &[&str]) -> bool {
+        let mut has_env_vars = false;
+        for env_var in env_vars {
+            if let Some(negated) = env_var.strip_prefix('!') {
+                if self.get_env_os(negated).is_some() {
+                    return false;
+                }
+            } else if self.get_env_os(env_var).is_some() {
+                has_env_vars = true;
+            }
+        }
+        has_env_vars
+    }
+
+    // Retrieves the current time, or a mocked time if in testing mode
+    #[cfg(test)]
+    pub fn get_now(&self) -> DateTime<Local> {
+        self.now.unwrap_or_else(Local::now)
+    }
+
+    #[cfg(not(test))]
+    #[inline]
+    pub fn get_now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+
     /// Convert a `~` in a path to the home directory
     pub fn expand_tilde(dir: PathBuf) -> PathBuf {
         if dir.starts_with("~") {
@@ -326,21 +435,24 @@ impl<'a> Context<'a> {
         })
     }
 
+    /// A random number in `[0, 1)` that stays the same for the whole render, so
+    /// that every use of a random gradient phase agrees
+    pub fn random_gradient_phase(&self) -> f64 {
+        *self.random_gradient_phase.get_or_init(rand::random)
+    }
+
+    /// Whether the prompt runs below an `sshd` process, or in a tmux session
+    /// whose client does
+    ///
+    /// The process tree is only walked once, however many modules ask.
+    pub fn has_sshd_ancestor(&self) -> bool {
+        *self
+            .sshd_ancestor
+            .get_or_init(|| crate::modules::has_sshd_ancestor(self))
+    }
+
     fn get_shell() -> Shell {
-        let shell = env::var("STARSHIP_SHELL").unwrap_or_default();
-        match shell.as_str() {
-            "bash" => Shell::Bash,
-            "fish" => Shell::Fish,
-            "ion" => Shell::Ion,
-            "powershell" | "pwsh" => Shell::PowerShell,
-            "zsh" => Shell::Zsh,
-            "elvish" => Shell::Elvish,
-            "tcsh" => Shell::Tcsh,
-            "nu" => Shell::Nu,
-            "xonsh" => Shell::Xonsh,
-            "cmd" => Shell::Cmd,
-            _ => Shell::Unknown,
-        }
+        Shell::from_name(&env::var("STARSHIP_SHELL").unwrap_or_default())
     }
 
     // TODO: This should be used directly by clap parse
@@ -357,6 +469,17 @@ impl<'a> Context<'a> {
         &self,
         cmd: T,
         args: &[U],
+    ) -> Option<CommandOutput> {
+        self.exec_cmd_with_input(cmd, args, None)
+    }
+
+    /// Execute a command with `input` written to its stdin, and return the output on
+    /// stdout and stderr if successful
+    pub fn exec_cmd_with_input<T: AsRef<OsStr> + Debug, U: AsRef<OsStr> + Debug>(
+        &self,
+        cmd: T,
+        args: &[U],
+        input: Option<&str>,
     ) -> Option<CommandOutput> {
         log::trace!(
             "Executing command {:?} with args {:?} from context",
@@ -365,6 +488,9 @@ impl<'a> Context<'a> {
         );
         #[cfg(test)]
         {
+            if let Some(cmd_calls) = self.cmd_calls {
+                cmd_calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
             let command = crate::utils::display_command(&cmd, args);
             if let Some(output) = self
                 .cmd
@@ -377,8 +503,9 @@ impl<'a> Context<'a> {
         }
         let mut cmd = create_command(cmd).ok()?;
         cmd.args(args).current_dir(&self.current_dir);
-        exec_timeout(
+        exec_timeout_with_input(
             &mut cmd,
+            input,
             Duration::from_millis(self.root_config.command_timeout),
         )
     }
@@ -655,6 +782,56 @@ pub enum Shell {
     Unknown,
 }
 
+impl Shell {
+    /// The shells that starship can be initialized in
+    pub const ALL: &'static [Self] = &[
+        Self::Bash,
+        Self::Elvish,
+        Self::Fish,
+        Self::Ion,
+        Self::PowerShell,
+        Self::Tcsh,
+        Self::Zsh,
+        Self::Nu,
+        Self::Xonsh,
+        Self::Cmd,
+    ];
+
+    /// The shell called `name` in `starship init <name>` or `STARSHIP_SHELL`
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "bash" => Self::Bash,
+            "fish" => Self::Fish,
+            "ion" => Self::Ion,
+            "powershell" | "pwsh" => Self::PowerShell,
+            "zsh" => Self::Zsh,
+            "elvish" => Self::Elvish,
+            "tcsh" => Self::Tcsh,
+            "nu" => Self::Nu,
+            "xonsh" => Self::Xonsh,
+            "cmd" => Self::Cmd,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The name of the shell in `starship init <name>`
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bash => "bash",
+            Self::Fish => "fish",
+            Self::Ion => "ion",
+            Self::PowerShell => "powershell",
+            Self::Zsh => "zsh",
+            Self::Elvish => "elvish",
+            Self::Tcsh => "tcsh",
+            Self::Nu => "nu",
+            Self::Xonsh => "xonsh",
+            Self::Cmd => "cmd",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
 /// Which kind of prompt target to print (main prompt, rprompt, ...)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Target {
@@ -691,6 +868,9 @@ pub struct Properties {
     /// The number of currently running jobs
     #[clap(short, long, default_value_t, value_parser=parse_jobs)]
     pub jobs: i64,
+    /// The commands of the currently running jobs, one per line or per use of the option
+    #[clap(long, value_delimiter = '\n')]
+    pub jobs_names: Option<Vec<String>>,
 }
 
 impl Default for Properties {
@@ -704,6 +884,7 @@ impl Default for Properties {
             cmd_duration: None,
             keymap: "viins".to_string(),
             jobs: 0,
+            jobs_names: None,
         }
     }
 }
@@ -746,6 +927,16 @@ mod tests {
         Ok(dir)
     }
 
+    #[test]
+    fn shell_names_round_trip() {
+        for shell in Shell::ALL {
+            assert_eq!(*shell, Shell::from_name(shell.name()));
+        }
+        assert_eq!(Shell::PowerShell, Shell::from_name("pwsh"));
+        assert_eq!(Shell::Unknown, Shell::from_name("murex"));
+        assert_eq!(Shell::Unknown, Shell::from_name(""));
+    }
+
     #[test]
     fn test_scan_dir() -> Result<(), Box<dyn std::error::Error>> {
         let empty = testdir(&[])?;
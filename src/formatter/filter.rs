@@ -0,0 +1,171 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::print::UnicodeWidthGraphemes;
+
+/// The symbol that replaces the end of a value cut off by `truncate`
+const TRUNCATION_SYMBOL: &str = "…";
+
+/// A transformation of the value of a variable, e.g. `$branch|truncate(12)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Keeps the first n graphemes, followed by an ellipsis if anything was cut off
+    Truncate(usize),
+    Upper,
+    Lower,
+    StripPrefix(String),
+    StripSuffix(String),
+    /// Adds spaces before the value until it is n columns wide
+    PadLeft(usize),
+    /// Adds spaces after the value until it is n columns wide
+    PadRight(usize),
+}
+
+impl Filter {
+    /// Creates the filter called `name`, with the text in its parentheses as `arg`
+    pub fn new(name: &str, arg: Option<String>) -> Result<Self, String> {
+        let length = |arg: Option<String>| {
+            let arg = arg.ok_or_else(|| format!("The filter `{}` requires a length", name))?;
+            arg.trim().parse::<usize>().map_err(|_| {
+                format!(
+                    "The filter `{}` requires a length, but `{}` is not a number",
+                    name, arg
+                )
+            })
+        };
+
+        match (name, arg) {
+            ("truncate", arg) => length(arg).map(Self::Truncate),
+            ("pad_left", arg) => length(arg).map(Self::PadLeft),
+            ("pad_right", arg) => length(arg).map(Self::PadRight),
+            ("upper", None) => Ok(Self::Upper),
+            ("lower", None) => Ok(Self::Lower),
+            ("strip_prefix", Some(prefix)) => Ok(Self::StripPrefix(prefix)),
+            ("strip_suffix", Some(suffix)) => Ok(Self::StripSuffix(suffix)),
+            ("upper" | "lower", Some(_)) => {
+                Err(format!("The filter `{}` doesn't take an argument", name))
+            }
+            ("strip_prefix" | "strip_suffix", None) => {
+                Err(format!("The filter `{}` requires an argument", name))
+            }
+            _ => Err(format!("Unknown filter `{}`", name)),
+        }
+    }
+
+    /// Returns `value` transformed by the filter
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Self::Truncate(length) => {
+                if value.graphemes(true).nth(*length).is_none() {
+                    return value.to_owned();
+                }
+                let mut truncated: String = value.graphemes(true).take(*length).collect();
+                truncated.push_str(TRUNCATION_SYMBOL);
+                truncated
+            }
+            Self::Upper => value.to_uppercase(),
+            Self::Lower => value.to_lowercase(),
+            Self::StripPrefix(prefix) => value
+                .strip_prefix(prefix.as_str())
+                .unwrap_or(value)
+                .to_owned(),
+            Self::StripSuffix(suffix) => value
+                .strip_suffix(suffix.as_str())
+                .unwrap_or(value)
+                .to_owned(),
+            Self::PadLeft(width) => format!("{}{}", padding(value, *width), value),
+            Self::PadRight(width) => format!("{}{}", value, padding(value, *width)),
+        }
+    }
+}
+
+/// Returns `value` transformed by each of `filters` in turn
+pub fn apply_filters(filters: &[Filter], value: &str) -> String {
+    filters
+        .iter()
+        .fold(value.to_owned(), |value, filter| filter.apply(&value))
+}
+
+fn padding(value: &str, width: usize) -> String {
+    " ".repeat(width.saturating_sub(value.width_graphemes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(name: &str, arg: Option<&str>) -> Filter {
+        Filter::new(name, arg.map(str::to_owned)).unwrap()
+    }
+
+    #[test]
+    fn truncate() {
+        let truncate = filter("truncate", Some("5"));
+        assert_eq!(truncate.apply("main"), "main");
+        assert_eq!(truncate.apply("build"), "build");
+        assert_eq!(truncate.apply("feature"), "featu…");
+        // "🇺🇸" and "e\u{301}" are single graphemes
+        assert_eq!(truncate.apply("🇺🇸e\u{301}abcd"), "🇺🇸e\u{301}abc…");
+        assert_eq!(filter("truncate", Some("0")).apply("a"), "…");
+    }
+
+    #[test]
+    fn upper_and_lower() {
+        assert_eq!(filter("upper", None).apply("Straße"), "STRASSE");
+        assert_eq!(filter("lower", None).apply("ÄBC"), "äbc");
+    }
+
+    #[test]
+    fn strip_prefix_and_suffix() {
+        assert_eq!(filter("strip_prefix", Some("v")).apply("v1.2.3"), "1.2.3");
+        assert_eq!(filter("strip_prefix", Some("v")).apply("1.2.3v"), "1.2.3v");
+        assert_eq!(
+            filter("strip_suffix", Some(".git")).apply("repo.git"),
+            "repo"
+        );
+        assert_eq!(filter("strip_suffix", Some(".git")).apply("repo"), "repo");
+    }
+
+    #[test]
+    fn pad() {
+        assert_eq!(filter("pad_left", Some("5")).apply("ab"), "   ab");
+        assert_eq!(filter("pad_right", Some("5")).apply("ab"), "ab   ");
+        assert_eq!(filter("pad_right", Some("2")).apply("abc"), "abc");
+        // Wide characters take up two columns
+        assert_eq!(filter("pad_left", Some("4")).apply("日本"), "日本");
+    }
+
+    #[test]
+    fn chained_filters() {
+        let filters = [
+            filter("strip_prefix", Some("feature/")),
+            filter("truncate", Some("6")),
+            filter("upper", None),
+            filter("pad_right", Some("10")),
+        ];
+        assert_eq!(apply_filters(&filters, "feature/login-page"), "LOGIN-…   ");
+        assert_eq!(apply_filters(&[], "value"), "value");
+    }
+
+    #[test]
+    fn invalid_filters() {
+        let error =
+            |name: &str, arg: Option<&str>| Filter::new(name, arg.map(str::to_owned)).unwrap_err();
+        assert_eq!(error("reverse", None), "Unknown filter `reverse`");
+        assert_eq!(
+            error("truncate", None),
+            "The filter `truncate` requires a length"
+        );
+        assert_eq!(
+            error("pad_left", Some("wide")),
+            "The filter `pad_left` requires a length, but `wide` is not a number"
+        );
+        assert_eq!(
+            error("upper", Some("x")),
+            "The filter `upper` doesn't take an argument"
+        );
+        assert_eq!(
+            error("strip_suffix", None),
+            "The filter `strip_suffix` requires an argument"
+        );
+    }
+}
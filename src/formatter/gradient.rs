@@ -0,0 +1,1547 @@
+use crate::configs::gradient::GradientConfig;
+use crate::context::{Context, Target};
+use crate::formatter::string_formatter::StringFormatterError;
+use crate::module::Module;
+use crate::segment::{Segment, TextSegment};
+use chrono::Timelike;
+use colorgrad::{BlendMode, CustomGradient, Gradient, Interpolation};
+use nu_ansi_term::{Color, Style};
+use std::error::Error;
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+type Preset = (&'static str, fn() -> Gradient);
+
+/// The built-in colorgrad gradients that can be referenced by name
+const PRESETS: &[Preset] = &[
+    ("blues", colorgrad::blues),
+    ("br_bg", colorgrad::br_bg),
+    ("bu_gn", colorgrad::bu_gn),
+    ("bu_pu", colorgrad::bu_pu),
+    ("cividis", colorgrad::cividis),
+    ("cool", colorgrad::cool),
+    ("cubehelix", colorgrad::cubehelix_default),
+    ("gn_bu", colorgrad::gn_bu),
+    ("greens", colorgrad::greens),
+    ("greys", colorgrad::greys),
+    ("inferno", colorgrad::inferno),
+    ("magma", colorgrad::magma),
+    ("or_rd", colorgrad::or_rd),
+    ("oranges", colorgrad::oranges),
+    ("pi_yg", colorgrad::pi_yg),
+    ("plasma", colorgrad::plasma),
+    ("pr_gn", colorgrad::pr_gn),
+    ("pu_bu", colorgrad::pu_bu),
+    ("pu_bu_gn", colorgrad::pu_bu_gn),
+    ("pu_or", colorgrad::pu_or),
+    ("pu_rd", colorgrad::pu_rd),
+    ("purples", colorgrad::purples),
+    ("rainbow", colorgrad::rainbow),
+    ("rd_bu", colorgrad::rd_bu),
+    ("rd_gy", colorgrad::rd_gy),
+    ("rd_pu", colorgrad::rd_pu),
+    ("rd_yl_bu", colorgrad::rd_yl_bu),
+    ("rd_yl_gn", colorgrad::rd_yl_gn),
+    ("reds", colorgrad::reds),
+    ("sinebow", colorgrad::sinebow),
+    ("spectral", colorgrad::spectral),
+    ("turbo", colorgrad::turbo),
+    ("viridis", colorgrad::viridis),
+    ("warm", colorgrad::warm),
+    ("yl_gn", colorgrad::yl_gn),
+    ("yl_gn_bu", colorgrad::yl_gn_bu),
+    ("yl_or_br", colorgrad::yl_or_br),
+    ("yl_or_rd", colorgrad::yl_or_rd),
+];
+
+/// Look up one of colorgrad's preset gradients by name
+///
+/// Names are matched case-insensitively and may use `-` in place of `_`
+/// (e.g. `rd-yl-bu`).
+pub fn preset_gradient(name: &str) -> Option<Gradient> {
+    let name = name.trim().to_lowercase().replace('-', "_");
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, gradient)| gradient())
+}
+
+/// Names of all gradients accepted by `preset_gradient`
+pub fn preset_names() -> impl Iterator<Item = &'static str> {
+    PRESETS.iter().map(|(name, _)| *name)
+}
+
+/// A problem with the gradient options of a config
+#[derive(Debug, Clone, PartialEq)]
+pub enum GradientError {
+    /// The domain has neither two stops nor one per color
+    DomainCount { domain: Vec<f64>, colors: usize },
+    /// The domain stops aren't in increasing order
+    DomainOrder { domain: Vec<f64> },
+    /// The domain contains `inf` or `nan`
+    DomainNotFinite { domain: Vec<f64> },
+}
+
+impl fmt::Display for GradientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DomainCount { domain, colors } => write!(
+                f,
+                "`domain = {:?}` has {} stops, expected 2 or one for each of the {} colors",
+                domain,
+                domain.len(),
+                colors
+            ),
+            Self::DomainOrder { domain } => {
+                write!(f, "`domain = {:?}` is not in increasing order", domain)
+            }
+            Self::DomainNotFinite { domain } => write!(
+                f,
+                "`domain = {:?}` contains values that are not finite numbers",
+                domain
+            ),
+        }
+    }
+}
+
+impl Error for GradientError {}
+
+/// Checks the gradient options of a config for problems that would stop the
+/// gradient from being built
+///
+/// The domain is ignored for presets, so only color lists are checked.
+pub fn validate_gradient(config: &GradientConfig) -> Result<(), Vec<GradientError>> {
+    let domain = &config.domain;
+    let colors = match gradient_colors(config) {
+        Some(colors) if !domain.is_empty() => colors,
+        _ => return Ok(()),
+    };
+
+    let mut errors = Vec::new();
+    if domain.iter().any(|stop| !stop.is_finite()) {
+        errors.push(GradientError::DomainNotFinite {
+            domain: domain.clone(),
+        });
+    }
+    // A single color is stretched over the whole gradient, like two equal ones
+    let stops = colors.len().max(2);
+    if domain.len() != 2 && domain.len() != stops {
+        errors.push(GradientError::DomainCount {
+            domain: domain.clone(),
+            colors: colors.len(),
+        });
+    }
+    let increasing = if domain.len() == 2 {
+        domain[0] < domain[1]
+    } else {
+        domain.windows(2).all(|pair| pair[0] <= pair[1])
+    };
+    if !increasing {
+        errors.push(GradientError::DomainOrder {
+            domain: domain.clone(),
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The list of colors in the config, or `None` if it names a preset
+fn gradient_colors<'a>(config: &GradientConfig<'a>) -> Option<Vec<&'a str>> {
+    if let [name] = config.gradient.0.as_slice() {
+        if !name.contains(',') && preset_gradient(name).is_some() {
+            return None;
+        }
+    }
+
+    Some(
+        config
+            .gradient
+            .0
+            .iter()
+            .flat_map(|colors| colors.split(','))
+            .map(str::trim)
+            .filter(|color| !color.is_empty())
+            .collect(),
+    )
+}
+
+/// Builds the gradient described by the config
+///
+/// The gradient is either the name of a colorgrad preset (e.g. `viridis`), or a
+/// list of colors given as an array or as a single comma-separated string. If no
+/// colors are set, or the gradient can't be built from them, the default palette
+/// is used instead. The `interpolation` of the config sets how a list of colors
+/// is blended, and an invalid `domain` is ignored.
+///
+/// Building the gradient parses every color, so modules should call this once
+/// per render and share the result between all of their segments.
+pub fn build_gradient(config: &GradientConfig) -> Gradient {
+    let colors = match gradient_colors(config) {
+        Some(colors) => colors,
+        None => {
+            let name = config.gradient.0[0];
+            return preset_gradient(name).unwrap_or_else(colorgrad::magma);
+        }
+    };
+
+    if let [name] = colors.as_slice() {
+        if colorgrad::Color::from_html(name).is_err() {
+            log::warn!(
+                "Unknown gradient preset `{}`, valid presets are: {}",
+                name,
+                preset_names().collect::<Vec<_>>().join(", ")
+            );
+        }
+    }
+
+    if !colors.is_empty() {
+        let mut builder = CustomGradient::new();
+        builder.html_colors(&colors);
+        match validate_gradient(config) {
+            Ok(()) if !config.domain.is_empty() => {
+                builder.domain(&config.domain);
+            }
+            Ok(()) => {}
+            Err(errors) => {
+                for error in errors {
+                    log::warn!("Ignoring gradient domain: {}", error);
+                }
+            }
+        }
+        set_interpolation(&mut builder, config.interpolation);
+        match builder.build() {
+            Ok(gradient) => return gradient,
+            Err(error) => log::warn!("Unable to build gradient: {}", error),
+        }
+    }
+
+    let default = GradientConfig::default();
+    CustomGradient::new()
+        .html_colors(&default.gradient.0)
+        .build()
+        .unwrap_or_else(|_| colorgrad::magma())
+}
+
+/// Sets how the colors of a gradient are blended into each other
+///
+/// - `linear-rgb` blends linearly between neighboring colors in RGB
+/// - `oklab` blends linearly in the perceptually uniform Oklab color space
+/// - `catmull-rom` follows a smooth spline through all the colors
+fn set_interpolation(builder: &mut CustomGradient, interpolation: &str) {
+    match interpolation {
+        "linear-rgb" => {}
+        "oklab" => {
+            builder.mode(BlendMode::Oklab);
+        }
+        "catmull-rom" => {
+            builder.interpolation(Interpolation::CatmullRom);
+        }
+        _ => log::warn!(
+            "Unknown gradient interpolation `{}`, expected one of `linear-rgb`, `oklab` or `catmull-rom`",
+            interpolation
+        ),
+    }
+}
+
+/// Builds a two-color gradient whose hues are derived from a hash of `seed`
+///
+/// The same seed always yields the same gradient, while different seeds are
+/// spread around the color wheel.
+pub fn seeded_gradient(seed: &str) -> Gradient {
+    let hash = fnv1a(seed);
+    let start = (hash % 360) as f64;
+    // Keep the end hue at least 60° away so the gradient is always visible
+    let end = start + 60.0 + ((hash >> 16) % 180) as f64;
+
+    CustomGradient::new()
+        .colors(&[
+            colorgrad::Color::from_hsla(start, 0.7, 0.65, 1.0),
+            colorgrad::Color::from_hsla(end, 0.7, 0.65, 1.0),
+        ])
+        .build()
+        .unwrap_or_else(|_| colorgrad::magma())
+}
+
+/// Builds a gradient from three hues derived from `seed`
+///
+/// Every stop gets a lightness between `min_lightness` and `max_lightness`
+/// (from 0 to 1), which keeps the text readable on the user's background.
+pub fn seeded_gradient_in_lightness(
+    seed: &str,
+    min_lightness: f64,
+    max_lightness: f64,
+) -> Gradient {
+    let hash = fnv1a(seed);
+    let min_lightness = min_lightness.clamp(0.0, 1.0);
+    let max_lightness = max_lightness.clamp(min_lightness, 1.0);
+
+    let start = (hash % 360) as f64;
+    let hues = [
+        start,
+        start + 60.0 + ((hash >> 16) % 60) as f64,
+        start + 150.0 + ((hash >> 24) % 90) as f64,
+    ];
+    let colors: Vec<_> = hues
+        .iter()
+        .enumerate()
+        .map(|(i, &hue)| {
+            let amount = ((hash >> (32 + 8 * i)) & 0xff) as f64 / 255.0;
+            let lightness = min_lightness + (max_lightness - min_lightness) * amount;
+            colorgrad::Color::from_hsla(hue % 360.0, 0.7, lightness, 1.0)
+        })
+        .collect();
+
+    CustomGradient::new()
+        .colors(&colors)
+        .build()
+        .unwrap_or_else(|_| colorgrad::magma())
+}
+
+/// 64-bit FNV-1a, used instead of `DefaultHasher` as its output is stable
+/// across Rust releases
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Colors every grapheme of the text segments with the next color of the gradient
+///
+/// The gradient is stretched over all graphemes of the text segments, so the
+/// first one gets the gradient's start color and the last one its end color.
+/// Each grapheme becomes a segment of its own, keeping the attributes (bold,
+/// underline, ...) of the segment it came from. Fill segments are expanded to
+/// the width they take up in the terminal and colored like text, while line
+/// breaks are passed through untouched.
+///
+/// The `direction` of the config controls how the gradient is laid out, its
+/// `phase` where along the gradient the first grapheme starts, and its `target`
+/// whether the foreground, the background or both are colored. With
+/// `right_align_gradient` set, segments in the right prompt only get the colors
+/// of the last columns of a gradient as wide as the terminal, and transient
+/// prompts only get its first color. On
+/// terminals without truecolor support the colors are reduced to the xterm-256
+/// palette, and with `NO_COLOR` set the segments are returned unchanged.
+pub fn gradientify_segments(
+    context: &Context,
+    segments: &[Segment],
+    gradient: &Gradient,
+    config: &GradientConfig,
+) -> Vec<Segment> {
+    let segments = expand_fills(segments, Some(context.width));
+    gradientify(
+        context,
+        segments.iter().map(|segment| (segment, true)),
+        gradient,
+        config,
+    )
+    .0
+}
+
+/// Like `gradientify_segments`, but only colors the segments that are selected
+///
+/// The gradient is stretched over the selected segments alone, while all other
+/// segments keep their style.
+pub fn gradientify_selected_segments(
+    context: &Context,
+    segments: &[(Segment, bool)],
+    gradient: &Gradient,
+    config: &GradientConfig,
+) -> Vec<Segment> {
+    let (segments, selected): (Vec<_>, Vec<_>) = segments.iter().cloned().unzip();
+    let segments = expand_fills(&segments, Some(context.width));
+    gradientify(context, segments.iter().zip(selected), gradient, config).0
+}
+
+/// How the output of a module was colored, as shown by `starship explain --verbose`
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradientInfo {
+    /// The name of the preset or the colors of the gradient
+    pub gradient: Vec<String>,
+    pub domain: Vec<f64>,
+    pub interpolation: String,
+    /// The colors sampled for the first and the last colored grapheme
+    pub first_color: Option<(u8, u8, u8)>,
+    pub last_color: Option<(u8, u8, u8)>,
+}
+
+/// Colors the selected segments like `gradientify_selected_segments` and sets
+/// them as the segments of the module, recording the gradient on it
+pub fn gradientify_module(
+    module: &mut Module,
+    context: &Context,
+    segments: &[(Segment, bool)],
+    gradient: &Gradient,
+    config: &GradientConfig,
+) {
+    let (segments, selected): (Vec<_>, Vec<_>) = segments.iter().cloned().unzip();
+    let segments = expand_fills(&segments, Some(context.width));
+    let (segments, [first_color, last_color]) =
+        gradientify(context, segments.iter().zip(selected), gradient, config);
+
+    module.set_segments(segments);
+    module.gradient = Some(GradientInfo {
+        gradient: config.gradient.0.iter().map(ToString::to_string).collect(),
+        domain: config.domain.clone(),
+        interpolation: config.interpolation.to_string(),
+        first_color,
+        last_color,
+    });
+}
+
+/// The powerline glyphs `gradientify_background` colors in the foreground
+const POWERLINE_GLYPHS: &[&str] = &["\u{e0ba}", "\u{e0bc}"];
+
+/// Colors the background of the text segments with the colors of `gradient`
+/// sampled at `columns` evenly spaced points, the way the `gradient` module did
+/// when it only rendered the current directory
+///
+/// Every segment starts at the color of its byte offset in the output, and
+/// continues with one color per grapheme. Powerline glyphs get the color in
+/// their foreground instead, so they blend into the background next to them.
+/// Graphemes past the last column keep its color.
+pub fn gradientify_background(
+    context: &Context,
+    segments: &[Segment],
+    gradient: &Gradient,
+    columns: usize,
+    config: &GradientConfig,
+) -> Vec<Segment> {
+    let color_mode = color_mode(context, config.color_mode);
+    if color_mode == ColorMode::None {
+        return segments.to_vec();
+    }
+    let colors: Vec<Color> = sample_gradient(gradient, columns, "forward", 0.0)
+        .iter()
+        .map(|color| {
+            let (r, g, b, _) = color.to_linear_rgba_u8();
+            match color_mode {
+                ColorMode::Ansi256 => Color::Fixed(ansi256(r, g, b)),
+                _ => Color::Rgb(r, g, b),
+            }
+        })
+        .collect();
+    let color_at = |index: usize| colors.get(index.min(columns.saturating_sub(1))).copied();
+
+    let mut gradientified = Vec::new();
+    let mut offset = 0;
+    for segment in segments {
+        let text = match segment {
+            Segment::Text(text) => text,
+            _ => {
+                gradientified.push(segment.clone());
+                continue;
+            }
+        };
+        let style = text.style.unwrap_or_default();
+        for (index, grapheme) in text.value.graphemes(true).enumerate() {
+            let style = match color_at(offset + index) {
+                Some(color) if POWERLINE_GLYPHS.contains(&grapheme) => Style {
+                    foreground: Some(color),
+                    ..style
+                },
+                Some(color) => Style {
+                    background: Some(color),
+                    ..style
+                },
+                None => style,
+            };
+            gradientified.push(Segment::Text(TextSegment {
+                value: grapheme.into(),
+                style: Some(style),
+            }));
+        }
+        offset += text.value.len();
+    }
+    gradientified
+}
+
+type Rgb = (u8, u8, u8);
+
+/// Colors the selected segments, returning them with the first and the last
+/// color that was sampled
+fn gradientify<'a, I>(
+    context: &Context,
+    segments: I,
+    gradient: &Gradient,
+    config: &GradientConfig,
+) -> (Vec<Segment>, [Option<Rgb>; 2])
+where
+    I: Iterator<Item = (&'a Segment, bool)> + Clone,
+{
+    let color_mode = color_mode(context, config.color_mode);
+    if color_mode == ColorMode::None {
+        let segments = segments.map(|(segment, _)| segment.clone()).collect();
+        return (segments, [None, None]);
+    }
+
+    let graphemes = segments
+        .clone()
+        .filter_map(|(segment, selected)| match segment {
+            Segment::Text(text) if selected => Some(text.value.graphemes(true).count()),
+            _ => None,
+        })
+        .sum();
+    // The right prompt takes up the last columns of the line
+    let columns = if config.right_align_gradient && context.target == Target::Right {
+        context.width.max(graphemes)
+    } else {
+        graphemes
+    };
+    let offset = phase_offset(context, config.phase);
+    let rgb: Vec<Rgb> = sample_gradient(gradient, columns, config.direction, offset)
+        .into_iter()
+        .skip(columns - graphemes)
+        .map(|color| {
+            let (r, g, b, _) = color.to_linear_rgba_u8();
+            (r, g, b)
+        })
+        .collect();
+    let edges = [rgb.first().copied(), rgb.last().copied()];
+    let mut colors = rgb.into_iter().map(|(r, g, b)| match color_mode {
+        ColorMode::Ansi256 => Color::Fixed(ansi256(r, g, b)),
+        _ => Color::Rgb(r, g, b),
+    });
+    let (foreground, background) = match config.target {
+        "foreground" => (true, false),
+        "background" => (false, true),
+        "both" => (true, true),
+        target => {
+            log::warn!(
+                "Unknown gradient target `{}`, expected one of `foreground`, `background` or `both`",
+                target
+            );
+            (true, false)
+        }
+    };
+
+    let paint = |mut style: Style, color: Color| {
+        if foreground {
+            style.foreground = Some(color);
+        }
+        if background {
+            style.background = Some(color);
+        }
+        style
+    };
+
+    // Transient prompts stay in scrollback, so they get a single solid color
+    if context.transient {
+        let color = colors.next();
+        let segments = segments
+            .map(|(segment, selected)| match (segment, color) {
+                (Segment::Text(text), Some(color)) if selected => Segment::Text(TextSegment {
+                    value: text.value.clone(),
+                    style: Some(paint(text.style.unwrap_or_default(), color)),
+                }),
+                _ => segment.clone(),
+            })
+            .collect();
+        return (segments, [edges[0], edges[0]]);
+    }
+
+    let mut gradientified = Vec::new();
+    for (segment, selected) in segments {
+        let text = match segment {
+            Segment::Text(text) if selected => text,
+            _ => {
+                gradientified.push(segment.clone());
+                continue;
+            }
+        };
+        let style = text.style.unwrap_or_default();
+
+        for (grapheme, color) in text.value.graphemes(true).zip(&mut colors) {
+            gradientified.push(Segment::Text(TextSegment {
+                value: grapheme.into(),
+                style: Some(paint(style, color)),
+            }));
+        }
+    }
+    (gradientified, edges)
+}
+
+/// The `gradient_start_style` and `gradient_end_style` style variables, and the
+/// `gradient_start_color` and `gradient_end_color` colors in them
+///
+/// They are plain foreground styles with the colors `gradientify_segments`
+/// gives the first and the last grapheme, so separators around a gradient (e.g.
+/// powerline arrows) can blend in with it. The colors can be used anywhere in
+/// a style string, e.g. `bg:$gradient_end_color`.
+pub fn edge_styles(
+    context: &Context,
+    gradient: &Gradient,
+    config: &GradientConfig,
+) -> [(&'static str, String); 4] {
+    let color_mode = color_mode(context, config.color_mode);
+    let offset = phase_offset(context, config.phase);
+
+    let colors = sample_gradient(gradient, 2, config.direction, offset);
+    [
+        (
+            "gradient_start_style",
+            foreground_style(color_mode, &colors[0]),
+        ),
+        (
+            "gradient_end_style",
+            foreground_style(color_mode, &colors[1]),
+        ),
+        ("gradient_start_color", color_value(color_mode, &colors[0])),
+        ("gradient_end_color", color_value(color_mode, &colors[1])),
+    ]
+}
+
+/// A plain foreground style with the color at `t` of the gradient, from 0 to 1
+///
+/// This is for modules that pick a single color for their whole output, e.g.
+/// the battery module coloring its percentage by the charge.
+pub fn style_at(context: &Context, gradient: &Gradient, config: &GradientConfig, t: f64) -> String {
+    let (min, max) = gradient.domain();
+    let color = gradient.at(min + t.clamp(0.0, 1.0) * (max - min));
+    foreground_style(color_mode(context, config.color_mode), &color)
+}
+
+/// A plain foreground style with a color derived from a hash of `seed`
+///
+/// See `seeded_color` for how the color is picked.
+pub fn seeded_style(context: &Context, seed: &str, palette: &[&str]) -> String {
+    let color = seeded_color(context, seed, palette);
+    if color.is_empty() {
+        return color;
+    }
+    format!("fg:{}", color)
+}
+
+/// A color derived from a hash of `seed`, for use in a style string
+///
+/// With a `palette`, e.g. for terminals with only 16 colors, the color is
+/// picked from it. Otherwise the hash picks a hue, at a saturation and
+/// lightness that stay readable on dark and light backgrounds.
+pub fn seeded_color(context: &Context, seed: &str, palette: &[&str]) -> String {
+    let hash = fnv1a(seed);
+    if !palette.is_empty() {
+        return palette[(hash % palette.len() as u64) as usize].to_owned();
+    }
+
+    let color = colorgrad::Color::from_hsla((hash % 360) as f64, 0.7, 0.55, 1.0);
+    color_value(color_mode(context, "auto"), &color)
+}
+
+fn foreground_style(color_mode: ColorMode, color: &colorgrad::Color) -> String {
+    match color_mode {
+        ColorMode::None => String::new(),
+        _ => format!("fg:{}", color_value(color_mode, color)),
+    }
+}
+
+/// The color as it is written in a style string, or nothing without colors
+fn color_value(color_mode: ColorMode, color: &colorgrad::Color) -> String {
+    let (r, g, b, _) = color.to_linear_rgba_u8();
+    match color_mode {
+        ColorMode::TrueColor => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        ColorMode::Ansi256 => ansi256(r, g, b).to_string(),
+        ColorMode::None => String::new(),
+    }
+}
+
+pub fn style_variable(
+    styles: &[(&str, String)],
+    variable: &str,
+) -> Option<Result<String, StringFormatterError>> {
+    styles
+        .iter()
+        .find(|(name, _)| *name == variable)
+        .map(|(_, style)| Ok(style.clone()))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColorMode {
+    TrueColor,
+    Ansi256,
+    None,
+}
+
+/// Resolves the configured `color_mode`, detecting it from the environment
+/// for `auto`
+///
+/// `NO_COLOR` disables the gradient, and truecolor is assumed when `COLORTERM`
+/// or `TERM` advertise it, or when `TERM` isn't set at all (e.g. on Windows).
+fn color_mode(context: &Context, color_mode: &str) -> ColorMode {
+    match color_mode {
+        "truecolor" => return ColorMode::TrueColor,
+        "ansi256" => return ColorMode::Ansi256,
+        "none" => return ColorMode::None,
+        "auto" => {}
+        _ => log::warn!(
+            "Unknown gradient color_mode `{}`, expected one of `auto`, `truecolor`, `ansi256` or `none`",
+            color_mode
+        ),
+    }
+
+    let is_set = |name| context.get_env(name).filter(|value| !value.is_empty());
+    if is_set("NO_COLOR").is_some() {
+        return ColorMode::None;
+    }
+    if let Some("truecolor" | "24bit") = is_set("COLORTERM").as_deref() {
+        return ColorMode::TrueColor;
+    }
+    match is_set("TERM") {
+        Some(term) if !term.contains("direct") => ColorMode::Ansi256,
+        _ => ColorMode::TrueColor,
+    }
+}
+
+/// Finds the xterm-256 color closest to the given RGB color, picking from the
+/// 6x6x6 color cube and the grayscale ramp
+fn ansi256(r: u8, g: u8, b: u8) -> u8 {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| (i32::from(a) - i32::from(b)).pow(2))
+            .sum::<i32>()
+    };
+    let nearest_level = |value: u8| {
+        (0..LEVELS.len())
+            .min_by_key(|&i| (i32::from(LEVELS[i]) - i32::from(value)).abs())
+            .unwrap_or_default()
+    };
+
+    let (ri, gi, bi) = (nearest_level(r), nearest_level(g), nearest_level(b));
+    let cube = (LEVELS[ri], LEVELS[gi], LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    let average = (u32::from(r) + u32::from(g) + u32::from(b)) / 3;
+    let gray_step = ((average.saturating_sub(3)) / 10).min(23);
+    let gray_level = (8 + 10 * gray_step) as u8;
+    let gray_index = 232 + gray_step as usize;
+
+    if distance((gray_level, gray_level, gray_level)) < distance(cube) {
+        gray_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+/// Replaces every fill segment with a text segment of the width it expands to
+///
+/// This mirrors how fills are laid out when the prompt is printed: the columns
+/// left on a line are split evenly between the fills of that line. Expanding
+/// them up front lets the gradient be spread over the columns they take up.
+pub fn expand_fills(segments: &[Segment], term_width: Option<usize>) -> Vec<Segment> {
+    segments
+        .split_inclusive(|segment| {
+            matches!(segment, Segment::LineTerm | Segment::ConditionalLineTerm(_))
+        })
+        .flat_map(|line| {
+            let fills = line
+                .iter()
+                .filter(|segment| matches!(segment, Segment::Fill(_)))
+                .count();
+            let used: usize = line
+                .iter()
+                .filter(|segment| !matches!(segment, Segment::Fill(_)))
+                .map(Segment::width_graphemes)
+                .sum();
+            let fill_size = term_width
+                .filter(|width| *width > used)
+                .map(|width| (width - used) / fills.max(1));
+
+            line.iter().flat_map(move |segment| match segment {
+                Segment::Fill(fill) => fill
+                    .expanded_text(fill_size)
+                    .into_iter()
+                    .map(Segment::Text)
+                    .collect(),
+                _ => vec![segment.clone()],
+            })
+        })
+        .collect()
+}
+
+/// How far along the gradient sampling starts, as a fraction of its length
+///
+/// - `static` always starts at the beginning of the gradient
+/// - `time` slides along the gradient with the second of the current minute
+/// - `random` starts at a new random position on every render
+fn phase_offset(context: &Context, phase: &str) -> f64 {
+    match phase {
+        "static" => 0.0,
+        "time" => f64::from(context.get_now().second()) / 60.0,
+        "random" => context.random_gradient_phase(),
+        _ => {
+            log::warn!(
+                "Unknown gradient phase `{}`, expected one of `static`, `time` or `random`",
+                phase
+            );
+            0.0
+        }
+    }
+}
+
+/// Samples `n` evenly spaced colors from the gradient
+///
+/// - `forward` runs from the start to the end of the gradient
+/// - `reverse` runs from the end to the start
+/// - `mirror` runs from the start to the end and back again
+///
+/// A non-zero `offset` shifts every position along the gradient, wrapping
+/// around to its start once the end is reached.
+fn sample_gradient(
+    gradient: &Gradient,
+    n: usize,
+    direction: &str,
+    offset: f64,
+) -> Vec<colorgrad::Color> {
+    let position: fn(f64) -> f64 = match direction {
+        "forward" => |t| t,
+        "reverse" => |t| 1.0 - t,
+        "mirror" => |t| 1.0 - (2.0 * t - 1.0).abs(),
+        _ => {
+            log::warn!(
+                "Unknown gradient direction `{}`, expected one of `forward`, `reverse` or `mirror`",
+                direction
+            );
+            |t| t
+        }
+    };
+
+    let (min, max) = gradient.domain();
+    let last = n.saturating_sub(1).max(1) as f64;
+    (0..n)
+        .map(|i| {
+            let mut t = position(i as f64 / last);
+            if offset != 0.0 {
+                t = (t + offset).rem_euclid(1.0);
+            }
+            gradient.at(min + t * (max - min))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VecOr;
+    use crate::test::default_context;
+    use chrono::{Local, TimeZone};
+    use nu_ansi_term::Style;
+
+    fn red_to_blue() -> Gradient {
+        colorgrad::CustomGradient::new()
+            .html_colors(&["#ff0000", "#0000ff"])
+            .build()
+            .unwrap()
+    }
+
+    fn sampled(gradient: &Gradient, offset: usize, total: usize) -> Color {
+        let (r, g, b, _) = gradient.colors(total)[offset].to_linear_rgba_u8();
+        Color::Rgb(r, g, b)
+    }
+
+    fn values_and_colors(segments: &[Segment]) -> Vec<(&str, Option<Color>)> {
+        segments
+            .iter()
+            .map(|segment| (segment.value(), segment.style().and_then(|s| s.foreground)))
+            .collect()
+    }
+
+    fn assert_gradientified(input: &str, expected_graphemes: &[&str]) {
+        let gradient = red_to_blue();
+        let segments = Segment::from_text(Some(Style::new().bold()), input);
+        let actual = gradientify_segments(
+            &default_context(),
+            &segments,
+            &gradient,
+            &GradientConfig::default(),
+        );
+
+        let expected: Vec<_> = expected_graphemes
+            .iter()
+            .enumerate()
+            .map(|(i, grapheme)| {
+                let color = sampled(&gradient, i, expected_graphemes.len());
+                (*grapheme, Some(color))
+            })
+            .collect();
+        assert_eq!(expected, values_and_colors(&actual));
+        assert!(actual
+            .iter()
+            .all(|segment| segment.style().unwrap().is_bold));
+    }
+
+    #[test]
+    fn gradientify_ascii() {
+        assert_gradientified("astronaut", &["a", "s", "t", "r", "o", "n", "a", "u", "t"]);
+    }
+
+    #[test]
+    fn gradientify_emoji() {
+        assert_gradientified("🚀x👩‍🚀", &["🚀", "x", "👩‍🚀"]);
+    }
+
+    #[test]
+    fn gradientify_cjk() {
+        assert_gradientified("宇宙飛行士", &["宇", "宙", "飛", "行", "士"]);
+    }
+
+    #[test]
+    fn gradientify_continues_across_segments() {
+        let gradient = red_to_blue();
+        let segments: Vec<Segment> = [
+            Segment::from_text(None, "ü🚀"),
+            Segment::from_text(None, "宙"),
+        ]
+        .concat();
+        let actual = gradientify_segments(
+            &default_context(),
+            &segments,
+            &gradient,
+            &GradientConfig::default(),
+        );
+
+        let expected = vec![
+            ("ü", Some(sampled(&gradient, 0, 3))),
+            ("🚀", Some(sampled(&gradient, 1, 3))),
+            ("宙", Some(sampled(&gradient, 2, 3))),
+        ];
+        assert_eq!(expected, values_and_colors(&actual));
+    }
+
+    #[test]
+    fn gradientify_spans_whole_gradient() {
+        let gradient = red_to_blue();
+        let segments = Segment::from_text(None, "x".repeat(200));
+        let actual = gradientify_segments(
+            &default_context(),
+            &segments,
+            &gradient,
+            &GradientConfig::default(),
+        );
+
+        assert_eq!(200, actual.len());
+        assert_eq!(
+            Some(Color::Rgb(255, 0, 0)),
+            actual[0].style().unwrap().foreground
+        );
+        assert_eq!(
+            Some(Color::Rgb(0, 0, 255)),
+            actual[199].style().unwrap().foreground
+        );
+    }
+
+    fn foreground_colors(segments: &[Segment]) -> Vec<Color> {
+        segments
+            .iter()
+            .filter_map(|segment| segment.style()?.foreground)
+            .collect()
+    }
+
+    fn gradientify_in_direction(input: &str, direction: &str) -> Vec<Color> {
+        let config = GradientConfig {
+            direction,
+            ..GradientConfig::default()
+        };
+        let segments = Segment::from_text(None, input);
+        foreground_colors(&gradientify_segments(
+            &default_context(),
+            &segments,
+            &red_to_blue(),
+            &config,
+        ))
+    }
+
+    #[test]
+    fn gradientify_reverse() {
+        for input in ["astronaut", "ab", "宇宙飛行士🚀"] {
+            let mut forward = gradientify_in_direction(input, "forward");
+            let reverse = gradientify_in_direction(input, "reverse");
+
+            forward.reverse();
+            assert_eq!(forward, reverse, "{input}");
+        }
+
+        // A single grapheme starts at the end of the gradient
+        assert_eq!(
+            vec![Color::Rgb(0, 0, 255)],
+            gradientify_in_direction("a", "reverse")
+        );
+    }
+
+    #[test]
+    fn gradientify_mirror() {
+        let mirror = gradientify_in_direction("abcde", "mirror");
+        let forward = gradientify_in_direction("abc", "forward");
+
+        assert_eq!(
+            vec![
+                Color::Rgb(255, 0, 0),
+                forward[1],
+                Color::Rgb(0, 0, 255),
+                forward[1],
+                Color::Rgb(255, 0, 0),
+            ],
+            mirror
+        );
+    }
+
+    #[test]
+    fn gradientify_unknown_direction_is_forward() {
+        assert_eq!(
+            gradientify_in_direction("astronaut", "forward"),
+            gradientify_in_direction("astronaut", "sideways")
+        );
+    }
+
+    fn gradientify_with_target(target: &str) -> Vec<Style> {
+        let config = GradientConfig {
+            target,
+            ..GradientConfig::default()
+        };
+        let style = Color::White.on(Color::Black).bold().underline();
+        let segments = Segment::from_text(Some(style), "ab");
+        gradientify_segments(&default_context(), &segments, &red_to_blue(), &config)
+            .iter()
+            .filter_map(Segment::style)
+            .collect()
+    }
+
+    #[test]
+    fn gradientify_background() {
+        let expected = vec![
+            Color::White.on(Color::Rgb(255, 0, 0)).bold().underline(),
+            Color::White.on(Color::Rgb(0, 0, 255)).bold().underline(),
+        ];
+        assert_eq!(expected, gradientify_with_target("background"));
+    }
+
+    #[test]
+    fn gradientify_both() {
+        let expected = vec![
+            Color::Rgb(255, 0, 0)
+                .on(Color::Rgb(255, 0, 0))
+                .bold()
+                .underline(),
+            Color::Rgb(0, 0, 255)
+                .on(Color::Rgb(0, 0, 255))
+                .bold()
+                .underline(),
+        ];
+        assert_eq!(expected, gradientify_with_target("both"));
+    }
+
+    #[test]
+    fn gradientify_foreground() {
+        let expected = vec![
+            Color::Rgb(255, 0, 0).on(Color::Black).bold().underline(),
+            Color::Rgb(0, 0, 255).on(Color::Black).bold().underline(),
+        ];
+        assert_eq!(expected, gradientify_with_target("foreground"));
+        assert_eq!(expected, gradientify_with_target("nowhere"));
+    }
+
+    #[test]
+    fn gradientify_passes_through_line_breaks() {
+        let gradient = red_to_blue();
+        let segments = Segment::from_text(None, "a\nb");
+        let actual = gradientify_segments(
+            &default_context(),
+            &segments,
+            &gradient,
+            &GradientConfig::default(),
+        );
+
+        let expected = vec![
+            ("a", Some(Color::Rgb(255, 0, 0))),
+            ("\n", None),
+            ("b", Some(Color::Rgb(0, 0, 255))),
+        ];
+        assert_eq!(expected, values_and_colors(&actual));
+        assert!(matches!(actual[1], Segment::LineTerm));
+    }
+
+    #[test]
+    fn gradientify_transient_uses_first_color() {
+        let gradient = red_to_blue();
+        let mut context = default_context();
+        context.transient = true;
+        let mut segments = Segment::from_text(None, "ab\ncd");
+        segments.extend(Segment::from_text(Some(Style::new().bold()), "ef"));
+        let red = Color::Rgb(255, 0, 0);
+
+        let actual =
+            gradientify_segments(&context, &segments, &gradient, &GradientConfig::default());
+
+        let expected = vec![
+            ("ab", Some(red)),
+            ("\n", None),
+            ("cd", Some(red)),
+            ("ef", Some(red)),
+        ];
+        assert_eq!(expected, values_and_colors(&actual));
+        assert!(matches!(&actual[3], Segment::Text(text) if text.style == Some(red.bold())));
+    }
+
+    fn rgb_at(gradient: &Gradient, t: f64) -> [u8; 4] {
+        gradient.at(t).to_rgba8()
+    }
+
+    #[test]
+    fn resolves_presets_by_name() {
+        for (name, expected) in [
+            ("viridis", colorgrad::viridis()),
+            ("magma", colorgrad::magma()),
+            ("rainbow", colorgrad::rainbow()),
+            ("sinebow", colorgrad::sinebow()),
+        ] {
+            let actual = preset_gradient(name).unwrap();
+            for t in [0.0, 0.25, 0.5, 1.0] {
+                assert_eq!(rgb_at(&expected, t), rgb_at(&actual, t), "{name} at {t}");
+            }
+        }
+    }
+
+    #[test]
+    fn preset_names_are_normalized() {
+        let expected = colorgrad::rd_yl_bu();
+        for name in ["rd_yl_bu", "RD_YL_BU", "rd-yl-bu", " rd_yl_bu "] {
+            let actual = preset_gradient(name).unwrap();
+            assert_eq!(rgb_at(&expected, 0.5), rgb_at(&actual, 0.5), "{name}");
+        }
+    }
+
+    #[test]
+    fn seeded_gradient_is_stable() {
+        for seed in ["localhost", "prod-db-01", ""] {
+            let first = seeded_gradient(seed);
+            let second = seeded_gradient(seed);
+            for t in [0.0, 0.5, 1.0] {
+                assert_eq!(rgb_at(&first, t), rgb_at(&second, t), "{seed} at {t}");
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_gradient_differs_per_seed() {
+        let a = seeded_gradient("prod-db-01");
+        let b = seeded_gradient("staging-web-02");
+        assert_ne!(rgb_at(&a, 0.0), rgb_at(&b, 0.0));
+        assert_ne!(rgb_at(&a, 0.0), rgb_at(&a, 1.0));
+    }
+
+    #[test]
+    fn seeded_gradient_in_lightness_is_stable() {
+        let first = seeded_gradient_in_lightness("alice", 0.5, 0.75);
+        let second = seeded_gradient_in_lightness("alice", 0.5, 0.75);
+        let other = seeded_gradient_in_lightness("bob", 0.5, 0.75);
+        for t in [0.0, 0.5, 1.0] {
+            assert_eq!(rgb_at(&first, t), rgb_at(&second, t));
+        }
+        assert_ne!(rgb_at(&first, 0.0), rgb_at(&other, 0.0));
+    }
+
+    #[test]
+    fn seeded_gradient_in_lightness_stays_in_range() {
+        for seed in ["alice", "bob", "root", "firstname.lastname", ""] {
+            let gradient = seeded_gradient_in_lightness(seed, 0.4, 0.6);
+            for t in [0.0, 0.5, 1.0] {
+                let (_, _, lightness, _) = gradient.at(t).to_hsla();
+                assert!(
+                    (0.39..=0.61).contains(&lightness),
+                    "{seed} at {t}: {lightness}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn seeded_gradient_in_lightness_swapped_range() {
+        let gradient = seeded_gradient_in_lightness("alice", 0.9, 0.2);
+        for t in [0.0, 0.5, 1.0] {
+            let (_, _, lightness, _) = gradient.at(t).to_hsla();
+            assert!((0.89..=0.91).contains(&lightness), "{t}: {lightness}");
+        }
+    }
+
+    #[test]
+    fn fnv1a_reference_values() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, fnv1a(""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, fnv1a("a"));
+        assert_eq!(0x8594_4171_f739_67e8, fnv1a("foobar"));
+    }
+
+    fn validate_domain(gradient: &str, domain: &[f64]) -> Result<(), Vec<GradientError>> {
+        validate_gradient(&GradientConfig {
+            gradient: VecOr(vec![gradient]),
+            domain: domain.to_vec(),
+            ..GradientConfig::default()
+        })
+    }
+
+    #[test]
+    fn valid_domains() {
+        assert_eq!(Ok(()), validate_domain("#ff0000,#0000ff", &[]));
+        assert_eq!(
+            Ok(()),
+            validate_domain("#ff0000,#00ff00,#0000ff", &[0.0, 10.0])
+        );
+        assert_eq!(
+            Ok(()),
+            validate_domain("#ff0000,#00ff00,#0000ff", &[0.0, 0.0, 1.0])
+        );
+        assert_eq!(Ok(()), validate_domain("#ff0000", &[0.0, 1.0]));
+        // Presets ignore the domain
+        assert_eq!(Ok(()), validate_domain("viridis", &[3.0, 2.0, 1.0]));
+    }
+
+    #[test]
+    fn domain_not_increasing() {
+        let domain = vec![0.0, 200.0, 100.0];
+        assert_eq!(
+            Err(vec![GradientError::DomainOrder {
+                domain: domain.clone()
+            }]),
+            validate_domain("#ff0000,#00ff00,#0000ff", &domain)
+        );
+        // With two stops they may not be equal either
+        assert_eq!(
+            Err(vec![GradientError::DomainOrder {
+                domain: vec![1.0, 1.0]
+            }]),
+            validate_domain("#ff0000,#00ff00,#0000ff", &[1.0, 1.0])
+        );
+    }
+
+    #[test]
+    fn domain_count_mismatch() {
+        assert_eq!(
+            Err(vec![GradientError::DomainCount {
+                domain: vec![0.0, 0.5, 1.0],
+                colors: 2
+            }]),
+            validate_domain("#ff0000,#0000ff", &[0.0, 0.5, 1.0])
+        );
+        assert_eq!(
+            Err(vec![GradientError::DomainCount {
+                domain: vec![0.0],
+                colors: 3
+            }]),
+            validate_domain("#ff0000,#00ff00,#0000ff", &[0.0])
+        );
+    }
+
+    #[test]
+    fn domain_not_finite() {
+        let errors = validate_domain("#ff0000,#0000ff", &[0.0, f64::INFINITY]).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [GradientError::DomainNotFinite { .. }]
+        ));
+
+        let errors = validate_domain("#ff0000,#0000ff", &[f64::NAN, 1.0]).unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                GradientError::DomainNotFinite { .. },
+                GradientError::DomainOrder { .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn domain_errors_are_reported_together() {
+        let errors = validate_domain("#ff0000,#0000ff", &[3.0, 2.0, 1.0]).unwrap_err();
+        assert_eq!(
+            vec![
+                "`domain = [3.0, 2.0, 1.0]` has 3 stops, expected 2 or one for each of the 2 colors",
+                "`domain = [3.0, 2.0, 1.0]` is not in increasing order",
+            ],
+            errors.iter().map(ToString::to_string).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn invalid_domain_keeps_colors() {
+        let gradient = build_gradient(&GradientConfig {
+            gradient: VecOr(vec!["#ff0000,#0000ff"]),
+            domain: vec![0.0, 200.0, 100.0],
+            ..GradientConfig::default()
+        });
+
+        assert_eq!([255, 0, 0, 255], rgb_at(&gradient, 0.0));
+        assert_eq!([0, 0, 255, 255], rgb_at(&gradient, 1.0));
+    }
+
+    #[test]
+    fn unknown_preset() {
+        assert!(preset_gradient("not_a_gradient").is_none());
+        assert!(preset_gradient("").is_none());
+    }
+
+    #[test]
+    fn preset_names_are_sorted() {
+        let names: Vec<_> = preset_names().collect();
+        let mut sorted = names.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, names);
+    }
+
+    #[test]
+    fn quantizes_to_ansi256() {
+        assert_eq!(196, ansi256(255, 0, 0));
+        assert_eq!(21, ansi256(0, 0, 255));
+        assert_eq!(16, ansi256(0, 0, 0));
+        assert_eq!(231, ansi256(255, 255, 255));
+        assert_eq!(244, ansi256(128, 128, 128));
+        assert_eq!(209, ansi256(250, 140, 100));
+    }
+
+    #[test]
+    fn gradientify_ansi256_colors() {
+        let config = GradientConfig {
+            color_mode: "ansi256",
+            ..GradientConfig::default()
+        };
+        let segments = Segment::from_text(None, "ab");
+        let actual = gradientify_segments(&default_context(), &segments, &red_to_blue(), &config);
+
+        assert_eq!(
+            vec![Color::Fixed(196), Color::Fixed(21)],
+            foreground_colors(&actual)
+        );
+    }
+
+    #[test]
+    fn expand_fills_to_remaining_width() {
+        let segments: Vec<Segment> = [
+            Segment::from_text(None, "ab"),
+            vec![Segment::fill(None, ".")],
+            Segment::from_text(None, "cd"),
+            vec![Segment::fill(None, "-")],
+            Segment::from_text(None, "e\nf"),
+            vec![Segment::fill(None, "=")],
+        ]
+        .concat();
+        let actual: Vec<_> = expand_fills(&segments, Some(11))
+            .iter()
+            .map(|segment| segment.value().to_owned())
+            .collect();
+
+        let expected = vec!["ab", "...", "cd", "---", "e", "\n", "f", "=========="];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn expand_fills_without_width() {
+        let segments = vec![Segment::fill(None, "."), Segment::fill(None, "-")];
+        let actual = expand_fills(&segments, None);
+
+        assert!(actual
+            .iter()
+            .all(|segment| matches!(segment, Segment::Text(_))));
+        assert_eq!(".", actual[0].value());
+        assert_eq!("-", actual[1].value());
+    }
+
+    fn gradientify_with_phase(input: &str, phase: &str, second: u32) -> Vec<Color> {
+        let mut context = default_context();
+        context.now = Some(Local.ymd(2022, 10, 1).and_hms(12, 0, second));
+        let config = GradientConfig {
+            phase,
+            ..GradientConfig::default()
+        };
+        let segments = Segment::from_text(None, input);
+        foreground_colors(&gradientify_segments(
+            &context,
+            &segments,
+            &red_to_blue(),
+            &config,
+        ))
+    }
+
+    #[test]
+    fn gradientify_time_phase() {
+        let forward = gradientify_in_direction("abc", "forward");
+        for second in [0, 30, 59] {
+            assert_eq!(forward, gradientify_with_phase("abc", "static", second));
+        }
+        assert_eq!(forward, gradientify_with_phase("abc", "time", 0));
+
+        // Half a minute in, sampling starts halfway and wraps around to the start
+        assert_eq!(
+            vec![forward[1], forward[0], forward[1]],
+            gradientify_with_phase("abc", "time", 30)
+        );
+        assert_eq!(
+            gradientify_with_phase("abc", "time", 15),
+            gradientify_with_phase("abc", "time", 15)
+        );
+    }
+
+    #[test]
+    fn gradientify_random_phase() {
+        let colors = gradientify_with_phase("astronaut", "random", 0);
+        assert_eq!(9, colors.len());
+        assert!(colors
+            .iter()
+            .all(|color| matches!(color, Color::Rgb(_, 0, _))));
+    }
+
+    #[test]
+    fn gradientify_unknown_phase_is_static() {
+        assert_eq!(
+            gradientify_with_phase("astronaut", "static", 30),
+            gradientify_with_phase("astronaut", "sideways", 30)
+        );
+    }
+
+    #[test]
+    fn offset_wraps_around() {
+        let gradient = red_to_blue();
+        let rgb = |colors: Vec<colorgrad::Color>| -> Vec<_> {
+            colors.iter().map(colorgrad::Color::to_rgba8).collect()
+        };
+
+        assert_eq!(
+            rgb(vec![
+                gradient.at(0.75),
+                gradient.at(0.25),
+                gradient.at(0.75)
+            ]),
+            rgb(sample_gradient(&gradient, 3, "forward", 0.75)),
+        );
+    }
+
+    #[test]
+    fn gradientify_selected_only() {
+        let style = Some(Color::Green.bold());
+        let segments: Vec<_> = [
+            (Segment::from_text(style, "ab"), true),
+            (Segment::from_text(style, " in "), false),
+            (Segment::from_text(style, "c"), true),
+        ]
+        .into_iter()
+        .flat_map(|(segments, selected)| segments.into_iter().map(move |s| (s, selected)))
+        .collect();
+        let actual = gradientify_selected_segments(
+            &default_context(),
+            &segments,
+            &red_to_blue(),
+            &GradientConfig::default(),
+        );
+
+        let gradient = red_to_blue();
+        let expected = vec![
+            ("a", Some(Color::Rgb(255, 0, 0))),
+            ("b", Some(sampled(&gradient, 1, 3))),
+            (" in ", Some(Color::Green)),
+            ("c", Some(Color::Rgb(0, 0, 255))),
+        ];
+        assert_eq!(expected, values_and_colors(&actual));
+        assert!(actual
+            .iter()
+            .all(|segment| segment.style().unwrap().is_bold));
+    }
+
+    fn edge_styles_with(config: GradientConfig) -> Vec<String> {
+        // The styles, followed by the colors in them
+        edge_styles(&default_context(), &red_to_blue(), &config)
+            .iter()
+            .map(|(_, style)| style.clone())
+            .collect()
+    }
+
+    #[test]
+    fn edge_styles_follow_the_config() {
+        assert_eq!(
+            vec!["fg:#ff0000", "fg:#0000ff", "#ff0000", "#0000ff"],
+            edge_styles_with(GradientConfig::default())
+        );
+        assert_eq!(
+            vec!["fg:#0000ff", "fg:#ff0000", "#0000ff", "#ff0000"],
+            edge_styles_with(GradientConfig {
+                direction: "reverse",
+                ..GradientConfig::default()
+            })
+        );
+        assert_eq!(
+            vec!["fg:196", "fg:21", "196", "21"],
+            edge_styles_with(GradientConfig {
+                color_mode: "ansi256",
+                ..GradientConfig::default()
+            })
+        );
+        assert_eq!(
+            vec!["", "", "", ""],
+            edge_styles_with(GradientConfig {
+                color_mode: "none",
+                ..GradientConfig::default()
+            })
+        );
+    }
+
+    #[test]
+    fn random_phase_is_shared_within_a_render() {
+        let context = default_context();
+        let config = GradientConfig {
+            phase: "random",
+            ..GradientConfig::default()
+        };
+        let segments = Segment::from_text(None, "astronaut");
+        let first = gradientify_segments(&context, &segments, &red_to_blue(), &config);
+        let second = gradientify_segments(&context, &segments, &red_to_blue(), &config);
+
+        assert_eq!(foreground_colors(&first), foreground_colors(&second));
+    }
+
+    #[test]
+    fn style_variable_lookup() {
+        let styles = [("a", "bold".to_owned()), ("b", "red".to_owned())];
+        assert_eq!(Some(Ok("red".to_owned())), style_variable(&styles, "b"));
+        assert_eq!(None, style_variable(&styles, "c"));
+    }
+
+    fn midpoint(interpolation: &str, colors: &[&str]) -> (u8, u8, u8, u8) {
+        let config = GradientConfig {
+            gradient: VecOr(colors.to_vec()),
+            interpolation,
+            ..GradientConfig::default()
+        };
+        build_gradient(&config).at(0.5).to_linear_rgba_u8()
+    }
+
+    #[test]
+    fn interpolation_midpoints() {
+        let red_blue = ["#ff0000", "#0000ff"];
+        assert_eq!((55, 0, 55, 255), midpoint("linear-rgb", &red_blue));
+        assert_eq!((67, 22, 93, 255), midpoint("oklab", &red_blue));
+        assert_eq!((55, 0, 55, 255), midpoint("catmull-rom", &red_blue));
+
+        // Catmull-Rom only differs from linear blending with more than two colors
+        let red_green_blue = ["#ff0000", "#00ff00", "#0000ff"];
+        let quarter = |interpolation| {
+            build_gradient(&GradientConfig {
+                gradient: VecOr(red_green_blue.to_vec()),
+                interpolation,
+                ..GradientConfig::default()
+            })
+            .at(0.25)
+            .to_linear_rgba_u8()
+        };
+        assert_eq!((55, 55, 0, 255), quarter("linear-rgb"));
+        assert_eq!((30, 89, 0, 255), quarter("catmull-rom"));
+    }
+
+    #[test]
+    fn unknown_interpolation_is_linear_rgb() {
+        let red_blue = ["#ff0000", "#0000ff"];
+        assert_eq!(
+            midpoint("linear-rgb", &red_blue),
+            midpoint("bezier", &red_blue)
+        );
+    }
+}
@@ -1,6 +1,9 @@
+pub mod filter;
+pub mod gradient;
 pub mod model;
 mod parser;
 pub mod string_formatter;
+mod truncate;
 mod version;
 
 pub use model::{StyleVariableHolder, VariableHolder};
@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::collections::BTreeSet;
 
+use super::filter::Filter;
+
 /// Type that holds a number of variables of type `T`
 pub trait VariableHolder<T> {
     fn get_variables(&self) -> BTreeSet<T>;
@@ -17,12 +19,30 @@ pub struct TextGroup<'a> {
     pub style: Vec<StyleElement<'a>>,
 }
 
+/// A variable whose value is transformed by `filters` before it is rendered
+#[derive(Clone)]
+pub struct FilteredVariable<'a> {
+    pub name: Cow<'a, str>,
+    pub filters: Vec<Filter>,
+}
+
+/// A format string that is rendered instead of `otherwise` if the variable
+/// `condition` isn't empty
+#[derive(Clone)]
+pub struct IfElse<'a> {
+    pub condition: Cow<'a, str>,
+    pub format: Vec<FormatElement<'a>>,
+    pub otherwise: Vec<FormatElement<'a>>,
+}
+
 #[derive(Clone)]
 pub enum FormatElement<'a> {
     Text(Cow<'a, str>),
     Variable(Cow<'a, str>),
+    FilteredVariable(FilteredVariable<'a>),
     TextGroup(TextGroup<'a>),
     Conditional(Vec<FormatElement<'a>>),
+    IfElse(IfElse<'a>),
 }
 
 #[derive(Clone)]
@@ -39,8 +59,19 @@ impl<'a> VariableHolder<Cow<'a, str>> for FormatElement<'a> {
                 variables.insert(var.clone());
                 variables
             }
+            FormatElement::FilteredVariable(variable) => {
+                let mut variables = BTreeSet::new();
+                variables.insert(variable.name.clone());
+                variables
+            }
             FormatElement::TextGroup(textgroup) => textgroup.format.get_variables(),
             FormatElement::Conditional(format) => format.get_variables(),
+            FormatElement::IfElse(if_else) => {
+                let mut variables = if_else.format.get_variables();
+                variables.extend(if_else.otherwise.get_variables());
+                variables.insert(if_else.condition.clone());
+                variables
+            }
             _ => Default::default(),
         }
     }
@@ -91,12 +122,18 @@ impl<'a> StyleVariableHolder<Cow<'a, str>> for Vec<FormatElement<'a>> {
         self.iter().fold(BTreeSet::new(), |mut acc, el| match el {
             FormatElement::TextGroup(textgroup) => {
                 acc.extend(textgroup.style.get_style_variables());
+                acc.extend(textgroup.format.get_style_variables());
                 acc
             }
             FormatElement::Conditional(format) => {
                 acc.extend(format.get_style_variables());
                 acc
             }
+            FormatElement::IfElse(if_else) => {
+                acc.extend(if_else.format.get_style_variables());
+                acc.extend(if_else.otherwise.get_style_variables());
+                acc
+            }
             _ => acc,
         })
     }
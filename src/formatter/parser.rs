@@ -1,49 +1,93 @@
 // Can't rename internal Pest names
 #![allow(clippy::upper_case_acronyms)]
 
-use pest::{error::Error, iterators::Pair, Parser};
+use pest::{
+    error::{Error, ErrorVariant},
+    iterators::Pair,
+    Parser,
+};
 use pest_derive::*;
 
+use super::filter::Filter;
 use super::model::*;
 
 #[derive(Parser)]
 #[grammar = "formatter/spec.pest"]
 struct IdentParser;
 
-fn parse_value(value: Pair<Rule>) -> FormatElement {
-    match value.as_rule() {
+fn parse_value(value: Pair<Rule>) -> Result<FormatElement, Error<Rule>> {
+    Ok(match value.as_rule() {
         Rule::text => FormatElement::Text(parse_text(value).into()),
         Rule::variable => FormatElement::Variable(parse_variable(value).into()),
-        Rule::textgroup => FormatElement::TextGroup(parse_textgroup(value)),
+        Rule::filtered_variable => FormatElement::FilteredVariable(parse_filtered_variable(value)?),
+        Rule::textgroup => FormatElement::TextGroup(parse_textgroup(value)?),
         Rule::conditional => {
-            FormatElement::Conditional(parse_format(value.into_inner().next().unwrap()))
+            FormatElement::Conditional(parse_format(value.into_inner().next().unwrap())?)
         }
+        Rule::if_else => FormatElement::IfElse(parse_if_else(value)?),
         _ => unreachable!(),
-    }
+    })
 }
 
-fn parse_textgroup(textgroup: Pair<Rule>) -> TextGroup {
+fn parse_if_else(if_else: Pair<Rule>) -> Result<IfElse, Error<Rule>> {
+    let mut inner_rules = if_else.into_inner();
+    let condition = inner_rules.next().unwrap();
+    let format = inner_rules.next().unwrap();
+
+    Ok(IfElse {
+        condition: condition.as_str().into(),
+        format: parse_format(format)?,
+        otherwise: inner_rules
+            .next()
+            .map(parse_format)
+            .transpose()?
+            .unwrap_or_default(),
+    })
+}
+
+fn parse_textgroup(textgroup: Pair<Rule>) -> Result<TextGroup, Error<Rule>> {
     let mut inner_rules = textgroup.into_inner();
     let format = inner_rules.next().unwrap();
     let style = inner_rules.next().unwrap();
 
-    TextGroup {
-        format: parse_format(format),
+    Ok(TextGroup {
+        format: parse_format(format)?,
         style: parse_style(style),
-    }
+    })
 }
 
 fn parse_variable(variable: Pair<Rule>) -> &str {
     variable.into_inner().next().unwrap().as_str()
 }
 
+fn parse_filtered_variable(variable: Pair<Rule>) -> Result<FilteredVariable, Error<Rule>> {
+    let mut inner_rules = variable.into_inner();
+    let name = parse_variable(inner_rules.next().unwrap());
+
+    Ok(FilteredVariable {
+        name: name.into(),
+        filters: inner_rules.map(parse_filter).collect::<Result<_, _>>()?,
+    })
+}
+
+fn parse_filter(filter: Pair<Rule>) -> Result<Filter, Error<Rule>> {
+    let span = filter.as_span();
+    let mut inner_rules = filter.into_inner();
+    let name = inner_rules.next().unwrap().as_str();
+    let arg = inner_rules.next().map(parse_text);
+
+    // Unknown filters and invalid arguments are reported like syntax errors
+    Filter::new(name, arg)
+        .map_err(|message| Error::new_from_span(ErrorVariant::CustomError { message }, span))
+}
+
 fn parse_text(text: Pair<Rule>) -> String {
     text.into_inner()
         .flat_map(|pair| pair.as_str().chars())
         .collect()
 }
 
-fn parse_format(format: Pair<Rule>) -> Vec<FormatElement> {
+fn parse_format(format: Pair<Rule>) -> Result<Vec<FormatElement>, Error<Rule>> {
     format.into_inner().map(parse_value).collect()
 }
 
@@ -59,7 +103,7 @@ fn parse_style(style: Pair<Rule>) -> Vec<StyleElement> {
 }
 
 pub fn parse(format: &str) -> Result<Vec<FormatElement>, Error<Rule>> {
-    IdentParser::parse(Rule::expression, format).map(|pairs| {
+    IdentParser::parse(Rule::expression, format).and_then(|pairs| {
         pairs
             .take_while(|pair| pair.as_rule() != Rule::EOI)
             .map(parse_value)
@@ -10,8 +10,10 @@ use crate::config::parse_style_string;
 use crate::context::{Context, Shell};
 use crate::segment::Segment;
 
+use super::filter::{apply_filters, Filter};
 use super::model::*;
 use super::parser::{parse, Rule};
+use super::truncate::MaxWidth;
 
 #[derive(Clone)]
 enum VariableValue<'a> {
@@ -31,6 +33,8 @@ type VariableMapType<'a> =
     BTreeMap<String, Option<Result<VariableValue<'a>, StringFormatterError>>>;
 type StyleVariableMapType<'a> =
     BTreeMap<String, Option<Result<Cow<'a, str>, StringFormatterError>>>;
+/// Segments paired with the name of the variable they were rendered from
+type VariableSegments = Vec<(Segment, Option<String>)>;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum StringFormatterError {
@@ -111,6 +115,9 @@ impl<'a> StringFormatter<'a> {
     ///
     /// - `Some(Ok(_))`: The value of this variable will be displayed in the format string.
     ///
+    /// Variables in style strings that no style mapper has provided are mapped as well, so a
+    /// module's variables can be used in its styles, e.g. `[$branch](fg:$branch_color)`.
+    ///
     #[must_use]
     pub fn map<T, M>(mut self, mapper: M) -> Self
     where
@@ -123,7 +130,7 @@ impl<'a> StringFormatter<'a> {
             .for_each(|(key, value)| {
                 *value = mapper(key).map(|var| var.map(|var| VariableValue::Plain(var.into())));
             });
-        self
+        self.map_unset_styles(&mapper)
     }
 
     /// Maps variable name into a value which is wrapped to prevent escaping later
@@ -145,7 +152,7 @@ impl<'a> StringFormatter<'a> {
                 *value = mapper(key)
                     .map(|var| var.map(|var| VariableValue::NoEscapingPlain(var.into())));
             });
-        self
+        self.map_unset_styles(&mapper)
     }
 
     /// Maps a meta-variable to a format string containing other variables.
@@ -210,6 +217,22 @@ impl<'a> StringFormatter<'a> {
         self
     }
 
+    /// Maps the variables in style strings that are still unset with the mapper of
+    /// the format string's variables
+    fn map_unset_styles<T, M>(mut self, mapper: &M) -> Self
+    where
+        T: Into<Cow<'a, str>>,
+        M: Fn(&str) -> Option<Result<T, StringFormatterError>> + Sync,
+    {
+        self.style_variables
+            .par_iter_mut()
+            .filter(|(_, value)| value.is_none())
+            .for_each(|(key, value)| {
+                *value = mapper(key).map(|var| var.map(std::convert::Into::into));
+            });
+        self
+    }
+
     /// Maps variable name in a style string to its value
     ///
     /// See `StringFormatter::map` for description on the parameters.
@@ -239,27 +262,55 @@ impl<'a> StringFormatter<'a> {
         default_style: Option<Style>,
         context: Option<&Context>,
     ) -> Result<Vec<Segment>, StringFormatterError> {
+        self.parse_with_variables(default_style, context)
+            .map(|segments| segments.into_iter().map(|(segment, _)| segment).collect())
+    }
+
+    /// Parse the format string and consume self, pairing every segment with the
+    /// name of the variable it was rendered from.
+    ///
+    /// Segments of literal text are paired with `None`, while everything rendered
+    /// from a meta variable is paired with the name of the meta variable.
+    ///
+    /// See `StringFormatter::parse` for the errors this method may throw.
+    pub fn parse_with_variables(
+        self,
+        default_style: Option<Style>,
+        context: Option<&Context>,
+    ) -> Result<VariableSegments, StringFormatterError> {
+        fn tag(segments: Vec<Segment>, variable: Option<&str>) -> VariableSegments {
+            segments
+                .into_iter()
+                .map(|segment| (segment, variable.map(str::to_owned)))
+                .collect()
+        }
+
         fn parse_textgroup<'a>(
             textgroup: TextGroup<'a>,
+            parent_style: Option<Style>,
             variables: &'a VariableMapType<'a>,
             style_variables: &'a StyleVariableMapType<'a>,
             context: Option<&Context>,
-        ) -> Result<Vec<Segment>, StringFormatterError> {
-            let style = parse_style(textgroup.style, style_variables, context);
-            parse_format(
-                textgroup.format,
-                style.transpose()?,
-                variables,
-                style_variables,
-                context,
-            )
+        ) -> Result<VariableSegments, StringFormatterError> {
+            let (style, max_width) =
+                parse_style(textgroup.style, parent_style, style_variables, context)?;
+            let segments =
+                parse_format(textgroup.format, style, variables, style_variables, context)?;
+            Ok(match max_width {
+                Some(max_width) => max_width.truncate(segments),
+                None => segments,
+            })
         }
 
         fn parse_style<'a>(
             style: Vec<StyleElement>,
+            parent_style: Option<Style>,
             variables: &'a StyleVariableMapType<'a>,
             context: Option<&Context>,
-        ) -> Option<Result<Style, StringFormatterError>> {
+        ) -> Result<(Option<Style>, Option<MaxWidth>), StringFormatterError> {
+            let has_variables = style
+                .iter()
+                .any(|style| matches!(style, StyleElement::Variable(_)));
             let style_strings = style
                 .into_iter()
                 .map(|style| match style {
@@ -273,13 +324,127 @@ impl<'a> StringFormatter<'a> {
                     }
                 })
                 .collect::<Result<Vec<Cow<str>>, StringFormatterError>>();
-            style_strings
-                .map(|style_strings| {
-                    let style_string: String =
-                        style_strings.iter().flat_map(|s| s.chars()).collect();
-                    parse_style_string(&style_string, context)
+            style_strings.map(|style_strings| {
+                let style_string: String = style_strings.iter().flat_map(|s| s.chars()).collect();
+                let (style_string, max_width) = MaxWidth::take_from(&style_string);
+                // A leading `=` replaces the style of the parent text group instead of
+                // being merged onto it
+                let style = match style_string.trim_start().strip_prefix('=') {
+                    Some(style_string) => parse_style_string(style_string, context),
+                    None => parse_style_string(&style_string, context)
+                        .map(|style| merge_styles(parent_style, style)),
+                };
+                // Variables with values that aren't valid in a style fall back to the
+                // style around the text group, unlike an explicit `none`
+                let is_none = style_string.split_whitespace().any(|token| {
+                    let token = token.trim_start_matches('=');
+                    token.eq_ignore_ascii_case("none") || token.eq_ignore_ascii_case("fg:none")
+                });
+                if style.is_none() && has_variables && !is_none {
+                    log::warn!(
+                        "Invalid style string {:?}, using the default style instead",
+                        style_string
+                    );
+                    return (parent_style, max_width);
+                }
+                (style, max_width)
+            })
+        }
+
+        // Show the conditional format string if all the variables inside are not
+        // none or empty string.
+        fn should_show_elements<'a>(
+            format_elements: &[FormatElement],
+            variables: &'a VariableMapType<'a>,
+        ) -> bool {
+            format_elements.get_variables().iter().any(|var| {
+                variables
+                    .get(var.as_ref())
+                    // false if can't find the variable in format string
+                    .map_or(false, |map_result| {
+                        let map_result = map_result.as_ref();
+                        map_result
+                            .and_then(|result| result.as_ref().ok())
+                            // false if the variable is None or Err, or a meta variable
+                            // that shouldn't show
+                            .map_or(false, |result| match result {
+                                // If the variable is a meta variable, also
+                                // check the format string inside it.
+                                VariableValue::Meta(meta_elements) => {
+                                    let meta_variables = clone_without_meta(variables);
+                                    should_show_elements(meta_elements, &meta_variables)
+                                }
+                                VariableValue::Plain(plain_value) => !plain_value.is_empty(),
+                                VariableValue::NoEscapingPlain(no_escaping_plain_value) => {
+                                    !no_escaping_plain_value.is_empty()
+                                }
+                                VariableValue::Styled(segments) => {
+                                    segments.iter().any(|x| !x.value().is_empty())
+                                }
+                            })
+                    })
+            })
+        }
+
+        fn parse_variable<'a>(
+            name: &str,
+            filters: &[Filter],
+            style: Option<Style>,
+            variables: &'a VariableMapType<'a>,
+            style_variables: &'a StyleVariableMapType<'a>,
+            context: Option<&Context>,
+        ) -> Result<VariableSegments, StringFormatterError> {
+            // Filters transform the text of every segment of the variable
+            let filter_segments = |segments: Vec<Segment>| {
+                segments
+                    .into_iter()
+                    .map(|segment| match segment {
+                        Segment::Text(mut text) if !filters.is_empty() => {
+                            text.value = apply_filters(filters, &text.value);
+                            Segment::Text(text)
+                        }
+                        segment => segment,
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            variables
+                .get(name)
+                .expect("Uncached variable found")
+                .as_ref()
+                .map(|segments| match segments.clone()? {
+                    VariableValue::Styled(segments) => Ok(filter_segments(segments)
+                        .into_iter()
+                        .map(|mut segment| {
+                            // Derive upper style if the style of segments are none.
+                            segment.set_style_if_empty(style);
+                            segment
+                        })
+                        .collect()),
+                    VariableValue::Plain(text) => Ok(Segment::from_text(
+                        style,
+                        shell_prompt_escape(
+                            apply_filters(filters, &text),
+                            match context {
+                                None => Shell::Unknown,
+                                Some(c) => c.shell,
+                            },
+                        ),
+                    )),
+                    VariableValue::NoEscapingPlain(text) => {
+                        Ok(Segment::from_text(style, apply_filters(filters, &text)))
+                    }
+                    VariableValue::Meta(format) => {
+                        let formatter = StringFormatter {
+                            format,
+                            variables: clone_without_meta(variables),
+                            style_variables: style_variables.clone(),
+                        };
+                        formatter.parse(style, context).map(filter_segments)
+                    }
                 })
-                .transpose()
+                .unwrap_or_else(|| Ok(Vec::new()))
+                .map(|segments| tag(segments, Some(name)))
         }
 
         fn parse_format<'a>(
@@ -288,106 +453,43 @@ impl<'a> StringFormatter<'a> {
             variables: &'a VariableMapType<'a>,
             style_variables: &'a StyleVariableMapType<'a>,
             context: Option<&Context>,
-        ) -> Result<Vec<Segment>, StringFormatterError> {
-            let results: Result<Vec<Vec<Segment>>, StringFormatterError> = format
+        ) -> Result<VariableSegments, StringFormatterError> {
+            let results: Result<Vec<VariableSegments>, StringFormatterError> = format
                 .into_iter()
                 .map(|el| {
                     match el {
-                        FormatElement::Text(text) => Ok(Segment::from_text(
-                            style,
-                            shell_prompt_escape(
-                                text,
-                                match context {
-                                    None => Shell::Unknown,
-                                    Some(c) => c.shell,
-                                },
+                        FormatElement::Text(text) => Ok(tag(
+                            Segment::from_text(
+                                style,
+                                shell_prompt_escape(
+                                    text,
+                                    match context {
+                                        None => Shell::Unknown,
+                                        Some(c) => c.shell,
+                                    },
+                                ),
                             ),
+                            None,
                         )),
                         FormatElement::TextGroup(textgroup) => {
                             let textgroup = TextGroup {
                                 format: textgroup.format,
                                 style: textgroup.style,
                             };
-                            parse_textgroup(textgroup, variables, style_variables, context)
+                            parse_textgroup(textgroup, style, variables, style_variables, context)
                         }
-                        FormatElement::Variable(name) => variables
-                            .get(name.as_ref())
-                            .expect("Uncached variable found")
-                            .as_ref()
-                            .map(|segments| match segments.clone()? {
-                                VariableValue::Styled(segments) => Ok(segments
-                                    .into_iter()
-                                    .map(|mut segment| {
-                                        // Derive upper style if the style of segments are none.
-                                        segment.set_style_if_empty(style);
-                                        segment
-                                    })
-                                    .collect()),
-                                VariableValue::Plain(text) => Ok(Segment::from_text(
-                                    style,
-                                    shell_prompt_escape(
-                                        text,
-                                        match context {
-                                            None => Shell::Unknown,
-                                            Some(c) => c.shell,
-                                        },
-                                    ),
-                                )),
-                                VariableValue::NoEscapingPlain(text) => {
-                                    Ok(Segment::from_text(style, text))
-                                }
-                                VariableValue::Meta(format) => {
-                                    let formatter = StringFormatter {
-                                        format,
-                                        variables: clone_without_meta(variables),
-                                        style_variables: style_variables.clone(),
-                                    };
-                                    formatter.parse(style, context)
-                                }
-                            })
-                            .unwrap_or_else(|| Ok(Vec::new())),
+                        FormatElement::Variable(name) => {
+                            parse_variable(&name, &[], style, variables, style_variables, context)
+                        }
+                        FormatElement::FilteredVariable(variable) => parse_variable(
+                            &variable.name,
+                            &variable.filters,
+                            style,
+                            variables,
+                            style_variables,
+                            context,
+                        ),
                         FormatElement::Conditional(format) => {
-                            // Show the conditional format string if all the variables inside are not
-                            // none or empty string.
-                            fn should_show_elements<'a>(
-                                format_elements: &[FormatElement],
-                                variables: &'a VariableMapType<'a>,
-                            ) -> bool {
-                                format_elements.get_variables().iter().any(|var| {
-                                    variables
-                                        .get(var.as_ref())
-                                        // false if can't find the variable in format string
-                                        .map_or(false, |map_result| {
-                                            let map_result = map_result.as_ref();
-                                            map_result
-                                                .and_then(|result| result.as_ref().ok())
-                                                // false if the variable is None or Err, or a meta variable
-                                                // that shouldn't show
-                                                .map_or(false, |result| match result {
-                                                    // If the variable is a meta variable, also
-                                                    // check the format string inside it.
-                                                    VariableValue::Meta(meta_elements) => {
-                                                        let meta_variables =
-                                                            clone_without_meta(variables);
-                                                        should_show_elements(
-                                                            meta_elements,
-                                                            &meta_variables,
-                                                        )
-                                                    }
-                                                    VariableValue::Plain(plain_value) => {
-                                                        !plain_value.is_empty()
-                                                    }
-                                                    VariableValue::NoEscapingPlain(
-                                                        no_escaping_plain_value,
-                                                    ) => !no_escaping_plain_value.is_empty(),
-                                                    VariableValue::Styled(segments) => segments
-                                                        .iter()
-                                                        .any(|x| !x.value().is_empty()),
-                                                })
-                                        })
-                                })
-                            }
-
                             let should_show: bool = should_show_elements(&format, variables);
 
                             if should_show {
@@ -396,6 +498,16 @@ impl<'a> StringFormatter<'a> {
                                 Ok(Vec::new())
                             }
                         }
+                        FormatElement::IfElse(if_else) => {
+                            // Unknown variables are treated as empty
+                            let condition = [FormatElement::Variable(if_else.condition)];
+                            let format = if should_show_elements(&condition, variables) {
+                                if_else.format
+                            } else {
+                                if_else.otherwise
+                            };
+                            parse_format(format, style, variables, style_variables, context)
+                        }
                     }
                 })
                 .collect();
@@ -424,6 +536,27 @@ impl<'a> StyleVariableHolder<String> for StringFormatter<'a> {
     }
 }
 
+/// Adds the colors and attributes of `style` to `parent`, with the colors of
+/// `style` taking precedence
+fn merge_styles(parent: Option<Style>, style: Style) -> Style {
+    let parent = match parent {
+        Some(parent) => parent,
+        None => return style,
+    };
+    Style {
+        foreground: style.foreground.or(parent.foreground),
+        background: style.background.or(parent.background),
+        is_bold: style.is_bold || parent.is_bold,
+        is_dimmed: style.is_dimmed || parent.is_dimmed,
+        is_italic: style.is_italic || parent.is_italic,
+        is_underline: style.is_underline || parent.is_underline,
+        is_blink: style.is_blink || parent.is_blink,
+        is_reverse: style.is_reverse || parent.is_reverse,
+        is_hidden: style.is_hidden || parent.is_hidden,
+        is_strikethrough: style.is_strikethrough || parent.is_strikethrough,
+    }
+}
+
 fn clone_without_meta<'a>(variables: &VariableMapType<'a>) -> VariableMapType<'a> {
     variables
         .iter()
@@ -559,7 +692,8 @@ mod tests {
         const FORMAT_STR: &str = "outer [middle [inner](blue)](red bold)";
         let outer_style = Some(Color::Green.normal());
         let middle_style = Some(Color::Red.bold());
-        let inner_style = Some(Color::Blue.normal());
+        // Nested text groups are styled on top of their parent
+        let inner_style = Some(Color::Blue.bold());
 
         let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
         let result = formatter.parse(outer_style, None).unwrap();
@@ -569,6 +703,81 @@ mod tests {
         match_next!(result_iter, "inner", inner_style);
     }
 
+    #[test]
+    fn test_nested_textgroup_merged_style() {
+        const FORMAT_STR: &str = "[a [b](underline) [c](red bg:blue) [d]()](bold green)";
+        let outer_style = Color::Green.bold();
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a ", Some(outer_style));
+        match_next!(result_iter, "b", Some(outer_style.underline()));
+        match_next!(result_iter, " ", Some(outer_style));
+        match_next!(result_iter, "c", Some(Color::Red.on(Color::Blue).bold()));
+        match_next!(result_iter, " ", Some(outer_style));
+        match_next!(result_iter, "d", Some(outer_style));
+    }
+
+    #[test]
+    fn test_nested_textgroup_absolute_style() {
+        const FORMAT_STR: &str = "[a [b](=underline) [c](= red) [d]($style) [e](none)](bold green)";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_style(|variable| match variable {
+                "style" => Some(Ok("=italic")),
+                _ => None,
+            })
+            .map(empty_mapper);
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a ", Some(Color::Green.bold()));
+        match_next!(result_iter, "b", Some(Style::new().underline()));
+        match_next!(result_iter, " ", Some(Color::Green.bold()));
+        match_next!(result_iter, "c", Some(Color::Red.normal()));
+        match_next!(result_iter, " ", Some(Color::Green.bold()));
+        match_next!(result_iter, "d", Some(Style::new().italic()));
+        match_next!(result_iter, " ", Some(Color::Green.bold()));
+        match_next!(result_iter, "e", None);
+    }
+
+    #[test]
+    fn test_three_level_nested_textgroup() {
+        const FORMAT_STR: &str = "[1[2[3](underline)[3](=dimmed)](bg:blue red)](bold green)";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "1", Some(Color::Green.bold()));
+        match_next!(result_iter, "2", Some(Color::Red.on(Color::Blue).bold()));
+        match_next!(
+            result_iter,
+            "3",
+            Some(Color::Red.on(Color::Blue).bold().underline())
+        );
+        match_next!(result_iter, "3", Some(Style::new().dimmed()));
+    }
+
+    #[test]
+    fn test_nested_textgroup_in_meta_variable() {
+        const FORMAT_STR: &str = "[$all](bold)";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_meta(|var, _| match var {
+                "all" => Some("[$a](red)"),
+                _ => None,
+            })
+            .map(|var| match var {
+                "a" => Some(Ok("a")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a", Some(Color::Red.bold()));
+    }
+
     #[test]
     fn test_styled_variable_as_text() {
         const FORMAT_STR: &str = "[$var](red bold)";
@@ -737,6 +946,334 @@ mod tests {
         match_next!(result_iter, " ", None);
     }
 
+    #[test]
+    fn test_if_else() {
+        const FORMAT_STR: &str =
+            "${if some}$some${else}none${end} ${if none}$none${else}detached${end}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "some" => Some(Ok("$some")),
+                "none" => Some(Ok("")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "$some", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "detached", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_if_without_else() {
+        const FORMAT_STR: &str = "${if some}[on $some](red)${end}${if none} hidden${end}";
+        let red = Some(Color::Red.normal());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "some" => Some(Ok("main")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "on ", red);
+        match_next!(result_iter, "main", red);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_nested_if_else() {
+        const FORMAT_STR: &str =
+            "${if a}${if b}ab${else}a${end}${else}${if b}b${else}(${if b}b${end}$b)none${end}${end}";
+
+        let render = |a: &'static str, b: &'static str| {
+            StringFormatter::new(FORMAT_STR)
+                .unwrap()
+                .map(|var| match var {
+                    "a" => Some(Ok(a)),
+                    "b" => Some(Ok(b)),
+                    _ => None,
+                })
+                .parse(None, None)
+                .unwrap()
+                .iter()
+                .map(Segment::value)
+                .collect::<String>()
+        };
+        assert_eq!(render("x", "y"), "ab");
+        assert_eq!(render("x", ""), "a");
+        assert_eq!(render("", "y"), "b");
+        assert_eq!(render("", ""), "none");
+    }
+
+    #[test]
+    fn test_if_unknown_variable() {
+        const FORMAT_STR: &str = "${if unknown}$unknown${else}fallback${end}";
+
+        let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
+        assert!(formatter.get_variables().contains("unknown"));
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "fallback", None);
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_if_meta_variable() {
+        const FORMAT_STR: &str = "${if all}$all${else}empty${end}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_meta(|var, _| match var {
+                "all" => Some("(\\[$a\\])"),
+                _ => None,
+            })
+            .map(empty_mapper);
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "empty", None);
+    }
+
+    #[test]
+    fn test_escaped_keywords() {
+        const FORMAT_STR: &str = r"\${if a}$else\${else}${else_}\${end}";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| Some(Ok(format!("<{}>", variable))));
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "${if a}", None);
+        match_next!(result_iter, "<else>", None);
+        match_next!(result_iter, "${else}", None);
+        match_next!(result_iter, "<else_>", None);
+        match_next!(result_iter, "${end}", None);
+    }
+
+    #[test]
+    fn test_if_else_parse_error() {
+        // missing end
+        assert!(StringFormatter::new("${if a}$a").is_err());
+        assert!(StringFormatter::new("${if a}$a${else}b").is_err());
+        // else or end without if
+        assert!(StringFormatter::new("a${else}b").is_err());
+        assert!(StringFormatter::new("a${end}").is_err());
+        // end inside of a text group that is outside of the if
+        assert!(StringFormatter::new("${if a}[$a${end}](red)").is_err());
+        // missing condition
+        assert!(StringFormatter::new("${if }a${end}").is_err());
+    }
+
+    #[test]
+    fn test_filtered_variable() {
+        const FORMAT_STR: &str =
+            "[$branch|strip_prefix(feature/)|truncate(5)|upper](red) $user|pad_left(6)|";
+        let red = Some(Color::Red.normal());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "branch" => Some(Ok("feature/login")),
+                "user" => Some(Ok("root")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "LOGIN", red);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "  root", None);
+        match_next!(result_iter, "|", None);
+    }
+
+    #[test]
+    fn test_filter_escaped_argument() {
+        const FORMAT_STR: &str = r"$a|strip_suffix(\)\\) ${env:X}|strip_prefix(\$)|lower";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "a" => Some(Ok(r"(a)\")),
+                "env:X" => Some(Ok("$HOME")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "(a", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "home", None);
+    }
+
+    #[test]
+    fn test_filter_is_not_a_conditional() {
+        // The parentheses after a filter that takes no argument start a conditional
+        const FORMAT_STR: &str = "$a|upper($b) $a|$b";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|var| match var {
+                "a" => Some(Ok("a")),
+                "b" => Some(Ok("b")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let values: String = result.iter().map(Segment::value).collect();
+        assert_eq!(values, "Ab a|b");
+    }
+
+    #[test]
+    fn test_filtered_styled_variable() {
+        const FORMAT_STR: &str = "$segments|upper $meta|truncate(2)";
+        let styled = Some(Color::Blue.bold());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_meta(|var, _| match var {
+                "meta" => Some("[$a](green)"),
+                _ => None,
+            })
+            .map_variables_to_segments(|var| match var {
+                "segments" => Some(Ok(vec![
+                    Segment::from_text(styled, "blue").remove(0),
+                    Segment::from_text(None, "plain").remove(0),
+                ])),
+                _ => None,
+            })
+            .map(|var| match var {
+                "a" => Some(Ok("green")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "BLUE", styled);
+        match_next!(result_iter, "PLAIN", None);
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "gr…", Some(Color::Green.normal()));
+    }
+
+    #[test]
+    fn test_filter_error() {
+        let error = |format| match StringFormatter::new(format) {
+            Err(StringFormatterError::Parse(error)) => error.to_string(),
+            _ => panic!("{:?} should fail to parse", format),
+        };
+        assert!(error("$branch|reverse").contains("Unknown filter `reverse`"));
+        assert!(error("[$a|truncate(x)](red)").contains("`x` is not a number"));
+        assert!(error("($a|upper(1))").contains("doesn't take an argument"));
+    }
+
+    #[test]
+    fn test_variable_in_style_from_mapper() {
+        const FORMAT_STR: &str = "[$branch](bold fg:$color) [x]($style fg:$color)";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_style(|variable| match variable {
+                "style" => Some(Ok("italic")),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "branch" => Some(Ok("main")),
+                "color" => Some(Ok("red")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "main", Some(Color::Red.bold()));
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "x", Some(Color::Red.italic()));
+    }
+
+    #[test]
+    fn test_style_mapper_takes_precedence() {
+        const FORMAT_STR: &str = "[x]($style)";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_style(|variable| match variable {
+                "style" => Some(Ok("green")),
+                _ => None,
+            })
+            .map(|_| Some(Ok("red")));
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "x", Some(Color::Green.normal()));
+    }
+
+    #[test]
+    fn test_invalid_variable_in_style() {
+        const FORMAT_STR: &str = "[a [b](bold fg:$color)](green) [c](fg:$color) [d](bold $none)";
+        let default_style = Some(Color::Blue.normal());
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "color" => Some(Ok("not-a-color")),
+                "none" => Some(Ok("none")),
+                _ => None,
+            });
+        let result = formatter.parse(default_style, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "a ", Some(Color::Green.normal()));
+        match_next!(result_iter, "b", Some(Color::Green.normal()));
+        match_next!(result_iter, " ", default_style);
+        match_next!(result_iter, "c", default_style);
+        match_next!(result_iter, " ", default_style);
+        // An explicit `none` still removes the style
+        match_next!(result_iter, "d", None);
+    }
+
+    #[test]
+    fn test_max_width_textgroup() {
+        const FORMAT_STR: &str =
+            "[$directory [$branch](purple)](bold max_width=12) [$branch](truncate=start max_width=4)";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "directory" => Some(Ok("~/starship")),
+                "branch" => Some(Ok("main")),
+                _ => None,
+            });
+        let result = formatter.parse(None, None).unwrap();
+        let mut result_iter = result.iter();
+        match_next!(result_iter, "~/starship", Some(Style::new().bold()));
+        match_next!(result_iter, " ", Some(Style::new().bold()));
+        match_next!(result_iter, "…", Some(Color::Purple.bold()));
+        match_next!(result_iter, " ", None);
+        match_next!(result_iter, "main", Some(Style::new()));
+        assert!(result_iter.next().is_none());
+    }
+
+    #[test]
+    fn test_max_width_keeps_variable_tags() {
+        const FORMAT_STR: &str = "[$a$b](max_width=4)";
+
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map(|variable| match variable {
+                "a" => Some(Ok("ab")),
+                "b" => Some(Ok("cdef")),
+                _ => None,
+            });
+        let result: Vec<_> = formatter
+            .parse_with_variables(None, None)
+            .unwrap()
+            .into_iter()
+            .map(|(segment, variable)| (segment.value().to_owned(), variable))
+            .collect();
+        assert_eq!(
+            vec![
+                ("ab".to_owned(), Some("a".to_owned())),
+                ("c".to_owned(), Some("b".to_owned())),
+                ("…".to_owned(), Some("b".to_owned())),
+            ],
+            result
+        );
+    }
+
     #[test]
     fn test_variable_holder() {
         const FORMAT_STR: &str = "($a [($b) $c](none $s)) $d [t]($t)";
@@ -752,8 +1289,8 @@ mod tests {
 
     #[test]
     fn test_style_variable_holder() {
-        const FORMAT_STR: &str = "($a [($b) $c](none $s)) $d [t]($t)";
-        let expected_variables = vec!["s", "t"].into_iter().map(String::from).collect();
+        const FORMAT_STR: &str = "($a [($b) $c [n]($n)](none $s)) $d [t]($t)";
+        let expected_variables = vec!["n", "s", "t"].into_iter().map(String::from).collect();
 
         let formatter = StringFormatter::new(FORMAT_STR).unwrap().map(empty_mapper);
         let variables = formatter.get_style_variables();
@@ -832,4 +1369,35 @@ mod tests {
             test
         );
     }
+
+    #[test]
+    fn test_parse_with_variables() {
+        const FORMAT_STR: &str = "[$a](red) in [$meta]($style) !";
+        let formatter = StringFormatter::new(FORMAT_STR)
+            .unwrap()
+            .map_meta(|var, _| match var {
+                "meta" => Some("<$b>"),
+                _ => None,
+            })
+            .map(|var| match var {
+                "a" => Some(Ok("1")),
+                "b" => Some(Ok("2")),
+                _ => None,
+            });
+        let result: Vec<_> = formatter
+            .parse_with_variables(None, None)
+            .unwrap()
+            .into_iter()
+            .map(|(segment, variable)| (segment.value().to_owned(), variable))
+            .collect();
+        let expected = vec![
+            ("1".to_owned(), Some("a".to_owned())),
+            (" in ".to_owned(), None),
+            ("<".to_owned(), Some("meta".to_owned())),
+            ("2".to_owned(), Some("meta".to_owned())),
+            (">".to_owned(), Some("meta".to_owned())),
+            (" !".to_owned(), None),
+        ];
+        assert_eq!(expected, result);
+    }
 }
@@ -0,0 +1,295 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::print::{Grapheme, UnicodeWidthGraphemes};
+use crate::segment::{Segment, TextSegment};
+
+/// The symbol that replaces the part of a text group that was cut off
+const ELLIPSIS: &str = "…";
+
+/// The `max_width=<n>` and `truncate=<start|end>` modifiers of a text group,
+/// e.g. `[$directory$git_branch](bold max_width=40)`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaxWidth {
+    pub width: usize,
+    /// Whether the start of the text group is cut off instead of the end
+    pub from_start: bool,
+}
+
+impl MaxWidth {
+    /// Removes the modifiers from a style string, returning the rest of it
+    pub fn take_from(style_string: &str) -> (String, Option<Self>) {
+        let mut width = None;
+        let mut from_start = false;
+        let tokens: Vec<&str> = style_string
+            .split_whitespace()
+            .filter(|token| {
+                if let Some(value) = token.strip_prefix("max_width=") {
+                    match value.parse() {
+                        Ok(value) => width = Some(value),
+                        Err(_) => log::warn!("Invalid max_width {:?} in style string", value),
+                    }
+                    false
+                } else if let Some(value) = token.strip_prefix("truncate=") {
+                    match value {
+                        "start" => from_start = true,
+                        "end" => from_start = false,
+                        _ => log::warn!("Invalid truncate {:?} in style string", value),
+                    }
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect();
+
+        (
+            tokens.join(" "),
+            width.map(|width| Self { width, from_start }),
+        )
+    }
+
+    /// Cuts the segments off on a grapheme boundary if they are wider than the
+    /// maximum width, ending them with an ellipsis in the style of the segment
+    /// that was cut
+    pub fn truncate<T: Clone>(&self, segments: Vec<(Segment, T)>) -> Vec<(Segment, T)> {
+        let width: usize = segments
+            .iter()
+            .map(|(segment, _)| text_width(segment))
+            .sum();
+        if width <= self.width {
+            return segments;
+        }
+
+        if self.from_start {
+            let mut segments = segments;
+            segments.reverse();
+            let mut truncated = cut(segments, self.width, true);
+            truncated.reverse();
+            truncated
+        } else {
+            cut(segments, self.width, false)
+        }
+    }
+}
+
+fn text_width(segment: &Segment) -> usize {
+    match segment {
+        Segment::Text(text) => text.value.width_graphemes(),
+        _ => 0,
+    }
+}
+
+/// Keeps the segments that fit in `width` columns along with the ellipsis,
+/// with the segments in reverse order if `reversed`
+fn cut<T: Clone>(segments: Vec<(Segment, T)>, width: usize, reversed: bool) -> Vec<(Segment, T)> {
+    let ellipsis_width = ELLIPSIS.width_graphemes();
+    let mut remaining = width.saturating_sub(ellipsis_width);
+    let mut is_cut = false;
+
+    let mut truncated = Vec::new();
+    for (segment, tag) in segments {
+        let text = match segment {
+            // Escape sequences take up no space, and dropping one could leave
+            // the sequence it ends unterminated
+            Segment::Escape(_) => {
+                truncated.push((segment, tag));
+                continue;
+            }
+            _ if is_cut => continue,
+            Segment::Text(text) if text.value.width_graphemes() > remaining => text,
+            Segment::Text(ref text) => {
+                remaining -= text.value.width_graphemes();
+                truncated.push((segment, tag));
+                continue;
+            }
+            _ => {
+                truncated.push((segment, tag));
+                continue;
+            }
+        };
+
+        let mut graphemes: Vec<&str> = text.value.graphemes(true).collect();
+        if reversed {
+            graphemes.reverse();
+        }
+        let mut kept = Vec::new();
+        for grapheme in graphemes {
+            let grapheme_width = Grapheme(grapheme).width();
+            if grapheme_width > remaining {
+                break;
+            }
+            remaining -= grapheme_width;
+            kept.push(grapheme);
+        }
+        if reversed {
+            kept.reverse();
+        }
+
+        if !kept.is_empty() {
+            let value = kept.concat();
+            truncated.push((Segment::Text(TextSegment { value, ..text }), tag.clone()));
+        }
+        if width >= ellipsis_width {
+            let ellipsis = TextSegment {
+                value: ELLIPSIS.to_owned(),
+                style: text.style,
+            };
+            truncated.push((Segment::Text(ellipsis), tag));
+        }
+        is_cut = true;
+    }
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nu_ansi_term::Color;
+
+    fn text(value: &str, color: Color) -> (Segment, ()) {
+        let segment = TextSegment {
+            value: value.to_owned(),
+            style: Some(color.normal()),
+        };
+        (Segment::Text(segment), ())
+    }
+
+    fn values(segments: &[(Segment, ())]) -> Vec<(String, Option<nu_ansi_term::Style>)> {
+        segments
+            .iter()
+            .map(|(segment, _)| (segment.value().to_owned(), segment.style()))
+            .collect()
+    }
+
+    fn max_width(width: usize, from_start: bool) -> MaxWidth {
+        MaxWidth { width, from_start }
+    }
+
+    #[test]
+    fn modifiers_are_taken_from_the_style() {
+        assert_eq!(
+            (
+                "bold fg:red".to_owned(),
+                Some(MaxWidth {
+                    width: 40,
+                    from_start: true
+                })
+            ),
+            MaxWidth::take_from("bold max_width=40 fg:red truncate=start")
+        );
+        assert_eq!(
+            ("=underline".to_owned(), Some(max_width(3, false))),
+            MaxWidth::take_from("=underline max_width=3 truncate=end")
+        );
+        assert_eq!(("".to_owned(), None), MaxWidth::take_from("max_width=x"));
+        assert_eq!(("red".to_owned(), None), MaxWidth::take_from("red"));
+    }
+
+    #[test]
+    fn narrow_groups_are_kept() {
+        let segments = vec![text("abc", Color::Red), text("de", Color::Blue)];
+        assert_eq!(
+            values(&segments),
+            values(&max_width(5, false).truncate(segments.clone()))
+        );
+    }
+
+    #[test]
+    fn truncate_end_keeps_styles() {
+        let segments = vec![
+            text("abc", Color::Red),
+            text("defg", Color::Blue),
+            text("hij", Color::Green),
+        ];
+
+        let red = Some(Color::Red.normal());
+        let blue = Some(Color::Blue.normal());
+        assert_eq!(
+            vec![
+                ("abc".to_owned(), red),
+                ("de".to_owned(), blue),
+                ("…".to_owned(), blue)
+            ],
+            values(&max_width(6, false).truncate(segments.clone()))
+        );
+        // Cut exactly at the end of a segment
+        assert_eq!(
+            vec![("abc".to_owned(), red), ("…".to_owned(), blue)],
+            values(&max_width(4, false).truncate(segments))
+        );
+    }
+
+    #[test]
+    fn truncate_start() {
+        let segments = vec![text("abc", Color::Red), text("defg", Color::Blue)];
+
+        let red = Some(Color::Red.normal());
+        let blue = Some(Color::Blue.normal());
+        assert_eq!(
+            vec![
+                ("…".to_owned(), red),
+                ("c".to_owned(), red),
+                ("defg".to_owned(), blue)
+            ],
+            values(&max_width(6, true).truncate(segments))
+        );
+    }
+
+    #[test]
+    fn mixed_width_content() {
+        // Each of "日本語" takes up two columns, and "e\u{301}" is a single grapheme
+        let segments = vec![text("日本語", Color::Red), text("e\u{301}x", Color::Blue)];
+
+        let red = Some(Color::Red.normal());
+        // The third wide character would end at column 6, with no space left for the ellipsis
+        assert_eq!(
+            vec![("日本".to_owned(), red), ("…".to_owned(), red)],
+            values(&max_width(6, false).truncate(segments.clone()))
+        );
+        assert_eq!(
+            vec![("日本".to_owned(), red), ("…".to_owned(), red)],
+            values(&max_width(5, false).truncate(segments.clone()))
+        );
+        assert_eq!(
+            vec![
+                ("…".to_owned(), red),
+                ("e\u{301}x".to_owned(), Some(Color::Blue.normal()))
+            ],
+            values(&max_width(4, true).truncate(segments))
+        );
+    }
+
+    #[test]
+    fn escapes_are_kept() {
+        let segments = vec![
+            (Segment::escape("\x1b]8;;https://starship.rs\x1b\\"), ()),
+            text("starship", Color::Red),
+            (Segment::escape("\x1b]8;;\x1b\\"), ()),
+            text("rs", Color::Red),
+        ];
+
+        let truncated = max_width(5, false).truncate(segments);
+        let rendered: Vec<_> = truncated
+            .iter()
+            .map(|(segment, _)| match segment {
+                Segment::Escape(escape) => escape.clone(),
+                segment => segment.value().to_owned(),
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                "\x1b]8;;https://starship.rs\x1b\\",
+                "star",
+                "…",
+                "\x1b]8;;\x1b\\"
+            ],
+            rendered
+        );
+    }
+
+    #[test]
+    fn zero_width_drops_all_text() {
+        let segments = vec![text("abc", Color::Red)];
+        assert!(max_width(0, false).truncate(segments).is_empty());
+    }
+}
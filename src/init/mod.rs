@@ -1,3 +1,6 @@
+use crate::config::{ModuleConfig, StarshipConfig};
+use crate::configs::StarshipRootConfig;
+use crate::context::Shell;
 use crate::utils::create_command;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
@@ -100,8 +103,8 @@ pub fn init_stub(shell_name: &str) -> io::Result<()> {
 
     let starship = StarshipPath::init()?;
 
-    match shell_basename {
-        "bash" => print!(
+    match Shell::from_name(shell_basename) {
+        Shell::Bash => print!(
             /*
              * The standard bash bootstrap is:
              *      `source <(starship init bash --print-full-init)`
@@ -149,53 +152,48 @@ pub fn init_stub(shell_name: &str) -> io::Result<()> {
             "#,
             starship.sprint_posix()?
         ),
-        "zsh" => print!(
+        Shell::Zsh => print!(
             r#"source <({} init zsh --print-full-init)"#,
             starship.sprint_posix()?
         ),
-        "fish" => print!(
+        Shell::Fish => print!(
             // Fish does process substitution with pipes and psub instead of bash syntax
             r#"source ({} init fish --print-full-init | psub)"#,
             starship.sprint_posix()?
         ),
-        "powershell" => print!(
+        Shell::PowerShell => print!(
             r#"Invoke-Expression (& {} init powershell --print-full-init | Out-String)"#,
             starship.sprint_pwsh()?
         ),
-        "ion" => print!("eval $({} init ion --print-full-init)", starship.sprint()?),
-        "elvish" => print!(
+        Shell::Ion => print!("eval $({} init ion --print-full-init)", starship.sprint()?),
+        Shell::Elvish => print!(
             r#"eval ({} init elvish --print-full-init | slurp)"#,
             starship.sprint_posix()?
         ),
-        "tcsh" => print!(
+        Shell::Tcsh => print!(
             r#"eval `({} init tcsh --print-full-init)`"#,
             starship.sprint_posix()?
         ),
-        "nu" => print_script(NU_INIT, &StarshipPath::init()?.sprint()?),
-        "xonsh" => print!(
+        Shell::Nu => print_script(NU_INIT, &StarshipPath::init()?.sprint()?),
+        Shell::Xonsh => print!(
             r#"execx($({} init xonsh --print-full-init))"#,
             starship.sprint_posix()?
         ),
-        "cmd" => print_script(CMDEXE_INIT, &StarshipPath::init()?.sprint_cmdexe()?),
-        _ => {
+        Shell::Cmd => print_script(CMDEXE_INIT, &StarshipPath::init()?.sprint_cmdexe()?),
+        Shell::Unknown => {
+            let supported: String = Shell::ALL
+                .iter()
+                .map(|shell| format!("* {}\n", shell.name()))
+                .collect();
             eprintln!(
                 "{0} is not yet supported by starship.\n\
                  For the time being, we support the following shells:\n\
-                 * bash\n\
-                 * elvish\n\
-                 * fish\n\
-                 * ion\n\
-                 * powershell\n\
-                 * tcsh\n\
-                 * zsh\n\
-                 * nu\n\
-                 * xonsh\n\
-                 * cmd\n\
+                 {1}\
                  \n\
                  Please open an issue in the starship repo if you would like to \
                  see support for {0}:\n\
                  https://github.com/starship/starship/issues/new\n",
-                shell_basename
+                shell_basename, supported
             )
         }
     };
@@ -207,16 +205,17 @@ prints out the main initialization script */
 pub fn init_main(shell_name: &str) -> io::Result<()> {
     let starship_path = StarshipPath::init()?;
 
-    match shell_name {
-        "bash" => print_script(BASH_INIT, &starship_path.sprint_posix()?),
-        "zsh" => print_script(ZSH_INIT, &starship_path.sprint_posix()?),
-        "fish" => print_script(FISH_INIT, &starship_path.sprint_posix()?),
-        "powershell" => print_script(PWSH_INIT, &starship_path.sprint_pwsh()?),
-        "ion" => print_script(ION_INIT, &starship_path.sprint()?),
-        "elvish" => print_script(ELVISH_INIT, &starship_path.sprint_posix()?),
-        "tcsh" => print_script(TCSH_INIT, &starship_path.sprint_posix()?),
-        "xonsh" => print_script(XONSH_INIT, &starship_path.sprint_posix()?),
-        _ => {
+    match Shell::from_name(shell_name) {
+        Shell::Bash => print_script(BASH_INIT, &starship_path.sprint_posix()?),
+        Shell::Zsh => print_script(ZSH_INIT, &starship_path.sprint_posix()?),
+        Shell::Fish => print_script(FISH_INIT, &starship_path.sprint_posix()?),
+        Shell::PowerShell => print_script(PWSH_INIT, &starship_path.sprint_pwsh()?),
+        Shell::Ion => print_script(ION_INIT, &starship_path.sprint()?),
+        Shell::Elvish => print_script(ELVISH_INIT, &starship_path.sprint_posix()?),
+        Shell::Tcsh => print_script(TCSH_INIT, &starship_path.sprint_posix()?),
+        Shell::Xonsh => print_script(XONSH_INIT, &starship_path.sprint_posix()?),
+        // The stub prints the whole script of these shells
+        Shell::Nu | Shell::Cmd | Shell::Unknown => {
             println!(
                 "printf \"Shell name detection failed on phase two init.\\n\
                  This probably indicates a bug within starship: please open\\n\
@@ -228,8 +227,22 @@ pub fn init_main(shell_name: &str) -> io::Result<()> {
 }
 
 fn print_script(script: &str, path: &str) {
-    let script = script.replace("::STARSHIP::", path);
-    print!("{}", script);
+    let config = StarshipConfig::initialize();
+    let root_config = config
+        .config
+        .as_ref()
+        .map_or_else(StarshipRootConfig::default, StarshipRootConfig::load);
+    print!("{}", render_script(script, path, &root_config));
+}
+
+fn render_script(script: &str, path: &str, root_config: &StarshipRootConfig) -> String {
+    script
+        .replace("::STARSHIP::", path)
+        .replace(
+            "::SEMANTIC_PROMPT::",
+            &root_config.semantic_prompt.to_string(),
+        )
+        .replace("::OSC7::", &root_config.osc7.to_string())
 }
 
 /* GENERAL INIT SCRIPT NOTES
@@ -244,7 +257,8 @@ to avoid the cost of an additional shell fork every shell draw.
 Note that the init scripts are not in their final form--they are processed by
 `starship init` prior to emitting the final form. In this processing, some tokens
 are replaced, e.g. `::STARSHIP::` is replaced by the full path to the
-starship binary.
+starship binary. `::SEMANTIC_PROMPT::` and `::OSC7::` are replaced by the values
+of the `semantic_prompt` and `osc7` options, which are read when the shell starts.
 */
 
 const BASH_INIT: &str = include_str!("starship.bash");
@@ -270,6 +284,54 @@ const CMDEXE_INIT: &str = include_str!("starship.lua");
 #[cfg(test)]
 mod tests {
     use super::*;
+    fn render_with(script: &str, semantic_prompt: bool, osc7: bool) -> String {
+        let mut root_config = StarshipRootConfig::default();
+        root_config.semantic_prompt = semantic_prompt;
+        root_config.osc7 = osc7;
+        render_script(script, "starship", &root_config)
+    }
+
+    #[test]
+    fn semantic_prompt_token() {
+        let scripts = [
+            (BASH_INIT, "STARSHIP_SEMANTIC_PROMPT="),
+            (ZSH_INIT, "STARSHIP_SEMANTIC_PROMPT="),
+            (FISH_INIT, "set -g STARSHIP_SEMANTIC_PROMPT "),
+        ];
+        for (script, assignment) in scripts {
+            assert!(script.contains("133;C") && script.contains("133;D"));
+
+            let enabled = render_with(script, true, false);
+            assert!(enabled.contains(&format!("{}true\n", assignment)));
+
+            let disabled = render_with(script, false, false);
+            assert!(disabled.contains(&format!("{}false\n", assignment)));
+            assert!(!disabled.contains("::SEMANTIC_PROMPT::"));
+        }
+    }
+
+    #[test]
+    fn osc7_token() {
+        let scripts = [
+            (BASH_INIT, "STARSHIP_OSC7="),
+            (ZSH_INIT, "STARSHIP_OSC7="),
+            (FISH_INIT, "set -g STARSHIP_OSC7 "),
+        ];
+        for (script, assignment) in scripts {
+            let enabled = render_with(script, false, true);
+            assert!(enabled.contains(&format!("{}true\n", assignment)));
+            assert!(enabled.contains("starship prompt --print-osc7"));
+
+            let disabled = render_with(script, false, false);
+            assert!(disabled.contains(&format!("{}false\n", assignment)));
+        }
+
+        let enabled = render_with(PWSH_INIT, false, true);
+        assert!(enabled.contains("$script:ReportCwd = \"true\" -eq \"true\""));
+        assert!(enabled.contains("]9;9;"));
+        assert!(!render_with(PWSH_INIT, false, false).contains("::OSC7::"));
+    }
+
     #[test]
     fn escape_pwsh() -> io::Result<()> {
         let starship_path = StarshipPath {
@@ -24,6 +24,8 @@ starship_preexec() {
     if [ "$STARSHIP_PREEXEC_READY" = "true" ]; then
         STARSHIP_PREEXEC_READY=false
         STARSHIP_START_TIME=$(::STARSHIP:: time)
+        # Mark the start of the output of the command
+        if [[ $STARSHIP_SEMANTIC_PROMPT == true ]]; then printf '\033]133;C\007'; fi
     fi
 
     : "$PREV_LAST_ARG"
@@ -37,6 +39,14 @@ starship_precmd() {
         STARSHIP_PIPE_STATUS=(${BP_PIPESTATUS[@]})
     fi
 
+    # Mark the end of the output of the command that was run, if any
+    if [[ $STARSHIP_SEMANTIC_PROMPT == true && $STARSHIP_PREEXEC_READY == false ]]; then
+        printf '\033]133;D;%s\007' "$STARSHIP_CMD_STATUS"
+    fi
+
+    # Report the current directory to the terminal
+    if [[ $STARSHIP_OSC7 == true ]]; then ::STARSHIP:: prompt --print-osc7; fi
+
     local NUM_JOBS=0
     # Evaluate the number of jobs before running the preserved prompt command, so that tools
     # like z/autojump, which background certain jobs, do not cause spurious background jobs
@@ -99,6 +109,11 @@ else
     fi
 fi
 
+# Whether to mark the prompt and the command output for terminals with shell integration
+STARSHIP_SEMANTIC_PROMPT=::SEMANTIC_PROMPT::
+# Whether to report the current directory to the terminal, e.g. to open new tabs in it
+STARSHIP_OSC7=::OSC7::
+
 # Ensure that $COLUMNS gets set
 shopt -s checkwinsize
 
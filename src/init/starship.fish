@@ -10,6 +10,7 @@ function fish_prompt
     # Account for changes in variable name between v2.7 and v3.0
     set STARSHIP_DURATION "$CMD_DURATION$cmd_duration"
     set STARSHIP_JOBS (count (jobs -p))
+    set STARSHIP_JOBS_NAMES (jobs -c 2>/dev/null)
     if test "$TRANSIENT" = "1"
         # Clear from cursor to end of screen as `commandline -f repaint` does not do this
         # See https://github.com/fish-shell/fish-shell/issues/8418
@@ -17,10 +18,10 @@ function fish_prompt
         if type -q starship_transient_prompt_func
             starship_transient_prompt_func
         else
-            printf "\e[1;32m❯\e[0m "
+            ::STARSHIP:: prompt --transient --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS --jobs-names=$STARSHIP_JOBS_NAMES
         end
     else
-        ::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS
+        ::STARSHIP:: prompt --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS --jobs-names=$STARSHIP_JOBS_NAMES
     end
 end
 
@@ -36,14 +37,15 @@ function fish_right_prompt
     # Account for changes in variable name between v2.7 and v3.0
     set STARSHIP_DURATION "$CMD_DURATION$cmd_duration"
     set STARSHIP_JOBS (count (jobs -p))
+    set STARSHIP_JOBS_NAMES (jobs -c 2>/dev/null)
     if test "$TRANSIENT" = "1"
         if type -q starship_transient_rprompt_func
             starship_transient_rprompt_func
         else
-            printf ""
+            ::STARSHIP:: prompt --right --transient --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS --jobs-names=$STARSHIP_JOBS_NAMES
         end
     else
-        ::STARSHIP:: prompt --right --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS
+        ::STARSHIP:: prompt --right --terminal-width="$COLUMNS" --status=$STARSHIP_CMD_STATUS --pipestatus="$STARSHIP_CMD_PIPESTATUS" --keymap=$STARSHIP_KEYMAP --cmd-duration=$STARSHIP_DURATION --jobs=$STARSHIP_JOBS --jobs-names=$STARSHIP_JOBS_NAMES
     end
 end
 
@@ -55,6 +57,26 @@ builtin functions -e fish_mode_prompt
 
 set -gx STARSHIP_SHELL "fish"
 
+# Mark the start and end of the output of commands for terminals with shell integration
+set -g STARSHIP_SEMANTIC_PROMPT ::SEMANTIC_PROMPT::
+if test "$STARSHIP_SEMANTIC_PROMPT" = true
+    function __starship_mark_output_start --on-event fish_preexec
+        printf '\033]133;C\007'
+    end
+
+    function __starship_mark_output_end --on-event fish_postexec
+        printf '\033]133;D;%s\007' $status
+    end
+end
+
+# Report the current directory to the terminal, e.g. to open new tabs in it
+set -g STARSHIP_OSC7 ::OSC7::
+if test "$STARSHIP_OSC7" = true
+    function __starship_report_cwd --on-event fish_prompt
+        ::STARSHIP:: prompt --print-osc7
+    end
+end
+
 # Transience related functions
 function reset-transient --on-event fish_postexec
     set -g TRANSIENT 0
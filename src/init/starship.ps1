@@ -115,6 +115,12 @@ $null = New-Module starship {
         $jobs = @(Get-Job | Where-Object { $_.State -eq 'Running' }).Count
 
         $cwd = Get-Cwd
+
+        # Report the current directory to Windows Terminal, e.g. to open new tabs in it
+        if ($script:ReportCwd) {
+            [Console]::Write("$([char]0x1B)]9;9;`"$($cwd.Path)`"$([char]0x1B)\")
+        }
+
         $arguments = @(
             "prompt"
             "--path=$($cwd.Path)",
@@ -134,11 +140,18 @@ $null = New-Module starship {
                 # it was an internal Powershell command, otherwise, there MUST be an error code.
                 $lastExitCodeForPrompt = if ($null -ne $lastCmdletError -and $lastCmd.CommandLine -eq $lastCmdletError.Line) { 1 } else { $origLastExitCode }
             }
-            $duration = [math]::Round(($lastCmd.EndExecutionTime - $lastCmd.StartExecutionTime).TotalMilliseconds)
 
-            $arguments += "--cmd-duration=$($duration)"
+            # Empty command lines aren't added to the history, so only a command line that
+            # was just run has a duration. The execution times are precise to the millisecond.
+            if ($lastCmd.Id -ne $script:LastHistoryId) {
+                $script:LastHistoryId = $lastCmd.Id
+                $duration = [math]::Round(($lastCmd.EndExecutionTime - $lastCmd.StartExecutionTime).TotalMilliseconds)
+                $arguments += "--cmd-duration=$($duration)"
+            }
         }
 
+        # PowerShell doesn't report the status of each command in a pipeline, so no
+        # `--pipestatus` is passed
         $arguments += "--status=$($lastExitCodeForPrompt)"
 
         # Invoke Starship
@@ -187,6 +200,8 @@ $null = New-Module starship {
     $ENV:VIRTUAL_ENV_DISABLE_PROMPT=1
 
     $script:TransientPrompt = $false
+    $script:LastHistoryId = -1
+    $script:ReportCwd = "::OSC7::" -eq "true"
     $script:DoesUseLists = (Get-PSReadLineOption).PredictionViewStyle -eq 'ListView'
 
     if ($PSVersionTable.PSVersion.Major -gt 5) {
@@ -7,6 +7,7 @@ shadow!(shadow);
 
 // Lib is present to allow for benchmarking
 pub mod bug_report;
+pub mod cache;
 pub mod config;
 pub mod configs;
 pub mod configure;
@@ -19,6 +20,7 @@ mod modules;
 pub mod print;
 mod segment;
 mod serde_utils;
+pub mod session_state;
 mod utils;
 
 #[cfg(test)]
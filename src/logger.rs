@@ -20,13 +20,7 @@ pub struct StarshipLogger {
 
 impl Default for StarshipLogger {
     fn default() -> Self {
-        let log_dir = env::var_os("STARSHIP_CACHE")
-            .map(PathBuf::from)
-            .unwrap_or_else(|| {
-                utils::home_dir()
-                    .expect("Unable to find home directory")
-                    .join(".cache/starship")
-            });
+        let log_dir = utils::cache_dir();
 
         fs::create_dir_all(&log_dir)
             .unwrap_or_else(|err| panic!("Unable to create log dir {:?}: {:?}!", log_dir, err));
@@ -31,21 +31,43 @@ struct Cli {
 enum Commands {
     /// Create a pre-populated GitHub issue with information about your configuration
     BugReport,
+    /// Manage the cache of rendered modules
+    Cache {
+        #[clap(subcommand)]
+        command: CacheCommands,
+    },
     /// Generate starship shell completions for your shell to stdout
     Completions {
         #[clap(arg_enum)]
         shell: CompletionShell,
     },
     /// Edit the starship configuration
+    #[clap(args_conflicts_with_subcommands = true)]
     Config {
+        #[clap(subcommand)]
+        command: Option<ConfigCommands>,
         /// Configuration key to edit
         #[clap(requires = "value")]
         name: Option<String>,
         /// Value to place into that key
         value: Option<String>,
+        /// Check the configuration for problems instead of editing it
+        #[clap(long, conflicts_with = "name")]
+        validate: bool,
     },
     /// Explains the currently showing modules
-    Explain(Properties),
+    Explain {
+        /// Also print the style of every segment and the gradient of gradient modules
+        #[clap(long)]
+        verbose: bool,
+        #[clap(flatten)]
+        properties: Properties,
+    },
+    /// Work with the gradients of the gradient modules
+    Gradient {
+        #[clap(subcommand)]
+        command: GradientCommands,
+    },
     ///  Prints the shell function used to execute starship
     Init {
         shell: String,
@@ -77,6 +99,9 @@ enum Commands {
         /// Print the default instead of the computed config
         #[clap(short, long)]
         default: bool,
+        /// Apply this profile instead of the one in STARSHIP_PROFILE
+        #[clap(long, conflicts_with = "default")]
+        profile: Option<String>,
         /// Configuration keys to print
         name: Vec<String>,
     },
@@ -88,6 +113,13 @@ enum Commands {
         /// Print the continuation prompt (instead of the standard left prompt)
         #[clap(long, conflicts_with = "right")]
         continuation: bool,
+        /// Print the transient prompt that replaces a prompt once its command was run
+        #[clap(long, conflicts_with = "continuation")]
+        transient: bool,
+        /// Print the escape sequence that reports the current directory to the terminal
+        /// (instead of a prompt)
+        #[clap(long, conflicts_with_all = &["right", "continuation", "transient"])]
+        print_osc7: bool,
         #[clap(flatten)]
         properties: Properties,
     },
@@ -97,7 +129,19 @@ enum Commands {
     #[clap(hide = true)]
     Time,
     /// Prints timings of all active modules
-    Timings(Properties),
+    Timings {
+        /// Print a table, or a JSON array to process in scripts
+        #[clap(long, value_enum, default_value = "text")]
+        format: print::TimingsFormat,
+        /// Sort the modules by duration or by name
+        #[clap(long, value_enum, default_value = "duration")]
+        sort: print::TimingsSort,
+        /// Hide modules that took less than this many milliseconds
+        #[clap(long, value_name = "MS")]
+        threshold: Option<u64>,
+        #[clap(flatten)]
+        properties: Properties,
+    },
     /// Toggle a given starship module
     Toggle {
         /// The name of the module to be toggled
@@ -111,6 +155,49 @@ enum Commands {
     ConfigSchema,
 }
 
+#[derive(Subcommand, Debug)]
+enum CacheCommands {
+    /// Removes every cached module
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// Prints the value of a key in the configuration file
+    Get {
+        /// Configuration key, e.g. `directory.style`
+        name: String,
+    },
+    /// Sets a key in the configuration file
+    Set {
+        /// Configuration key, e.g. `directory.style`
+        name: String,
+        /// Value to place into that key
+        value: String,
+    },
+    /// Removes a key from the configuration file
+    Unset {
+        /// Configuration key, e.g. `directory.style`
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum GradientCommands {
+    /// Prints sample text and a swatch colored with a module's gradient
+    Preview {
+        /// The gradient module whose gradient is previewed
+        #[clap(long, default_value = "gradient_username")]
+        module: String,
+        /// The text to color
+        #[clap(long, default_value = "The quick brown fox jumps over the lazy dog")]
+        text: String,
+        /// The number of blocks in the swatch
+        #[clap(long, default_value_t = 40)]
+        blocks: usize,
+    },
+}
+
 fn main() {
     // Configure the current terminal on windows to support ANSI escape sequences.
     #[cfg(windows)]
@@ -163,17 +250,24 @@ fn main() {
                 init::init_stub(&shell).expect("can't init_stub");
             }
         }
+        Commands::Prompt {
+            properties,
+            print_osc7: true,
+            ..
+        } => print::osc7(properties),
         Commands::Prompt {
             properties,
             right,
             continuation,
+            transient,
+            ..
         } => {
             let target = match (right, continuation) {
                 (true, _) => Target::Right,
                 (_, true) => Target::Continuation,
                 (_, _) => Target::Main,
             };
-            print::prompt(properties, target)
+            print::prompt(properties, target, transient)
         }
         Commands::Module {
             name,
@@ -192,8 +286,23 @@ fn main() {
             }
         }
         Commands::Preset { name, list } => print::preset_command(name, list),
-        Commands::Config { name, value } => {
-            if let Some(name) = name {
+        Commands::Config {
+            command: Some(command),
+            ..
+        } => match command {
+            ConfigCommands::Get { name } => configure::print_configuration_value(&name),
+            ConfigCommands::Set { name, value } => configure::update_configuration(&name, &value),
+            ConfigCommands::Unset { name } => configure::unset_configuration(&name),
+        },
+        Commands::Config {
+            command: None,
+            name,
+            value,
+            validate,
+        } => {
+            if validate {
+                configure::validate_configuration();
+            } else if let Some(name) = name {
                 if let Some(value) = value {
                     configure::update_configuration(&name, &value)
                 }
@@ -202,9 +311,24 @@ fn main() {
                 std::process::exit(1);
             }
         }
-        Commands::PrintConfig { default, name } => configure::print_configuration(default, &name),
+        Commands::Gradient {
+            command:
+                GradientCommands::Preview {
+                    module,
+                    text,
+                    blocks,
+                },
+        } => print::gradient_preview(&module, &text, blocks),
+        Commands::PrintConfig {
+            default,
+            profile,
+            name,
+        } => configure::print_configuration(default, profile.as_deref(), &name),
         Commands::Toggle { name, value } => configure::toggle_configuration(&name, &value),
         Commands::BugReport => bug_report::create(),
+        Commands::Cache {
+            command: CacheCommands::Clear,
+        } => cache::clear(),
         Commands::Time => {
             match SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
@@ -214,8 +338,16 @@ fn main() {
                 None => println!("{}", -1),
             }
         }
-        Commands::Explain(props) => print::explain(props),
-        Commands::Timings(props) => print::timings(props),
+        Commands::Explain {
+            verbose,
+            properties,
+        } => print::explain(properties, verbose),
+        Commands::Timings {
+            format,
+            sort,
+            threshold,
+            properties,
+        } => print::timings(properties, format, sort, threshold),
         Commands::Completions { shell } => generate(
             shell,
             &mut Cli::command(),
@@ -1,7 +1,8 @@
 use crate::context::Shell;
+use crate::formatter::gradient::GradientInfo;
 use crate::segment;
 use crate::segment::{FillSegment, Segment};
-use crate::utils::wrap_colorseq_for_shell;
+use crate::utils::wrap_escapes_for_shell;
 use nu_ansi_term::{AnsiString, AnsiStrings};
 use std::fmt;
 use std::time::Duration;
@@ -9,6 +10,7 @@ use std::time::Duration;
 // List of all modules
 // Default ordering is handled in configs/starship_root.rs
 pub const ALL_MODULES: &[&str] = &[
+    "agents",
     "aws",
     "azure",
     #[cfg(feature = "battery")]
@@ -27,6 +29,8 @@ pub const ALL_MODULES: &[&str] = &[
     "dart",
     "deno",
     "directory",
+    "direnv",
+    "disk_usage",
     "docker_context",
     "dotnet",
     "elixir",
@@ -42,6 +46,8 @@ pub const ALL_MODULES: &[&str] = &[
     "git_status",
     "golang",
     "gradient",
+    "gradient_directory",
+    "gradient_hostname",
     "gradient_username",
     "haskell",
     "helm",
@@ -56,6 +62,7 @@ pub const ALL_MODULES: &[&str] = &[
     "localip",
     "lua",
     "memory_usage",
+    "network",
     "nim",
     "nix_shell",
     "nodejs",
@@ -64,6 +71,7 @@ pub const ALL_MODULES: &[&str] = &[
     "package",
     "perl",
     "php",
+    "proxy",
     "pulumi",
     "purescript",
     "python",
@@ -73,6 +81,7 @@ pub const ALL_MODULES: &[&str] = &[
     "ruby",
     "rust",
     "scala",
+    "secrets",
     "shell",
     "shlvl",
     "singularity",
@@ -82,6 +91,7 @@ pub const ALL_MODULES: &[&str] = &[
     "swift",
     "terraform",
     "time",
+    "todos",
     "username",
     "vagrant",
     "vcsh",
@@ -106,6 +116,12 @@ pub struct Module<'a> {
 
     /// the time it took to compute this module
     pub duration: Duration,
+
+    /// Whether the module was abandoned for exceeding its timeout
+    pub timed_out: bool,
+
+    /// The gradient the module's output was colored with, if any
+    pub gradient: Option<GradientInfo>,
 }
 
 impl<'a> Module<'a> {
@@ -117,6 +133,8 @@ impl<'a> Module<'a> {
             description: desc.to_string(),
             segments: Vec::new(),
             duration: Duration::default(),
+            timed_out: false,
+            gradient: None,
         }
     }
 
@@ -162,9 +180,12 @@ impl<'a> Module<'a> {
         }
 
         match shell {
-            Shell::Bash => ansi_strings_modified(ansi_strings, shell),
-            Shell::Zsh => ansi_strings_modified(ansi_strings, shell),
-            Shell::Tcsh => ansi_strings_modified(ansi_strings, shell),
+            // The styles are only written out here, so that every escape sequence of
+            // them is wrapped, including the ones between the graphemes of gradients
+            Shell::Bash | Shell::Zsh | Shell::Tcsh => {
+                let ansi = AnsiStrings(&ansi_strings).to_string();
+                vec![AnsiString::from(wrap_escapes_for_shell(&ansi, shell))]
+            }
             _ => ansi_strings,
         }
     }
@@ -177,16 +198,6 @@ impl<'a> fmt::Display for Module<'a> {
     }
 }
 
-fn ansi_strings_modified(ansi_strings: Vec<AnsiString>, shell: Shell) -> Vec<AnsiString> {
-    ansi_strings
-        .into_iter()
-        .map(|ansi| {
-            let wrapped = wrap_colorseq_for_shell(ansi.to_string(), shell);
-            AnsiString::from(wrapped)
-        })
-        .collect::<Vec<AnsiString>>()
-}
-
 fn ansi_line<'a, I>(segments: &mut I, term_width: Option<usize>) -> Vec<AnsiString<'a>>
 where
     I: Iterator<Item = &'a Segment>,
@@ -207,7 +218,7 @@ where
             }
         }
 
-        if let Segment::LineTerm = segment {
+        if let Segment::LineTerm | Segment::ConditionalLineTerm(_) = segment {
             break;
         }
     }
@@ -220,10 +231,7 @@ where
             .map(|remaining| remaining / chunks.len());
         chunks
             .into_iter()
-            .flat_map(|(strs, fill)| {
-                strs.into_iter()
-                    .chain(std::iter::once(fill.ansi_string(fill_size)))
-            })
+            .flat_map(|(strs, fill)| strs.into_iter().chain(fill.ansi_strings(fill_size)))
             .chain(current.into_iter())
             .collect::<Vec<AnsiString>>()
     }
@@ -250,6 +258,8 @@ mod tests {
             description: desc.to_string(),
             segments: Vec::new(),
             duration: Duration::default(),
+            timed_out: false,
+            gradient: None,
         };
 
         assert!(module.is_empty());
@@ -265,6 +275,8 @@ mod tests {
             description: desc.to_string(),
             segments: Segment::from_text(None, ""),
             duration: Duration::default(),
+            timed_out: false,
+            gradient: None,
         };
 
         assert!(module.is_empty());
@@ -280,6 +292,8 @@ mod tests {
             description: desc.to_string(),
             segments: Segment::from_text(None, "\n"),
             duration: Duration::default(),
+            timed_out: false,
+            gradient: None,
         };
 
         assert!(!module.is_empty());
@@ -295,8 +309,30 @@ mod tests {
             description: desc.to_string(),
             segments: Segment::from_text(None, " "),
             duration: Duration::default(),
+            timed_out: false,
+            gradient: None,
         };
 
         assert!(!module.is_empty());
     }
+
+    #[test]
+    fn test_escape_segments_take_no_space() {
+        let mut segments = Segment::from_text(None, "a");
+        segments.push(Segment::escape("\u{1b}]9;message\u{7}"));
+        segments.push(Segment::fill(None, "-"));
+        segments.extend(Segment::from_text(None, "b"));
+        let module = Module {
+            config: None,
+            name: "unit_test".to_string(),
+            description: "This is a unit test".to_string(),
+            segments,
+            duration: Duration::default(),
+            timed_out: false,
+            gradient: None,
+        };
+
+        let actual = AnsiStrings(&module.ansi_strings_for_shell(Shell::Bash, Some(6))).to_string();
+        assert_eq!("a\\[\u{1b}]9;message\u{7}\\]----b", actual);
+    }
 }
@@ -0,0 +1,222 @@
+use std::path::Path;
+
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::agents::AgentsConfig;
+use crate::formatter::StringFormatter;
+
+/// The state of the ssh-agent, as reported by `ssh-add -l`
+#[derive(Debug, PartialEq)]
+enum SshAgent {
+    /// The agent has this many identities
+    Loaded(usize),
+    /// The agent has no identities
+    Empty,
+    /// No agent is reachable
+    NoAgent,
+}
+
+/// Creates a module showing whether ssh-agent and gpg-agent have keys loaded
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("agents");
+    let config = AgentsConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let ssh_agent = get_ssh_agent(context);
+    let gpg_cached =
+        (!config.gpg_keygrip.is_empty()).then(|| is_gpg_key_cached(context, config.gpg_keygrip));
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "ssh_symbol" => Some(match ssh_agent {
+                    SshAgent::Loaded(_) => config.ssh_loaded_symbol,
+                    SshAgent::Empty => config.ssh_empty_symbol,
+                    SshAgent::NoAgent => config.ssh_no_agent_symbol,
+                }),
+                "gpg_symbol" => gpg_cached.map(|cached| {
+                    if cached {
+                        config.gpg_cached_symbol
+                    } else {
+                        config.gpg_uncached_symbol
+                    }
+                }),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "ssh_keys" => match ssh_agent {
+                    SshAgent::Loaded(keys) => Some(Ok(keys.to_string())),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `agents`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+fn get_ssh_agent(context: &Context) -> SshAgent {
+    let socket = match context.get_env("SSH_AUTH_SOCK") {
+        Some(socket) if !socket.is_empty() => socket,
+        _ => return SshAgent::NoAgent,
+    };
+
+    // `ssh-add -l` exits with 1 if the agent has no identities and with 2 if
+    // it can't reach the agent. Failed commands have no output, so the two are
+    // told apart by whether the socket of the agent exists.
+    match context.exec_cmd("ssh-add", &["-l"]) {
+        Some(output) => SshAgent::Loaded(
+            output
+                .stdout
+                .lines()
+                .filter(|l| !l.trim().is_empty())
+                .count(),
+        ),
+        None if Path::new(&socket).exists() => SshAgent::Empty,
+        None => SshAgent::NoAgent,
+    }
+}
+
+/// Whether gpg-agent has cached the passphrase of the key with `keygrip`
+fn is_gpg_key_cached(context: &Context, keygrip: &str) -> bool {
+    // The line of the key looks like `S KEYINFO <keygrip> D - - 1 P - - -`,
+    // where the seventh field is `1` if the passphrase is cached
+    let keyinfo = format!("KEYINFO {}", keygrip);
+    context
+        .exec_cmd("gpg-connect-agent", &[keyinfo.as_str(), "/bye"])
+        .map_or(false, |output| {
+            output.stdout.lines().any(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                fields.get(..3) == Some(&["S", "KEYINFO", keygrip][..])
+                    && fields.get(6) == Some(&"1")
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
+    use nu_ansi_term::Color;
+    use std::io;
+
+    const KEYS: &str = "\
+256 SHA256:nThbg6kXUpJWGl7E1IGOCspRomTxdCARLviKw6E5SY8 user@laptop (ED25519)
+3072 SHA256:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU user@work (RSA)
+";
+
+    fn output(stdout: &str) -> Option<CommandOutput> {
+        Some(CommandOutput {
+            stdout: stdout.to_string(),
+            stderr: String::default(),
+        })
+    }
+
+    fn renderer<'a>() -> ModuleRenderer<'a> {
+        ModuleRenderer::new("agents").config(toml::toml! {
+            [agents]
+            disabled = false
+            ssh_no_agent_symbol = "✗ "
+        })
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let actual = ModuleRenderer::new("agents")
+            .env("SSH_AUTH_SOCK", "/tmp/ssh-agent.sock")
+            .cmd("ssh-add -l", output(KEYS))
+            .collect();
+
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn ssh_keys_loaded() {
+        let actual = renderer()
+            .env("SSH_AUTH_SOCK", "/tmp/ssh-agent.sock")
+            .cmd("ssh-add -l", output(KEYS))
+            .collect();
+
+        assert_eq!(Some(Color::Green.bold().paint("🔑 2 ").to_string()), actual);
+    }
+
+    #[test]
+    fn ssh_agent_empty() -> io::Result<()> {
+        let socket = tempfile::NamedTempFile::new()?;
+
+        let actual = renderer()
+            .env("SSH_AUTH_SOCK", socket.path().to_string_lossy())
+            .cmd("ssh-add -l", None)
+            .collect();
+
+        assert_eq!(Some(Color::Green.bold().paint("🔒 ").to_string()), actual);
+        socket.close()
+    }
+
+    #[test]
+    fn ssh_agent_unreachable() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = renderer()
+            .env(
+                "SSH_AUTH_SOCK",
+                dir.path().join("gone.sock").to_string_lossy(),
+            )
+            .cmd("ssh-add -l", None)
+            .collect();
+        assert_eq!(Some(Color::Green.bold().paint("✗ ").to_string()), actual);
+
+        let actual = renderer().cmd("ssh-add -l", output(KEYS)).collect();
+        assert_eq!(Some(Color::Green.bold().paint("✗ ").to_string()), actual);
+        dir.close()
+    }
+
+    #[test]
+    fn gpg_key_cached() {
+        let keygrip = "7D6B5A4C3B2A1908F7E6D5C4B3A291807F6E5D4C";
+        let render = |cached: &str| {
+            ModuleRenderer::new("agents")
+                .config(toml::toml! {
+                    [agents]
+                    disabled = false
+                    gpg_keygrip = keygrip
+                    gpg_uncached_symbol = "🔐 "
+                })
+                .cmd(
+                    "gpg-connect-agent KEYINFO 7D6B5A4C3B2A1908F7E6D5C4B3A291807F6E5D4C /bye",
+                    output(&format!(
+                        "S KEYINFO {} D - - {} P - - -\nOK\n",
+                        keygrip, cached
+                    )),
+                )
+                .collect()
+        };
+
+        assert_eq!(
+            Some(Color::Green.bold().paint("🔏 ").to_string()),
+            render("1")
+        );
+        assert_eq!(
+            Some(Color::Green.bold().paint("🔐 ").to_string()),
+            render("-")
+        );
+    }
+}
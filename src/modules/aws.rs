@@ -1,8 +1,10 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
-use chrono::DateTime;
+use chrono::{DateTime, FixedOffset};
 use ini::Ini;
 use once_cell::unsync::OnceCell;
 
@@ -10,7 +12,7 @@ use super::{Context, Module, ModuleConfig};
 
 use crate::configs::aws::AwsConfig;
 use crate::formatter::StringFormatter;
-use crate::utils::render_time;
+use crate::utils::{self, render_time};
 
 type Profile = String;
 type Region = String;
@@ -119,24 +121,121 @@ fn get_aws_profile_and_region(
 fn get_credentials_duration(
     context: &Context,
     aws_profile: &Option<String>,
+    aws_config: &AwsConfigFile,
     aws_creds: &AwsCredsFile,
 ) -> Option<i64> {
-    let expiration_env_vars = ["AWS_SESSION_EXPIRATION", "AWSUME_EXPIRATION"];
+    let expiration_env_vars = [
+        "AWS_SESSION_EXPIRATION",
+        "AWSUME_EXPIRATION",
+        "AWS_CREDENTIAL_EXPIRATION",
+    ];
     let expiration_date = if let Some(expiration_date) = expiration_env_vars
         .iter()
         .find_map(|env_var| context.get_env(env_var))
     {
-        chrono::DateTime::parse_from_rfc3339(&expiration_date).ok()
+        parse_expiration(&expiration_date)
     } else {
-        let creds = get_creds(context, aws_creds)?;
-        let section = get_profile_creds(creds, aws_profile)?;
-
-        section
-            .get("expiration")
-            .and_then(|expiration| DateTime::parse_from_rfc3339(expiration).ok())
+        get_creds(context, aws_creds)
+            .and_then(|creds| get_profile_creds(creds, aws_profile))
+            .and_then(|section| section.get("expiration"))
+            .and_then(parse_expiration)
+            .or_else(|| get_cached_expiration(context, aws_profile, aws_config))
     }?;
 
-    Some(expiration_date.timestamp() - chrono::Local::now().timestamp())
+    Some(expiration_date.timestamp() - context.get_now().timestamp())
+}
+
+fn parse_expiration(expiration: &str) -> Option<DateTime<FixedOffset>> {
+    // Older versions of the AWS CLI wrote SSO expirations as `...UTC`
+    let expiration = match expiration.strip_suffix("UTC") {
+        Some(expiration) => Cow::Owned(format!("{}Z", expiration)),
+        None => Cow::Borrowed(expiration),
+    };
+    DateTime::parse_from_rfc3339(&expiration).ok()
+}
+
+/// The expiration of the credentials the AWS CLI cached for the profile
+///
+/// Credentials of assumed roles are cached in `~/.aws/cli/cache` and SSO
+/// tokens in `~/.aws/sso/cache`. Their file names are hashes of the request,
+/// so the files are matched on the role and the SSO start URL instead.
+fn get_cached_expiration(
+    context: &Context,
+    aws_profile: &Option<Profile>,
+    aws_config: &AwsConfigFile,
+) -> Option<DateTime<FixedOffset>> {
+    let config = get_config(context, aws_config)?;
+    let section = get_profile_config(config, aws_profile)?;
+    let cache_dir = context.get_home()?.join(".aws");
+
+    if let Some(role_prefix) = section.get("role_arn").and_then(assumed_role_prefix) {
+        return latest_cached_expiration(&cache_dir.join("cli/cache"), |cache| {
+            let credentials = &cache["Credentials"];
+            let arn = cache["AssumedRoleUser"]["Arn"].as_str()?;
+            if !arn.starts_with(&role_prefix) {
+                return None;
+            }
+            credentials["Expiration"]
+                .as_str()
+                .and_then(parse_expiration)
+        });
+    }
+
+    let start_url = section.get("sso_start_url").or_else(|| {
+        let session = section.get("sso_session")?;
+        config
+            .section(Some(format!("sso-session {}", session)))?
+            .get("sso_start_url")
+    })?;
+    latest_cached_expiration(&cache_dir.join("sso/cache"), |cache| {
+        if cache["startUrl"].as_str()? != start_url {
+            return None;
+        }
+        cache["expiresAt"].as_str().and_then(parse_expiration)
+    })
+}
+
+/// The prefix of the ARNs of sessions of the role `role_arn`, e.g.
+/// `arn:aws:sts::123456789012:assumed-role/admin/`
+fn assumed_role_prefix(role_arn: &str) -> Option<String> {
+    let parts: Vec<&str> = role_arn.splitn(6, ':').collect();
+    match parts.as_slice() {
+        ["arn", partition, "iam", _, account, resource] if resource.starts_with("role/") => {
+            let role_name = resource.rsplit('/').next()?;
+            Some(format!(
+                "arn:{}:sts::{}:assumed-role/{}/",
+                partition, account, role_name
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// The latest expiration `expiration` finds in the JSON files in `dir`
+fn latest_cached_expiration<F>(dir: &Path, expiration: F) -> Option<DateTime<FixedOffset>>
+where
+    F: Fn(&serde_json::Value) -> Option<DateTime<FixedOffset>>,
+{
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+        .filter_map(|path| {
+            let contents = utils::read_file(&path).ok()?;
+            serde_json::from_str(&contents)
+                .map_err(|error| log::debug!("Invalid AWS cache file {:?}: {}", path, error))
+                .ok()
+        })
+        .filter_map(|cache| expiration(&cache))
+        .max()
+}
+
+/// The time left until the credentials expire, e.g. `01:29:59`
+fn render_countdown(seconds: i64) -> String {
+    let (minutes, seconds) = (seconds / 60, seconds % 60);
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
 }
 
 fn alias_name(name: Option<String>, aliases: &HashMap<String, &str>) -> Option<String> {
@@ -154,7 +253,13 @@ fn has_credential_process_or_sso(
     let config = get_config(context, aws_config)?;
 
     let section = get_profile_config(config, aws_profile)?;
-    Some(section.contains_key("credential_process") || section.contains_key("sso_start_url"))
+    let keys = [
+        "credential_process",
+        "sso_start_url",
+        "sso_session",
+        "role_arn",
+    ];
+    Some(keys.iter().any(|key| section.contains_key(key)))
 }
 
 fn has_defined_credentials(
@@ -201,15 +306,29 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
-    let duration = {
-        get_credentials_duration(context, &aws_profile, &aws_creds).map(|duration| {
-            if duration > 0 {
-                render_time((duration * 1000) as u128, false)
-            } else {
-                config.expiration_symbol.to_string()
-            }
-        })
+    let remaining = get_credentials_duration(context, &aws_profile, &aws_config, &aws_creds);
+    let (duration, expiration) = match remaining {
+        Some(remaining) if remaining > 0 => (
+            Some(render_time((remaining * 1000) as u128, false)),
+            Some(render_countdown(remaining)),
+        ),
+        Some(_) => (
+            Some(config.expiration_symbol.to_string()),
+            Some(config.expiration_symbol.to_string()),
+        ),
+        None => (None, None),
     };
+    // The style of the lowest threshold the credentials are about to expire within
+    let style = remaining
+        .and_then(|remaining| {
+            config
+                .expiration_styles
+                .iter()
+                .filter(|(threshold, _)| remaining <= *threshold)
+                .min_by_key(|(threshold, _)| *threshold)
+                .map(|(_, style)| *style)
+        })
+        .unwrap_or(config.style);
 
     let mapped_region = alias_name(aws_region, &config.region_aliases);
 
@@ -222,13 +341,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(style)),
                 _ => None,
             })
             .map(|variable| match variable {
                 "profile" => mapped_profile.as_ref().map(Ok),
                 "region" => mapped_region.as_ref().map(Ok),
                 "duration" => duration.as_ref().map(Ok),
+                "expiration" => expiration.as_ref().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -248,9 +368,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
+    use chrono::{DateTime, Duration, Local, SecondsFormat, TimeZone, Utc};
     use nu_ansi_term::Color;
-    use std::fs::{create_dir, File};
+    use std::fs::{self, create_dir, File};
     use std::io::{self, Write};
+    use std::path::Path;
 
     #[test]
     #[ignore]
@@ -960,4 +1082,192 @@ sso_role_name = <AWS-ROLE-NAME>
 
         assert_eq!(expected, actual);
     }
+
+    fn fixed_now() -> DateTime<Local> {
+        Local.ymd(2022, 8, 1).and_hms(12, 0, 0)
+    }
+
+    fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        let mut file = File::create(path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    }
+
+    /// Renders the module for `profile` with `home` as the home directory and
+    /// the AWS config in it
+    fn render_expiration(home: &Path, profile: &str, config: toml::Value) -> Option<String> {
+        ModuleRenderer::new("aws")
+            .env("HOME", home.to_string_lossy())
+            .env("AWS_PROFILE", profile)
+            .env(
+                "AWS_CONFIG_FILE",
+                home.join(".aws/config").to_string_lossy(),
+            )
+            .env(
+                "AWS_SHARED_CREDENTIALS_FILE",
+                home.join(".aws/credentials").to_string_lossy(),
+            )
+            .now(fixed_now())
+            .config(config)
+            .collect()
+    }
+
+    fn expiration_config() -> toml::Value {
+        toml::toml! {
+            [aws]
+            format = "[$profile $expiration]($style)"
+            expiration_styles = [[600, "yellow"], [60, "red"]]
+        }
+    }
+
+    fn expires_in(seconds: i64) -> String {
+        (fixed_now() + Duration::seconds(seconds))
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    }
+
+    #[test]
+    fn expiration_from_sso_cache() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_file(
+            &dir.path().join(".aws/config"),
+            "[profile legacy]
+sso_start_url = https://starship.awsapps.com/start
+[profile session]
+sso_session = starship
+[sso-session starship]
+sso_start_url = https://session.awsapps.com/start
+",
+        )?;
+        let cache_dir = dir.path().join(".aws/sso/cache");
+        write_file(
+            &cache_dir.join("0123.json"),
+            &format!(
+                r#"{{"startUrl": "https://starship.awsapps.com/start", "expiresAt": "{}"}}"#,
+                expires_in(3600).replace('Z', "UTC")
+            ),
+        )?;
+        write_file(
+            &cache_dir.join("4567.json"),
+            &format!(
+                r#"{{"startUrl": "https://session.awsapps.com/start", "expiresAt": "{}"}}"#,
+                expires_in(300)
+            ),
+        )?;
+        // Client registrations of the SSO login share the directory
+        write_file(
+            &cache_dir.join("botocore-client-id.json"),
+            &format!(r#"{{"clientId": "id", "expiresAt": "{}"}}"#, expires_in(60)),
+        )?;
+
+        assert_eq!(
+            Some(format!("{}", Color::Yellow.bold().paint("legacy 01:00:00"))),
+            render_expiration(dir.path(), "legacy", expiration_config())
+        );
+        assert_eq!(
+            Some(format!("{}", Color::Yellow.paint("session 00:05:00"))),
+            render_expiration(dir.path(), "session", expiration_config())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn expiration_from_assumed_role_cache() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_file(
+            &dir.path().join(".aws/config"),
+            "[profile admin]
+role_arn = arn:aws:iam::123456789012:role/ops/admin
+source_profile = default
+",
+        )?;
+        let cache = |arn: &str, seconds: i64| {
+            format!(
+                r#"{{"Credentials": {{"Expiration": "{}"}}, "AssumedRoleUser": {{"Arn": "{}"}}}}"#,
+                expires_in(seconds),
+                arn
+            )
+        };
+        let cache_dir = dir.path().join(".aws/cli/cache");
+        write_file(
+            &cache_dir.join("old.json"),
+            &cache("arn:aws:sts::123456789012:assumed-role/admin/session", -600),
+        )?;
+        write_file(
+            &cache_dir.join("new.json"),
+            &cache("arn:aws:sts::123456789012:assumed-role/admin/session", 30),
+        )?;
+        write_file(
+            &cache_dir.join("other.json"),
+            &cache(
+                "arn:aws:sts::123456789012:assumed-role/viewer/session",
+                3600,
+            ),
+        )?;
+
+        assert_eq!(
+            Some(format!("{}", Color::Red.paint("admin 00:00:30"))),
+            render_expiration(dir.path(), "admin", expiration_config())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn expired_credentials_show_symbol() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_file(
+            &dir.path().join(".aws/credentials"),
+            &format!(
+                "[astronauts]
+aws_access_key_id=dummy
+expiration={}
+",
+                expires_in(-1)
+            ),
+        )?;
+
+        let actual = render_expiration(
+            dir.path(),
+            "astronauts",
+            toml::toml! {
+                [aws]
+                format = "[$profile $expiration $duration]($style)"
+                expiration_symbol = "expired"
+                expiration_styles = [[600, "yellow"], [60, "red"]]
+            },
+        );
+        assert_eq!(
+            Some(format!(
+                "{}",
+                Color::Red.paint("astronauts expired expired")
+            )),
+            actual
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn expiration_far_away_keeps_style() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        write_file(
+            &dir.path().join(".aws/credentials"),
+            &format!(
+                "[astronauts]
+aws_access_key_id=dummy
+expiration={}
+",
+                expires_in(601)
+            ),
+        )?;
+
+        assert_eq!(
+            Some(format!(
+                "{}",
+                Color::Yellow.bold().paint("astronauts 00:10:01")
+            )),
+            render_expiration(dir.path(), "astronauts", expiration_config())
+        );
+        dir.close()
+    }
 }
@@ -1,15 +1,22 @@
 use super::{Context, Module, ModuleConfig};
 use crate::configs::battery::BatteryConfig;
+use crate::configs::gradient::GradientConfig;
 #[cfg(test)]
 use mockall::automock;
 use starship_battery as battery;
 
-use crate::formatter::StringFormatter;
+use crate::formatter::{gradient, StringFormatter};
+use crate::utils::{render_duration, DurationStyle};
 
-/// Creates a module for the battery percentage and charging state
+/// Creates a module for the battery percentage, charging state and the time until
+/// the battery is empty or full
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let battery_status = get_battery_status(context)?;
-    let BatteryStatus { state, percentage } = battery_status;
+    let BatteryStatus {
+        state,
+        percentage,
+        time_remaining,
+    } = battery_status;
 
     let mut module = context.new_module("battery");
     let config: BatteryConfig = BatteryConfig::try_load(module.config);
@@ -22,6 +29,24 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         .iter()
         .find(|display_style| percentage <= display_style.threshold as f32)?;
 
+    // Color by the charge instead of the threshold, keeping the rest of its style
+    let module_style = if config.use_gradient {
+        let gradient_config = GradientConfig {
+            gradient: config.gradient.clone(),
+            ..GradientConfig::default()
+        };
+        let gradient = gradient::build_gradient(&gradient_config);
+        let color = gradient::style_at(
+            context,
+            &gradient,
+            &gradient_config,
+            f64::from(percentage) / 100.0,
+        );
+        format!("{} {}", display_style.style, color)
+    } else {
+        display_style.style.to_string()
+    };
+
     // Parse the format string and build the module
     match StringFormatter::new(config.format) {
         Ok(formatter) => {
@@ -45,11 +70,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     _ => None,
                 })
                 .map_style(|style| match style {
-                    "style" => Some(Ok(display_style.style)),
+                    "style" => Some(Ok(module_style.as_str())),
                     _ => None,
                 })
                 .map(|variable| match variable {
                     "percentage" => Some(Ok(format!("{}%", percentage.round()))),
+                    "time_remaining" if config.display_time => {
+                        time_remaining.map(format_time_remaining).map(Ok)
+                    }
                     _ => None,
                 });
 
@@ -71,12 +99,24 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 }
 
+/// Renders the time left in whole minutes, e.g. `1h24m`
+fn format_time_remaining(seconds: f32) -> String {
+    let minutes = (seconds / 60.0).round().max(1.0) as u128;
+    render_duration(minutes * 60_000, DurationStyle::Compact, false, 2)
+}
+
 fn get_battery_status(context: &Context) -> Option<BatteryStatus> {
     let battery_info = context.battery_info_provider.get_battery_info()?;
     if battery_info.energy_full != 0.0 {
+        let time_remaining = match battery_info.state {
+            battery::State::Charging => battery_info.time_to_full,
+            battery::State::Discharging => battery_info.time_to_empty,
+            _ => None,
+        };
         let battery = BatteryStatus {
             percentage: battery_info.energy / battery_info.energy_full * 100.0,
             state: battery_info.state,
+            time_remaining,
         };
         log::debug!("Battery status: {:?}", battery);
         Some(battery)
@@ -107,16 +147,30 @@ fn merge_battery_states(state1: battery::State, state2: battery::State) -> batte
     }
 }
 
+/// Adds up the times of two batteries, if any of them reports one
+fn add_times(time1: Option<f32>, time2: Option<f32>) -> Option<f32> {
+    match (time1, time2) {
+        (Some(time1), Some(time2)) => Some(time1 + time2),
+        (time1, time2) => time1.or(time2),
+    }
+}
+
 pub struct BatteryInfo {
     energy: f32,
     energy_full: f32,
     state: battery::State,
+    /// Seconds until the batteries are empty, if the platform reports it
+    time_to_empty: Option<f32>,
+    /// Seconds until the batteries are full, if the platform reports it
+    time_to_full: Option<f32>,
 }
 
 #[derive(Debug)]
 struct BatteryStatus {
     percentage: f32,
     state: battery::State,
+    /// Seconds until the batteries are empty while discharging, or full while charging
+    time_remaining: Option<f32>,
 }
 
 #[cfg_attr(test, automock)]
@@ -139,6 +193,8 @@ impl BatteryInfoProvider for BatteryInfoProviderImpl {
                             energy: battery.energy().value,
                             energy_full: battery.energy_full().value,
                             state: battery.state(),
+                            time_to_empty: battery.time_to_empty().map(|time| time.value),
+                            time_to_full: battery.time_to_full().map(|time| time.value),
                         })
                     }
                     Err(e) => {
@@ -156,11 +212,15 @@ impl BatteryInfoProvider for BatteryInfoProviderImpl {
                         energy: 0.0,
                         energy_full: 0.0,
                         state: battery::State::Unknown,
+                        time_to_empty: None,
+                        time_to_full: None,
                     },
                     |mut acc, x| {
                         acc.energy += x.energy;
                         acc.energy_full += x.energy_full;
                         acc.state = merge_battery_states(acc.state, x.state);
+                        acc.time_to_empty = add_times(acc.time_to_empty, x.time_to_empty);
+                        acc.time_to_full = add_times(acc.time_to_full, x.time_to_full);
                         acc
                     },
                 ),
@@ -202,6 +262,8 @@ mod tests {
                 energy: 0.0,
                 energy_full: 0.0,
                 state: battery::State::Full,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -227,6 +289,8 @@ mod tests {
                 energy: 1000.0,
                 energy_full: 1000.0,
                 state: battery::State::Full,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -252,6 +316,8 @@ mod tests {
                 energy: 800.0,
                 energy_full: 1000.0,
                 state: battery::State::Charging,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -277,6 +343,8 @@ mod tests {
                 energy: 800.0,
                 energy_full: 1000.0,
                 state: battery::State::Discharging,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -302,6 +370,8 @@ mod tests {
                 energy: 0.0,
                 energy_full: 1.0,
                 state: battery::State::Unknown,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -327,6 +397,8 @@ mod tests {
                 energy: 0.0,
                 energy_full: 1000.0,
                 state: battery::State::Empty,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -352,6 +424,8 @@ mod tests {
                 energy: 600.0,
                 energy_full: 1000.0,
                 state: battery::State::Full,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -377,6 +451,8 @@ mod tests {
                 energy: 400.0,
                 energy_full: 1000.0,
                 state: battery::State::Discharging,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -402,6 +478,8 @@ mod tests {
                 energy: 129.87654,
                 energy_full: 1000.0,
                 state: battery::State::Discharging,
+                time_to_empty: None,
+                time_to_full: None,
             })
         });
 
@@ -417,4 +495,137 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    /// Renders the time remaining of a battery in `state` with the given times
+    fn render_time_remaining(
+        state: battery::State,
+        time_to_empty: Option<f32>,
+        time_to_full: Option<f32>,
+        display_time: bool,
+    ) -> Option<String> {
+        let mut mock = MockBatteryInfoProvider::new();
+
+        mock.expect_get_battery_info().times(1).returning(move || {
+            Some(BatteryInfo {
+                energy: 800.0,
+                energy_full: 1000.0,
+                state,
+                time_to_empty,
+                time_to_full,
+            })
+        });
+
+        ModuleRenderer::new("battery")
+            .config(toml::toml! {
+                [battery]
+                format = "$percentage( $time_remaining)"
+                display_time = display_time
+                [[battery.display]]
+                threshold = 100
+                style = ""
+            })
+            .battery_info_provider(&mock)
+            .collect()
+    }
+
+    #[test]
+    fn battery_time_to_full_while_charging() {
+        let actual =
+            render_time_remaining(battery::State::Charging, Some(600.0), Some(5040.0), true);
+        assert_eq!(Some(String::from("80% 1h24m")), actual);
+    }
+
+    #[test]
+    fn battery_time_to_empty_while_discharging() {
+        let actual = render_time_remaining(battery::State::Discharging, Some(1790.0), None, true);
+        assert_eq!(Some(String::from("80% 30m")), actual);
+
+        let actual = render_time_remaining(battery::State::Discharging, Some(10.0), None, true);
+        assert_eq!(Some(String::from("80% 1m")), actual);
+    }
+
+    #[test]
+    fn battery_time_remaining_unavailable() {
+        // The state has no time to wait for
+        let actual = render_time_remaining(battery::State::Unknown, Some(600.0), Some(600.0), true);
+        assert_eq!(Some(String::from("80%")), actual);
+
+        // The platform doesn't report it
+        let actual = render_time_remaining(battery::State::Discharging, None, Some(600.0), true);
+        assert_eq!(Some(String::from("80%")), actual);
+
+        let actual = render_time_remaining(battery::State::Discharging, Some(600.0), None, false);
+        assert_eq!(Some(String::from("80%")), actual);
+    }
+
+    #[test]
+    fn battery_times_are_added_up() {
+        assert_eq!(Some(3.0), add_times(Some(1.0), Some(2.0)));
+        assert_eq!(Some(1.0), add_times(Some(1.0), None));
+        assert_eq!(Some(2.0), add_times(None, Some(2.0)));
+        assert_eq!(None, add_times(None, None));
+    }
+
+    fn render_gradient(energy: f32) -> Option<String> {
+        let mut mock = MockBatteryInfoProvider::new();
+
+        mock.expect_get_battery_info().times(1).returning(move || {
+            Some(BatteryInfo {
+                energy,
+                energy_full: 100.0,
+                state: battery::State::Discharging,
+                time_to_empty: None,
+                time_to_full: None,
+            })
+        });
+
+        ModuleRenderer::new("battery")
+            .config(toml::toml! {
+                [battery]
+                format = "[$percentage]($style)"
+                use_gradient = true
+                [[battery.display]]
+                threshold = 100
+                style = "bold"
+            })
+            .battery_info_provider(&mock)
+            .collect()
+    }
+
+    #[test]
+    fn battery_gradient_colors_by_charge() {
+        let paint = |r, g, b, text: &str| Some(Color::Rgb(r, g, b).bold().paint(text).to_string());
+
+        assert_eq!(paint(255, 3, 0, "5%"), render_gradient(5.0));
+        assert_eq!(paint(255, 255, 0, "50%"), render_gradient(50.0));
+        assert_eq!(paint(3, 255, 0, "95%"), render_gradient(95.0));
+    }
+
+    #[test]
+    fn battery_gradient_keeps_thresholds() {
+        let mut mock = MockBatteryInfoProvider::new();
+
+        mock.expect_get_battery_info().times(1).returning(|| {
+            Some(BatteryInfo {
+                energy: 50.0,
+                energy_full: 100.0,
+                state: battery::State::Discharging,
+                time_to_empty: None,
+                time_to_full: None,
+            })
+        });
+
+        let actual = ModuleRenderer::new("battery")
+            .config(toml::toml! {
+                [battery]
+                use_gradient = true
+                [[battery.display]]
+                threshold = 30
+                style = "bold"
+            })
+            .battery_info_provider(&mock)
+            .collect();
+
+        assert_eq!(None, actual);
+    }
 }
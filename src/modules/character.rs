@@ -1,5 +1,6 @@
 use super::{Context, Module, ModuleConfig, Shell};
 use crate::configs::character::CharacterConfig;
+use crate::context::Target;
 use crate::formatter::StringFormatter;
 
 /// Creates a module for the prompt character
@@ -10,6 +11,8 @@ use crate::formatter::StringFormatter;
 ///   (green arrow by default)
 /// - If the exit-code was anything else, it will be formatted with
 ///   `error_symbol` (red arrow by default)
+///
+/// In the continuation prompt it prints `continuation_symbol` instead.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     enum ShellEditMode {
         Normal,
@@ -19,7 +22,6 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         Insert,
     }
     const ASSUMED_MODE: ShellEditMode = ShellEditMode::Insert;
-    // TODO: extend config to more modes
 
     let mut module = context.new_module("character");
     let config: CharacterConfig = CharacterConfig::try_load(module.config);
@@ -34,17 +36,20 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     // Unfortunately, this is also the name of the non-vi default mode.
     // We do some environment detection in src/init.rs to translate.
     // The result: in non-vi fish, keymap is always reported as "insert"
+    // zsh has no keymaps of its own for visual and replace mode, so its init
+    // script reports them with the names fish uses.
     let mode = match (&context.shell, keymap) {
         (Shell::Fish, "default") | (Shell::Zsh, "vicmd") | (Shell::Cmd, "vi") => {
             ShellEditMode::Normal
         }
-        (Shell::Fish, "visual") => ShellEditMode::Visual,
-        (Shell::Fish, "replace") => ShellEditMode::Replace,
+        (Shell::Fish | Shell::Zsh, "visual") => ShellEditMode::Visual,
+        (Shell::Fish | Shell::Zsh, "replace") => ShellEditMode::Replace,
         (Shell::Fish, "replace_one") => ShellEditMode::ReplaceOne,
         _ => ASSUMED_MODE,
     };
 
     let symbol = match mode {
+        _ if context.target == Target::Continuation => config.continuation_symbol,
         ShellEditMode::Normal => config.vimcmd_symbol,
         ShellEditMode::Visual => config.vimcmd_visual_symbol,
         ShellEditMode::Replace => config.vimcmd_replace_symbol,
@@ -80,7 +85,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
 #[cfg(test)]
 mod test {
-    use crate::context::Shell;
+    use crate::context::{Shell, Target};
     use crate::test::ModuleRenderer;
     use nu_ansi_term::Color;
 
@@ -145,6 +150,8 @@ mod test {
     fn zsh_keymap() {
         let expected_vicmd = Some(format!("{} ", Color::Green.bold().paint("❮")));
         let expected_specified = Some(format!("{} ", Color::Green.bold().paint("V")));
+        let expected_visual = Some(format!("{} ", Color::Yellow.bold().paint("❮")));
+        let expected_replace = Some(format!("{} ", Color::Purple.bold().paint("❮")));
         let expected_other = Some(format!("{} ", Color::Green.bold().paint("❯")));
 
         // zle keymap is vicmd
@@ -165,12 +172,43 @@ mod test {
             .collect();
         assert_eq!(expected_specified, actual);
 
-        // zle keymap is other
+        // zle keymap is visual
         let actual = ModuleRenderer::new("character")
             .shell(Shell::Zsh)
             .keymap("visual")
             .collect();
+        assert_eq!(expected_visual, actual);
+
+        // zle keymap is replace
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Zsh)
+            .keymap("replace")
+            .collect();
+        assert_eq!(expected_replace, actual);
+
+        // replace_one can't be detected in zsh
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Zsh)
+            .keymap("replace_one")
+            .collect();
         assert_eq!(expected_other, actual);
+
+        // zle keymap is other
+        for keymap in ["main", "viins", "emacs", ""] {
+            let actual = ModuleRenderer::new("character")
+                .shell(Shell::Zsh)
+                .keymap(keymap)
+                .collect();
+            assert_eq!(expected_other, actual);
+        }
+
+        // unknown keymaps keep the error symbol
+        let actual = ModuleRenderer::new("character")
+            .shell(Shell::Zsh)
+            .keymap("isearch")
+            .status(1)
+            .collect();
+        assert_eq!(Some(format!("{} ", Color::Red.bold().paint("❯"))), actual);
     }
 
     #[test]
@@ -260,4 +298,27 @@ mod test {
             .collect();
         assert_eq!(expected_other, actual);
     }
+
+    #[test]
+    fn continuation_symbol() {
+        let expected = Some(format!("{} ", Color::DarkGray.paint("∙")));
+        let expected_specified = Some(format!("{} ", Color::Blue.paint("+")));
+
+        let actual = ModuleRenderer::new("character")
+            .target(Target::Continuation)
+            .status(1)
+            .keymap("vicmd")
+            .shell(Shell::Zsh)
+            .collect();
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("character")
+            .config(toml::toml! {
+                [character]
+                continuation_symbol = "[+](blue)"
+            })
+            .target(Target::Continuation)
+            .collect();
+        assert_eq!(expected_specified, actual);
+    }
 }
@@ -1,8 +1,10 @@
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::cmd_duration::CmdDurationConfig;
+use crate::context::Target;
 use crate::formatter::StringFormatter;
-use crate::utils::render_time;
+use crate::segment::Segment;
+use crate::utils::{render_duration, DurationStyle};
 
 /// Outputs the time it took the last command to execute
 ///
@@ -21,12 +23,40 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let elapsed = context.get_cmd_duration()?;
+    // Every command is recorded, including those shorter than `min_time`
+    let avg_duration = if config.format.contains("avg_duration") {
+        record_duration(context, elapsed, config.history_size)
+    } else {
+        None
+    };
     let config_min = config.min_time as u128;
 
     if elapsed < config_min {
         return None;
     }
 
+    let style = match config.format_style {
+        "compact" => DurationStyle::Compact,
+        "precise" => DurationStyle::Precise,
+        "digital" => DurationStyle::Digital,
+        unknown => {
+            log::warn!(
+                "Unknown format_style `{}` in [cmd_duration], expected one of `compact`, `precise` or `digital`",
+                unknown
+            );
+            DurationStyle::Compact
+        }
+    };
+    let duration = render_duration(elapsed, style, config.show_milliseconds, config.max_units);
+    let avg_duration = avg_duration.map(|avg_duration| {
+        render_duration(
+            avg_duration,
+            style,
+            config.show_milliseconds,
+            config.max_units,
+        )
+    });
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
@@ -34,23 +64,110 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "duration" => Some(Ok(render_time(elapsed, config.show_milliseconds))),
+                "duration" => Some(Ok(duration.as_str())),
+                "avg_duration" => avg_duration.as_deref().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
     });
 
-    module.set_segments(match parsed {
+    let mut segments = match parsed {
         Ok(segments) => segments,
         Err(error) => {
             log::warn!("Error in module `cmd_duration`: \n{}", error);
             return None;
         }
-    });
+    };
+    if config.notify && config.notify_min_time >= 0 && config.notify_min_time as u128 <= elapsed {
+        segments.extend(terminal_notification(context, &config, &duration));
+    }
+    module.set_segments(segments);
 
     Some(undistract_me(module, &config, elapsed))
 }
 
+/// The escape sequence for a desktop notification that the command finished,
+/// if the terminal supports one
+fn terminal_notification(
+    context: &Context,
+    config: &CmdDurationConfig,
+    duration: &str,
+) -> Option<Segment> {
+    // Only the main prompt notifies, so each command is only announced once
+    if context.target != Target::Main || context.transient {
+        return None;
+    }
+
+    let protocol = match config.notify_protocol {
+        "auto" => detect_notify_protocol(context)?,
+        "osc9" => NotifyProtocol::Osc9,
+        "osc777" => NotifyProtocol::Osc777,
+        unknown => {
+            log::warn!(
+                "Unknown notify_protocol `{}` in [cmd_duration], expected one of `auto`, `osc9` or `osc777`",
+                unknown
+            );
+            return None;
+        }
+    };
+
+    let summary = "Command finished";
+    let body = format!("Command execution took {}", duration);
+    let sequence = match protocol {
+        NotifyProtocol::Osc9 => format!("\u{1b}]9;{}: {}\u{7}", summary, body),
+        NotifyProtocol::Osc777 => format!("\u{1b}]777;notify;{};{}\u{7}", summary, body),
+    };
+    Some(Segment::escape(sequence))
+}
+
+#[derive(Debug, PartialEq)]
+enum NotifyProtocol {
+    Osc9,
+    Osc777,
+}
+
+/// The notification escape sequence the terminal understands
+fn detect_notify_protocol(context: &Context) -> Option<NotifyProtocol> {
+    let term_program = context.get_env("TERM_PROGRAM").unwrap_or_default();
+    let term = context.get_env("TERM").unwrap_or_default();
+
+    if matches!(term_program.as_str(), "iTerm.app" | "WezTerm") || term.contains("kitty") {
+        Some(NotifyProtocol::Osc9)
+    } else if term.starts_with("rxvt") {
+        Some(NotifyProtocol::Osc777)
+    } else {
+        log::debug!(
+            "No notification protocol known for TERM_PROGRAM={:?} TERM={:?}",
+            term_program,
+            term
+        );
+        None
+    }
+}
+
+const HISTORY_KEY: &str = "cmd_duration.history";
+
+/// Adds the duration of the last command to the durations of the session's
+/// last `history_size` commands, and returns their average
+fn record_duration(context: &Context, elapsed: u128, history_size: usize) -> Option<u128> {
+    let state = context.session_state()?;
+    let mut history: Vec<u64> = state.get(HISTORY_KEY).unwrap_or_default();
+
+    // Only the main prompt records the command, so it's counted once
+    if context.target == Target::Main && !context.transient {
+        history.push(u64::try_from(elapsed).unwrap_or(u64::MAX));
+        let excess = history.len().saturating_sub(history_size.max(1));
+        history.drain(..excess);
+        state.set(HISTORY_KEY, &history);
+    }
+
+    if history.is_empty() {
+        return None;
+    }
+    let total: u128 = history.iter().map(|&duration| u128::from(duration)).sum();
+    Some(total / history.len() as u128)
+}
+
 #[cfg(not(feature = "notify"))]
 fn undistract_me<'a, 'b>(
     module: Module<'a>,
@@ -175,4 +292,176 @@ mod tests {
         let expected = Some(format!("underwent {} ", Color::Yellow.bold().paint("5s")));
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn config_precise_format_style() {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                format_style = "precise"
+            })
+            .cmd_duration(133_412)
+            .collect();
+
+        let expected = Some(format!(
+            "took {} ",
+            Color::Yellow.bold().paint("2m 13s 412ms")
+        ));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn config_digital_format_style() {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                format_style = "digital"
+            })
+            .cmd_duration(133_412)
+            .collect();
+
+        let expected = Some(format!(
+            "took {} ",
+            Color::Yellow.bold().paint("00:02:13.412")
+        ));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn config_max_units() {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                format_style = "precise"
+                max_units = 2
+            })
+            .cmd_duration(3_605_412)
+            .collect();
+
+        let expected = Some(format!("took {} ", Color::Yellow.bold().paint("1h 0m")));
+        assert_eq!(expected, actual);
+    }
+
+    fn render_avg(dir: &std::path::Path, duration: u64) -> Option<String> {
+        ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                format = "$duration avg $avg_duration"
+                min_time = 0
+                history_size = 3
+            })
+            .env("STARSHIP_SESSION_KEY", "session")
+            .session_dir(dir)
+            .cmd_duration(duration)
+            .collect()
+    }
+
+    #[test]
+    fn avg_duration_of_recent_commands() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        assert_eq!(render_avg(dir.path(), 1_000), Some("1s avg 1s".to_string()));
+        assert_eq!(render_avg(dir.path(), 3_000), Some("3s avg 2s".to_string()));
+        assert_eq!(render_avg(dir.path(), 8_000), Some("8s avg 4s".to_string()));
+        // Only the last `history_size` commands count
+        assert_eq!(
+            render_avg(dir.path(), 10_000),
+            Some("10s avg 7s".to_string())
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn avg_duration_with_corrupted_state() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join("session.json"), "{ not json")?;
+        assert_eq!(render_avg(dir.path(), 4_000), Some("4s avg 4s".to_string()));
+
+        std::fs::write(
+            dir.path().join("session.json"),
+            r#"{"cmd_duration.history": "oops"}"#,
+        )?;
+        assert_eq!(render_avg(dir.path(), 2_000), Some("2s avg 2s".to_string()));
+        dir.close()
+    }
+
+    #[test]
+    fn avg_duration_without_session() {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                format = "$duration( avg $avg_duration)"
+            })
+            .cmd_duration(5_000)
+            .collect();
+
+        assert_eq!(actual, Some("5s".to_string()));
+    }
+
+    #[test]
+    fn terminal_notification_escape() {
+        let render = |duration: u64, term_program: &str| {
+            ModuleRenderer::new("cmd_duration")
+                .config(toml::toml! {
+                    [cmd_duration]
+                    format = "took $duration"
+                    notify = true
+                    notify_min_time = 10_000
+                })
+                .env("TERM_PROGRAM", term_program)
+                .cmd_duration(duration)
+                .collect()
+        };
+
+        assert_eq!(render(5_000, "iTerm.app"), Some("took 5s".to_string()));
+        assert_eq!(
+            render(12_000, "iTerm.app"),
+            Some("took 12s\u{1b}]9;Command finished: Command execution took 12s\u{7}".to_string())
+        );
+        assert_eq!(
+            render(12_000, "Apple_Terminal"),
+            Some("took 12s".to_string())
+        );
+    }
+
+    #[test]
+    fn terminal_notification_protocols() {
+        let render = |protocol: &str, term: &str| {
+            ModuleRenderer::new("cmd_duration")
+                .config(toml::toml! {
+                    [cmd_duration]
+                    format = "took $duration"
+                    notify = true
+                    notify_min_time = 0
+                    notify_protocol = protocol
+                })
+                .env("TERM", term)
+                .cmd_duration(3_000)
+                .collect()
+        };
+
+        let osc9 = "took 3s\u{1b}]9;Command finished: Command execution took 3s\u{7}";
+        let osc777 = "took 3s\u{1b}]777;notify;Command finished;Command execution took 3s\u{7}";
+        assert_eq!(render("auto", "xterm-kitty"), Some(osc9.to_string()));
+        assert_eq!(
+            render("auto", "rxvt-unicode-256color"),
+            Some(osc777.to_string())
+        );
+        assert_eq!(render("osc777", "xterm-kitty"), Some(osc777.to_string()));
+        assert_eq!(render("osc9", "dumb"), Some(osc9.to_string()));
+    }
+
+    #[test]
+    fn terminal_notification_is_opt_in() {
+        let actual = ModuleRenderer::new("cmd_duration")
+            .config(toml::toml! {
+                [cmd_duration]
+                format = "took $duration"
+            })
+            .env("TERM_PROGRAM", "WezTerm")
+            .cmd_duration(60_000)
+            .collect();
+
+        assert_eq!(actual, Some("took 1m".to_string()));
+    }
 }
@@ -5,6 +5,7 @@ use std::process::{Command, Stdio};
 use std::time::Duration;
 use std::time::Instant;
 
+use once_cell::sync::OnceCell;
 use process_control::{ChildExt, Control, Output};
 
 use super::{Context, Module, ModuleConfig};
@@ -51,6 +52,13 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
     }
 
     if is_match {
+        // The command is only run if the format uses its output, and at most once
+        let output = OnceCell::new();
+        let output = || {
+            output
+                .get_or_init(|| exec_command(config.command, context, &config))
+                .as_ref()
+        };
         let parsed = StringFormatter::new(config.format).and_then(|formatter| {
             formatter
                 .map_meta(|var, _| match var {
@@ -63,16 +71,34 @@ pub fn module<'a>(name: &str, context: &'a Context) -> Option<Module<'a>> {
                 })
                 .map_no_escaping(|variable| match variable {
                     "output" => {
-                        let output = exec_command(config.command, context, &config)?;
-                        let trimmed = output.trim();
+                        let output = output()?;
+                        let merged = if config.ignore_stderr || output.stderr.is_empty() {
+                            output.stdout.clone()
+                        } else if output.stdout.is_empty() {
+                            output.stderr.clone()
+                        } else {
+                            format!("{} {}", output.stdout, output.stderr)
+                        };
 
-                        if trimmed.is_empty() {
+                        if merged.is_empty() {
                             None
                         } else {
-                            Some(Ok(trimmed.to_string()))
+                            Some(Ok(merged))
                         }
                     }
-                    _ => None,
+                    "stderr" => {
+                        let stderr = &output()?.stderr;
+                        if stderr.is_empty() {
+                            None
+                        } else {
+                            Some(Ok(stderr.clone()))
+                        }
+                    }
+                    "exit_code" => Some(Ok(output()?.exit_code.to_string())),
+                    _ => {
+                        let path = variable.strip_prefix("json.")?;
+                        json_field(output()?.json.as_ref()?, path).map(Ok)
+                    }
                 })
                 .parse(None, Some(context))
         });
@@ -209,30 +235,78 @@ fn exec_when(cmd: &str, config: &CustomConfig, context: &Context) -> bool {
     }
 }
 
-/// Execute the given command, returning its output on success
-fn exec_command(cmd: &str, context: &Context, config: &CustomConfig) -> Option<String> {
+/// The trimmed output of a command that exited with one of the `when_exit_codes`
+struct CommandResult {
+    stdout: String,
+    stderr: String,
+    exit_code: i64,
+    /// The parsed stdout, if `output_format` is `json`
+    json: Option<serde_json::Value>,
+}
+
+/// Execute the given command, returning its output if it exited with one of the
+/// `when_exit_codes`
+fn exec_command(cmd: &str, context: &Context, config: &CustomConfig) -> Option<CommandResult> {
     log::trace!("Running '{cmd}'");
 
     if let Some(output) = shell_command(cmd, config, context) {
-        if !output.status.success() {
-            log::trace!("Non-zero exit code '{:?}'", output.status.code());
-            log::trace!(
-                "stdout: {}",
-                std::str::from_utf8(&output.stdout).unwrap_or("<invalid utf8>")
-            );
-            log::trace!(
-                "stderr: {}",
-                std::str::from_utf8(&output.stderr).unwrap_or("<invalid utf8>")
-            );
-            return None;
-        }
+        // Commands killed by a signal have no exit code
+        let exit_code = output
+            .status
+            .code()
+            .filter(|code| config.when_exit_codes.contains(code));
+        let exit_code = match exit_code {
+            Some(exit_code) => exit_code,
+            None => {
+                log::trace!("Unexpected exit code '{:?}'", output.status.code());
+                log::trace!(
+                    "stdout: {}",
+                    std::str::from_utf8(&output.stdout).unwrap_or("<invalid utf8>")
+                );
+                log::trace!(
+                    "stderr: {}",
+                    std::str::from_utf8(&output.stderr).unwrap_or("<invalid utf8>")
+                );
+                return None;
+            }
+        };
 
-        Some(String::from_utf8_lossy(&output.stdout).into())
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let json = match config.output_format {
+            "json" => serde_json::from_str(&stdout)
+                .map_err(|error| {
+                    log::warn!(
+                        "Unable to parse the output of '{cmd}' as JSON, showing it as text: {error}"
+                    );
+                })
+                .ok(),
+            _ => None,
+        };
+        Some(CommandResult {
+            stdout,
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            exit_code,
+            json,
+        })
     } else {
         None
     }
 }
 
+/// The field at the dot separated `path` in `json`, where array elements are
+/// indexed by number, e.g. `tools.0.name`
+fn json_field(json: &serde_json::Value, path: &str) -> Option<String> {
+    let field = path.split('.').try_fold(json, |value, key| match value {
+        serde_json::Value::Array(array) => array.get(key.parse::<usize>().ok()?),
+        _ => value.get(key),
+    })?;
+    match field {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(string) => Some(string.clone()),
+        value => Some(value.to_string()),
+    }
+}
+
 /// If the specified shell refers to `PowerShell`, adds the arguments "-Command -" to the
 /// given command.
 /// Returns `false` if the shell shell expects scripts as arguments, `true` if as `stdin`.
@@ -498,6 +572,138 @@ mod tests {
         dir.close()
     }
 
+    #[test]
+    #[cfg(not(windows))]
+    fn unexpected_exit_code() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                format = "$output"
+                command = "echo out; echo err >&2; exit 2"
+                when = true
+                shell = ["sh"]
+                ignore_timeout = true
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn stderr_and_exit_code() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                format = "$output|$stderr|$exit_code"
+                command = "echo out; echo err >&2; echo >&2; exit 2"
+                when = true
+                when_exit_codes = [0, 2]
+                shell = ["sh"]
+                ignore_timeout = true
+            })
+            .collect();
+
+        assert_eq!(Some("out|err|2".to_string()), actual);
+        dir.close()
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn merge_stderr() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                format = "$output"
+                command = "echo out; echo err >&2; exit 2"
+                when = true
+                when_exit_codes = [2]
+                ignore_stderr = false
+                shell = ["sh"]
+                ignore_timeout = true
+            })
+            .collect();
+
+        assert_eq!(Some("out err".to_string()), actual);
+        dir.close()
+    }
+
+    #[cfg(not(windows))]
+    fn render_json(command: &str, format: &str) -> io::Result<Option<String>> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("custom.test")
+            .path(dir.path())
+            .config(toml::toml! {
+                [custom.test]
+                format = format
+                command = command
+                when = true
+                output_format = "json"
+                shell = ["sh"]
+                ignore_timeout = true
+            })
+            .collect();
+
+        dir.close()?;
+        Ok(actual)
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn json_flat() -> io::Result<()> {
+        let actual = render_json(
+            r#"echo '{"version": "1.2", "dirty": true, "missing": null}'"#,
+            "${json.version}|${json.dirty}|(${json.missing})",
+        )?;
+
+        assert_eq!(Some("1.2|true|".to_string()), actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn json_nested() -> io::Result<()> {
+        let actual = render_json(
+            r#"echo '{"tool": {"name": "cargo", "version": {"major": 1}}}'"#,
+            "${json.tool.name} ${json.tool.version.major}(${json.tool.missing})",
+        )?;
+
+        assert_eq!(Some("cargo 1".to_string()), actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn json_array() -> io::Result<()> {
+        let actual = render_json(
+            r#"echo '{"targets": [{"name": "x86"}, {"name": "arm"}]}'"#,
+            "${json.targets.1.name}(${json.targets.2.name})(${json.targets.first})",
+        )?;
+
+        assert_eq!(Some("arm".to_string()), actual);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn json_malformed() -> io::Result<()> {
+        let actual = render_json(r#"echo '{"version": '"#, "(${json.version})$output")?;
+
+        assert_eq!(Some(r#"{"version":"#.to_string()), actual);
+        Ok(())
+    }
+
     #[test]
     #[cfg(not(windows))]
     fn when_true_with_string() -> std::io::Result<()> {
@@ -4,18 +4,26 @@ use super::utils::directory_nix as directory_utils;
 use super::utils::directory_win as directory_utils;
 use super::utils::path::PathExt as SPathExt;
 use indexmap::IndexMap;
+use once_cell::sync::Lazy;
 use path_slash::{PathBufExt, PathExt};
+use regex::Regex;
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::iter::FromIterator;
 use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::{Context, Module};
 
-use super::utils::directory::truncate;
+use super::utils::directory::{truncate, truncate_middle};
 use crate::config::ModuleConfig;
 use crate::configs::directory::DirectoryConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::string_formatter::StringFormatterError;
+use crate::formatter::{gradient, StringFormatter};
+use crate::segment::Segment;
 
 /// Creates a module with the current logical or physical directory
 ///
@@ -27,14 +35,36 @@ use crate::formatter::StringFormatter;
 /// - Paths containing a git repo will contract to begin at the repo root
 ///
 /// **Substitution**
-/// Paths will undergo user-provided substitutions of substrings
+/// Paths will undergo user-provided substitutions of substrings, then of
+/// regular expressions
 ///
 /// **Truncation**
-/// Paths will be limited in length to `3` path components by default.
+/// Paths will be limited in length to `3` path components by default. With
+/// `truncation_style = "middle"`, the first and last components are kept instead.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("directory");
     let config: DirectoryConfig = DirectoryConfig::try_load(module.config);
 
+    module.set_segments(match format_directory(context, &config, &[])? {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `directory`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Formats the current directory into segments as configured by `config`
+///
+/// Shared with the `gradient_directory` module, which renders the same path.
+/// The `style_variables` can be used in the format next to the module's own.
+pub fn format_directory(
+    context: &Context,
+    config: &DirectoryConfig,
+    style_variables: &[(&str, String)],
+) -> Option<Result<Vec<Segment>, StringFormatterError>> {
     let home_symbol = String::from(config.home_symbol);
     let home_dir = context
         .get_home()
@@ -72,20 +102,46 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     // Apply path substitutions
     let dir_string = substitute_path(dir_string, &config.substitutions);
+    let dir_string = substitute_path_regex(dir_string, &config.regex_substitutions);
+
+    let truncate_middle_components = match config.truncation_style {
+        "leading" => false,
+        "middle" => true,
+        unknown => {
+            log::warn!(
+                "Unknown truncation_style `{}` in [directory], expected `leading` or `middle`",
+                unknown
+            );
+            false
+        }
+    };
 
-    // Truncate the dir string to the maximum number of path components
-    let dir_string =
-        if let Some(truncated) = truncate(&dir_string, config.truncation_length as usize) {
+    // Truncate the dir string to the maximum number of path components. Middle
+    // truncation happens below, as it has to know where the repo root is.
+    let dir_string = match truncate(&dir_string, config.truncation_length as usize) {
+        Some(truncated) if !truncate_middle_components => {
             is_truncated = true;
             truncated
-        } else {
-            dir_string
-        };
+        }
+        _ => dir_string,
+    };
+    let truncate_middle = |root| {
+        truncate_middle(
+            &dir_string,
+            config.keep_first,
+            config.keep_last,
+            config.truncation_symbol,
+            root,
+        )
+    };
 
     let prefix = if is_truncated {
         // Substitutions could have changed the prefix, so don't allow them and
         // fish-style path contraction together
-        if config.fish_style_pwd_dir_length > 0 && config.substitutions.is_empty() {
+        if config.fish_style_pwd_dir_length > 0
+            && config.substitutions.is_empty()
+            && config.regex_substitutions.is_empty()
+        {
             // If user is using fish style path, we need to add the segment first
             let contracted_home_dir = contract_path(display_dir, &home_dir, &home_symbol);
             to_fish_style(
@@ -101,6 +157,16 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     };
 
     let path_vec = match &repo.and_then(|r| r.workdir.as_ref()) {
+        Some(repo_root) if config.repo_root_style.is_some() && truncate_middle_components => {
+            let contracted_path = contract_repo_path(display_dir, repo_root)?;
+            let components: Vec<&str> = dir_string.split('/').collect();
+            let repo_components: Vec<&str> = contracted_path.split('/').collect();
+            let root = components
+                .ends_with(&repo_components)
+                .then(|| components.len() - repo_components.len());
+            let [before, root, after] = truncate_middle(root);
+            [prefix + before.as_str(), root, after]
+        }
         Some(repo_root) if config.repo_root_style.is_some() => {
             let contracted_path = contract_repo_path(display_dir, repo_root)?;
             let repo_path_vec: Vec<&str> = contracted_path.split('/').collect();
@@ -117,6 +183,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 ["".to_string(), "".to_string(), prefix + dir_string.as_str()]
             }
         }
+        _ if truncate_middle_components => {
+            let [_, _, path] = truncate_middle(None);
+            ["".to_string(), "".to_string(), prefix + path.as_str()]
+        }
         _ => ["".to_string(), "".to_string(), prefix + dir_string.as_str()],
     };
 
@@ -142,12 +212,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 "repo_root_style" => Some(Ok(repo_root_style)),
                 _ => None,
             })
+            .map_style(|variable| gradient::style_variable(style_variables, variable))
             .map(|variable| match variable {
                 "path" => Some(Ok(&path_vec[2])),
                 "before_root_path" => Some(Ok(&path_vec[0])),
                 "repo_root" => Some(Ok(&path_vec[1])),
                 "read_only" => {
-                    if is_readonly_dir(physical_dir) {
+                    if is_readonly_dir(context, config, physical_dir) {
                         Some(Ok(&lock_symbol))
                     } else {
                         None
@@ -158,15 +229,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .parse(None, Some(context))
     });
 
-    module.set_segments(match parsed {
-        Ok(segments) => segments,
-        Err(error) => {
-            log::warn!("Error in module `directory`:\n{}", error);
-            return None;
-        }
-    });
-
-    Some(module)
+    Some(parsed)
 }
 
 #[cfg(windows)]
@@ -187,8 +250,22 @@ fn remove_extended_path_prefix(path: String) -> String {
     path
 }
 
-fn is_readonly_dir(path: &Path) -> bool {
-    match directory_utils::is_write_allowed(path) {
+fn is_readonly_dir(context: &Context, config: &DirectoryConfig, path: &Path) -> bool {
+    let write_allowed = match config.read_only_detection {
+        "access" => is_access_allowed(
+            path,
+            Duration::from_millis(context.root_config.command_timeout),
+        ),
+        "mode" => directory_utils::is_write_allowed(path),
+        unknown => {
+            log::warn!(
+                "Unknown read_only_detection `{}` in [directory], expected `mode` or `access`",
+                unknown
+            );
+            directory_utils::is_write_allowed(path)
+        }
+    };
+    match write_allowed {
         Ok(res) => !res,
         Err(e) => {
             log::debug!(
@@ -201,6 +278,17 @@ fn is_readonly_dir(path: &Path) -> bool {
     }
 }
 
+/// Checks the effective permissions of `path` on another thread, so a hanging
+/// network mount can't stall the prompt for longer than `command_timeout`
+fn is_access_allowed(path: &Path, timeout: Duration) -> Result<bool, String> {
+    let (sender, receiver) = mpsc::channel();
+    let path = path.to_path_buf();
+    thread::spawn(move || sender.send(directory_utils::is_access_allowed(&path)));
+    receiver
+        .recv_timeout(timeout)
+        .unwrap_or_else(|_| Err(format!("Timed out after {:?}", timeout)))
+}
+
 /// Contract the root component of a path
 ///
 /// Replaces the `top_level_path` in a given `full_path` with the provided
@@ -299,6 +387,39 @@ fn substitute_path(dir_string: String, substitutions: &IndexMap<String, &str>) -
     substituted_dir
 }
 
+/// Patterns in `regex_substitutions` that were already reported as invalid
+static INVALID_PATTERNS: Lazy<Mutex<HashSet<String>>> = Lazy::new(Default::default);
+
+/// Perform the first matching regex substitution on the path
+///
+/// Every match of the first pattern that matches the path is replaced, with
+/// `$1`, `$name` etc. in the replacement expanding to the captured groups.
+/// Invalid patterns are skipped.
+fn substitute_path_regex(dir_string: String, substitutions: &IndexMap<String, &str>) -> String {
+    for (pattern, replacement) in substitutions {
+        let regex = match Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(error) => {
+                let newly_invalid = INVALID_PATTERNS
+                    .lock()
+                    .map_or(true, |mut invalid| invalid.insert(pattern.clone()));
+                if newly_invalid {
+                    log::warn!(
+                        "Invalid pattern {:?} in `directory.regex_substitutions`:\n{}",
+                        pattern,
+                        error
+                    );
+                }
+                continue;
+            }
+        };
+        if regex.is_match(&dir_string) {
+            return regex.replace_all(&dir_string, *replacement).into_owned();
+        }
+    }
+    dir_string
+}
+
 /// Takes part before contracted path and replaces it with fish style path
 ///
 /// Will take the first letter of each directory before the contracted path and
@@ -660,6 +781,83 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn regex_substitution_with_captures() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/srv/www/example.com/public")
+            .config(toml::toml! {
+                [directory.regex_substitutions]
+                "^/srv/www/([^/]+)" = "🌐 $1"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan
+                .bold()
+                .paint(convert_path_sep("🌐 example.com/public"))
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn regex_substitution_first_match_wins() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/home/work/projects/starship")
+            .config(toml::toml! {
+                [directory.regex_substitutions]
+                "(" = "invalid"
+                "^/home/work/projects/(?P<project>[^/]+)$" = "projects:$project"
+                "^/home/work" = "work"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint("projects:starship")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn regex_substitution_after_literal_substitution() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/mnt/network/share/team-a/docs")
+            .config(toml::toml! {
+                [directory.substitutions]
+                "/mnt/network/share" = "/net"
+                [directory.regex_substitutions]
+                "^/net/team-(\\w+)" = "/team/$1"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(convert_path_sep("/team/a/docs"))
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn regex_substitution_before_truncation() {
+        let actual = ModuleRenderer::new("directory")
+            .path("/very/deeply/nested/build/output/dir")
+            .config(toml::toml! {
+                [directory]
+                truncation_length = 2
+                truncation_symbol = "…/"
+                [directory.regex_substitutions]
+                "/build/output" = "/out"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(convert_path_sep("…/out/dir"))
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn strange_substitution() {
         let strange_sub = "/\\/;,!";
@@ -765,6 +963,35 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn read_only_detection_access() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir()?;
+        let render = || {
+            ModuleRenderer::new("directory")
+                .config(toml::toml! {
+                    [directory]
+                    format = "$read_only"
+                    read_only = "RO"
+                    read_only_detection = "access"
+                })
+                .path(dir.path())
+                .collect()
+        };
+        assert_eq!(None, render());
+
+        // root can write to any directory on a writable file system
+        if !nix::unistd::Uid::effective().is_root() {
+            fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o555))?;
+            let actual = render();
+            fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755))?;
+            assert_eq!(Some("RO".to_string()), actual);
+        }
+        dir.close()
+    }
+
     #[test]
     fn truncated_directory_in_root() -> io::Result<()> {
         let (tmp_dir, name) = make_known_tempdir(Path::new("/tmp"))?;
@@ -1724,6 +1951,126 @@ mod tests {
         tmp_dir.close()
     }
 
+    #[test]
+    fn middle_truncation() {
+        let render = |path: &str| {
+            ModuleRenderer::new("directory")
+                .config(toml::toml! {
+                    [directory]
+                    truncation_length = 2
+                    truncation_style = "middle"
+                    truncation_symbol = "…/"
+                    keep_first = 2
+                    keep_last = 3
+                })
+                .path(path)
+                .collect()
+        };
+        let expected = |path: &str| {
+            Some(format!(
+                "{} ",
+                Color::Cyan.bold().paint(convert_path_sep(path))
+            ))
+        };
+
+        assert_eq!(render("/srv/work/api"), expected("/srv/work/api"));
+        assert_eq!(
+            render("/srv/work/service/api/handlers"),
+            expected("/srv/work/service/api/handlers")
+        );
+        assert_eq!(
+            render("/srv/work/mono/service/api/handlers"),
+            expected("/srv/work/…/service/api/handlers")
+        );
+    }
+
+    #[test]
+    fn middle_truncation_of_git_repo() -> io::Result<()> {
+        let (tmp_dir, name) = make_known_tempdir(Path::new("/tmp"))?;
+        let repo_dir = tmp_dir.path().join("above").join("repo");
+        let dir = repo_dir.join("src/sub/path");
+        fs::create_dir_all(&dir)?;
+        init_repo(&repo_dir).unwrap();
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_style = "middle"
+                truncation_symbol = "…/"
+                keep_first = 1
+                keep_last = 2
+            })
+            .path(&dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan
+                .bold()
+                .paint(convert_path_sep("…/repo/…/sub/path"))
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = ModuleRenderer::new("directory")
+            .config(toml::toml! {
+                [directory]
+                truncation_style = "middle"
+                truncation_symbol = "…/"
+                keep_first = 2
+                keep_last = 4
+                truncate_to_repo = false
+            })
+            .path(&dir)
+            .collect();
+        let expected = Some(format!(
+            "{} ",
+            Color::Cyan.bold().paint(convert_path_sep(&format!(
+                "/tmp/{}/…/repo/src/sub/path",
+                name
+            )))
+        ));
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+
+    #[test]
+    fn middle_truncation_with_highlighted_git_root() -> io::Result<()> {
+        let (tmp_dir, _) = make_known_tempdir(Path::new("/tmp"))?;
+        let repo_dir = tmp_dir.path().join("above").join("repo");
+        let dir = repo_dir.join("src/sub/path");
+        fs::create_dir_all(&dir)?;
+        init_repo(&repo_dir).unwrap();
+
+        let render = |keep_first: i64, keep_last: i64| {
+            ModuleRenderer::new("directory")
+                .config(toml::toml! {
+                    [directory]
+                    truncation_style = "middle"
+                    truncation_symbol = "…/"
+                    keep_first = keep_first
+                    keep_last = keep_last
+                    truncate_to_repo = false
+                    repo_root_style = "green"
+                })
+                .path(&dir)
+                .collect()
+        };
+        let expected = |before: &str, after: &str| {
+            Some(format!(
+                "{}{}repo{} ",
+                Color::Cyan.bold().paint(convert_path_sep(before)),
+                Color::Green.prefix(),
+                Color::Cyan.bold().paint(convert_path_sep(after))
+            ))
+        };
+
+        // The repo root is left out, so it's shown next to the truncation symbol
+        assert_eq!(render(1, 1), expected("/tmp/…/", "/…/path"));
+        assert_eq!(render(1, 3), expected("/tmp/…/", "/src/sub/path"));
+        // The repo root is among the last components
+        assert_eq!(render(1, 4), expected("/tmp/…/", "/src/sub/path"));
+        tmp_dir.close()
+    }
+
     // sample for invalid unicode from https://doc.rust-lang.org/std/ffi/struct.OsStr.html#method.to_string_lossy
     #[cfg(any(unix, target_os = "redox"))]
     fn invalid_path() -> PathBuf {
@@ -0,0 +1,246 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::direnv::DirenvConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with whether the `.envrc` of the current directory is allowed and loaded
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("direnv");
+    let config = DirenvConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let has_env_file = context
+        .try_begin_scan()?
+        .set_files(&config.detect_files)
+        .set_extensions(&config.detect_extensions)
+        .set_folders(&config.detect_folders)
+        .is_match();
+
+    if !has_env_file {
+        return None;
+    }
+
+    let status = context.exec_cmd("direnv", &["status", "--json"])?.stdout;
+    let state = match serde_json::from_str::<DirenvStatus>(&status) {
+        Ok(status) => status.state,
+        Err(error) => {
+            log::warn!("Unable to parse the output of `direnv status`: {}", error);
+            return None;
+        }
+    };
+    // `direnv` found no `.envrc` for this directory
+    let found = state.found_rc?;
+    let is_loaded = state
+        .loaded_rc
+        .map_or(false, |loaded| loaded.path == found.path);
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                "loaded" if is_loaded => Some(config.loaded_symbol),
+                "loaded" => Some(config.unloaded_symbol),
+                "allowed" => Some(match found.allowed {
+                    AllowStatus::Allowed => config.allowed_symbol,
+                    AllowStatus::NotAllowed => config.not_allowed_symbol,
+                    AllowStatus::Denied => config.denied_symbol,
+                }),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "rc_path" => Some(Ok(found.path.to_string_lossy().into_owned())),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `direnv`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// The output of `direnv status --json`
+#[derive(Deserialize)]
+struct DirenvStatus {
+    state: DirenvState,
+}
+
+#[derive(Deserialize)]
+struct DirenvState {
+    #[serde(rename = "foundRC")]
+    found_rc: Option<RcFile>,
+    #[serde(rename = "loadedRC")]
+    loaded_rc: Option<RcFile>,
+}
+
+#[derive(Deserialize)]
+struct RcFile {
+    allowed: AllowStatus,
+    path: PathBuf,
+}
+
+/// Whether `direnv allow` or `direnv deny` was run for a `.envrc`
+#[derive(Deserialize)]
+#[serde(from = "u8")]
+enum AllowStatus {
+    Allowed,
+    NotAllowed,
+    Denied,
+}
+
+impl From<u8> for AllowStatus {
+    fn from(status: u8) -> Self {
+        match status {
+            0 => Self::Allowed,
+            2 => Self::Denied,
+            _ => Self::NotAllowed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
+    use nu_ansi_term::Color;
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+
+    fn status_json(dir: &Path, allowed: u8, loaded: bool) -> String {
+        let rc = format!(
+            r#"{{"allowed":{},"path":{:?}}}"#,
+            allowed,
+            dir.join(".envrc").to_string_lossy()
+        );
+        format!(
+            r#"{{"config":{{"ConfigDir":"/home/user/.config/direnv"}},"state":{{"foundRC":{},"loadedRC":{}}}}}"#,
+            rc,
+            if loaded { &rc } else { "null" }
+        )
+    }
+
+    fn render(dir: &Path, status: Option<String>) -> Option<String> {
+        ModuleRenderer::new("direnv")
+            .path(dir)
+            .config(toml::toml! {
+                [direnv]
+                disabled = false
+            })
+            .cmd(
+                "direnv status --json",
+                status.map(|stdout| CommandOutput {
+                    stdout,
+                    stderr: String::default(),
+                }),
+            )
+            .collect()
+    }
+
+    #[test]
+    fn folder_without_envrc() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let actual = render(dir.path(), Some(status_json(dir.path(), 0, true)));
+
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".envrc"))?.sync_all()?;
+
+        let actual = ModuleRenderer::new("direnv")
+            .path(dir.path())
+            .cmd(
+                "direnv status --json",
+                Some(CommandOutput {
+                    stdout: status_json(dir.path(), 0, true),
+                    stderr: String::default(),
+                }),
+            )
+            .collect();
+
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn loaded_and_allowed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".envrc"))?.sync_all()?;
+
+        let actual = render(dir.path(), Some(status_json(dir.path(), 0, true)));
+
+        let expected = Some(format!(
+            "{} ",
+            Color::LightYellow.bold().paint("direnv loaded/allowed")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn not_allowed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".envrc"))?.sync_all()?;
+
+        let actual = render(dir.path(), Some(status_json(dir.path(), 1, false)));
+
+        let expected = Some(format!(
+            "{} ",
+            Color::LightYellow
+                .bold()
+                .paint("direnv not loaded/not allowed")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn denied() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".env"))?.sync_all()?;
+
+        let actual = render(dir.path(), Some(status_json(dir.path(), 2, false)));
+
+        let expected = Some(format!(
+            "{} ",
+            Color::LightYellow.bold().paint("direnv not loaded/denied")
+        ));
+        assert_eq!(expected, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn direnv_without_rc_or_output() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".envrc"))?.sync_all()?;
+
+        let no_rc = r#"{"config":{},"state":{"foundRC":null,"loadedRC":null}}"#;
+        assert_eq!(None, render(dir.path(), Some(no_rc.to_string())));
+        assert_eq!(None, render(dir.path(), Some("not json".to_string())));
+        assert_eq!(None, render(dir.path(), None));
+        dir.close()
+    }
+}
@@ -0,0 +1,179 @@
+use systemstat::data::ByteSize;
+
+use super::memory_usage::display_bs;
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::disk_usage::DiskUsageConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the usage of the filesystem the current directory is on
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("disk_usage");
+    let config = DiskUsageConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let provider = context.filesystem_stats_provider;
+    // Asking a network filesystem for its size may block until it responds
+    if let Some(fs_type) = provider.filesystem_type(&context.current_dir) {
+        if config.excluded_fs_types.contains(&fs_type.as_str()) {
+            log::trace!("Not checking the disk usage of a {} filesystem", fs_type);
+            return None;
+        }
+    }
+
+    let space = provider.disk_space(&context.current_dir)?;
+    if space.total == 0 {
+        return None;
+    }
+    let used = space.total.saturating_sub(space.available);
+    let used_pct = (100.0 * used as f64 / space.total as f64).round() as i64;
+    if used_pct < config.threshold {
+        return None;
+    }
+
+    // The display with the highest threshold that was reached
+    let style = config
+        .display
+        .iter()
+        .filter(|display| used_pct >= display.threshold)
+        .max_by_key(|display| display.threshold)
+        .map_or(config.style, |display| display.style);
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "used_pct" => Some(Ok(used_pct.to_string())),
+                "free" => Some(Ok(display_bs(ByteSize::b(space.available)))),
+                "total" => Some(Ok(display_bs(ByteSize::b(space.total)))),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `disk_usage`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::utils::filesystem::DiskSpace;
+    use crate::modules::utils::filesystem::MockFilesystemStatsProvider;
+    use crate::test::ModuleRenderer;
+    use nu_ansi_term::Color;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn mock(fs_type: &str, available_gib: u64) -> MockFilesystemStatsProvider {
+        let mut mock = MockFilesystemStatsProvider::new();
+        mock.expect_filesystem_type()
+            .return_const(Some(fs_type.to_string()));
+        mock.expect_disk_space().return_const(Some(DiskSpace {
+            total: 100 * GIB,
+            available: available_gib * GIB,
+        }));
+        mock
+    }
+
+    fn render(mock: &MockFilesystemStatsProvider) -> Option<String> {
+        ModuleRenderer::new("disk_usage")
+            .filesystem_stats_provider(mock)
+            .config(toml::toml! {
+                [disk_usage]
+                disabled = false
+            })
+            .collect()
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let actual = ModuleRenderer::new("disk_usage")
+            .filesystem_stats_provider(&mock("ext4", 1))
+            .collect();
+
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn below_threshold() {
+        assert_eq!(None, render(&mock("ext4", 16)));
+    }
+
+    #[test]
+    fn above_threshold() {
+        let expected = Some(format!(
+            "{} ",
+            Color::Yellow.bold().paint("💾 90% (10GiB free)")
+        ));
+
+        assert_eq!(expected, render(&mock("ext4", 10)));
+    }
+
+    #[test]
+    fn display_style_of_the_highest_threshold() {
+        let expected = Some(format!(
+            "{} ",
+            Color::Red.bold().paint("💾 97% (3GiB free)")
+        ));
+        assert_eq!(expected, render(&mock("btrfs", 3)));
+
+        let actual = ModuleRenderer::new("disk_usage")
+            .filesystem_stats_provider(&mock("btrfs", 3))
+            .config(toml::toml! {
+                [disk_usage]
+                disabled = false
+                threshold = 0
+                format = "[$used_pct]($style)"
+                [[disk_usage.display]]
+                threshold = 50
+                style = "green"
+                [[disk_usage.display]]
+                threshold = 99
+                style = "red"
+            })
+            .collect();
+        assert_eq!(Some(Color::Green.paint("97").to_string()), actual);
+    }
+
+    #[test]
+    fn excluded_fs_types_are_never_stat() {
+        let mut mock = MockFilesystemStatsProvider::new();
+        mock.expect_filesystem_type()
+            .return_const(Some("nfs4".to_string()));
+        mock.expect_disk_space().never();
+
+        assert_eq!(None, render(&mock));
+    }
+
+    #[test]
+    fn unknown_fs_type() {
+        let mut mock = MockFilesystemStatsProvider::new();
+        mock.expect_filesystem_type().return_const(None);
+        mock.expect_disk_space().return_const(Some(DiskSpace {
+            total: 100 * GIB,
+            available: 0,
+        }));
+
+        let expected = Some(format!("{} ", Color::Red.bold().paint("💾 100% (0B free)")));
+        assert_eq!(expected, render(&mock));
+    }
+}
@@ -6,6 +6,22 @@ use crate::configs::docker_context::DockerContextConfig;
 use crate::formatter::StringFormatter;
 use crate::utils;
 
+/// A container engine whose active context can be shown
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Engine {
+    Docker,
+    Podman,
+}
+
+impl Engine {
+    fn name(self) -> &'static str {
+        match self {
+            Engine::Docker => "docker",
+            Engine::Podman => "podman",
+        }
+    }
+}
+
 /// Creates a module with the currently active Docker context
 ///
 /// Will display the Docker context if the following criteria are met:
@@ -19,6 +35,11 @@ use crate::utils;
 ///     - `DOCKER_HOST`, `DOCKER_CONTEXT`, $HOME/.docker/config.json, $`DOCKER_CONFIG/config.json`
 ///     - (This is the same order docker follows, as `DOCKER_HOST` and `DOCKER_CONTEXT` override the
 ///     config)
+///
+/// With `podman` in `detect_engines`, the Podman connection is read from
+/// `CONTAINER_HOST`, `CONTAINER_CONNECTION` or the default connection of
+/// Podman's config. Contexts from the environment take precedence over the
+/// ones from config files, so the engine the environment points at is shown.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("docker_context");
     let config: DockerContextConfig = DockerContextConfig::try_load(module.config);
@@ -34,28 +55,30 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         return None;
     }
 
-    let docker_config = PathBuf::from(
-        &context
-            .get_env_os("DOCKER_CONFIG")
-            .unwrap_or(context.get_home()?.join(".docker").into_os_string()),
-    )
-    .join("config.json");
-
-    let docker_context_env = ["DOCKER_MACHINE_NAME", "DOCKER_HOST", "DOCKER_CONTEXT"]
-        .into_iter()
-        .find_map(|env| context.get_env(env));
-
-    let ctx = match docker_context_env {
-        Some(data) => data,
-        _ => {
-            if !docker_config.exists() {
-                return None;
+    let engines: Vec<Engine> = config
+        .detect_engines
+        .iter()
+        .filter_map(|engine| match *engine {
+            "docker" => Some(Engine::Docker),
+            "podman" => Some(Engine::Podman),
+            unknown => {
+                log::warn!(
+                    "Unknown engine `{}` in `docker_context.detect_engines`, expected `docker` or `podman`",
+                    unknown
+                );
+                None
             }
-            let json = utils::read_file(docker_config).ok()?;
-            let parsed_json: serde_json::Value = serde_json::from_str(&json).ok()?;
-            parsed_json.get("currentContext")?.as_str()?.to_owned()
-        }
-    };
+        })
+        .collect();
+
+    let (engine, ctx) = engines
+        .iter()
+        .find_map(|&engine| get_env_context(context, engine, &engines))
+        .or_else(|| {
+            engines
+                .iter()
+                .find_map(|&engine| Some((engine, get_config_context(context, engine)?)))
+        })?;
 
     if ctx == "default" {
         return None;
@@ -64,7 +87,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
-                "symbol" => Some(config.symbol),
+                "symbol" => match engine {
+                    Engine::Docker => Some(config.symbol),
+                    Engine::Podman => Some(config.podman_symbol),
+                },
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -73,6 +99,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "context" => Some(Ok(ctx.as_str())),
+                "engine" => Some(Ok(engine.name())),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -88,12 +115,78 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The context of `engine` set in the environment, with the engine it points at
+fn get_env_context(
+    context: &Context,
+    engine: Engine,
+    engines: &[Engine],
+) -> Option<(Engine, String)> {
+    match engine {
+        Engine::Docker => {
+            let (env, ctx) = ["DOCKER_MACHINE_NAME", "DOCKER_HOST", "DOCKER_CONTEXT"]
+                .into_iter()
+                .find_map(|env| Some((env, context.get_env(env)?)))?;
+            // Podman provides a Docker compatible socket
+            if env == "DOCKER_HOST" && ctx.contains("podman") && engines.contains(&Engine::Podman) {
+                return Some((Engine::Podman, ctx));
+            }
+            Some((Engine::Docker, ctx))
+        }
+        Engine::Podman => ["CONTAINER_HOST", "CONTAINER_CONNECTION"]
+            .into_iter()
+            .find_map(|env| context.get_env(env))
+            .map(|ctx| (Engine::Podman, ctx)),
+    }
+}
+
+/// The context of `engine` set in its config files
+fn get_config_context(context: &Context, engine: Engine) -> Option<String> {
+    match engine {
+        Engine::Docker => {
+            let docker_config = PathBuf::from(
+                &context
+                    .get_env_os("DOCKER_CONFIG")
+                    .unwrap_or(context.get_home()?.join(".docker").into_os_string()),
+            )
+            .join("config.json");
+            let json = utils::read_file(docker_config).ok()?;
+            let parsed_json: serde_json::Value = serde_json::from_str(&json).ok()?;
+            Some(parsed_json.get("currentContext")?.as_str()?.to_owned())
+        }
+        Engine::Podman => {
+            let config_dir = context
+                .get_env_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| Some(context.get_home()?.join(".config")))?
+                .join("containers");
+
+            // Podman 4.8 and later keep the connections apart from containers.conf
+            let connections = utils::read_file(config_dir.join("podman-connections.json"))
+                .ok()
+                .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+                .and_then(|json| Some(json["Connection"]["Default"].as_str()?.to_owned()));
+            connections.or_else(|| {
+                let conf = utils::read_file(config_dir.join("containers.conf")).ok()?;
+                let parsed_conf: toml::Value = toml::from_str(&conf).ok()?;
+                Some(
+                    parsed_conf
+                        .get("engine")?
+                        .get("active_service")?
+                        .as_str()?
+                        .to_owned(),
+                )
+            })
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
     use nu_ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io::{self, Write};
+    use std::path::Path;
 
     #[test]
     fn only_trigger_when_docker_config_exists() -> io::Result<()> {
@@ -423,4 +516,167 @@ mod tests {
 
         cfg_dir.close()
     }
+
+    fn write_file(path: &Path, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        let mut file = File::create(path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()
+    }
+
+    fn render_engines(home: &Path) -> ModuleRenderer {
+        ModuleRenderer::new("docker_context")
+            .env("HOME", home.to_string_lossy())
+            .config(toml::toml! {
+                [docker_context]
+                format = "via [$symbol$context \\($engine\\)]($style) "
+                only_with_files = false
+                detect_engines = ["docker", "podman"]
+            })
+    }
+
+    #[test]
+    fn test_podman_from_env() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+
+        let actual = render_engines(home.path())
+            .env("CONTAINER_HOST", "ssh://core@localhost:52345")
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue
+                .bold()
+                .paint("🦭 ssh://core@localhost:52345 (podman)")
+        ));
+        assert_eq!(expected, actual);
+
+        let actual = render_engines(home.path())
+            .env("CONTAINER_CONNECTION", "remote")
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("🦭 remote (podman)")
+        ));
+        assert_eq!(expected, actual);
+
+        home.close()
+    }
+
+    #[test]
+    fn test_docker_host_pointing_at_podman() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+        let socket = "unix:///run/user/1000/podman/podman.sock";
+
+        let actual = render_engines(home.path())
+            .env("DOCKER_HOST", socket)
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint(format!("🦭 {} (podman)", socket))
+        ));
+        assert_eq!(expected, actual);
+
+        // Without podman in `detect_engines`, the socket is shown as docker's
+        let actual = ModuleRenderer::new("docker_context")
+            .env("HOME", home.path().to_string_lossy())
+            .env("DOCKER_HOST", socket)
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint(format!("🐳 {}", socket))
+        ));
+        assert_eq!(expected, actual);
+
+        home.close()
+    }
+
+    #[test]
+    fn test_podman_from_config_files() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+        let containers = home.path().join(".config/containers");
+        write_file(
+            &containers.join("containers.conf"),
+            "[engine]\nactive_service = \"legacy\"\n",
+        )?;
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("🦭 legacy (podman)")
+        ));
+        assert_eq!(expected, render_engines(home.path()).collect());
+
+        write_file(
+            &containers.join("podman-connections.json"),
+            &serde_json::json!({
+                "Connection": {
+                    "Default": "podman-machine-default",
+                    "Connections": {}
+                }
+            })
+            .to_string(),
+        )?;
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue
+                .bold()
+                .paint("🦭 podman-machine-default (podman)")
+        ));
+        assert_eq!(expected, render_engines(home.path()).collect());
+
+        home.close()
+    }
+
+    #[test]
+    fn test_env_takes_precedence_over_config_files() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+        write_file(
+            &home.path().join(".docker/config.json"),
+            &serde_json::json!({ "currentContext": "starship" }).to_string(),
+        )?;
+        write_file(
+            &home.path().join(".config/containers/containers.conf"),
+            "[engine]\nactive_service = \"legacy\"\n",
+        )?;
+
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("🐳 starship (docker)")
+        ));
+        assert_eq!(expected, render_engines(home.path()).collect());
+
+        let actual = render_engines(home.path())
+            .env("CONTAINER_HOST", "unix:///run/podman/podman.sock")
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue
+                .bold()
+                .paint("🦭 unix:///run/podman/podman.sock (podman)")
+        ));
+        assert_eq!(expected, actual);
+
+        home.close()
+    }
+
+    #[test]
+    fn test_podman_not_detected_by_default() -> io::Result<()> {
+        let home = tempfile::tempdir()?;
+
+        let actual = ModuleRenderer::new("docker_context")
+            .env("HOME", home.path().to_string_lossy())
+            .env("CONTAINER_HOST", "ssh://core@localhost:52345")
+            .config(toml::toml! {
+                [docker_context]
+                only_with_files = false
+            })
+            .collect();
+        assert_eq!(None, actual);
+
+        home.close()
+    }
 }
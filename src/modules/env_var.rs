@@ -1,3 +1,6 @@
+use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+
 use super::{Context, Module};
 
 use crate::config::ModuleConfig;
@@ -54,6 +57,7 @@ fn env_var_module<'a>(module_config_path: Vec<&str>, context: &'a Context) -> Op
     let variable_name = get_variable_name(module_config_path, &config);
 
     let env_value = get_env_value(context, variable_name?, config.default)?;
+    let env_value = transform_value(env_value, &config);
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -102,6 +106,40 @@ fn get_env_value(context: &Context, name: &str, default: Option<&str>) -> Option
     }
 }
 
+/// Applies the `regex`, `replace` and `max_length` options to the value, in that order
+fn transform_value(mut value: String, config: &EnvVarConfig) -> String {
+    if let Some(pattern) = config.regex.and_then(compile_regex) {
+        // The first capture group, or the whole match if there is none
+        if let Some(extracted) = pattern
+            .captures(&value)
+            .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+        {
+            value = extracted.as_str().to_string();
+        }
+    }
+
+    if let Some([pattern, replacement]) = config.replace {
+        if let Some(pattern) = compile_regex(pattern) {
+            value = pattern.replace_all(&value, replacement).into_owned();
+        }
+    }
+
+    match config.max_length {
+        Some(max_length) if value.graphemes(true).count() > max_length => {
+            let mut truncated: String = value.graphemes(true).take(max_length).collect();
+            truncated.push_str(config.truncation_symbol);
+            truncated
+        }
+        _ => value,
+    }
+}
+
+fn compile_regex(pattern: &str) -> Option<Regex> {
+    Regex::new(pattern)
+        .map_err(|error| log::warn!("Invalid regex {:?} in module `env_var`: {}", pattern, error))
+        .ok()
+}
+
 #[cfg(test)]
 mod test {
     use crate::test::ModuleRenderer;
@@ -253,6 +291,85 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn regex_capture() {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var.AWS_ROLE]
+                regex = "^arn:aws:iam::(\\d+):"
+            })
+            .env("AWS_ROLE", "arn:aws:iam::123456789012:role/admin")
+            .collect();
+        let expected = Some(format!("with {} ", style().paint("123456789012")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn regex_without_match() {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+                regex = "^arn:(.*)"
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .collect();
+        let expected = Some(format!("with {} ", style().paint(TEST_VAR_VALUE)));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn replace() {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+                replace = ["(astro)(nauts)", "${2}_$1"]
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .collect();
+        let expected = Some(format!("with {} ", style().paint("nauts_astro")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn max_length() {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+                max_length = 5
+                [env_var.TEST_VAR2]
+                max_length = 10
+                truncation_symbol = "..."
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .env("TEST_VAR2", TEST_VAR_VALUE)
+            .collect();
+        let expected = Some(format!(
+            "with {} with {} ",
+            style().paint("astro…"),
+            style().paint(TEST_VAR_VALUE)
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let actual = ModuleRenderer::new("env_var")
+            .config(toml::toml! {
+                [env_var.TEST_VAR]
+                regex = "(astro"
+                replace = ["[", ""]
+            })
+            .env("TEST_VAR", TEST_VAR_VALUE)
+            .collect();
+        let expected = Some(format!("with {} ", style().paint(TEST_VAR_VALUE)));
+
+        assert_eq!(expected, actual);
+    }
+
     fn style() -> Style {
         // default style
         Color::Black.bold().dimmed()
@@ -2,7 +2,8 @@ use super::{Context, Module};
 
 use crate::config::{parse_style_string, ModuleConfig};
 use crate::configs::fill::FillConfig;
-use crate::segment::Segment;
+use crate::formatter::gradient;
+use crate::segment::{FillSegment, Segment, TextSegment};
 
 /// Creates a module that fills the any extra space on the line.
 ///
@@ -16,14 +17,42 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     let style = parse_style_string(config.style, Some(context));
 
-    module.set_segments(vec![Segment::fill(style, config.symbol)]);
+    let mut fill = FillSegment {
+        style,
+        value: config.symbol.to_string(),
+        gradient: Vec::new(),
+    };
+    let has_gradient = module
+        .config
+        .map_or(false, |config| config.get("gradient").is_some());
+    if has_gradient {
+        // The width of the fill is only known once the line is laid out, so the
+        // gradient is sampled for the widest fill possible and stretched to fit
+        let widest = Segment::Text(TextSegment {
+            style,
+            value: fill.expanded_value(Some(context.width)),
+        });
+        fill.gradient = gradient::gradientify_segments(
+            context,
+            &[widest],
+            &gradient::build_gradient(&config.gradient),
+            &config.gradient,
+        )
+        .iter()
+        .map(|segment| segment.style().unwrap_or_default())
+        .collect();
+    }
+
+    module.set_segments(vec![Segment::Fill(fill)]);
 
     Some(module)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test::ModuleRenderer;
+    use crate::config::StarshipConfig;
+    use crate::print::{get_prompt, UnicodeWidthGraphemes};
+    use crate::test::{default_context, ModuleRenderer};
     use nu_ansi_term::Color;
 
     #[test]
@@ -65,4 +94,85 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    /// Renders the prompt line `format` at a width of 20 columns
+    fn render_line(format: &str, fill: toml::Value) -> String {
+        let mut context = default_context();
+        let mut config = toml::value::Table::new();
+        config.insert("fill".to_string(), fill);
+        context.config = StarshipConfig {
+            config: Some(toml::Value::Table(config)),
+        };
+        context.root_config.format = format.to_string();
+        context.root_config.add_newline = false;
+        context.width = 20;
+        get_prompt(context)
+    }
+
+    /// The foreground colors of the graphemes colored with a 24-bit color
+    fn rgb_graphemes(line: &str) -> Vec<((u8, u8, u8), String)> {
+        let painted =
+            regex::Regex::new("\x1b\\[(?:1;)?38;2;(\\d+);(\\d+);(\\d+)m([^\x1b]+)").unwrap();
+        painted
+            .captures_iter(line)
+            .map(|captures| {
+                let channel = |i: usize| captures[i].parse().unwrap();
+                ((channel(1), channel(2), channel(3)), captures[4].to_owned())
+            })
+            .collect()
+    }
+
+    fn visible_width(line: &str) -> usize {
+        let escapes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        escapes.replace_all(line, "").width_graphemes()
+    }
+
+    #[test]
+    fn pattern_fills_the_width() {
+        let line = render_line(
+            "[left](red)$fill[right](blue)",
+            toml::toml! {
+                symbol = "·—"
+            },
+        );
+
+        assert_eq!(20, visible_width(&line));
+        assert!(line.contains("·—·—·—·—·—·"), "{line:?}");
+    }
+
+    #[test]
+    fn wide_pattern_fills_the_width() {
+        // 10 columns are left, which 🟦- can't fill without a space
+        let line = render_line(
+            "[left](red)$fill[right!](blue)",
+            toml::toml! {
+                symbol = "🟦-"
+            },
+        );
+
+        assert_eq!(20, visible_width(&line));
+        assert!(line.contains("🟦-🟦-🟦- "), "{line:?}");
+    }
+
+    #[test]
+    fn gradient_across_the_fill() {
+        let line = render_line(
+            "left$fill right",
+            toml::toml! {
+                symbol = "·—"
+                gradient = ["#ff0000", "#0000ff"]
+            },
+        );
+
+        assert_eq!(20, visible_width(&line));
+        let fill = rgb_graphemes(&line);
+        let text: String = fill.iter().map(|(_, grapheme)| grapheme.as_str()).collect();
+        assert_eq!("·—·—·—·—·—", text);
+        assert_eq!((255, 0, 0), fill[0].0);
+        assert_eq!((0, 0, 255), fill[fill.len() - 1].0);
+        // The red fades and the blue grows across the fill
+        assert!(fill
+            .windows(2)
+            .all(|pair| pair[0].0 .0 > pair[1].0 .0 && pair[0].0 .2 < pair[1].0 .2));
+    }
 }
@@ -1,9 +1,11 @@
+use once_cell::sync::Lazy;
 use unicode_segmentation::UnicodeSegmentation;
 
+use super::git_status::get_base_ahead_behind;
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::git_branch::GitBranchConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::{gradient, StringFormatter};
 
 /// Creates a module with the Git branch in the current directory
 ///
@@ -70,6 +72,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let show_remote = config.always_show_remote
         || (!graphemes.eq(&remote_branch_graphemes) && !remote_branch_graphemes.is_empty());
 
+    let base_ahead_behind = Lazy::new(|| {
+        config
+            .compare_to
+            .and_then(|base| get_base_ahead_behind(context, base))
+    });
+    let base_count = |count: usize| (count > 0).then(|| Ok(count.to_string()));
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -96,6 +105,14 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                         None
                     }
                 }
+                "base_ahead" => base_ahead_behind.and_then(|(ahead, _)| base_count(ahead)),
+                "base_behind" => base_ahead_behind.and_then(|(_, behind)| base_count(behind)),
+                // The color is derived from the full name, so it doesn't change with truncation
+                "branch_color" => Some(Ok(gradient::seeded_color(
+                    context,
+                    branch_name,
+                    &config.hash_palette,
+                ))),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -123,7 +140,8 @@ mod tests {
     use nu_ansi_term::Color;
     use std::io;
 
-    use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
+    use crate::formatter::gradient;
+    use crate::test::{default_context, fixture_repo, FixtureProvider, ModuleRenderer};
     use crate::utils::create_command;
 
     #[test]
@@ -264,6 +282,61 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_format_branch_color() -> io::Result<()> {
+        test_format(
+            "1337_hello_world",
+            "[$branch](bold fg:$branch_color)",
+            r#"hash_palette = ["red"]"#,
+            Color::Red.bold().paint("1337_hello_world").to_string(),
+        )?;
+        // The color doesn't change with the truncation of the name
+        test_format(
+            "1337_hello_world",
+            "[$branch](=fg:$branch_color)",
+            r#"
+            hash_palette = ["red"]
+            truncation_length = 4
+        "#,
+            Color::Red.paint("1337…").to_string(),
+        )
+    }
+
+    #[test]
+    fn test_format_branch_color_from_hash() -> io::Result<()> {
+        let palette = ["red", "green", "blue"];
+        let expected = |branch: &str| {
+            let color = gradient::seeded_color(&default_context(), branch, &palette);
+            let color = match color.as_str() {
+                "red" => Color::Red,
+                "green" => Color::Green,
+                _ => Color::Blue,
+            };
+            color.paint(branch).to_string()
+        };
+
+        for branch in ["main", "feature/a", "feature/b", "release"] {
+            test_format(
+                branch,
+                "[$branch](fg:$branch_color)",
+                r#"hash_palette = ["red", "green", "blue"]"#,
+                expected(branch),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_invalid_branch_color() -> io::Result<()> {
+        // Falls back to the default style around the text group
+        test_format(
+            "1337_hello_world",
+            "[on [$branch](fg:$branch_color)](green)",
+            r#"hash_palette = ["not-a-color"]"#,
+            Color::Green.paint("on 1337_hello_world").to_string(),
+        )
+    }
+
     #[test]
     fn test_works_with_unborn_default_branch() -> io::Result<()> {
         let repo_dir = tempfile::tempdir()?;
@@ -472,6 +545,47 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_base_ahead_behind() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        for args in [
+            &["checkout", "-b", "base", "HEAD^"][..],
+            &["commit", "--allow-empty", "-m", "Base", "--no-gpg-sign"],
+            &["checkout", "-b", "feature", "master"],
+            &["commit", "--allow-empty", "-m", "Feature", "--no-gpg-sign"],
+        ] {
+            create_command("git")?
+                .args(args)
+                .current_dir(repo_dir.path())
+                .output()?;
+        }
+
+        let render = |compare_to: &str| {
+            ModuleRenderer::new("git_branch")
+                .config(toml::toml! {
+                    [git_branch]
+                    format = "$branch( ⇡$base_ahead)( ⇣$base_behind)"
+                    compare_to = compare_to
+                })
+                .path(repo_dir.path())
+                .collect()
+        };
+
+        assert_eq!(Some("feature ⇡2 ⇣1".to_string()), render("base"));
+        assert_eq!(Some("feature ⇡1".to_string()), render("origin/master"));
+        assert_eq!(Some("feature".to_string()), render("origin/main"));
+
+        let actual = ModuleRenderer::new("git_branch")
+            .config(toml::toml! {
+                [git_branch]
+                format = "$branch( ⇡$base_ahead)"
+            })
+            .path(repo_dir.path())
+            .collect();
+        assert_eq!(Some("feature".to_string()), actual);
+        repo_dir.close()
+    }
+
     fn test_truncate_length_with_config(
         branch_name: &str,
         truncate_length: i64,
@@ -1,5 +1,6 @@
 use super::{Context, Module, ModuleConfig};
 use git_repository::commit::describe::SelectRef::AnnotatedTags;
+use std::ffi::OsStr;
 
 use crate::configs::git_commit::GitCommitConfig;
 use crate::context::Repo;
@@ -7,7 +8,8 @@ use crate::formatter::StringFormatter;
 
 /// Creates a module with the Git commit in the current directory
 ///
-/// Will display the commit hash if the current directory is a git repo
+/// Will display the commit hash if the current directory is a git repo, and whether
+/// the commit is signed
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("git_commit");
     let config: GitCommitConfig = GitCommitConfig::try_load(module.config);
@@ -34,6 +36,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     config.tag_symbol,
                     git_tag(context.get_repo().ok()?, &config)?
                 ))),
+                "signed" => {
+                    let symbol = signed_symbol(context, context.get_repo().ok()?, &config)?;
+                    (!symbol.is_empty()).then(|| Ok(symbol.to_string()))
+                }
                 _ => None,
             })
             .parse(None, Some(context))
@@ -66,6 +72,50 @@ fn git_tag(repo: &Repo, config: &GitCommitConfig) -> Option<String> {
     Some(formatter.name?.to_string())
 }
 
+/// The symbol for the signature of HEAD
+///
+/// Without `verify`, any signature counts as signed. Otherwise the signature is checked
+/// with `git verify-commit`, which needs gpg or ssh-keygen.
+fn signed_symbol<'a>(
+    context: &Context,
+    repo: &Repo,
+    config: &GitCommitConfig<'a>,
+) -> Option<&'a str> {
+    let git_repo = repo.open();
+    let head_commit = git_repo.head_commit().ok()?;
+    let has_signature = head_commit
+        .decode()
+        .ok()?
+        .extra_headers()
+        .pgp_signature()
+        .is_some();
+
+    if !has_signature {
+        return Some(config.unsigned_symbol);
+    }
+    if !config.verify {
+        return Some(config.signed_symbol);
+    }
+
+    let workdir = repo.workdir.as_ref()?;
+    let verified = context
+        .exec_cmd(
+            "git",
+            &[
+                OsStr::new("-C"),
+                workdir.as_os_str(),
+                OsStr::new("verify-commit"),
+                OsStr::new("HEAD"),
+            ],
+        )
+        .is_some();
+    Some(if verified {
+        config.signed_symbol
+    } else {
+        config.unverified_symbol
+    })
+}
+
 fn git_hash(repo: &Repo, config: &GitCommitConfig) -> Option<String> {
     let git_repo = repo.open();
     let head_id = git_repo.head_id().ok()?;
@@ -79,7 +129,9 @@ fn git_hash(repo: &Repo, config: &GitCommitConfig) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use nu_ansi_term::Color;
-    use std::{io, str};
+    use std::io;
+    use std::path::Path;
+    use std::str;
 
     use crate::test::{fixture_repo, FixtureProvider, ModuleRenderer};
     use crate::utils::create_command;
@@ -364,4 +416,127 @@ mod tests {
         assert_eq!(expected, actual);
         Ok(())
     }
+
+    fn render_signed(repo_dir: &Path, verify: bool) -> Option<String> {
+        ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    format = "$signed"
+                    unsigned_symbol = "unsigned"
+                    verify = verify
+            })
+            .path(repo_dir)
+            .collect()
+    }
+
+    fn git(repo_dir: &Path, args: &[&str]) -> io::Result<Vec<u8>> {
+        let output = create_command("git")?
+            .args(args)
+            .current_dir(repo_dir)
+            .output()?;
+        if output.status.success() {
+            Ok(output.stdout)
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                String::from_utf8_lossy(&output.stderr),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_render_unsigned() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let path = repo_dir.path();
+
+        git(
+            path,
+            &[
+                "commit",
+                "--allow-empty",
+                "--no-gpg-sign",
+                "--message",
+                "Unsigned commit",
+            ],
+        )?;
+
+        assert_eq!(Some("unsigned".to_string()), render_signed(path, false));
+        assert_eq!(Some("unsigned".to_string()), render_signed(path, true));
+
+        let actual = ModuleRenderer::new("git_commit")
+            .config(toml::toml! {
+                [git_commit]
+                    only_detached = false
+                    format = "$signed"
+            })
+            .path(path)
+            .collect();
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_signature_presence() -> io::Result<()> {
+        // HEAD of the fixture is signed with a key that isn't available here
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        assert_eq!(
+            Some("🔏".to_string()),
+            render_signed(repo_dir.path(), false)
+        );
+        assert_eq!(Some("❓".to_string()), render_signed(repo_dir.path(), true));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn test_render_verified_signature() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+        let path = repo_dir.path();
+        let key_dir = tempfile::tempdir()?;
+        let key = key_dir.path().join("key");
+
+        let keygen = create_command("ssh-keygen").and_then(|mut command| {
+            command
+                .args(&["-q", "-t", "ed25519", "-N", "", "-C", "", "-f"])
+                .arg(&key)
+                .output()
+        });
+        if !keygen.map_or(false, |output| output.status.success()) {
+            // ssh-keygen isn't available to sign commits
+            return repo_dir.close();
+        }
+        let public_key = std::fs::read_to_string(key.with_extension("pub"))?;
+        let allowed_signers = key_dir.path().join("allowed_signers");
+        std::fs::write(
+            &allowed_signers,
+            format!("starship@example.com {}", public_key),
+        )?;
+
+        git(path, &["config", "gpg.format", "ssh"])?;
+        git(path, &["config", "user.signingkey", key.to_str().unwrap()])?;
+        git(
+            path,
+            &[
+                "config",
+                "gpg.ssh.allowedSignersFile",
+                allowed_signers.to_str().unwrap(),
+            ],
+        )?;
+        git(
+            path,
+            &[
+                "commit",
+                "--allow-empty",
+                "-S",
+                "--message",
+                "Signed commit",
+            ],
+        )?;
+
+        assert_eq!(Some("🔏".to_string()), render_signed(path, false));
+        assert_eq!(Some("🔏".to_string()), render_signed(path, true));
+        key_dir.close()?;
+        repo_dir.close()
+    }
 }
@@ -1,5 +1,7 @@
 use regex::Regex;
+use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::path::Path;
 
 use crate::{
     config::ModuleConfig, configs::git_metrics::GitMetricsConfig,
@@ -23,22 +25,27 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let repo = context.get_repo().ok()?;
     let repo_root = repo.workdir.as_ref()?;
 
-    let diff = context
-        .exec_cmd(
-            "git",
-            &[
-                OsStr::new("--git-dir"),
-                repo.path.as_os_str(),
-                OsStr::new("--work-tree"),
-                repo_root.as_os_str(),
-                OsStr::new("--no-optional-locks"),
-                OsStr::new("diff"),
-                OsStr::new("--shortstat"),
-            ],
-        )?
-        .stdout;
+    let mut args = vec![
+        OsStr::new("--git-dir"),
+        repo.path.as_os_str(),
+        OsStr::new("--work-tree"),
+        repo_root.as_os_str(),
+        OsStr::new("--no-optional-locks"),
+        OsStr::new("diff"),
+        OsStr::new("--shortstat"),
+    ];
+    // The per-file stats are printed before the summary of `--shortstat`
+    if config.breakdown {
+        args.push(OsStr::new("--numstat"));
+    }
+    let diff = context.exec_cmd("git", &args)?.stdout;
 
     let stats = GitDiff::parse(&diff);
+    let breakdown = if config.breakdown {
+        format_breakdown(&diff, config.breakdown_limit)
+    } else {
+        String::new()
+    };
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -50,6 +57,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .map(|variable| match variable {
                 "added" => GitDiff::get_variable(config.only_nonzero_diffs, stats.added),
                 "deleted" => GitDiff::get_variable(config.only_nonzero_diffs, stats.deleted),
+                "breakdown" if !breakdown.is_empty() => Some(Ok(breakdown.as_str())),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -107,6 +115,65 @@ impl<'a> GitDiff<'a> {
     }
 }
 
+/// Lists the extensions with the most changed lines in the output of `git diff --numstat`,
+/// e.g. `rs+120/-30 toml+4`, followed by the number of changed binary files as `bin(2)`
+fn format_breakdown(diff: &str, limit: usize) -> String {
+    let mut lines: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut binary_files = 0;
+    for line in diff.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (added, deleted, path) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(added), Some(deleted), Some(path)) => (added, deleted, path),
+            _ => continue,
+        };
+        let (added, deleted) = match (added.parse::<usize>(), deleted.parse::<usize>()) {
+            (Ok(added), Ok(deleted)) => (added, deleted),
+            _ if added == "-" && deleted == "-" => {
+                binary_files += 1;
+                continue;
+            }
+            _ => continue,
+        };
+
+        // Renamed files are listed as `old => new` or `dir/{old => new}`
+        let path = path.rsplit("=> ").next().unwrap_or(path).replace('}', "");
+        let path = Path::new(&path);
+        let name = path.extension().or_else(|| path.file_name());
+        let name = name.map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        let entry = lines.entry(name).or_default();
+        entry.0 += added;
+        entry.1 += deleted;
+    }
+
+    let mut lines: Vec<_> = lines
+        .into_iter()
+        .filter(|(_, (added, deleted))| added + deleted > 0)
+        .collect();
+    lines.sort_by(
+        |(a_name, (a_added, a_deleted)), (b_name, (b_added, b_deleted))| {
+            (b_added + b_deleted)
+                .cmp(&(a_added + a_deleted))
+                .then_with(|| a_name.cmp(b_name))
+        },
+    );
+    if limit > 0 {
+        lines.truncate(limit);
+    }
+
+    let mut parts: Vec<String> = lines
+        .into_iter()
+        .map(|(name, (added, deleted))| match (added, deleted) {
+            (_, 0) => format!("{}+{}", name, added),
+            (0, _) => format!("{}-{}", name, deleted),
+            _ => format!("{}+{}/-{}", name, added, deleted),
+        })
+        .collect();
+    if binary_files > 0 {
+        parts.push(format!("bin({})", binary_files));
+    }
+    parts.join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::create_command;
@@ -228,6 +295,68 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_breakdown_by_extension() -> io::Result<()> {
+        let repo_dir = create_repo_with_commit()?;
+        let path = repo_dir.path();
+
+        write_file(path.join("main.rs"), "fn main() {}")?;
+        write_file(path.join("Cargo.toml"), "[package]")?;
+        write_file(path.join("README.md"), "# Title\nText\nMore text")?;
+        write_file(path.join("logo.png"), "\0\u{1}")?;
+        run_git_cmd(&["add", "."], Some(path), true)?;
+        run_git_cmd(
+            &["commit", "--message", "Commit B", "--no-gpg-sign"],
+            Some(path),
+            true,
+        )?;
+
+        write_file(
+            path.join("main.rs"),
+            "fn main() {\n    run();\n}\nfn run() {}",
+        )?;
+        write_file(path.join("Cargo.toml"), "[package]\nname = \"app\"")?;
+        write_file(path.join("README.md"), "# Title")?;
+        write_file(path.join("logo.png"), "\0\u{2}")?;
+
+        let render = |breakdown_limit: usize| {
+            ModuleRenderer::new("git_metrics")
+                .config(toml::toml! {
+                    [git_metrics]
+                    disabled = false
+                    format = "$breakdown"
+                    breakdown = true
+                    breakdown_limit = breakdown_limit
+                })
+                .path(path)
+                .collect()
+        };
+
+        assert_eq!(Some("rs+4/-1 md-2 toml+1 bin(1)".to_string()), render(3));
+        assert_eq!(Some("rs+4/-1 bin(1)".to_string()), render(1));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_no_breakdown_if_disabled() -> io::Result<()> {
+        let repo_dir = create_repo_with_commit()?;
+        let path = repo_dir.path();
+
+        write_file(path.join("the_file"), "Changed")?;
+
+        let actual = ModuleRenderer::new("git_metrics")
+            .config(toml::toml! {
+                [git_metrics]
+                disabled = false
+                format = "($breakdown )[+$added]($added_style)"
+            })
+            .path(path)
+            .collect();
+
+        assert_eq!(Some(Color::Green.bold().paint("+1").to_string()), actual);
+        repo_dir.close()
+    }
+
     fn render_metrics(path: &Path) -> Option<String> {
         ModuleRenderer::new("git_metrics")
             .config(toml::toml! {
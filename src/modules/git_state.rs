@@ -9,7 +9,8 @@ use crate::formatter::StringFormatter;
 /// Creates a module with the state of the git repository at the current directory
 ///
 /// During a git operation it will show: REBASING, BISECTING, MERGING, etc.
-/// If the progress information is available (e.g. rebasing 3/10), it will show that too.
+/// If the progress information is available (e.g. rebasing 3/10), it will show that too,
+/// optionally as a progress bar.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("git_state");
     let config: GitStateConfig = GitStateConfig::try_load(module.config);
@@ -17,6 +18,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let repo = context.get_repo().ok()?;
 
     let state_description = get_state_description(repo, &config)?;
+    let progress_bar = state_description
+        .progress
+        .and_then(|(current, total)| format_progress_bar(&config, current, total));
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -31,6 +35,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .map(|variable| match variable {
                 "progress_current" => state_description.current.as_ref().map(Ok),
                 "progress_total" => state_description.total.as_ref().map(Ok),
+                "progress_bar" => progress_bar.as_ref().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -59,41 +64,49 @@ fn get_state_description<'a>(
             label: config.merge,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::Revert => Some(StateDescription {
             label: config.revert,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::RevertSequence => Some(StateDescription {
             label: config.revert,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::CherryPick => Some(StateDescription {
             label: config.cherry_pick,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::CherryPickSequence => Some(StateDescription {
             label: config.cherry_pick,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::Bisect => Some(StateDescription {
             label: config.bisect,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::ApplyMailbox => Some(StateDescription {
             label: config.am,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::ApplyMailboxRebase => Some(StateDescription {
             label: config.am_or_rebase,
             current: None,
             total: None,
+            progress: None,
         }),
         InProgress::Rebase => Some(describe_rebase(repo, config.rebase)),
         InProgress::RebaseInteractive => Some(describe_rebase(repo, config.rebase)),
@@ -146,13 +159,31 @@ fn describe_rebase<'a>(repo: &'a Repo, rebase_config: &'a str) -> StateDescripti
         label: rebase_config,
         current,
         total,
+        progress,
     }
 }
 
+/// Renders `current` out of `total` steps as `progress_bar_width` glyphs, or `None`
+/// if there is no step to count
+fn format_progress_bar(config: &GitStateConfig, current: usize, total: usize) -> Option<String> {
+    if total == 0 || config.progress_bar_width == 0 {
+        return None;
+    }
+
+    let width = config.progress_bar_width;
+    let filled = (current.min(total) * width) / total;
+    Some(format!(
+        "{}{}",
+        config.progress_bar_filled.repeat(filled),
+        config.progress_bar_empty.repeat(width - filled)
+    ))
+}
+
 struct StateDescription<'a> {
     label: &'a str,
     current: Option<String>,
     total: Option<String>,
+    progress: Option<(usize, usize)>,
 }
 
 #[cfg(test)]
@@ -196,6 +227,70 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_rebase_progress_bar() -> io::Result<()> {
+        let repo_dir = create_repo_with_conflict()?;
+        let path = repo_dir.path();
+
+        run_git_cmd(&["rebase", "other-branch"], Some(path), false)?;
+
+        let render = |width: usize| {
+            ModuleRenderer::new("git_state")
+                .config(toml::toml! {
+                    [git_state]
+                    format = "$progress_bar"
+                    progress_bar_width = width
+                })
+                .path(path)
+                .collect()
+        };
+        let set_progress = |current: &str, total: &str| -> io::Result<()> {
+            let rebase_dir = path.join(".git").join("rebase-merge");
+            std::fs::write(rebase_dir.join("msgnum"), current)?;
+            std::fs::write(rebase_dir.join("end"), total)
+        };
+
+        assert_eq!(Some("▰▰▰▰▰▰▰▰".to_string()), render(8));
+
+        set_progress("3\n", "12\n")?;
+        assert_eq!(Some("▰▰▱▱▱▱▱▱".to_string()), render(8));
+        assert_eq!(Some("▰▱▱▱".to_string()), render(4));
+        assert_eq!(None, render(0));
+
+        set_progress("13\n", "12\n")?;
+        assert_eq!(Some("▰▰▰▰".to_string()), render(4));
+
+        set_progress("0\n", "0\n")?;
+        assert_eq!(None, render(4));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_custom_progress_bar_glyphs() -> io::Result<()> {
+        let repo_dir = create_repo_with_conflict()?;
+        let path = repo_dir.path();
+
+        run_git_cmd(&["rebase", "other-branch"], Some(path), false)?;
+        let rebase_dir = path.join(".git").join("rebase-merge");
+        std::fs::write(rebase_dir.join("msgnum"), "1")?;
+        std::fs::write(rebase_dir.join("end"), "2")?;
+
+        let actual = ModuleRenderer::new("git_state")
+            .config(toml::toml! {
+                [git_state]
+                format = "[$state $progress_bar]($style)"
+                progress_bar_width = 6
+                progress_bar_filled = "#"
+                progress_bar_empty = "-"
+            })
+            .path(path)
+            .collect();
+
+        let expected = Some(Color::Yellow.bold().paint("REBASING ###---").to_string());
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_merging() -> io::Result<()> {
         let repo_dir = create_repo_with_conflict()?;
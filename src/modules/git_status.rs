@@ -26,6 +26,9 @@ const ALL_STATUS_FORMAT: &str = "$conflicted$stashed$deleted$renamed$modified$st
 ///   - `+` — A new file has been added to the staging area
 ///   - `»` — A renamed file has been added to the staging area
 ///   - `✘` — A file's deletion has been added to the staging area
+///
+/// Counts above `count_limit` are shown as the limit followed by `count_overflow_suffix`.
+/// With `compare_to`, the number of commits ahead and behind that branch are shown too.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("git_status");
     let config: GitStatusConfig = GitStatusConfig::try_load(module.config);
@@ -57,7 +60,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 let info = Arc::clone(&info);
                 let segments = match variable {
                     "stashed" => info.get_stashed().and_then(|count| {
-                        format_count(config.stashed, "git_status.stashed", context, count)
+                        format_count(&config, config.stashed, "stashed", context, count)
                     }),
                     "ahead_behind" => info.get_ahead_behind().and_then(|(ahead, behind)| {
                         let (ahead, behind) = (ahead?, behind?);
@@ -67,36 +70,45 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                                 "git_status.diverged",
                                 context,
                                 |variable| match variable {
-                                    "ahead_count" => Some(ahead.to_string()),
-                                    "behind_count" => Some(behind.to_string()),
+                                    "ahead_count" => Some(limit_count(&config, "ahead", ahead)),
+                                    "behind_count" => Some(limit_count(&config, "behind", behind)),
                                     _ => None,
                                 },
                             )
                         } else if ahead > 0 && behind == 0 {
-                            format_count(config.ahead, "git_status.ahead", context, ahead)
+                            format_count(&config, config.ahead, "ahead", context, ahead)
                         } else if behind > 0 && ahead == 0 {
-                            format_count(config.behind, "git_status.behind", context, behind)
+                            format_count(&config, config.behind, "behind", context, behind)
                         } else {
                             format_symbol(config.up_to_date, "git_status.up_to_date", context)
                         }
                     }),
                     "conflicted" => info.get_conflicted().and_then(|count| {
-                        format_count(config.conflicted, "git_status.conflicted", context, count)
+                        format_count(&config, config.conflicted, "conflicted", context, count)
                     }),
                     "deleted" => info.get_deleted().and_then(|count| {
-                        format_count(config.deleted, "git_status.deleted", context, count)
+                        format_count(&config, config.deleted, "deleted", context, count)
                     }),
                     "renamed" => info.get_renamed().and_then(|count| {
-                        format_count(config.renamed, "git_status.renamed", context, count)
+                        format_count(&config, config.renamed, "renamed", context, count)
                     }),
                     "modified" => info.get_modified().and_then(|count| {
-                        format_count(config.modified, "git_status.modified", context, count)
+                        format_count(&config, config.modified, "modified", context, count)
                     }),
                     "staged" => info.get_staged().and_then(|count| {
-                        format_count(config.staged, "git_status.staged", context, count)
+                        format_count(&config, config.staged, "staged", context, count)
                     }),
                     "untracked" => info.get_untracked().and_then(|count| {
-                        format_count(config.untracked, "git_status.untracked", context, count)
+                        format_count(&config, config.untracked, "untracked", context, count)
+                    }),
+                    "total_changed" => info.get_total_changed().and_then(|count| {
+                        format_count(&config, "$count", "total_changed", context, count)
+                    }),
+                    "base_ahead" => info.get_base_ahead_behind().and_then(|(ahead, _)| {
+                        format_count(&config, "$count", "base_ahead", context, ahead)
+                    }),
+                    "base_behind" => info.get_base_ahead_behind().and_then(|(_, behind)| {
+                        format_count(&config, "$count", "base_behind", context, behind)
                     }),
                     _ => None,
                 };
@@ -127,6 +139,7 @@ struct GitStatusInfo<'a> {
     config: GitStatusConfig<'a>,
     repo_status: OnceCell<Option<RepoStatus>>,
     stashed_count: OnceCell<Option<usize>>,
+    base_ahead_behind: OnceCell<Option<(usize, usize)>>,
 }
 
 impl<'a> GitStatusInfo<'a> {
@@ -136,6 +149,7 @@ impl<'a> GitStatusInfo<'a> {
             config,
             repo_status: OnceCell::new(),
             stashed_count: OnceCell::new(),
+            base_ahead_behind: OnceCell::new(),
         }
     }
 
@@ -165,6 +179,12 @@ impl<'a> GitStatusInfo<'a> {
             })
     }
 
+    pub fn get_base_ahead_behind(&self) -> Option<(usize, usize)> {
+        *self
+            .base_ahead_behind
+            .get_or_init(|| get_base_ahead_behind(self.context, self.config.compare_to?))
+    }
+
     pub fn get_conflicted(&self) -> Option<usize> {
         self.get_repo_status().map(|data| data.conflicted)
     }
@@ -188,6 +208,11 @@ impl<'a> GitStatusInfo<'a> {
     pub fn get_untracked(&self) -> Option<usize> {
         self.get_repo_status().map(|data| data.untracked)
     }
+
+    pub fn get_total_changed(&self) -> Option<usize> {
+        self.get_repo_status()
+            .map(|data| data.staged + data.modified + data.untracked)
+    }
 }
 
 /// Gets the number of files in various git states (staged, modified, deleted, etc...)
@@ -235,6 +260,39 @@ fn get_repo_status(context: &Context, config: &GitStatusConfig) -> Option<RepoSt
     Some(repo_status)
 }
 
+/// Counts the commits only `HEAD` has and the commits only `base` has
+///
+/// Returns `None` if `base` isn't a ref in the repository.
+pub fn get_base_ahead_behind(context: &Context, base: &str) -> Option<(usize, usize)> {
+    let range = format!("HEAD...{}", base);
+    let output = context.exec_cmd(
+        "git",
+        &[
+            OsStr::new("-C"),
+            context.current_dir.as_os_str(),
+            OsStr::new("--no-optional-locks"),
+            OsStr::new("rev-list"),
+            OsStr::new("--left-right"),
+            OsStr::new("--count"),
+            OsStr::new(&range),
+            OsStr::new("--"),
+        ],
+    );
+    let output = match output {
+        Some(output) => output,
+        None => {
+            log::debug!("Unable to compare HEAD to {:?}", base);
+            return None;
+        }
+    };
+
+    let mut counts = output.stdout.split_whitespace().map(str::parse::<usize>);
+    match (counts.next(), counts.next()) {
+        (Some(Ok(ahead)), Some(Ok(behind))) => Some((ahead, behind)),
+        _ => None,
+    }
+}
+
 fn get_stashed_count(context: &Context) -> Option<usize> {
     let stash_output = context.exec_cmd(
         "git",
@@ -338,8 +396,9 @@ where
 }
 
 fn format_count(
+    config: &GitStatusConfig,
     format_str: &str,
-    config_path: &str,
+    name: &str,
     context: &Context,
     count: usize,
 ) -> Option<Vec<Segment>> {
@@ -349,15 +408,29 @@ fn format_count(
 
     format_text(
         format_str,
-        config_path,
+        &format!("git_status.{}", name),
         context,
         |variable| match variable {
-            "count" => Some(count.to_string()),
+            "count" => Some(limit_count(config, name, count)),
             _ => None,
         },
     )
 }
 
+/// The count shown for `name`, cut off at its `count_limits` entry or `count_limit`
+fn limit_count(config: &GitStatusConfig, name: &str, count: usize) -> String {
+    let limit = config
+        .count_limits
+        .get(name)
+        .copied()
+        .unwrap_or(config.count_limit);
+    if limit > 0 && count > limit {
+        format!("{}{}", limit, config.count_overflow_suffix)
+    } else {
+        count.to_string()
+    }
+}
+
 fn format_symbol(format_str: &str, config_path: &str, context: &Context) -> Option<Vec<Segment>> {
     format_text(format_str, config_path, context, |_variable| None)
 }
@@ -534,6 +607,30 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_ahead_behind_base() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_base_branch(repo_dir.path())?;
+        ahead(repo_dir.path())?;
+
+        let render = |compare_to: &str| {
+            ModuleRenderer::new("git_status")
+                .config(toml::toml! {
+                    [git_status]
+                    format = "$ahead_behind( base ⇡$base_ahead)( ⇣$base_behind)"
+                    compare_to = compare_to
+                })
+                .path(repo_dir.path())
+                .collect()
+        };
+
+        assert_eq!(Some("⇡ base ⇡2 ⇣1".to_string()), render("base"));
+        assert_eq!(Some("⇡ base ⇡1".to_string()), render("origin/master"));
+        assert_eq!(Some("⇡".to_string()), render("origin/missing"));
+        repo_dir.close()
+    }
+
     #[test]
     fn shows_ahead() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -689,6 +786,90 @@ mod tests {
         repo_dir.close()
     }
 
+    #[test]
+    fn shows_counts_above_limit_as_overflow() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        for i in 0..12 {
+            File::create(repo_dir.path().join(format!("untracked-{}", i)))?.sync_all()?;
+        }
+        create_modified(repo_dir.path())?;
+
+        let render = |config: toml::Value| {
+            ModuleRenderer::new("git_status")
+                .config(config)
+                .path(&repo_dir.path())
+                .collect()
+        };
+
+        assert_eq!(
+            format_output("!1?9+"),
+            render(toml::toml! {
+                [git_status]
+                untracked = "?$count"
+                modified = "!$count"
+                count_limit = 9
+            })
+        );
+        assert_eq!(
+            format_output("!1?12"),
+            render(toml::toml! {
+                [git_status]
+                untracked = "?$count"
+                modified = "!$count"
+                count_limit = 20
+            })
+        );
+        assert_eq!(
+            format_output("!1?10…"),
+            render(toml::toml! {
+                [git_status]
+                untracked = "?$count"
+                modified = "!$count"
+                count_limit = 0
+                count_overflow_suffix = "…"
+                [git_status.count_limits]
+                untracked = 10
+            })
+        );
+        repo_dir.close()
+    }
+
+    #[test]
+    fn shows_total_changed() -> io::Result<()> {
+        let repo_dir = fixture_repo(FixtureProvider::Git)?;
+
+        create_staged(repo_dir.path())?;
+        for i in 0..12 {
+            File::create(repo_dir.path().join(format!("untracked-{}", i)))?.sync_all()?;
+        }
+        create_modified(repo_dir.path())?;
+
+        let render = |config: toml::Value| {
+            ModuleRenderer::new("git_status")
+                .config(config)
+                .path(&repo_dir.path())
+                .collect()
+        };
+
+        assert_eq!(
+            Some("14 changed".to_string()),
+            render(toml::toml! {
+                [git_status]
+                format = "($total_changed changed)"
+            })
+        );
+        assert_eq!(
+            Some("9+ changed".to_string()),
+            render(toml::toml! {
+                [git_status]
+                format = "($total_changed changed)"
+                count_limit = 9
+            })
+        );
+        repo_dir.close()
+    }
+
     #[test]
     fn doesnt_show_untracked_file_if_disabled() -> io::Result<()> {
         let repo_dir = fixture_repo(FixtureProvider::Git)?;
@@ -1031,6 +1212,27 @@ mod tests {
         Ok(())
     }
 
+    /// Creates a branch `base` with a commit the current branch doesn't have,
+    /// branching off before its last commit
+    fn create_base_branch(repo_dir: &Path) -> io::Result<()> {
+        create_command("git")?
+            .args(&["checkout", "-b", "base", "HEAD^"])
+            .current_dir(repo_dir)
+            .output()?;
+
+        create_command("git")?
+            .args(&["commit", "--allow-empty", "-m", "Base", "--no-gpg-sign"])
+            .current_dir(repo_dir)
+            .output()?;
+
+        create_command("git")?
+            .args(&["checkout", "master"])
+            .current_dir(repo_dir)
+            .output()?;
+
+        Ok(())
+    }
+
     fn behind(repo_dir: &Path) -> io::Result<()> {
         create_command("git")?
             .args(&["reset", "--hard", "HEAD^"])
@@ -3,6 +3,10 @@ use super::{Context, Module, ModuleConfig};
 use crate::configs::go::GoConfig;
 use crate::formatter::StringFormatter;
 use crate::formatter::VersionFormatter;
+use crate::utils;
+
+use once_cell::sync::Lazy;
+use std::path::PathBuf;
 
 /// Creates a module with the current Go version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
@@ -15,10 +19,18 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         .set_folders(&config.detect_folders)
         .is_match();
 
-    if !is_go_project {
+    // Modules nested in a Go workspace are detected through its `go.work` file
+    let go_work = find_file_in_ancestors(context, "go.work");
+    if !is_go_project && go_work.is_none() {
         return None;
     }
 
+    let go_work_contents = Lazy::new(|| {
+        go_work
+            .as_ref()
+            .and_then(|path| utils::read_file(path).ok())
+    });
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -31,8 +43,23 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "version" => {
-                    let golang_version =
-                        parse_go_version(&context.exec_cmd("go", &["version"])?.stdout)?;
+                    let file_version = || {
+                        find_file_in_ancestors(context, "go.mod")
+                            .and_then(|path| utils::read_file(path).ok())
+                            .and_then(|contents| parse_go_directive_version(&contents))
+                            .or_else(|| {
+                                go_work_contents
+                                    .as_deref()
+                                    .and_then(parse_go_directive_version)
+                            })
+                    };
+                    let binary_version =
+                        || parse_go_version(&context.exec_cmd("go", &["version"])?.stdout);
+                    let golang_version = if config.prefer_mod_version {
+                        file_version().or_else(binary_version)
+                    } else {
+                        binary_version().or_else(file_version)
+                    }?;
 
                     VersionFormatter::format_module_version(
                         module.get_name(),
@@ -41,6 +68,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     )
                     .map(Ok)
                 }
+                "workspace_modules" => go_work_contents
+                    .as_deref()
+                    .map(|contents| count_workspace_modules(contents).to_string())
+                    .map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -57,6 +88,60 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The path of the file `name` in the current directory or the nearest ancestor that has it
+fn find_file_in_ancestors(context: &Context, name: &str) -> Option<PathBuf> {
+    context
+        .current_dir
+        .ancestors()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Strips `//` comments from a line of a `go.mod` or `go.work` file
+fn strip_comment(line: &str) -> &str {
+    line.split_once("//").map_or(line, |(line, _)| line).trim()
+}
+
+/// The version of the `toolchain` directive of a `go.mod` or `go.work` file,
+/// or of its `go` directive if it has none
+fn parse_go_directive_version(contents: &str) -> Option<String> {
+    let directive = |name: &str| {
+        contents.lines().find_map(|line| {
+            let (key, value) = strip_comment(line).split_once(char::is_whitespace)?;
+            (key == name).then(|| value.trim())
+        })
+    };
+
+    directive("toolchain")
+        .and_then(|toolchain| toolchain.strip_prefix("go"))
+        .or_else(|| directive("go"))
+        .filter(|version| !version.is_empty())
+        .map(str::to_string)
+}
+
+/// The number of modules in the `use` directives of a `go.work` file
+fn count_workspace_modules(contents: &str) -> usize {
+    let mut count = 0;
+    let mut in_block = false;
+    for line in contents.lines().map(strip_comment) {
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if !line.is_empty() {
+                count += 1;
+            }
+        } else if let Some(rest) = line.strip_prefix("use") {
+            match rest.trim() {
+                "(" => in_block = true,
+                "" => {}
+                _ if rest.starts_with(char::is_whitespace) => count += 1,
+                _ => {}
+            }
+        }
+    }
+    count
+}
+
 fn parse_go_version(go_stdout: &str) -> Option<String> {
     // go version output looks like this:
     // go version go1.13.3 linux/amd64
@@ -198,6 +283,79 @@ mod tests {
         dir.close()
     }
 
+    fn go_workspace() -> io::Result<tempfile::TempDir> {
+        let dir = tempfile::tempdir()?;
+        fs::write(
+            dir.path().join("go.work"),
+            "go 1.22\n\nuse (\n\t./api // the server\n\t./cli\n)\n",
+        )?;
+        fs::create_dir_all(dir.path().join("api/internal"))?;
+        fs::write(
+            dir.path().join("api/go.mod"),
+            "module example.com/api\n\ngo 1.21\n\ntoolchain go1.21.5\n",
+        )?;
+        Ok(dir)
+    }
+
+    #[test]
+    fn nested_module_in_workspace() -> io::Result<()> {
+        let dir = go_workspace()?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path().join("api/internal"))
+            .config(toml::toml! {
+                [golang]
+                format = "$version $workspace_modules"
+            })
+            .collect();
+
+        assert_eq!(Some("v1.21.5 2".to_string()), actual);
+        dir.close()
+    }
+
+    #[test]
+    fn workspace_root_uses_go_work_version() -> io::Result<()> {
+        let dir = go_workspace()?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path())
+            .config(toml::toml! {
+                [golang]
+                format = "$version"
+            })
+            .collect();
+
+        assert_eq!(Some("v1.22".to_string()), actual);
+        dir.close()
+    }
+
+    #[test]
+    fn prefer_binary_version() -> io::Result<()> {
+        let dir = go_workspace()?;
+
+        let actual = ModuleRenderer::new("golang")
+            .path(dir.path().join("api"))
+            .config(toml::toml! {
+                [golang]
+                prefer_mod_version = false
+                format = "$version( $workspace_modules)"
+            })
+            .collect();
+
+        assert_eq!(Some("v1.12.1 2".to_string()), actual);
+        dir.close()
+    }
+
+    #[test]
+    fn test_count_workspace_modules() {
+        assert_eq!(count_workspace_modules("go 1.22\n"), 0);
+        assert_eq!(count_workspace_modules("use ./a\nuse ./b // b\n"), 2);
+        assert_eq!(
+            count_workspace_modules("use (\n\t./a\n\n\t// ./old\n\t./b\n)\nuse ./c\n"),
+            3
+        );
+    }
+
     #[test]
     fn test_format_go_version() {
         let input = "go version go1.12 darwin/amd64";
@@ -0,0 +1,158 @@
+use super::{directory, Context, Module, ModuleConfig};
+
+use crate::configs::gradient_directory::GradientDirectoryConfig;
+use crate::formatter::gradient;
+
+/// Creates a module with the current directory, colored with a gradient
+///
+/// The path is contracted, substituted and truncated exactly like in the
+/// `directory` module, then every character gets its own color sampled from the
+/// configured gradient.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("gradient_directory");
+    let GradientDirectoryConfig {
+        directory: config,
+        gradient: gradient_config,
+    } = GradientDirectoryConfig::try_load(module.config);
+
+    let gradient = gradient::build_gradient(&gradient_config);
+    let edge_styles = gradient::edge_styles(context, &gradient, &gradient_config);
+    let segments = match directory::format_directory(context, &config, &edge_styles)? {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `gradient_directory`:\n{}", error);
+            return None;
+        }
+    };
+    let segments: Vec<_> = segments
+        .into_iter()
+        .map(|segment| (segment, true))
+        .collect();
+    gradient::gradientify_module(&mut module, context, &segments, &gradient, &gradient_config);
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configs::gradient::GradientConfig;
+    use crate::formatter::gradient::gradientify_segments;
+    use crate::segment::Segment;
+    use crate::test::{default_context, ModuleRenderer};
+    use crate::utils::create_command;
+    use nu_ansi_term::{AnsiStrings, Color, Style};
+    use std::fs;
+    use std::io;
+
+    fn red_to_blue() -> colorgrad::Gradient {
+        colorgrad::CustomGradient::new()
+            .html_colors(&["#ff0000", "#0000ff"])
+            .build()
+            .unwrap()
+    }
+
+    fn paint(segments: &[Segment]) -> String {
+        let strings: Vec<_> = segments.iter().map(Segment::ansi_string).collect();
+        AnsiStrings(&strings).to_string()
+    }
+
+    #[test]
+    fn nested_directory() -> io::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let dir = tmp_dir.path().join("rocket").join("fuel");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("gradient_directory")
+            .config(toml::toml! {
+                [gradient_directory]
+                truncation_length = 2
+                truncation_symbol = ""
+                format = "[$path]($style)"
+                gradient = "#ff0000,#0000ff"
+            })
+            .path(dir)
+            .collect();
+        let path = Segment::from_text(Some(Color::Cyan.bold()), "rocket/fuel");
+        let expected = Some(paint(&gradientify_segments(
+            &default_context(),
+            &path,
+            &red_to_blue(),
+            &GradientConfig::default(),
+        )));
+
+        assert_eq!(expected, actual);
+        let first = Style::new().bold().fg(Color::Rgb(255, 0, 0)).prefix();
+        assert!(actual.unwrap().starts_with(&format!("{}r", first)));
+        tmp_dir.close()
+    }
+
+    #[test]
+    fn repo_root_keeps_its_attributes() -> io::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let repo_dir = tmp_dir.path().join("above").join("repo");
+        let dir = repo_dir.join("src").join("sub");
+        fs::create_dir_all(&dir)?;
+        create_command("git")?
+            .args(&["init"])
+            .current_dir(&repo_dir)
+            .output()?;
+
+        let actual = ModuleRenderer::new("gradient_directory")
+            .config(toml::toml! {
+                [gradient_directory]
+                truncate_to_repo = true
+                repo_root_style = "bold underline red"
+                repo_root_format = "[$repo_root]($repo_root_style)[$path]($style)"
+                gradient = "#ff0000,#0000ff"
+            })
+            .path(dir)
+            .collect();
+        let segments = [
+            Segment::from_text(Some(Color::Red.bold().underline()), "repo"),
+            Segment::from_text(Some(Color::Cyan.bold()), "/src/sub"),
+        ]
+        .concat();
+        let gradientified = gradientify_segments(
+            &default_context(),
+            &segments,
+            &red_to_blue(),
+            &GradientConfig::default(),
+        );
+        let expected = Some(paint(&gradientified));
+
+        assert_eq!(expected, actual);
+        assert!(gradientified[..4]
+            .iter()
+            .map(|segment| segment.style().unwrap())
+            .all(|style| style.is_bold && style.is_underline));
+        assert!(gradientified[4..]
+            .iter()
+            .map(|segment| segment.style().unwrap())
+            .all(|style| style.is_bold && !style.is_underline));
+        tmp_dir.close()
+    }
+
+    #[test]
+    fn directory_config_is_separate() -> io::Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let dir = tmp_dir.path().join("rocket");
+        fs::create_dir_all(&dir)?;
+
+        let actual = ModuleRenderer::new("gradient_directory")
+            .config(toml::toml! {
+                [directory]
+                format = "ignored"
+
+                [gradient_directory]
+                truncation_length = 1
+                format = "$path"
+                gradient = "#00ff00,#00ff00"
+            })
+            .path(&dir)
+            .collect();
+        let expected = Some(Color::Rgb(0, 255, 0).paint("rocket").to_string());
+
+        assert_eq!(expected, actual);
+        tmp_dir.close()
+    }
+}
@@ -0,0 +1,170 @@
+use super::{hostname, Context, Module, ModuleConfig};
+
+use crate::configs::gradient::GradientConfig;
+use crate::configs::gradient_hostname::GradientHostnameConfig;
+use crate::formatter::gradient;
+use colorgrad::Gradient;
+
+/// Creates a module with the system hostname, colored with a gradient
+///
+/// Shown under the same conditions as the `hostname` module. Unless colors are
+/// configured, the gradient is derived from the hostname so that every host
+/// gets its own, stable, colors.
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("gradient_hostname");
+    let GradientHostnameConfig {
+        hostname: config,
+        gradient: gradient_config,
+        seed_from_hostname,
+    } = GradientHostnameConfig::try_load(module.config);
+
+    let host = hostname::get_hostname(context, &config)?;
+    let gradient = hostname_gradient(module.config, &gradient_config, seed_from_hostname, &host);
+
+    let edge_styles = gradient::edge_styles(context, &gradient, &gradient_config);
+    let segments = match hostname::format_hostname(context, &config, &host, &edge_styles) {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `gradient_hostname`:\n{}", error);
+            return None;
+        }
+    };
+    let segments: Vec<_> = segments
+        .into_iter()
+        .map(|segment| (segment, true))
+        .collect();
+    gradient::gradientify_module(&mut module, context, &segments, &gradient, &gradient_config);
+
+    Some(module)
+}
+
+/// Builds the gradient for `host`, seeded from it unless colors are configured
+pub fn hostname_gradient(
+    module_config: Option<&toml::Value>,
+    gradient_config: &GradientConfig,
+    seed_from_hostname: bool,
+    host: &str,
+) -> Gradient {
+    // Explicitly configured colors take precedence over the seeded ones
+    let has_colors = module_config
+        .and_then(|config| config.get("gradient"))
+        .is_some();
+    if seed_from_hostname && !has_colors {
+        gradient::seeded_gradient(host)
+    } else {
+        gradient::build_gradient(gradient_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configs::gradient::GradientConfig;
+    use crate::formatter::gradient::{gradientify_segments, seeded_gradient};
+    use crate::segment::Segment;
+    use crate::test::{default_context, ModuleRenderer};
+    use nu_ansi_term::{AnsiStrings, Color};
+
+    fn get_hostname() -> Option<String> {
+        gethostname::gethostname().into_string().ok()
+    }
+
+    fn paint(segments: &[Segment]) -> String {
+        let strings: Vec<_> = segments.iter().map(Segment::ansi_string).collect();
+        AnsiStrings(&strings).to_string()
+    }
+
+    #[test]
+    fn ssh_only_without_ssh() {
+        let actual = ModuleRenderer::new("gradient_hostname").collect();
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn seeded_from_hostname() {
+        let hostname = match get_hostname() {
+            Some(hostname) => hostname,
+            None => return,
+        };
+        let render = || {
+            ModuleRenderer::new("gradient_hostname")
+                .env("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22")
+                .config(toml::toml! {
+                    [gradient_hostname]
+                    trim_at = ""
+                    format = "[$hostname]($style)"
+                })
+                .collect()
+        };
+        let segments = Segment::from_text(Some(Color::Green.bold().dimmed()), &hostname);
+        let expected = Some(paint(&gradientify_segments(
+            &default_context(),
+            &segments,
+            &seeded_gradient(&hostname),
+            &GradientConfig::default(),
+        )));
+
+        assert_eq!(expected, render());
+        assert_eq!(render(), render());
+    }
+
+    #[test]
+    fn different_hostnames_get_different_colors() {
+        let text = Segment::from_text(None, "hostname");
+        let config = GradientConfig::default();
+        let a = paint(&gradientify_segments(
+            &default_context(),
+            &text,
+            &seeded_gradient("prod-db-01"),
+            &config,
+        ));
+        let b = paint(&gradientify_segments(
+            &default_context(),
+            &text,
+            &seeded_gradient("dev-box"),
+            &config,
+        ));
+
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn configured_colors() {
+        let hostname = match get_hostname() {
+            Some(hostname) => hostname,
+            None => return,
+        };
+        let actual = ModuleRenderer::new("gradient_hostname")
+            .config(toml::toml! {
+                [gradient_hostname]
+                ssh_only = false
+                trim_at = ""
+                format = "$hostname"
+                gradient = "#ff0000,#ff0000"
+            })
+            .collect();
+        let expected = Some(Color::Rgb(255, 0, 0).paint(hostname).to_string());
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn seed_disabled_uses_default_palette() {
+        let render = |config: toml::Value| {
+            ModuleRenderer::new("gradient_hostname")
+                .config(config)
+                .collect()
+        };
+        let unseeded = render(toml::toml! {
+            [gradient_hostname]
+            ssh_only = false
+            seed_from_hostname = false
+        });
+        let default_palette = render(toml::toml! {
+            [gradient_hostname]
+            ssh_only = false
+            gradient = "#C7D2FE,#FECACA,#FEF9C3"
+        });
+
+        assert_eq!(default_palette, unseeded);
+    }
+}
@@ -1,82 +1,66 @@
-use super::{Context, Module, ModuleConfig};
-use crate::segment::FillSegment;
-use crate::segment::Segment;
-use crate::segment::TextSegment;
-use nu_ansi_term::Style;
-use unicode_segmentation::UnicodeSegmentation;
-
-use crate::configs::username::UsernameConfig;
-use crate::formatter::StringFormatter;
-
-fn gradientify(
-    segment: &Segment,
-    gradient: colorgrad::Gradient,
-    n: usize,
-    k: usize,
-) -> Vec<Segment> {
-    let st = match segment.style() {
-        Some(style) => style,
-        None => Style::default(),
-    };
+use super::utils::ssh;
+use super::{username, Context, Module, ModuleConfig};
 
-    gradient
-        .colors(n)
-        .iter()
-        .skip(k)
-        .map(|color| color.to_linear_rgba_u8())
-        .zip(segment.value().graphemes(true))
-        .map(|((r, g, b, _), val)| match segment {
-            Segment::Text(_) => Segment::Text(TextSegment {
-                value: val.into(),
-                style: Some(nu_ansi_term::Style {
-                    foreground: Some(nu_ansi_term::Color::Rgb(r, g, b)),
-                    ..st
-                }),
-            }),
-            Segment::Fill(_) => Segment::Fill(FillSegment {
-                value: val.into(),
-                style: Some(nu_ansi_term::Style { ..st }),
-            }),
-            _ => Segment::Text(TextSegment {
-                value: val.into(),
-                style: Some(nu_ansi_term::Style { ..st }),
-            }),
-        })
-        .collect()
-}
+use crate::configs::gradient::GradientConfig;
+use crate::configs::gradient_username::GradientUsernameConfig;
+use crate::formatter::{gradient, StringFormatter};
+use colorgrad::Gradient;
 
 #[cfg(not(target_os = "windows"))]
-const USERNAME_ENV_VAR: &str = "USER";
+pub const USERNAME_ENV_VAR: &str = "USER";
 
 #[cfg(target_os = "windows")]
-const USERNAME_ENV_VAR: &str = "USERNAME";
+pub const USERNAME_ENV_VAR: &str = "USERNAME";
 
 /// Creates a module with the current user's username
 ///
 /// Will display the username if any of the following criteria are met:
-///     - The current user is root (UID = 0) [1]
+///     - The current user is root, as detected by `root_detection` [1]
 ///     - The current user isn't the same as the one that is logged in (`$LOGNAME` != `$USER`) [2]
 ///     - The user is currently connected as an SSH session (`$SSH_CONNECTION`) [3]
+///     - One of `detect_env_vars` is set, and none of the negated ones [4]
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let mut username = context.get_env(USERNAME_ENV_VAR)?;
+    let username = context.get_env(USERNAME_ENV_VAR)?;
 
     let mut module = context.new_module("gradient_username");
-    let config: UsernameConfig = UsernameConfig::try_load(module.config);
+    let module_config = GradientUsernameConfig::try_load(module.config);
+    let config = &module_config.username;
 
-    let is_root = is_root_user();
-    if cfg!(target_os = "windows") && is_root {
-        username = "Administrator".to_string();
-    }
+    let is_root = username::is_root_user(context, config);
+    let (mut username, elevation) = if username::is_windows_elevated(context) {
+        username::show_elevated_as(username, config)
+    } else {
+        (username, None)
+    };
     let show_username = config.show_always
         || is_root // [1]
         || !is_login_user(context, &username) // [2]
-        || is_ssh_session(context); // [3]
+        || username::is_ssh_session(context, config) // [3]
+        || context.detect_env_vars(&config.detect_env_vars); // [4]
 
     if !show_username {
         return None;
     }
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    let (gradient, gradient_config) =
+        username_gradient(context, &module_config, &username, is_root);
+    let edge_styles = gradient::edge_styles(context, &gradient, &gradient_config);
+
+    // Aliases are applied after the checks above, which need the real login
+    if let Some(alias) = config.aliases.get(&username) {
+        username = alias.to_string();
+    }
+    let username = username::truncate_username(&username, config);
+    let sudo_user = username::get_sudo_user(context);
+    let ssh_connection = ssh::get_ssh_connection(context);
+
+    let format = if is_root {
+        config.format_root.unwrap_or(config.format)
+    } else {
+        config.format
+    };
+
+    let parsed = StringFormatter::new(format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => {
@@ -89,101 +73,113 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 }
                 _ => None,
             })
+            .map_style(|variable| gradient::style_variable(&edge_styles, variable))
             .map(|variable| match variable {
-                "user" => Some(Ok(&username)),
+                "user" => Some(Ok(username.as_str())),
+                "sudo_user" => sudo_user.as_deref().map(Ok),
+                "elevation" => elevation.map(Ok),
+                "ssh_client_ip" => ssh_connection
+                    .as_ref()
+                    .map(|ssh| Ok(ssh.client_ip.as_str())),
+                "ssh_port" => ssh_connection.as_ref().map(|ssh| Ok(ssh.port.as_str())),
                 _ => None,
             })
-            .parse(None, Some(context))
+            .parse_with_variables(None, Some(context))
     });
 
-    module.set_segments(match parsed {
-        Ok(segments) => {
-            let mut total = 0;
-
-            segments
-                .iter()
-                .flat_map(|segment| {
-                    let w = gradientify(
-                        segment,
-                        match colorgrad::CustomGradient::new()
-                            //.html_colors(&["#D2AC47", "#F7EF8a", "#EDC967"]) // Gold
-                            //.domain(&[0.0, 5.0, 100.0]) // Gold
-                            .html_colors(&["#C7D2FE", "#FECACA", "#FEF9C3"]) // Sunset
-                            .domain(&[0.0, 50.0, 100.0]) // Sunset
-                            .build()
-                        {
-                            Ok(g) => g,
-                            Err(error) => {
-                                log::warn!("Error in module `gradient`:\n{}", error);
-                                colorgrad::magma()
-                            }
-                        },
-                        144,
-                        total,
-                    );
-                    total += segment.value().len();
-                    w
-                })
-                .collect()
-        }
+    let segments = match parsed {
+        Ok(segments) => segments,
         Err(error) => {
             log::warn!("Error in module `gradient_username`:\n{}", error);
             return None;
         }
-    });
+    };
+    // Without a list of variables, the whole output is gradientified
+    let gradient_variables = &module_config.gradient_variables;
+    let segments: Vec<_> = segments
+        .into_iter()
+        .map(|(segment, variable)| {
+            let selected = gradient_variables.is_empty()
+                || variable.map_or(false, |variable| {
+                    gradient_variables.contains(&variable.as_str())
+                });
+            (segment, selected)
+        })
+        .collect();
+    gradient::gradientify_module(&mut module, context, &segments, &gradient, &gradient_config);
 
     Some(module)
 }
 
-fn is_login_user(context: &Context, username: &str) -> bool {
-    context
-        .get_env("LOGNAME")
-        .map_or(true, |logname| logname == username)
-}
+/// Builds the gradient for `username` from the palette that applies to them
+///
+/// The exit status palettes take precedence over the root and user ones, which
+/// take precedence over a seeded gradient and the configured colors.
+pub fn username_gradient<'a>(
+    context: &Context,
+    config: &GradientUsernameConfig<'a>,
+    username: &str,
+    is_root: bool,
+) -> (Gradient, GradientConfig<'a>) {
+    let mut gradient_config = config.gradient.clone();
 
-#[cfg(all(target_os = "windows", not(test)))]
-fn is_root_user() -> bool {
-    use deelevate::{PrivilegeLevel, Token};
-    let token = match Token::with_current_process() {
-        Ok(token) => token,
-        Err(e) => {
-            log::warn!("Failed to get process token: {e:?}");
-            return false;
+    let user_palette = if is_root {
+        &config.gradient_root
+    } else {
+        &config.gradient_user
+    };
+    let status_palette = if is_failed_command(context) {
+        &config.gradient_error
+    } else {
+        &config.gradient_success
+    };
+    let mut has_palette = false;
+    for palette in [user_palette, status_palette] {
+        if !palette.0.is_empty() {
+            gradient_config.gradient = palette.clone();
+            has_palette = true;
+        }
+    }
+
+    let gradient = match config.palette_seed {
+        "username" if !has_palette => gradient::seeded_gradient_in_lightness(
+            username,
+            config.min_lightness,
+            config.max_lightness,
+        ),
+        "" | "username" => gradient::build_gradient(&gradient_config),
+        unknown => {
+            log::warn!("Unknown palette_seed `{}`", unknown);
+            gradient::build_gradient(&gradient_config)
         }
     };
-    matches!(
-        match token.privilege_level() {
-            Ok(level) => level,
-            Err(e) => {
-                log::warn!("Failed to get privilege level: {e:?}");
-                return false;
-            }
-        },
-        PrivilegeLevel::Elevated | PrivilegeLevel::HighIntegrityAdmin
-    )
-}
 
-#[cfg(all(target_os = "windows", test))]
-fn is_root_user() -> bool {
-    false
+    (gradient, gradient_config)
 }
 
-#[cfg(not(target_os = "windows"))]
-fn is_root_user() -> bool {
-    nix::unistd::geteuid() == nix::unistd::ROOT
+fn is_login_user(context: &Context, username: &str) -> bool {
+    context
+        .get_env("LOGNAME")
+        .map_or(true, |logname| logname == username)
 }
 
-fn is_ssh_session(context: &Context) -> bool {
-    let ssh_env = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"];
-    ssh_env.iter().any(|env| context.get_env_os(env).is_some())
+/// Whether the last command, or any command of the last pipeline, failed
+fn is_failed_command(context: &Context) -> bool {
+    let props = &context.properties;
+    props.status_code.as_deref().unwrap_or("0") != "0"
+        || props
+            .pipestatus
+            .iter()
+            .flatten()
+            .any(|status| status != "0")
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::context::Target;
     use crate::test::ModuleRenderer;
-
-    // TODO: Add tests for if root user (UID == 0)
-    // Requires mocking
+    use chrono::{Local, TimeZone};
+    use nu_ansi_term::{AnsiStrings, Color};
 
     #[test]
     fn no_env_variables() {
@@ -312,4 +308,922 @@ mod tests {
 
         assert_eq!(expected, actual.as_deref());
     }
+
+    #[test]
+    fn gradient_from_comma_separated_colors() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = "#ff0000,#ff0000"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Rgb(255, 0, 0).paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn gradient_from_color_list() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = ["#0000ff", "#0000ff"]
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Rgb(0, 0, 255).paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn gradient_with_domain() {
+        // Only the very first character is sampled before the second stop
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = ["#ff0000", "#00ff00", "#00ff00"]
+                domain = [0.0, 0.0, 100.0]
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            AnsiStrings(&[
+                Color::Rgb(255, 0, 0).paint("a"),
+                Color::Rgb(0, 255, 0).paint("stronaut"),
+            ])
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    fn render_in_right_prompt(target: Target, right_align_gradient: bool) -> Option<String> {
+        ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "abcd")
+            .target(target)
+            .width(10)
+            .config(toml::toml! {
+                right_format = "$gradient_username"
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = "#ff0000,#0000ff"
+                right_align_gradient = right_align_gradient
+            })
+            .collect()
+    }
+
+    #[test]
+    fn right_aligned_gradient_ends_at_the_right_edge() {
+        // The four graphemes get the colors of the last four of ten columns
+        let expected = Some(format!(
+            "{}",
+            AnsiStrings(&[
+                Color::Rgb(23, 0, 103).paint("a"),
+                Color::Rgb(10, 0, 145).paint("b"),
+                Color::Rgb(3, 0, 195).paint("c"),
+                Color::Rgb(0, 0, 255).paint("d"),
+            ])
+        ));
+
+        assert_eq!(expected, render_in_right_prompt(Target::Right, true));
+    }
+
+    #[test]
+    fn right_align_gradient_only_applies_to_the_right_prompt() {
+        let expected = Some(format!(
+            "{}",
+            AnsiStrings(&[
+                Color::Rgb(255, 0, 0).paint("a"),
+                Color::Rgb(103, 0, 23).paint("b"),
+                Color::Rgb(23, 0, 103).paint("c"),
+                Color::Rgb(0, 0, 255).paint("d"),
+            ])
+        ));
+
+        assert_eq!(expected, render_in_right_prompt(Target::Main, true));
+        assert_eq!(expected, render_in_right_prompt(Target::Right, false));
+    }
+
+    #[test]
+    fn gradient_spans_multiple_segments() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "[$user](bold)[@](italic)[$user](underline)"
+                gradient = "#ff0000,#0000ff"
+            })
+            .collect();
+
+        let text = "astronaut@astronaut";
+        let colors = colorgrad::CustomGradient::new()
+            .html_colors(&["#ff0000", "#0000ff"])
+            .build()
+            .unwrap()
+            .colors(text.len());
+        let strings: Vec<_> = text
+            .chars()
+            .zip(colors)
+            .enumerate()
+            .map(|(i, (c, color))| {
+                let (r, g, b, _) = color.to_linear_rgba_u8();
+                let style = Color::Rgb(r, g, b);
+                let style = match i {
+                    0..=8 => style.bold(),
+                    9 => style.italic(),
+                    _ => style.underline(),
+                };
+                style.paint(c.to_string())
+            })
+            .collect();
+        let expected = Some(AnsiStrings(&strings).to_string());
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn gradient_phase_follows_time() {
+        let render = |second: u32| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .now(Local.ymd(2022, 10, 1).and_hms(12, 0, second))
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    format = "$user"
+                    phase = "time"
+                })
+                .collect()
+        };
+
+        assert_eq!(render(20), render(20));
+        assert_ne!(render(0), render(20));
+        assert_ne!(render(20), render(40));
+    }
+
+    #[test]
+    fn gradient_only_on_listed_variables() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                style_root = "bold yellow"
+                style_user = "bold yellow"
+                format = "[$user in ]($style)"
+                gradient = "#ff0000,#ff0000"
+                gradient_variables = ["user"]
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            AnsiStrings(&[
+                Color::Rgb(255, 0, 0).bold().paint("astronaut"),
+                Color::Yellow.bold().paint(" in "),
+            ])
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn gradient_variables_without_match() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                style_root = "bold yellow"
+                style_user = "bold yellow"
+                gradient_variables = ["hostname"]
+            })
+            .collect();
+        let expected = Some(format!("{} in ", Color::Yellow.bold().paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn root_user_palette() {
+        let render = |root_user: bool| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .root_user(root_user)
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    format = "[$user]($style)"
+                    style_root = "bold underline red"
+                    style_user = "italic yellow"
+                    gradient_root = "#ff0000,#ff0000"
+                    gradient_user = ["#0000ff", "#0000ff"]
+                })
+                .collect()
+        };
+
+        let root = Some(format!(
+            "{}",
+            Color::Rgb(255, 0, 0).bold().underline().paint("astronaut")
+        ));
+        let user = Some(format!(
+            "{}",
+            Color::Rgb(0, 0, 255).italic().paint("astronaut")
+        ));
+        assert_eq!(root, render(true));
+        assert_eq!(user, render(false));
+    }
+
+    #[test]
+    fn sudo_user_selects_root_palette() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env("SUDO_USER", "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                format = "[$user]($style)"
+                style_root = "bold"
+                root_detection = ["euid", "SUDO_USER"]
+                gradient_root = "#ff0000,#ff0000"
+                gradient_user = "#0000ff,#0000ff"
+            })
+            .collect();
+        let expected = Some(format!(
+            "{}",
+            Color::Rgb(255, 0, 0).bold().paint("astronaut")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn format_root() {
+        let render = |root_user: bool| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .root_user(root_user)
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    format = "$user"
+                    format_root = "root"
+                    gradient = "#ff0000,#ff0000"
+                })
+                .collect()
+        };
+
+        let root = Some(format!("{}", Color::Rgb(255, 0, 0).paint("root")));
+        let user = Some(format!("{}", Color::Rgb(255, 0, 0).paint("astronaut")));
+        assert_eq!(root, render(true));
+        assert_eq!(user, render(false));
+    }
+
+    #[test]
+    fn root_user_is_always_shown() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "root")
+            .env("LOGNAME", "root")
+            .root_user(true)
+            .config(toml::toml! {
+                [gradient_username]
+                format = "[$user]($style)"
+                gradient = "#ff0000,#ff0000"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Rgb(255, 0, 0).bold().paint("root")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn palette_falls_back_to_gradient() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .root_user(true)
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = "#00ff00,#00ff00"
+                gradient_user = "#0000ff,#0000ff"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Rgb(0, 255, 0).paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn non_ascii_username_has_no_gaps() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "jü🚀x")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "[$user](bold)@[$user](bold)"
+                gradient = "#ff0000,#0000ff"
+            })
+            .collect()
+            .unwrap();
+
+        let graphemes = ["j", "ü", "🚀", "x", "@", "j", "ü", "🚀", "x"];
+        let colors = colorgrad::CustomGradient::new()
+            .html_colors(&["#ff0000", "#0000ff"])
+            .build()
+            .unwrap()
+            .colors(graphemes.len());
+        let expected: Vec<_> = graphemes
+            .iter()
+            .zip(colors)
+            .map(|(grapheme, color)| {
+                let (r, g, b, _) = color.to_linear_rgba_u8();
+                (format!("{r};{g};{b}"), grapheme.to_string())
+            })
+            .collect();
+
+        let painted = regex::Regex::new("\x1b\\[(?:1;)?38;2;([0-9;]+)m([^\x1b]+)").unwrap();
+        let actual: Vec<_> = painted
+            .captures_iter(&actual)
+            .map(|captures| (captures[1].to_owned(), captures[2].to_owned()))
+            .collect();
+
+        assert_eq!(expected, actual);
+        let mut distinct: Vec<_> = actual.iter().map(|(color, _)| color).collect();
+        distinct.dedup();
+        assert_eq!(graphemes.len(), distinct.len());
+    }
+
+    #[test]
+    fn gradient_edge_styles() {
+        let render = |direction: &str| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    format = "[<]($gradient_start_style)$user[>]($gradient_end_style)"
+                    gradient = "#ff0000,#00ff00,#0000ff"
+                    gradient_variables = ["user"]
+                    direction = direction
+                })
+                .collect()
+                .unwrap()
+        };
+
+        // The separators share their color with the adjacent end of the body,
+        // so they are painted together with it
+        let forward = render("forward");
+        assert!(
+            forward.starts_with("\x1b[38;2;255;0;0m<a\x1b["),
+            "{forward:?}"
+        );
+        assert!(
+            forward.ends_with("\x1b[38;2;0;0;255mt>\x1b[0m"),
+            "{forward:?}"
+        );
+
+        let reverse = render("reverse");
+        assert!(
+            reverse.starts_with("\x1b[38;2;0;0;255m<a\x1b["),
+            "{reverse:?}"
+        );
+        assert!(
+            reverse.ends_with("\x1b[38;2;255;0;0mt>\x1b[0m"),
+            "{reverse:?}"
+        );
+    }
+
+    #[test]
+    fn max_width_group_is_gradientified_after_truncation() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astro🚀naut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "[$user](bold max_width=7)"
+                gradient = "#ff0000,#0000ff"
+            })
+            .collect()
+            .unwrap();
+
+        // The rocket takes up two columns, leaving no space for the ellipsis, which
+        // is painted with the rest
+        let graphemes = ["a", "s", "t", "r", "o", "…"];
+        let colors = colorgrad::CustomGradient::new()
+            .html_colors(&["#ff0000", "#0000ff"])
+            .build()
+            .unwrap()
+            .colors(graphemes.len());
+        let expected: Vec<_> = graphemes
+            .iter()
+            .zip(colors)
+            .map(|(grapheme, color)| {
+                let (r, g, b, _) = color.to_linear_rgba_u8();
+                (format!("{r};{g};{b}"), grapheme.to_string())
+            })
+            .collect();
+
+        let painted = regex::Regex::new("\x1b\\[(?:1;)?38;2;([0-9;]+)m([^\x1b]+)").unwrap();
+        let actual: Vec<_> = painted
+            .captures_iter(&actual)
+            .map(|captures| (captures[1].to_owned(), captures[2].to_owned()))
+            .collect();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn gradient_edge_colors() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "[<](bg:$gradient_start_color)$user[>](bg:$gradient_end_color)"
+                gradient = "#ff0000,#00ff00,#0000ff"
+                gradient_variables = ["user"]
+            })
+            .collect()
+            .unwrap();
+
+        assert!(actual.starts_with("\x1b[48;2;255;0;0m<"), "{actual:?}");
+        assert!(actual.ends_with("\x1b[48;2;0;0;255m>\x1b[0m"), "{actual:?}");
+    }
+
+    fn render_with_status(status: i64, pipestatus: &[i64], root_user: bool) -> Option<String> {
+        ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .status(status)
+            .pipestatus(pipestatus)
+            .root_user(root_user)
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient_root = "#ffffff,#ffffff"
+                gradient_success = "#00ff00,#00ff00"
+                gradient_error = ["#ff0000", "#ff0000"]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn status_palettes() {
+        let success = Some(format!("{}", Color::Rgb(0, 255, 0).paint("astronaut")));
+        let error = Some(format!("{}", Color::Rgb(255, 0, 0).paint("astronaut")));
+
+        assert_eq!(success, render_with_status(0, &[0], false));
+        assert_eq!(error, render_with_status(1, &[1], false));
+        assert_eq!(error, render_with_status(130, &[130], true));
+        assert_ne!(
+            render_with_status(0, &[0], false),
+            render_with_status(1, &[1], false)
+        );
+    }
+
+    #[test]
+    fn failed_pipeline_uses_error_palette() {
+        let error = Some(format!("{}", Color::Rgb(255, 0, 0).paint("astronaut")));
+        assert_eq!(error, render_with_status(0, &[1, 0], false));
+    }
+
+    #[test]
+    fn status_palette_without_error_palette() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .status(1)
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = "#0000ff,#0000ff"
+                gradient_success = "#00ff00,#00ff00"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Rgb(0, 0, 255).paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    fn render_seeded(username: &str, config: toml::Value) -> Option<String> {
+        ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, username)
+            .config(config)
+            .collect()
+    }
+
+    #[test]
+    fn palette_seed_is_stable_per_user() {
+        let config = toml::toml! {
+            [gradient_username]
+            show_always = true
+            format = "$user"
+            palette_seed = "username"
+        };
+        let alice = render_seeded("alice", config.clone());
+
+        assert!(alice.as_ref().unwrap().contains("38;2;"));
+        assert_eq!(alice, render_seeded("alice", config.clone()));
+        assert_ne!(alice, render_seeded("bobby", config));
+    }
+
+    #[test]
+    fn palette_seed_ignores_gradient() {
+        let seeded = render_seeded(
+            "alice",
+            toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                palette_seed = "username"
+            },
+        );
+        let with_colors = render_seeded(
+            "alice",
+            toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                palette_seed = "username"
+                gradient = "#ff0000,#0000ff"
+            },
+        );
+
+        assert_eq!(seeded, with_colors);
+    }
+
+    #[test]
+    fn palette_seed_lightness_range() {
+        let render = |min_lightness: f64, max_lightness: f64| {
+            render_seeded(
+                "alice",
+                toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    format = "$user"
+                    palette_seed = "username"
+                    min_lightness = min_lightness
+                    max_lightness = max_lightness
+                },
+            )
+        };
+
+        // Without any lightness, every stop is black
+        let black = Some(format!("{}", Color::Rgb(0, 0, 0).paint("alice")));
+        assert_eq!(black, render(0.0, 0.0));
+        assert_ne!(render(0.2, 0.4), render(0.6, 0.8));
+    }
+
+    #[test]
+    fn status_palette_overrides_palette_seed() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "alice")
+            .status(1)
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                palette_seed = "username"
+                gradient_error = "#ff0000,#ff0000"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Rgb(255, 0, 0).paint("alice")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn gradient_changes_with_config() {
+        let render = |gradient: &str| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    gradient = gradient
+                })
+                .collect()
+        };
+
+        assert_ne!(
+            render("#D2AC47,#F7EF8a,#EDC967"),
+            render("#C7D2FE,#FECACA,#FEF9C3")
+        );
+    }
+
+    #[test]
+    fn gradient_falls_back_to_default() {
+        let render = |config: toml::Value| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .config(config)
+                .collect()
+        };
+        let default = render(toml::toml! {
+            [gradient_username]
+            show_always = true
+        });
+
+        let empty = render(toml::toml! {
+            [gradient_username]
+            show_always = true
+            gradient = ""
+        });
+        assert_eq!(default, empty);
+
+        let unparsable = render(toml::toml! {
+            [gradient_username]
+            show_always = true
+            gradient = "#nothex,#zzzzzz"
+        });
+        assert_eq!(default, unparsable);
+
+        let bad_domain = render(toml::toml! {
+            [gradient_username]
+            show_always = true
+            gradient = "#C7D2FE,#FECACA,#FEF9C3"
+            domain = [50.0, 0.0]
+        });
+        assert_eq!(default, bad_domain);
+    }
+
+    #[test]
+    fn gradient_from_preset() {
+        let render = |gradient: &str| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    gradient = gradient
+                })
+                .collect()
+        };
+        let default = render("");
+        let viridis = render("viridis");
+
+        assert_ne!(default, viridis);
+        assert_ne!(viridis, render("magma"));
+        assert_eq!(viridis, render("Viridis"));
+    }
+
+    #[test]
+    fn gradient_unknown_preset_falls_back() {
+        let render = |gradient: &str| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    gradient = gradient
+                })
+                .collect()
+        };
+
+        assert_eq!(render(""), render("not_a_preset"));
+    }
+
+    #[test]
+    fn long_username_is_fully_rendered() {
+        let username = "astronaut".repeat(22) + "xx";
+        assert_eq!(200, username.len());
+
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, username.clone())
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+            })
+            .collect()
+            .unwrap();
+        let ansi_escape = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+
+        assert_eq!(username, ansi_escape.replace_all(&actual, ""));
+    }
+
+    #[test]
+    fn truncated_username_is_gradientified() {
+        let username = "firstname.lastname.contractor1";
+        assert_eq!(30, username.len());
+
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, username)
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = "#ff0000,#0000ff"
+                truncation_length = 10
+            })
+            .collect()
+            .unwrap();
+        let ansi_escape = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+
+        assert_eq!("firstname.…", ansi_escape.replace_all(&actual, ""));
+        // The symbol is the last grapheme, so it gets the end color
+        assert!(actual.starts_with("\x1b[38;2;255;0;0mf"), "{actual:?}");
+        assert!(actual.ends_with("\x1b[38;2;0;0;255m…\x1b[0m"), "{actual:?}");
+    }
+
+    fn render_aliased(logname: &str, show_always: bool) -> Option<String> {
+        ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "jsmith2847")
+            .env("LOGNAME", logname)
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = show_always
+                format = "$user"
+                gradient = "#ff0000,#ff0000"
+                aliases = { "jsmith2847" = "john", "root" = "admin" }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn alias_applied() {
+        let expected = Some(format!("{}", Color::Rgb(255, 0, 0).paint("john")));
+
+        assert_eq!(expected, render_aliased("someone_else", false));
+    }
+
+    #[test]
+    fn alias_missing() {
+        let actual = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "$user"
+                gradient = "#ff0000,#ff0000"
+                aliases = { "jsmith2847" = "john" }
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Rgb(255, 0, 0).paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn alias_keeps_login_user_detection() {
+        // LOGNAME matches the real login, not the alias
+        assert_eq!(None, render_aliased("jsmith2847", false));
+
+        let expected = Some(format!("{}", Color::Rgb(255, 0, 0).paint("john")));
+        assert_eq!(expected, render_aliased("jsmith2847", true));
+        assert_eq!(expected, render_aliased("john", false));
+    }
+
+    #[test]
+    fn sudo_user() {
+        let render = |sudo_user: Option<&str>| {
+            let mut renderer = ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "root")
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    format = "$user( as $sudo_user)"
+                    gradient = "#ff0000,#ff0000"
+                });
+            if let Some(sudo_user) = sudo_user {
+                renderer = renderer.env("SUDO_USER", sudo_user);
+            }
+            renderer.collect()
+        };
+
+        let present = Some(format!(
+            "{}",
+            Color::Rgb(255, 0, 0).paint("root as astronaut")
+        ));
+        let absent = Some(format!("{}", Color::Rgb(255, 0, 0).paint("root")));
+        assert_eq!(present, render(Some("astronaut")));
+        assert_eq!(absent, render(None));
+    }
+
+    #[test]
+    fn detect_env_vars() {
+        let render = |env_var: &'static str| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .env("LOGNAME", "astronaut")
+                .env(env_var, "1")
+                .config(toml::toml! {
+                    [gradient_username]
+                    format = "$user"
+                    gradient = "#ff0000,#ff0000"
+                    detect_env_vars = ["TOOLBOX_PATH"]
+                })
+                .collect()
+        };
+
+        let expected = Some(format!("{}", Color::Rgb(255, 0, 0).paint("astronaut")));
+        assert_eq!(expected, render("TOOLBOX_PATH"));
+        assert_eq!(None, render("OTHER"));
+    }
+
+    #[test]
+    fn gradient_on_background() {
+        let render = |target: &str| {
+            ModuleRenderer::new("gradient_username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .config(toml::toml! {
+                    [gradient_username]
+                    show_always = true
+                    style_root = "bold white"
+                    style_user = "bold white"
+                    target = target
+                })
+                .collect()
+                .unwrap()
+        };
+
+        let background = render("background");
+        assert!(background.contains("48;2;"));
+        assert!(!background.contains("38;2;"));
+
+        let foreground = render("foreground");
+        assert!(foreground.contains("38;2;"));
+        assert!(!foreground.contains("48;2;"));
+
+        let both = render("both");
+        assert!(both.contains("38;2;"));
+        assert!(both.contains("48;2;"));
+    }
+
+    fn render_with_env(env: &[(&'static str, &str)], color_mode: &str) -> String {
+        let mut renderer = ModuleRenderer::new("gradient_username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [gradient_username]
+                show_always = true
+                style_root = "bold white"
+                style_user = "bold white"
+                color_mode = color_mode
+            });
+        for (key, value) in env {
+            renderer = renderer.env(key, *value);
+        }
+        renderer.collect().unwrap()
+    }
+
+    #[test]
+    fn truecolor_terminal() {
+        for env in [
+            vec![("COLORTERM", "truecolor"), ("TERM", "xterm-256color")],
+            vec![("COLORTERM", "24bit"), ("TERM", "xterm-256color")],
+            vec![("TERM", "xterm-direct")],
+            vec![],
+        ] {
+            let actual = render_with_env(&env, "auto");
+            assert!(actual.contains("38;2;"), "{env:?}");
+            assert!(!actual.contains("38;5;"), "{env:?}");
+        }
+    }
+
+    #[test]
+    fn ansi256_terminal() {
+        for env in [
+            vec![("TERM", "xterm-256color")],
+            vec![("COLORTERM", "yes"), ("TERM", "screen-256color")],
+        ] {
+            let actual = render_with_env(&env, "auto");
+            assert!(actual.contains("38;5;"), "{env:?}");
+            assert!(!actual.contains("38;2;"), "{env:?}");
+        }
+    }
+
+    #[test]
+    fn no_color() {
+        let actual = render_with_env(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")], "auto");
+        let expected = format!("{} in ", Color::White.bold().paint("astronaut"));
+        assert_eq!(expected, actual);
+
+        // An empty NO_COLOR doesn't disable colors
+        let actual = render_with_env(&[("NO_COLOR", ""), ("COLORTERM", "truecolor")], "auto");
+        assert!(actual.contains("38;2;"));
+    }
+
+    #[test]
+    fn forced_color_mode() {
+        let truecolor = render_with_env(&[("TERM", "xterm-256color")], "truecolor");
+        assert!(truecolor.contains("38;2;"));
+
+        let ansi256 = render_with_env(&[("COLORTERM", "truecolor")], "ansi256");
+        assert!(ansi256.contains("38;5;"));
+        assert!(!ansi256.contains("38;2;"));
+
+        let none = render_with_env(&[("COLORTERM", "truecolor")], "none");
+        assert_eq!(
+            format!("{} in ", Color::White.bold().paint("astronaut")),
+            none
+        );
+    }
 }
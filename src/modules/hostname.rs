@@ -1,9 +1,12 @@
+use super::utils::ssh;
 use super::{Context, Module};
 use std::ffi::OsString;
 
 use crate::config::ModuleConfig;
 use crate::configs::hostname::HostnameConfig;
-use crate::formatter::StringFormatter;
+use crate::formatter::string_formatter::StringFormatterError;
+use crate::formatter::{gradient, StringFormatter};
+use crate::segment::Segment;
 
 /// Creates a module with the system hostname
 ///
@@ -14,8 +17,22 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("hostname");
     let config: HostnameConfig = HostnameConfig::try_load(module.config);
 
-    let ssh_connection = context.get_env("SSH_CONNECTION");
-    if config.ssh_only && ssh_connection.is_none() {
+    let host = get_hostname(context, &config)?;
+
+    module.set_segments(match format_hostname(context, &config, &host, &[]) {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `hostname`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Returns the hostname, trimmed at `trim_at`, if it should be shown
+pub fn get_hostname(context: &Context, config: &HostnameConfig) -> Option<String> {
+    if config.ssh_only && !is_ssh_session(context, config) {
         return None;
     }
 
@@ -29,23 +46,47 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         }
     };
 
-    //rustc doesn't let you do an "if" and an "if let" in the same if statement
-    // if this changes in the future this can become a lot cleaner
-    let host = if !config.trim_at.is_empty() {
+    Some(trim_hostname(&host, config).to_string())
+}
+
+/// Cuts the hostname off at the first `trim_at`
+pub fn trim_hostname<'a>(host: &'a str, config: &HostnameConfig) -> &'a str {
+    if !config.trim_at.is_empty() {
         if let Some(index) = host.find(config.trim_at) {
-            host.split_at(index).0
-        } else {
-            host.as_ref()
+            return host.split_at(index).0;
         }
+    }
+    host
+}
+
+pub fn is_ssh_session(context: &Context, config: &HostnameConfig) -> bool {
+    ssh::is_ssh_session(context, config.ssh_detection, &["SSH_CONNECTION"])
+}
+
+/// Formats the hostname into segments as configured by `config`
+///
+/// The `style_variables` can be used in the format next to the module's own.
+pub fn format_hostname(
+    context: &Context,
+    config: &HostnameConfig,
+    host: &str,
+    style_variables: &[(&str, String)],
+) -> Result<Vec<Segment>, StringFormatterError> {
+    let is_ssh = is_ssh_session(context, config);
+    let ssh_connection = ssh::get_ssh_connection(context);
+    // Only the color is replaced, so attributes like bold are kept
+    let style = if config.color_by_hash {
+        let color = gradient::seeded_style(context, host, &config.hash_palette);
+        format!("{} {}", config.style, color)
     } else {
-        host.as_ref()
+        config.style.to_string()
     };
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
                 "ssh_symbol" => {
-                    if ssh_connection.is_some() {
+                    if is_ssh {
                         Some(config.ssh_symbol)
                     } else {
                         None
@@ -54,31 +95,30 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map_style(|variable| match variable {
-                "style" => Some(Ok(config.style)),
+                "style" => Some(Ok(style.as_str())),
                 _ => None,
             })
+            .map_style(|variable| gradient::style_variable(style_variables, variable))
             .map(|variable| match variable {
                 "hostname" => Some(Ok(host)),
+                "ssh_client_ip" => ssh_connection
+                    .as_ref()
+                    .map(|ssh| Ok(ssh.client_ip.as_str())),
+                "ssh_port" => ssh_connection.as_ref().map(|ssh| Ok(ssh.port.as_str())),
                 _ => None,
             })
             .parse(None, Some(context))
-    });
-
-    module.set_segments(match parsed {
-        Ok(segments) => segments,
-        Err(error) => {
-            log::warn!("Error in module `hostname`:\n{}", error);
-            return None;
-        }
-    });
-
-    Some(module)
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::test::ModuleRenderer;
-    use nu_ansi_term::{Color, Style};
+    use super::format_hostname;
+    use crate::configs::hostname::HostnameConfig;
+    use crate::modules::utils::ssh::MockProcessTreeProvider;
+    use crate::segment::Segment;
+    use crate::test::{default_context, ModuleRenderer};
+    use nu_ansi_term::{AnsiStrings, Color, Style};
     use unicode_segmentation::UnicodeSegmentation;
 
     macro_rules! get_hostname {
@@ -156,6 +196,58 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn ssh_from_process_tree() {
+        let hostname = get_hostname!();
+        let mut mock = MockProcessTreeProvider::new();
+        mock.expect_ancestor_names()
+            .returning(|| vec!["tmux: server".to_string(), "sshd".to_string()]);
+        let actual = ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = true
+                trim_at = ""
+                ssh_detection = "process-tree"
+            })
+            .process_tree_provider(&mock)
+            .collect();
+        let expected = Some(format!(
+            "{} in ",
+            style().paint("🌐 ".to_owned() + hostname.as_str())
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    fn render_ssh(key: &str, value: &str) -> Option<String> {
+        ModuleRenderer::new("hostname")
+            .config(toml::toml! {
+                [hostname]
+                ssh_only = false
+                ssh_detection = "both"
+                format = "$ssh_client_ip(:$ssh_port)"
+            })
+            .env(key, value)
+            .collect()
+    }
+
+    #[test]
+    fn ssh_client_ip_and_port() {
+        assert_eq!(
+            Some("192.168.223.17:22"),
+            render_ssh("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22").as_deref()
+        );
+        assert_eq!(
+            Some("192.168.223.17:2222"),
+            render_ssh("SSH_CLIENT", "192.168.223.17 36673 2222").as_deref()
+        );
+    }
+
+    #[test]
+    fn ssh_client_ip_malformed() {
+        assert_eq!(None, render_ssh("SSH_CLIENT", "192.168.223.17 oops 22"));
+    }
+
     #[test]
     fn no_trim_at() {
         let hostname = get_hostname!();
@@ -189,6 +281,51 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    fn render_hashed(host: &str, palette: &[&'static str]) -> String {
+        let config = HostnameConfig {
+            format: "[$hostname]($style)",
+            color_by_hash: true,
+            hash_palette: palette.to_vec(),
+            ..HostnameConfig::default()
+        };
+        let mut context = default_context();
+        context.env.insert("COLORTERM", "truecolor".to_string());
+        let segments = format_hostname(&context, &config, host, &[]).unwrap();
+        let strings: Vec<_> = segments.iter().map(Segment::ansi_string).collect();
+        AnsiStrings(&strings).to_string()
+    }
+
+    #[test]
+    fn color_by_hash_is_stable() {
+        assert_eq!(
+            render_hashed("prod-db-01", &[]),
+            render_hashed("prod-db-01", &[])
+        );
+        assert_ne!(
+            render_hashed("prod-db-01", &[]),
+            render_hashed("dev-box", &[])
+        );
+    }
+
+    #[test]
+    fn color_by_hash_keeps_attributes() {
+        let actual = render_hashed("prod-db-01", &[]);
+        let plain = Color::Green.bold().dimmed().paint("prod-db-01").to_string();
+
+        assert_ne!(plain, actual);
+        assert!(actual.starts_with("\u{1b}[1;2;38;2;"));
+    }
+
+    #[test]
+    fn color_by_hash_from_palette() {
+        let palette = ["red", "blue"];
+        let prod = Color::Blue.bold().dimmed().paint("prod-db-01");
+        let dev = Color::Red.bold().dimmed().paint("dev-box");
+
+        assert_eq!(prod.to_string(), render_hashed("prod-db-01", &palette));
+        assert_eq!(dev.to_string(), render_hashed("dev-box", &palette));
+    }
+
     fn style() -> Style {
         Color::Green.bold().dimmed()
     }
@@ -3,7 +3,8 @@ use super::{Context, Module, ModuleConfig};
 use crate::configs::jobs::JobsConfig;
 use crate::formatter::StringFormatter;
 
-/// Creates a segment to show if there are any active jobs running
+/// Creates a segment to show if there are any active jobs running, and the
+/// commands they are running if the shell passes them
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("jobs");
     let config = JobsConfig::try_load(module.config);
@@ -69,6 +70,23 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         }
     }
 
+    // Only some shells pass the commands of their jobs
+    let names = props.jobs_names.as_ref().map(|names| {
+        let names = names
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty());
+        let limit = if config.max_names == 0 {
+            usize::MAX
+        } else {
+            config.max_names
+        };
+        names
+            .take(limit)
+            .collect::<Vec<_>>()
+            .join(config.names_separator)
+    });
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -81,6 +99,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "number" => Some(Ok(module_number.clone())),
+                "names" => names.clone().filter(|names| !names.is_empty()).map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -330,4 +349,58 @@ mod test {
         let expected = Some(format!("{} ", Color::Blue.bold().paint("✦1")));
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn config_names() {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                format = "[$symbol$number( \\($names\\))]($style) "
+            })
+            .jobs(2)
+            .jobs_names(&["vim", "cargo watch"])
+            .collect();
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Blue.bold().paint("✦2 (vim, cargo watch)")
+        ));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn config_names_separator_and_max_names() {
+        let render = |max_names: i64| {
+            ModuleRenderer::new("jobs")
+                .config(toml::toml! {
+                    [jobs]
+                    format = "$names"
+                    names_separator = " | "
+                    max_names = max_names
+                })
+                .jobs(3)
+                .jobs_names(&["vim", "", "cargo watch", "less README.md"])
+                .collect()
+        };
+
+        assert_eq!(Some("vim | cargo watch".to_string()), render(2));
+        assert_eq!(
+            Some("vim | cargo watch | less README.md".to_string()),
+            render(0)
+        );
+    }
+
+    #[test]
+    fn config_names_not_passed() {
+        let actual = ModuleRenderer::new("jobs")
+            .config(toml::toml! {
+                [jobs]
+                format = "[$symbol$number( \\($names\\))]($style) "
+            })
+            .jobs(2)
+            .collect();
+
+        let expected = Some(format!("{} ", Color::Blue.bold().paint("✦2")));
+        assert_eq!(expected, actual);
+    }
 }
@@ -5,6 +5,7 @@ use std::collections::HashMap;
 use std::env;
 use std::path;
 
+use super::utils::glob::glob_matches;
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::kubernetes::KubernetesConfig;
@@ -161,18 +162,26 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         None => false,
     });
 
+    let context_name = get_kube_context_name(&config, &kube_ctx);
+    let is_dangerous = config
+        .dangerous_contexts
+        .iter()
+        .any(|pattern| glob_matches(pattern, &context_name));
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
                 "symbol" => Some(config.symbol),
+                "danger_symbol" if is_dangerous => Some(config.danger_symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
+                "style" if is_dangerous => Some(Ok(config.style_dangerous)),
                 "style" => Some(Ok(config.style)),
                 _ => None,
             })
             .map(|variable| match variable {
-                "context" => Some(Ok(get_kube_context_name(&config, &kube_ctx))),
+                "context" => Some(Ok(context_name.clone())),
 
                 "namespace" => kube_ns.and_then(|ctx| {
                     ctx.as_ref().map(|kube| {
@@ -955,4 +964,79 @@ users: []
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    fn render_ctx(ctx_name: &str, config: toml::Value) -> io::Result<Option<String>> {
+        let dir = tempfile::tempdir()?;
+
+        let filename = dir.path().join("config");
+
+        let mut file = File::create(&filename)?;
+        file.write_all(
+            format!(
+                "
+apiVersion: v1
+clusters: []
+contexts: []
+current-context: {}
+kind: Config
+preferences: {{}}
+users: []
+",
+                ctx_name
+            )
+            .as_bytes(),
+        )?;
+        file.sync_all()?;
+
+        let actual = ModuleRenderer::new("kubernetes")
+            .path(dir.path())
+            .env("KUBECONFIG", filename.to_string_lossy().as_ref())
+            .config(config)
+            .collect();
+
+        dir.close()?;
+        Ok(actual)
+    }
+
+    fn dangerous_config() -> toml::Value {
+        toml::toml! {
+            [kubernetes]
+            disabled = false
+            dangerous_contexts = ["prod-*", "*-production", "db-?"]
+            [kubernetes.context_aliases]
+            "gke_.*_billing" = "billing-production"
+        }
+    }
+
+    #[test]
+    fn test_dangerous_context() -> io::Result<()> {
+        let expected = Some(format!("{} in ", Color::Red.bold().paint("⚠️ ☸ prod-eu")));
+        assert_eq!(expected, render_ctx("prod-eu", dangerous_config())?);
+
+        let expected = Some(format!("{} in ", Color::Red.bold().paint("⚠️ ☸ db-1")));
+        assert_eq!(expected, render_ctx("db-1", dangerous_config())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dangerous_context_matches_alias() -> io::Result<()> {
+        let expected = Some(format!(
+            "{} in ",
+            Color::Red.bold().paint("⚠️ ☸ billing-production")
+        ));
+        assert_eq!(expected, render_ctx("gke_eu_billing", dangerous_config())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_safe_context() -> io::Result<()> {
+        for ctx_name in ["staging", "eu-prod-1", "db-10"] {
+            let expected = Some(format!(
+                "{} in ",
+                Color::Cyan.bold().paint(format!("☸ {}", ctx_name))
+            ));
+            assert_eq!(expected, render_ctx(ctx_name, dangerous_config())?);
+        }
+        Ok(())
+    }
 }
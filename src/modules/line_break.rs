@@ -1,11 +1,20 @@
 use super::{Context, Module};
+use crate::config::ModuleConfig;
+use crate::configs::line_break::LineBreakConfig;
 use crate::segment::Segment;
 
 /// Creates a module for the line break
+///
+/// With `min_width`, whether the line is broken is only decided once the whole
+/// line before it is rendered.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("line_break");
+    let config = LineBreakConfig::try_load(module.config);
 
-    module.set_segments(vec![Segment::LineTerm]);
+    module.set_segments(vec![match config.min_width {
+        Some(min_width) => Segment::ConditionalLineTerm(min_width),
+        None => Segment::LineTerm,
+    }]);
 
     Some(module)
 }
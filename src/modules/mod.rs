@@ -1,4 +1,5 @@
 // While adding out new module add out module to src/module.rs ALL_MODULES const array also.
+mod agents;
 mod aws;
 mod azure;
 mod buf;
@@ -16,6 +17,8 @@ mod daml;
 mod dart;
 mod deno;
 mod directory;
+mod direnv;
+mod disk_usage;
 mod docker_context;
 mod dotnet;
 mod elixir;
@@ -30,7 +33,9 @@ mod git_metrics;
 mod git_state;
 mod git_status;
 mod golang;
-mod gradient;
+pub(crate) mod gradient;
+mod gradient_directory;
+mod gradient_hostname;
 mod gradient_username;
 mod haskell;
 mod helm;
@@ -45,6 +50,7 @@ mod line_break;
 mod localip;
 mod lua;
 mod memory_usage;
+mod network;
 mod nim;
 mod nix_shell;
 mod nodejs;
@@ -53,6 +59,7 @@ mod openstack;
 mod package;
 mod perl;
 mod php;
+mod proxy;
 mod pulumi;
 mod purescript;
 mod python;
@@ -62,6 +69,7 @@ mod rlang;
 mod ruby;
 mod rust;
 mod scala;
+mod secrets;
 mod shell;
 mod shlvl;
 mod singularity;
@@ -71,6 +79,7 @@ mod sudo;
 mod swift;
 mod terraform;
 mod time;
+mod todos;
 mod username;
 mod utils;
 mod vagrant;
@@ -84,17 +93,35 @@ mod battery;
 #[cfg(feature = "battery")]
 pub use self::battery::{BatteryInfoProvider, BatteryInfoProviderImpl};
 
+pub use self::utils::filesystem::{FilesystemStatsProvider, FilesystemStatsProviderImpl};
+pub use self::utils::network::{NetworkInterfaceProvider, NetworkInterfaceProviderImpl};
+pub use self::utils::ssh::{has_sshd_ancestor, ProcessTreeProvider, ProcessTreeProviderImpl};
+
+use crate::cache::ModuleCache;
 use crate::config::ModuleConfig;
+use crate::configs;
 use crate::context::{Context, Shell};
 use crate::module::Module;
 use std::time::Instant;
 
 pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
     let start: Instant = Instant::now();
+    let cache = ModuleCache::new(context, module);
+    if let Some(segments) = cache.as_ref().and_then(ModuleCache::load) {
+        log::trace!("Using the cached rendering of module {:?}", module);
+        return segments.map(|segments| {
+            let mut m = context.new_module(module);
+            m.set_segments(segments);
+            m.duration = start.elapsed();
+            m
+        });
+    }
+
     let mut m: Option<Module> = {
         match module {
             // Keep these ordered alphabetically.
             // Default ordering is handled in configs/starship_root.rs
+            "agents" => agents::module(context),
             "aws" => aws::module(context),
             "azure" => azure::module(context),
             #[cfg(feature = "battery")]
@@ -112,6 +139,8 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "dart" => dart::module(context),
             "deno" => deno::module(context),
             "directory" => directory::module(context),
+            "direnv" => direnv::module(context),
+            "disk_usage" => disk_usage::module(context),
             "docker_context" => docker_context::module(context),
             "dotnet" => dotnet::module(context),
             "elixir" => elixir::module(context),
@@ -127,6 +156,8 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "git_status" => git_status::module(context),
             "golang" => golang::module(context),
             "gradient" => gradient::module(context),
+            "gradient_directory" => gradient_directory::module(context),
+            "gradient_hostname" => gradient_hostname::module(context),
             "gradient_username" => gradient_username::module(context),
             "haskell" => haskell::module(context),
             "helm" => helm::module(context),
@@ -141,6 +172,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "localip" => localip::module(context),
             "lua" => lua::module(context),
             "memory_usage" => memory_usage::module(context),
+            "network" => network::module(context),
             "nim" => nim::module(context),
             "nix_shell" => nix_shell::module(context),
             "nodejs" => nodejs::module(context),
@@ -149,6 +181,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "package" => package::module(context),
             "perl" => perl::module(context),
             "php" => php::module(context),
+            "proxy" => proxy::module(context),
             "pulumi" => pulumi::module(context),
             "purescript" => purescript::module(context),
             "python" => python::module(context),
@@ -158,6 +191,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "ruby" => ruby::module(context),
             "rust" => rust::module(context),
             "scala" => scala::module(context),
+            "secrets" => secrets::module(context),
             "shell" => shell::module(context),
             "shlvl" => shlvl::module(context),
             "singularity" => singularity::module(context),
@@ -167,6 +201,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
             "sudo" => sudo::module(context),
             "terraform" => terraform::module(context),
             "time" => time::module(context),
+            "todos" => todos::module(context),
             "crystal" => crystal::module(context),
             "username" => username::module(context),
             "vlang" => vlang::module(context),
@@ -186,6 +221,10 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
         }
     };
 
+    if let Some(cache) = cache {
+        cache.store(m.as_ref().map(|m| m.segments.as_slice()));
+    }
+
     let elapsed = start.elapsed();
     log::trace!("Took {:?} to compute module {:?}", elapsed, module);
     if elapsed.as_millis() >= 1 {
@@ -200,6 +239,7 @@ pub fn handle<'a>(module: &str, context: &'a Context) -> Option<Module<'a>> {
 
 pub fn description(module: &str) -> &'static str {
     match module {
+        "agents" => "Whether ssh-agent and gpg-agent have keys loaded",
         "aws" => "The current AWS region and profile",
         "azure" => "The current Azure subscription",
         "battery" => "The current charge of the device's battery and its current charging status",
@@ -219,6 +259,8 @@ pub fn description(module: &str) -> &'static str {
         "dart" => "The currently installed version of Dart",
         "deno" => "The currently installed version of Deno",
         "directory" => "The current working directory",
+        "direnv" => "Whether the .envrc file of the current directory is allowed and loaded",
+        "disk_usage" => "The usage of the filesystem the current directory is on",
         "docker_context" => "The current docker context",
         "dotnet" => "The relevant version of the .NET Core SDK for the current directory",
         "elixir" => "The currently installed versions of Elixir and OTP",
@@ -233,8 +275,10 @@ pub fn description(module: &str) -> &'static str {
         "git_state" => "The current git operation, and it's progress",
         "git_status" => "Symbol representing the state of the repo",
         "golang" => "The currently installed version of Golang",
-        "gradient" => "Dynamically sizing Gradient",
-        "gradient_username" => "Dynamically sizing Gradient Username",
+        "gradient" => "Other modules, colored with a single gradient",
+        "gradient_directory" => "The current working directory, colored with a gradient",
+        "gradient_hostname" => "The system hostname, colored with a gradient",
+        "gradient_username" => "The active user's username, colored with a gradient",
         "haskell" => "The selected version of the Haskell toolchain",
         "helm" => "The currently installed version of Helm",
         "hg_branch" => "The active branch of the repo in your current directory",
@@ -248,6 +292,7 @@ pub fn description(module: &str) -> &'static str {
         "localip" => "The currently assigned ipv4 address",
         "lua" => "The currently installed version of Lua",
         "memory_usage" => "Current system memory and swap usage",
+        "network" => "The VPN interface that is up, if any",
         "nim" => "The currently installed version of Nim",
         "nix_shell" => "The nix-shell environment",
         "nodejs" => "The currently installed version of NodeJS",
@@ -256,6 +301,7 @@ pub fn description(module: &str) -> &'static str {
         "package" => "The package version of the current directory's project",
         "perl" => "The currently installed version of Perl",
         "php" => "The currently installed version of PHP",
+        "proxy" => "The host of the proxy set in the environment",
         "pulumi" => "The current username, stack, and installed version of Pulumi",
         "purescript" => "The currently installed version of PureScript",
         "python" => "The currently installed version of Python",
@@ -265,6 +311,7 @@ pub fn description(module: &str) -> &'static str {
         "ruby" => "The currently installed version of Ruby",
         "rust" => "The currently installed version of Rust",
         "scala" => "The currently installed version of Scala",
+        "secrets" => "Files that look like secrets and aren't ignored by git",
         "shell" => "The currently used shell indicator",
         "shlvl" => "The current value of SHLVL",
         "singularity" => "The currently used Singularity image",
@@ -274,6 +321,7 @@ pub fn description(module: &str) -> &'static str {
         "swift" => "The currently installed version of Swift",
         "terraform" => "The currently selected terraform workspace and version",
         "time" => "The current local time",
+        "todos" => "The number of TODO and FIXME markers in the current git repository",
         "username" => "The active user's username",
         "vagrant" => "The currently installed version of Vagrant",
         "vcsh" => "The currently active VCSH repository",
@@ -283,6 +331,17 @@ pub fn description(module: &str) -> &'static str {
     }
 }
 
+/// Whether the `username` or the `hostname` module treats the session as an
+/// SSH session, with their `ssh_detection`
+pub fn is_ssh_session(context: &Context) -> bool {
+    let username_config =
+        configs::username::UsernameConfig::try_load(context.config.get_module_config("username"));
+    let hostname_config =
+        configs::hostname::HostnameConfig::try_load(context.config.get_module_config("hostname"));
+    username::is_ssh_session(context, &username_config)
+        || hostname::is_ssh_session(context, &hostname_config)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,154 @@
+use super::utils::glob::glob_matches;
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::network::NetworkConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module showing the VPN interface that is up, if any
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("network");
+    let config = NetworkConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let interface = context
+        .network_interface_provider
+        .interfaces()
+        .into_iter()
+        .find(|interface| {
+            interface.is_up
+                && config.detect_interfaces.iter().any(|pattern| {
+                    glob_matches(&pattern.to_lowercase(), &interface.name.to_lowercase())
+                })
+        })?;
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "interface" => Some(Ok(interface.name.clone())),
+                "local_ip" => interface.ipv4.map(|ip| Ok(ip.to_string())),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `network`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::utils::network::MockNetworkInterfaceProvider;
+    use crate::modules::utils::network::NetworkInterface;
+    use crate::test::ModuleRenderer;
+    use nu_ansi_term::Color;
+    use std::net::Ipv4Addr;
+
+    fn interface(name: &str, is_up: bool, ipv4: Option<Ipv4Addr>) -> NetworkInterface {
+        NetworkInterface {
+            name: name.to_string(),
+            is_up,
+            ipv4,
+        }
+    }
+
+    fn render(interfaces: Vec<NetworkInterface>, config: toml::Value) -> Option<String> {
+        let mut mock = MockNetworkInterfaceProvider::new();
+        mock.expect_interfaces().return_const(interfaces);
+        ModuleRenderer::new("network")
+            .network_interface_provider(&mock)
+            .config(config)
+            .collect()
+    }
+
+    fn enabled() -> toml::Value {
+        toml::toml! {
+            [network]
+            disabled = false
+        }
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let actual = render(
+            vec![interface("wg0", true, None)],
+            toml::Value::Table(toml::value::Table::new()),
+        );
+
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn vpn_interface_up() {
+        let actual = render(
+            vec![
+                interface("lo", true, Some(Ipv4Addr::LOCALHOST)),
+                interface("eth0", true, Some(Ipv4Addr::new(192, 168, 1, 20))),
+                interface("wg0", true, Some(Ipv4Addr::new(10, 8, 0, 2))),
+            ],
+            enabled(),
+        );
+
+        let expected = Some(format!("via {} ", Color::Green.bold().paint("🛡 wg0")));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn no_vpn_interface() {
+        let interfaces = vec![
+            interface("eth0", true, Some(Ipv4Addr::new(192, 168, 1, 20))),
+            interface("tun0", false, None),
+            interface("wlan-tun", true, None),
+        ];
+
+        assert_eq!(None, render(interfaces, enabled()));
+    }
+
+    #[test]
+    fn detect_interfaces_and_local_ip() {
+        let interfaces = vec![
+            interface("eth0", true, Some(Ipv4Addr::new(192, 168, 1, 20))),
+            interface("Tailscale", true, Some(Ipv4Addr::new(100, 101, 102, 103))),
+        ];
+        let config = toml::toml! {
+            [network]
+            disabled = false
+            detect_interfaces = ["tailscale*"]
+            format = "[$interface( $local_ip)]($style)"
+        };
+        assert_eq!(
+            Some(
+                Color::Green
+                    .bold()
+                    .paint("Tailscale 100.101.102.103")
+                    .to_string()
+            ),
+            render(interfaces, config.clone())
+        );
+
+        let interfaces = vec![interface("tailscale0", true, None)];
+        assert_eq!(
+            Some(Color::Green.bold().paint("tailscale0").to_string()),
+            render(interfaces, config)
+        );
+    }
+}
@@ -6,11 +6,14 @@ use crate::formatter::StringFormatter;
 /// Creates a module showing if inside a nix-shell
 ///
 /// The module will use the `$IN_NIX_SHELL` and `$name` environment variable to
-/// determine if it's inside a nix-shell and the name of it.
+/// determine if it's inside a nix-shell and the name of it. Without `$name`,
+/// the attribute of `$FLAKE` or the packages in `$NIX_SHELL_PACKAGES` are used.
 ///
 /// The following options are availables:
-///     - `impure_msg` (string) // change the impure msg
-///     - `pure_msg` (string)   // change the pure msg
+///     - `impure_msg` (string)  // change the impure msg
+///     - `pure_msg` (string)    // change the pure msg
+///     - `unknown_msg` (string) // change the msg of shells found by `heuristic`
+///     - `heuristic` (bool)     // detect shells without `$IN_NIX_SHELL` through `/nix/store` in `$PATH`
 ///
 /// Will display the following:
 ///     - pure (name)    // $name == "name" in a pure nix-shell
@@ -21,11 +24,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("nix_shell");
     let config: NixShellConfig = NixShellConfig::try_load(module.config);
 
-    let shell_name = context.get_env("name");
-    let shell_type = context.get_env("IN_NIX_SHELL")?;
-    let shell_type_format = match shell_type.as_ref() {
-        "impure" => config.impure_msg,
-        "pure" => config.pure_msg,
+    let shell_name = get_shell_name(context);
+    let shell_type_format = match context.get_env("IN_NIX_SHELL").as_deref() {
+        Some("impure") => config.impure_msg,
+        Some("pure") => config.pure_msg,
+        None if config.heuristic && in_nix_store_path(context) => config.unknown_msg,
         _ => {
             return None;
         }
@@ -60,6 +63,30 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+fn get_shell_name(context: &Context) -> Option<String> {
+    context
+        .get_env("name")
+        .or_else(|| {
+            // e.g. `github:owner/repo#devShells.x86_64-linux.rust` is the `rust` shell
+            let flake = context.get_env("FLAKE")?;
+            let (_, attribute) = flake.split_once('#')?;
+            attribute.rsplit('.').next().map(str::to_string)
+        })
+        .or_else(|| {
+            let packages = context.get_env("NIX_SHELL_PACKAGES")?;
+            Some(packages.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .filter(|name| !name.is_empty())
+}
+
+/// Whether a directory of `$PATH` is in the Nix store, as in shells loaded by
+/// `direnv` that don't set `$IN_NIX_SHELL`
+fn in_nix_store_path(context: &Context) -> bool {
+    context.get_env_os("PATH").map_or(false, |path| {
+        std::env::split_paths(&path).any(|dir| dir.starts_with("/nix/store"))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
@@ -130,4 +157,82 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn flake_attribute_name() {
+        let actual = ModuleRenderer::new("nix_shell")
+            .env("IN_NIX_SHELL", "impure")
+            .env(
+                "FLAKE",
+                "github:starship/starship#devShells.x86_64-linux.rust",
+            )
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("❄️  impure (rust)")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn shell_packages_name() {
+        let actual = ModuleRenderer::new("nix_shell")
+            .env("IN_NIX_SHELL", "pure")
+            .env("NIX_SHELL_PACKAGES", " cargo  rustc ")
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("❄️  pure (cargo rustc)")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn heuristic_shell() {
+        let actual = ModuleRenderer::new("nix_shell")
+            .env(
+                "PATH",
+                "/nix/store/v5sv61sszx301i0x6xysaqzla09nksnd-cargo-1.62.0/bin:/usr/bin",
+            )
+            .env("FLAKE", ".#default")
+            .config(toml::toml! {
+                [nix_shell]
+                heuristic = true
+                unknown_msg = "unknown"
+            })
+            .collect();
+        let expected = Some(format!(
+            "via {} ",
+            Color::Blue.bold().paint("❄️  unknown (default)")
+        ));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn heuristic_without_nix_store_path() {
+        let actual = ModuleRenderer::new("nix_shell")
+            .env("PATH", "/usr/local/bin:/usr/bin")
+            .config(toml::toml! {
+                [nix_shell]
+                heuristic = true
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn heuristic_disabled() {
+        let actual = ModuleRenderer::new("nix_shell")
+            .env(
+                "PATH",
+                "/nix/store/v5sv61sszx301i0x6xysaqzla09nksnd-cargo-1.62.0/bin",
+            )
+            .collect();
+
+        assert_eq!(None, actual);
+    }
 }
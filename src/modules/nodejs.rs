@@ -35,6 +35,20 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             .exec_cmd("node", &["--version"])
             .map(|cmd| cmd.stdout)
     });
+    // The range of `engines.node` in `package.json`, if the version of Node.js
+    // isn't in it
+    let engines_mismatch = Lazy::new(|| {
+        if !config.check_engines {
+            return None;
+        }
+        let engines_version = get_engines_version(context)?;
+        let nodejs_version = nodejs_version.deref().as_ref()?;
+        if check_engines_version(nodejs_version, &engines_version) {
+            None
+        } else {
+            Some(engines_version)
+        }
+    });
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|var, _| match var {
@@ -43,13 +57,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map_style(|variable| match variable {
                 "style" => {
-                    let engines_version = get_engines_version(context);
-                    let in_engines_range =
-                        check_engines_version(nodejs_version.deref().as_ref()?, engines_version);
-                    if in_engines_range {
-                        Some(Ok(config.style))
-                    } else {
+                    nodejs_version.deref().as_ref()?;
+                    if engines_mismatch.is_some() {
                         Some(Ok(config.not_capable_style))
+                    } else {
+                        Some(Ok(config.style))
                     }
                 }
                 _ => None,
@@ -69,6 +81,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     )
                     .map(Ok)
                 }
+                "engines_version" => engines_mismatch.deref().clone().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -92,31 +105,62 @@ fn get_engines_version(context: &Context) -> Option<String> {
     Some(raw_version.to_string())
 }
 
-fn check_engines_version(nodejs_version: &str, engines_version: Option<String>) -> bool {
-    if engines_version.is_none() {
-        return true;
-    }
-    let r = match VersionReq::parse(&engines_version.unwrap()) {
-        Ok(r) => r,
-        Err(_e) => return true,
-    };
+/// Whether the version of Node.js satisfies the range `engines_version`
+///
+/// Ranges use the syntax of npm, e.g. `^18 || >=20 <21`. Ranges that can't be
+/// parsed are treated as satisfied.
+fn check_engines_version(nodejs_version: &str, engines_version: &str) -> bool {
     let re = Regex::new(r"\d+\.\d+\.\d+").unwrap();
-    let version = re
-        .captures(nodejs_version)
-        .unwrap()
-        .get(0)
-        .unwrap()
-        .as_str();
-    let v = match Version::parse(version) {
-        Ok(v) => v,
-        Err(_e) => return true,
+    let version = match re
+        .find(nodejs_version)
+        .and_then(|version| Version::parse(version.as_str()).ok())
+    {
+        Some(version) => version,
+        None => return true,
     };
-    r.matches(&v)
+
+    let alternatives: Option<Vec<VersionReq>> = engines_version
+        .split("||")
+        .map(parse_npm_comparators)
+        .collect();
+    match alternatives {
+        Some(alternatives) => alternatives.iter().any(|req| req.matches(&version)),
+        None => {
+            log::debug!("Unable to parse the engines range `{}`", engines_version);
+            true
+        }
+    }
+}
+
+/// Parses comparators of npm separated by spaces, e.g. `>=20 <21`, which all
+/// have to match
+fn parse_npm_comparators(comparators: &str) -> Option<VersionReq> {
+    let comparators: Vec<String> = comparators
+        .split_whitespace()
+        .map(|comparator| {
+            let operator_len = comparator
+                .find(|c: char| !matches!(c, '<' | '>' | '=' | '^' | '~'))
+                .unwrap_or(comparator.len());
+            let (operator, version) = comparator.split_at(operator_len);
+            let version = version.trim_start_matches('v');
+            // A bare version is exact in npm, but caret in Rust
+            match operator {
+                "" | "=" if matches!(version, "*" | "x" | "X") => "*".to_string(),
+                "" | "=" => format!("={}", version),
+                operator => format!("{}{}", operator, version),
+            }
+        })
+        .collect();
+    if comparators.is_empty() {
+        return VersionReq::parse("*").ok();
+    }
+    VersionReq::parse(&comparators.join(", ")).ok()
 }
 
 #[cfg(test)]
 mod tests {
     use crate::test::ModuleRenderer;
+    use crate::utils::CommandOutput;
     use nu_ansi_term::Color;
     use std::fs::{self, File};
     use std::io;
@@ -270,4 +314,113 @@ mod tests {
         assert_eq!(expected, actual);
         dir.close()
     }
+
+    fn render_engines(
+        node_version: &str,
+        engines: &str,
+        config: toml::Value,
+    ) -> io::Result<Option<String>> {
+        let dir = tempfile::tempdir()?;
+        let package_json = serde_json::json!({ "engines": { "node": engines } });
+        fs::write(dir.path().join("package.json"), package_json.to_string())?;
+
+        let actual = ModuleRenderer::new("nodejs")
+            .path(dir.path())
+            .cmd(
+                "node --version",
+                Some(CommandOutput {
+                    stdout: format!("{}\n", node_version),
+                    stderr: String::default(),
+                }),
+            )
+            .config(config)
+            .collect();
+        dir.close()?;
+        Ok(actual)
+    }
+
+    fn engines_config() -> toml::Value {
+        toml::toml! {
+            [nodejs]
+            format = "[$version( needs $engines_version)]($style)"
+        }
+    }
+
+    #[test]
+    fn engines_caret_range() -> io::Result<()> {
+        assert_eq!(
+            Some(Color::Green.bold().paint("v18.17.1").to_string()),
+            render_engines("v18.17.1", "^18", engines_config())?
+        );
+        assert_eq!(
+            Some(Color::Red.bold().paint("v20.5.0 needs ^18").to_string()),
+            render_engines("v20.5.0", "^18", engines_config())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn engines_bounded_range() -> io::Result<()> {
+        assert_eq!(
+            Some(Color::Green.bold().paint("v20.5.0").to_string()),
+            render_engines("v20.5.0", ">=20 <21", engines_config())?
+        );
+        assert_eq!(
+            Some(
+                Color::Red
+                    .bold()
+                    .paint("v21.0.0 needs >=20 <21")
+                    .to_string()
+            ),
+            render_engines("v21.0.0", ">=20 <21", engines_config())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn engines_alternative_ranges() -> io::Result<()> {
+        for version in ["v16.20.2", "v18.0.0", "v20.11.1"] {
+            assert_eq!(
+                Some(Color::Green.bold().paint(version).to_string()),
+                render_engines(version, "~16.20 || 18.0.0 || >=20", engines_config())?
+            );
+        }
+        assert_eq!(
+            Some(
+                Color::Red
+                    .bold()
+                    .paint("v18.1.0 needs ~16.20 || 18.0.0 || >=20")
+                    .to_string()
+            ),
+            render_engines("v18.1.0", "~16.20 || 18.0.0 || >=20", engines_config())?
+        );
+        assert_eq!(
+            Some(Color::Green.bold().paint("v18.1.0").to_string()),
+            render_engines("v18.1.0", "18.x || *", engines_config())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn engines_invalid_range_is_ignored() -> io::Result<()> {
+        assert_eq!(
+            Some(Color::Green.bold().paint("v20.5.0").to_string()),
+            render_engines("v20.5.0", "not a range", engines_config())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn engines_check_disabled() -> io::Result<()> {
+        let config = toml::toml! {
+            [nodejs]
+            format = "[$version( needs $engines_version)]($style)"
+            check_engines = false
+        };
+        assert_eq!(
+            Some(Color::Green.bold().paint("v20.5.0").to_string()),
+            render_engines("v20.5.0", "^18", config)?
+        );
+        Ok(())
+    }
 }
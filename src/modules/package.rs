@@ -1,20 +1,21 @@
 use super::{Context, Module, ModuleConfig};
 use crate::configs::package::PackageConfig;
 use crate::formatter::{StringFormatter, VersionFormatter};
+use crate::utils;
 
 use ini::Ini;
 use quick_xml::events::Event as QXEvent;
 use quick_xml::Reader as QXReader;
 use regex::Regex;
 use serde_json as json;
-use std::fs;
-use std::io::Read;
+use std::ffi::OsStr;
+use std::path::Path;
 
 /// Creates a module with the current package version
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("package");
     let config: PackageConfig = PackageConfig::try_load(module.config);
-    let module_version = get_version(context, &config)?;
+    let package = get_package(context, &config)?;
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
@@ -27,7 +28,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "version" => Some(Ok(&module_version)),
+                "version" => Some(Ok(&package.version)),
+                "name" => package.name.as_ref().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -80,13 +82,94 @@ fn get_pep621_version(pyproject: &toml::Value) -> Option<&str> {
     pyproject.get("project")?.get("version")?.as_str()
 }
 
+/// Whether the version is set by the build backend, e.g. from git tags with
+/// setuptools-scm or poetry-dynamic-versioning
+fn is_dynamic_pyproject_version(pyproject: &toml::Value) -> bool {
+    let is_dynamic_pep621 = pyproject
+        .get("project")
+        .and_then(|project| project.get("dynamic"))
+        .and_then(toml::Value::as_array)
+        .map_or(false, |dynamic| {
+            dynamic
+                .iter()
+                .any(|field| field.as_str() == Some("version"))
+        });
+    let is_dynamic_poetry = pyproject
+        .get("tool")
+        .and_then(|tool| tool.get("poetry-dynamic-versioning"))
+        .and_then(|versioning| versioning.get("enable"))
+        .and_then(toml::Value::as_bool)
+        .unwrap_or_default();
+    is_dynamic_pep621 || is_dynamic_poetry
+}
+
+/// The version setuptools-scm derives from the latest tag, e.g. `1.2.4.dev3+gabc1234`
+/// three commits after the tag `v1.2.3`
+fn get_git_describe_version(context: &Context) -> Option<String> {
+    let output = context.exec_cmd(
+        "git",
+        &[
+            OsStr::new("-C"),
+            context.current_dir.as_os_str(),
+            OsStr::new("describe"),
+            OsStr::new("--tags"),
+            OsStr::new("--long"),
+            OsStr::new("--dirty"),
+            OsStr::new("--match"),
+            OsStr::new("*[0-9]*"),
+        ],
+    )?;
+    let description = output.stdout.trim();
+    let (description, is_dirty) = match description.strip_suffix("-dirty") {
+        Some(description) => (description, true),
+        None => (description, false),
+    };
+
+    // `<tag>-<distance>-g<hash>`, where the tag may contain dashes itself
+    let mut parts = description.rsplitn(3, '-');
+    let hash = parts.next()?;
+    let distance: u64 = parts.next()?.parse().ok()?;
+    let tag = parts.next()?;
+    // Like setuptools-scm, drop prefixes such as `v` or `release-`
+    let tag = &tag[tag.find(|c: char| c.is_ascii_digit())?..];
+
+    if distance == 0 && !is_dirty {
+        return Some(tag.to_string());
+    }
+
+    // Guess the next version by bumping the last number of the tag
+    let (prefix, last) = tag.rsplit_once('.').unwrap_or(("", tag));
+    let next: u64 = last.parse::<u64>().ok()? + 1;
+    let next_version = match prefix {
+        "" => next.to_string(),
+        prefix => format!("{}.{}", prefix, next),
+    };
+    let mut version = format!("{}.dev{}+{}", next_version, distance, hash);
+    if is_dirty {
+        version.push_str(&context.get_now().format(".d%Y%m%d").to_string());
+    }
+    Some(version)
+}
+
 fn get_pyproject_version(context: &Context, config: &PackageConfig) -> Option<String> {
     let file_contents = context.read_file_from_pwd("pyproject.toml")?;
     let pyproject_toml: toml::Value = toml::from_str(&file_contents).ok()?;
+    let is_dynamic = is_dynamic_pyproject_version(&pyproject_toml);
 
-    get_pep621_version(&pyproject_toml)
-        .or_else(|| get_poetry_version(&pyproject_toml))
-        .and_then(|raw_version| format_version(raw_version, config.version_format))
+    let static_version = get_pep621_version(&pyproject_toml).or_else(|| {
+        // poetry-dynamic-versioning only uses `tool.poetry.version` as a placeholder
+        if is_dynamic {
+            return None;
+        }
+        get_poetry_version(&pyproject_toml)
+    });
+    let raw_version = match static_version {
+        Some(version) => version.to_string(),
+        // Running git is opt-in, as the module otherwise only reads files
+        None if is_dynamic && config.allow_dynamic_versions => get_git_describe_version(context)?,
+        None => return None,
+    };
+    format_version(&raw_version, config.version_format)
 }
 
 fn get_setup_cfg_version(context: &Context, config: &PackageConfig) -> Option<String> {
@@ -214,48 +297,79 @@ fn get_sbt_version(context: &Context, config: &PackageConfig) -> Option<String>
     format_version(&caps["version"], config.version_format)
 }
 
-fn get_cargo_version(context: &Context, config: &PackageConfig) -> Option<String> {
-    let mut file_contents = context.read_file_from_pwd("Cargo.toml")?;
+fn get_cargo_package_from_pwd(context: &Context, config: &PackageConfig) -> Option<Package> {
+    let file_contents = context.read_file_from_pwd("Cargo.toml")?;
+    get_cargo_package(&context.current_dir, &file_contents, config)
+}
+
+/// The package of the nearest `Cargo.toml` above the current directory,
+/// e.g. of the workspace member whose sources are being edited
+fn get_cargo_ancestor_package(context: &Context, config: &PackageConfig) -> Option<Package> {
+    context.current_dir.ancestors().skip(1).find_map(|dir| {
+        let file_contents = utils::read_file(dir.join("Cargo.toml")).ok()?;
+        get_cargo_package(dir, &file_contents, config)
+    })
+}
 
-    let mut cargo_toml: toml::Value = toml::from_str(&file_contents).ok()?;
-    let cargo_version = cargo_toml.get("package").and_then(|p| p.get("version"));
+/// Reads the package of the `Cargo.toml` in `dir`, with its `file_contents`
+///
+/// Versions inherited with `version.workspace = true` are read from the
+/// `workspace.package` of the workspace root, the first ancestor of `dir`
+/// with a `[workspace]`. Virtual manifests only have the version their
+/// `workspace.package` sets for their members.
+fn get_cargo_package(dir: &Path, file_contents: &str, config: &PackageConfig) -> Option<Package> {
+    let cargo_toml: toml::Value = toml::from_str(file_contents).ok()?;
+    let workspace_version = |cargo_toml: &toml::Value| -> Option<String> {
+        let version = cargo_toml
+            .get("workspace")?
+            .get("package")?
+            .get("version")?
+            .as_str()?;
+        Some(version.to_owned())
+    };
+
+    let package = match cargo_toml.get("package") {
+        Some(package) => package,
+        // This might be a workspace file
+        None => {
+            return Some(Package {
+                name: None,
+                version: format_version(&workspace_version(&cargo_toml)?, config.version_format)?,
+            })
+        }
+    };
+
+    let cargo_version = package.get("version");
     let raw_version = if let Some(v) = cargo_version.and_then(|v| v.as_str()) {
         // regular version string
-        v
+        v.to_owned()
     } else if cargo_version
         .and_then(|v| v.get("workspace"))
         .and_then(|w| w.as_bool())
         .unwrap_or_default()
     {
-        // workspace version string (`package.version.worspace = true`)
-        // need to read the Cargo.toml file from the workspace root
-        let mut version = None;
-        // disover the workspace root
-        for path in context.current_dir.ancestors().skip(1) {
-            // Assume the workspace root is the first ancestor that contains a Cargo.toml file
-            if let Ok(mut file) = fs::File::open(path.join("Cargo.toml")) {
-                file.read_to_string(&mut file_contents).ok()?;
-                cargo_toml = toml::from_str(&file_contents).ok()?;
-                // Read workspace.package.version
-                version = cargo_toml
-                    .get("workspace")?
-                    .get("package")?
-                    .get("version")?
-                    .as_str();
-                break;
-            }
+        // workspace version string (`package.version.workspace = true`)
+        if cargo_toml.get("workspace").is_some() {
+            // The root package of the workspace
+            workspace_version(&cargo_toml)?
+        } else {
+            let workspace_root = dir.ancestors().skip(1).find_map(|path| {
+                let file_contents = utils::read_file(path.join("Cargo.toml")).ok()?;
+                let cargo_toml: toml::Value = toml::from_str(&file_contents).ok()?;
+                cargo_toml.get("workspace").is_some().then(|| cargo_toml)
+            })?;
+            workspace_version(&workspace_root)?
         }
-        version?
     } else {
-        // This might be a workspace file
-        cargo_toml
-            .get("workspace")?
-            .get("package")?
-            .get("version")?
-            .as_str()?
+        return None;
     };
 
-    format_version(raw_version, config.version_format)
+    Some(Package {
+        name: package
+            .get("name")
+            .and_then(|name| Some(name.as_str()?.to_owned())),
+        version: format_version(&raw_version, config.version_format)?,
+    })
 }
 
 fn get_nimble_version(context: &Context, config: &PackageConfig) -> Option<String> {
@@ -302,9 +416,38 @@ fn get_dart_pub_version(context: &Context, config: &PackageConfig) -> Option<Str
     format_version(raw_version, config.version_format)
 }
 
+/// The name and version of a package
+struct Package {
+    name: Option<String>,
+    version: String,
+}
+
+fn get_package(context: &Context, config: &PackageConfig) -> Option<Package> {
+    if let Some(package) = get_cargo_package_from_pwd(context, config) {
+        return Some(package);
+    }
+
+    if let Some(version) = get_version(context, config) {
+        return Some(Package {
+            name: None,
+            version,
+        });
+    }
+
+    // Without a manifest in the current directory, fall back to the one of
+    // the crate the directory belongs to
+    if context
+        .try_begin_scan()?
+        .set_files(&["Cargo.toml"])
+        .is_match()
+    {
+        return None;
+    }
+    get_cargo_ancestor_package(context, config)
+}
+
 fn get_version(context: &Context, config: &PackageConfig) -> Option<String> {
     let package_version_fn: Vec<fn(&Context, &PackageConfig) -> Option<String>> = vec![
-        get_cargo_version,
         get_nimble_version,
         get_node_package_version,
         get_pyproject_version,
@@ -341,8 +484,9 @@ fn format_version(version: &str, version_format: &str) -> Option<String> {
 mod tests {
     use super::*;
     use crate::{test::ModuleRenderer, utils::CommandOutput};
+    use chrono::{Local, TimeZone};
     use nu_ansi_term::Color;
-    use std::fs::File;
+    use std::fs::{self, File};
     use std::io;
     use std::io::Write;
     use tempfile::TempDir;
@@ -475,6 +619,125 @@ mod tests {
         project_dir.close()
     }
 
+    /// A virtual workspace with a member that inherits its version and one
+    /// with a version of its own
+    fn create_virtual_workspace() -> io::Result<TempDir> {
+        let project_dir = create_project_dir()?;
+        let ws_config_content = toml::toml! {
+            [workspace]
+            members = ["crates/*"]
+            [workspace.package]
+            version = "0.3.0"
+        }
+        .to_string();
+        fill_config(&project_dir, "Cargo.toml", Some(&ws_config_content))?;
+
+        fs::create_dir_all(project_dir.path().join("crates/inherited/src"))?;
+        let inherited_content = toml::toml! {
+            [package]
+            name = "inherited"
+            version.workspace = true
+        }
+        .to_string();
+        fill_config(
+            &project_dir,
+            "crates/inherited/Cargo.toml",
+            Some(&inherited_content),
+        )?;
+
+        fs::create_dir_all(project_dir.path().join("crates/own/src/bin"))?;
+        let own_content = toml::toml! {
+            [package]
+            name = "own"
+            version = "1.2.0"
+        }
+        .to_string();
+        fill_config(&project_dir, "crates/own/Cargo.toml", Some(&own_content))?;
+        Ok(project_dir)
+    }
+
+    fn render_member(dir: &Path) -> Option<String> {
+        ModuleRenderer::new("package")
+            .path(dir)
+            .config(toml::toml! {
+                [package]
+                format = "$name@$version"
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_cargo_version_virtual_ws_members() -> io::Result<()> {
+        let project_dir = create_virtual_workspace()?;
+        let crates = project_dir.path().join("crates");
+
+        expect_output(&project_dir, Some("v0.3.0"), None);
+        assert_eq!(
+            Some("inherited@v0.3.0".to_string()),
+            render_member(&crates.join("inherited"))
+        );
+        assert_eq!(
+            Some("own@v1.2.0".to_string()),
+            render_member(&crates.join("own"))
+        );
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_cargo_version_below_ws_member() -> io::Result<()> {
+        let project_dir = create_virtual_workspace()?;
+        let crates = project_dir.path().join("crates");
+
+        assert_eq!(
+            Some("inherited@v0.3.0".to_string()),
+            render_member(&crates.join("inherited/src"))
+        );
+        assert_eq!(
+            Some("own@v1.2.0".to_string()),
+            render_member(&crates.join("own/src/bin"))
+        );
+        // Between the members, the workspace's version is shown
+        assert_eq!(Some("@v0.3.0".to_string()), render_member(&crates));
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_cargo_version_prefers_package_in_pwd() -> io::Result<()> {
+        let project_dir = create_virtual_workspace()?;
+        let src = project_dir.path().join("crates/own/src");
+        let package_json = json::json!({
+            "name": "web",
+            "version": "4.0.0"
+        })
+        .to_string();
+        let mut file = File::create(src.join("package.json"))?;
+        file.write_all(package_json.as_bytes())?;
+        file.sync_all()?;
+
+        assert_eq!(Some("@v4.0.0".to_string()), render_member(&src));
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_cargo_version_root_package_inherits() -> io::Result<()> {
+        let config_content = toml::toml! {
+            [package]
+            name = "root"
+            version.workspace = true
+            [workspace.package]
+            version = "2.0.0"
+        }
+        .to_string();
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, "Cargo.toml", Some(&config_content))?;
+        assert_eq!(
+            Some("root@v2.0.0".to_string()),
+            render_member(project_dir.path())
+        );
+        project_dir.close()
+    }
+
     #[test]
     fn test_extract_nimble_package_version() -> io::Result<()> {
         let config_name = "test_project.nimble";
@@ -833,6 +1096,135 @@ license = "MIT"
         project_dir.close()
     }
 
+    fn render_dynamic_version(
+        project_dir: &TempDir,
+        describe: Option<&str>,
+        allow_dynamic_versions: bool,
+    ) -> Option<String> {
+        let command = format!(
+            "git -C {} describe --tags --long --dirty --match *[0-9]*",
+            project_dir.path().to_string_lossy()
+        );
+        ModuleRenderer::new("package")
+            .path(project_dir.path())
+            .cmd(
+                &command,
+                describe.map(|describe| CommandOutput {
+                    stdout: format!("{}\n", describe),
+                    stderr: String::default(),
+                }),
+            )
+            .now(Local.ymd(2022, 8, 1).and_hms(12, 0, 0))
+            .config(toml::toml! {
+                [package]
+                format = "$version"
+                allow_dynamic_versions = allow_dynamic_versions
+            })
+            .collect()
+    }
+
+    fn create_dynamic_pyproject() -> io::Result<TempDir> {
+        let config_content = toml::toml! {
+            [project]
+            name = "starship"
+            dynamic = ["version"]
+            [tool.setuptools_scm]
+        }
+        .to_string();
+
+        let project_dir = create_project_dir()?;
+        fill_config(&project_dir, "pyproject.toml", Some(&config_content))?;
+        Ok(project_dir)
+    }
+
+    #[test]
+    fn test_extract_dynamic_pyproject_version_on_tag() -> io::Result<()> {
+        let project_dir = create_dynamic_pyproject()?;
+        assert_eq!(
+            Some("v1.2.3".to_string()),
+            render_dynamic_version(&project_dir, Some("v1.2.3-0-gabc1234"), true)
+        );
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_dynamic_pyproject_version_after_tag() -> io::Result<()> {
+        let project_dir = create_dynamic_pyproject()?;
+        assert_eq!(
+            Some("v1.2.4.dev3+gabc1234".to_string()),
+            render_dynamic_version(&project_dir, Some("v1.2.3-3-gabc1234"), true)
+        );
+        assert_eq!(
+            Some("v1.2.4.dev0+gabc1234.d20220801".to_string()),
+            render_dynamic_version(&project_dir, Some("v1.2.3-0-gabc1234-dirty"), true)
+        );
+        assert_eq!(
+            Some("v2.1.dev12+g0123abc".to_string()),
+            render_dynamic_version(&project_dir, Some("release-2.0-12-g0123abc"), true)
+        );
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_dynamic_pyproject_version_disabled() -> io::Result<()> {
+        let project_dir = create_dynamic_pyproject()?;
+        assert_eq!(
+            None,
+            render_dynamic_version(&project_dir, Some("v1.2.3-0-gabc1234"), false)
+        );
+        // Without any tags
+        assert_eq!(None, render_dynamic_version(&project_dir, None, true));
+        project_dir.close()
+    }
+
+    #[test]
+    fn test_extract_pyproject_version_sources() -> io::Result<()> {
+        let project_dir = create_project_dir()?;
+        let describe = Some("v1.2.3-3-gabc1234");
+
+        // A static version is preferred, even over a dynamic one
+        let config_content = toml::toml! {
+            [project]
+            version = "0.1.0"
+            dynamic = ["version"]
+            [tool.poetry]
+            version = "0.2.0"
+        }
+        .to_string();
+        fill_config(&project_dir, "pyproject.toml", Some(&config_content))?;
+        assert_eq!(
+            Some("v0.1.0".to_string()),
+            render_dynamic_version(&project_dir, describe, true)
+        );
+
+        let config_content = toml::toml! {
+            [tool.poetry]
+            version = "0.2.0"
+        }
+        .to_string();
+        fill_config(&project_dir, "pyproject.toml", Some(&config_content))?;
+        assert_eq!(
+            Some("v0.2.0".to_string()),
+            render_dynamic_version(&project_dir, describe, true)
+        );
+
+        // poetry-dynamic-versioning replaces the placeholder version
+        let config_content = toml::toml! {
+            [tool.poetry]
+            version = "0.0.0"
+            [tool.poetry-dynamic-versioning]
+            enable = true
+        }
+        .to_string();
+        fill_config(&project_dir, "pyproject.toml", Some(&config_content))?;
+        assert_eq!(
+            Some("v1.2.4.dev3+gabc1234".to_string()),
+            render_dynamic_version(&project_dir, describe, true)
+        );
+        assert_eq!(None, render_dynamic_version(&project_dir, describe, false));
+        project_dir.close()
+    }
+
     #[test]
     fn test_extract_setup_cfg_version() -> io::Result<()> {
         let config_name = "setup.cfg";
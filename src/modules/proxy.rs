@@ -0,0 +1,237 @@
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::proxy::ProxyConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module with the host of the proxy set in the environment
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("proxy");
+    let config = ProxyConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let (variable, url) = config.env_vars.iter().find_map(|variable| {
+        let url = context.get_env(variable)?;
+        (!url.trim().is_empty()).then(|| (*variable, url))
+    })?;
+    let host = parse_proxy_host(&url);
+    if host.is_none() {
+        log::debug!("Unable to parse the proxy URL in ${}", variable);
+    }
+
+    let bypasses_proxy = !config.no_proxy_domain.is_empty()
+        && ["NO_PROXY", "no_proxy"].iter().any(|name| {
+            context.get_env(name).map_or(false, |no_proxy| {
+                no_proxy_covers(&no_proxy, config.no_proxy_domain)
+            })
+        });
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|var, _| match var {
+                "symbol" => Some(config.symbol),
+                "no_proxy_symbol" if bypasses_proxy => Some(config.no_proxy_symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|var| match var {
+                "host" => host.as_deref().map(Ok),
+                "variable" => Some(Ok(variable)),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `proxy`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// The host of a proxy URL, without its scheme, credentials, port or path
+fn parse_proxy_host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = authority.split(&['/', '?', '#'][..]).next()?;
+    // Credentials may contain `@` themselves, the host follows the last one
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+
+    let host = if host_port.starts_with('[') {
+        // An IPv6 address like `[::1]:3128`
+        &host_port[..=host_port.find(']')?]
+    } else {
+        host_port.split(':').next()?
+    };
+
+    let is_valid = !host.is_empty()
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-._[]:".contains(c));
+    is_valid.then(|| host.to_string())
+}
+
+/// Whether the hosts in a `NO_PROXY` list include `domain`
+fn no_proxy_covers(no_proxy: &str, domain: &str) -> bool {
+    let domain = domain.trim_start_matches('.');
+    no_proxy
+        .split(',')
+        .map(|entry| {
+            let entry = entry.trim();
+            entry
+                .strip_prefix("*.")
+                .unwrap_or(entry)
+                .trim_start_matches('.')
+        })
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| {
+            entry == "*"
+                || entry.eq_ignore_ascii_case(domain)
+                || domain
+                    .to_ascii_lowercase()
+                    .ends_with(&format!(".{}", entry.to_ascii_lowercase()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::ModuleRenderer;
+    use nu_ansi_term::Color;
+
+    fn render(env: &[(&str, &str)]) -> Option<String> {
+        let mut renderer = ModuleRenderer::new("proxy").config(toml::toml! {
+            [proxy]
+            disabled = false
+            no_proxy_domain = "git.corp.example"
+        });
+        for (key, value) in env {
+            renderer = renderer.env(*key, *value);
+        }
+        renderer.collect()
+    }
+
+    fn expected(text: &str) -> Option<String> {
+        Some(format!("via {}", Color::Yellow.bold().paint(text)))
+    }
+
+    #[test]
+    fn no_proxy_set() {
+        assert_eq!(None, render(&[]));
+        assert_eq!(None, render(&[("HTTPS_PROXY", " ")]));
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let actual = ModuleRenderer::new("proxy")
+            .env("HTTPS_PROXY", "http://proxy.corp.example:3128")
+            .collect();
+
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn each_proxy_variable() {
+        for variable in [
+            "HTTPS_PROXY",
+            "https_proxy",
+            "HTTP_PROXY",
+            "http_proxy",
+            "ALL_PROXY",
+            "all_proxy",
+        ] {
+            assert_eq!(
+                expected("🔀 proxy.corp.example "),
+                render(&[(variable, "http://proxy.corp.example:3128/")]),
+                "{}",
+                variable
+            );
+        }
+    }
+
+    #[test]
+    fn first_variable_wins() {
+        let actual = render(&[
+            ("ALL_PROXY", "socks5://socks.corp.example:1080"),
+            ("https_proxy", "http://proxy.corp.example:3128"),
+        ]);
+
+        assert_eq!(expected("🔀 proxy.corp.example "), actual);
+    }
+
+    #[test]
+    fn credentials_are_hidden() {
+        let actual = render(&[("HTTPS_PROXY", "http://user:p@ss@proxy.corp.example:3128")]);
+
+        assert_eq!(expected("🔀 proxy.corp.example "), actual);
+    }
+
+    #[test]
+    fn malformed_url_shows_only_the_symbol() {
+        assert_eq!(
+            expected("🔀 "),
+            render(&[("HTTP_PROXY", "http://user:secret@")])
+        );
+        assert_eq!(
+            expected("🔀 "),
+            render(&[("HTTP_PROXY", "not a proxy url")])
+        );
+    }
+
+    #[test]
+    fn no_proxy_domain() {
+        let actual = render(&[
+            ("HTTP_PROXY", "proxy.corp.example:3128"),
+            ("NO_PROXY", "localhost, .corp.example"),
+        ]);
+
+        assert_eq!(expected("🔀 proxy.corp.example 🚫 "), actual);
+
+        let actual = render(&[
+            ("HTTP_PROXY", "proxy.corp.example:3128"),
+            ("no_proxy", "localhost,example.com"),
+        ]);
+
+        assert_eq!(expected("🔀 proxy.corp.example "), actual);
+    }
+
+    #[test]
+    fn test_parse_proxy_host() {
+        assert_eq!(
+            parse_proxy_host("http://proxy:8080"),
+            Some("proxy".to_string())
+        );
+        assert_eq!(
+            parse_proxy_host("socks5h://[::1]:1080"),
+            Some("[::1]".to_string())
+        );
+        assert_eq!(
+            parse_proxy_host("https://token@10.0.0.1/path?query"),
+            Some("10.0.0.1".to_string())
+        );
+        assert_eq!(parse_proxy_host("http://[::1"), None);
+        assert_eq!(parse_proxy_host("http://"), None);
+    }
+
+    #[test]
+    fn test_no_proxy_covers() {
+        assert!(no_proxy_covers("*", "example.com"));
+        assert!(no_proxy_covers("example.com", "EXAMPLE.com"));
+        assert!(no_proxy_covers("*.example.com", "git.example.com"));
+        assert!(!no_proxy_covers("example.com", "notexample.com"));
+        assert!(!no_proxy_covers(",,", "example.com"));
+    }
+}
@@ -5,7 +5,7 @@ use super::{Context, Module, ModuleConfig};
 use crate::configs::python::PythonConfig;
 use crate::formatter::StringFormatter;
 use crate::formatter::VersionFormatter;
-use crate::utils::get_command_string_output;
+use crate::utils::{self, get_command_string_output};
 
 /// Creates a module with the current Python version and, if active, virtual environment.
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
@@ -54,10 +54,9 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                     )
                     .map(Ok)
                 }
-                "virtualenv" => {
-                    let virtual_env = get_python_virtual_env(context);
-                    virtual_env.as_ref().map(|e| Ok(e.trim().to_string()))
-                }
+                "virtualenv" => get_environment(context, &config).map(Ok),
+                "conda_env" => get_conda_env(context, &config).map(Ok),
+                "pyenv_version" => get_pyenv_local_version(context).map(Ok),
                 "pyenv_prefix" => Some(Ok(pyenv_prefix.to_string())),
                 _ => None,
             })
@@ -75,6 +74,56 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// The environment of the first source in `detect_order` that has one, shown
+/// as `$virtualenv`
+fn get_environment(context: &Context, config: &PythonConfig) -> Option<String> {
+    config.detect_order.iter().find_map(|source| match *source {
+        "virtualenv" => get_python_virtual_env(context).map(|env| env.trim().to_string()),
+        "conda" => get_conda_env(context, config),
+        "pyenv" => get_pyenv_local_version(context),
+        unknown => {
+            log::warn!(
+                "Unknown source `{}` in `python.detect_order`, expected one of `virtualenv`, `conda` or `pyenv`",
+                unknown
+            );
+            None
+        }
+    })
+}
+
+/// The active conda (or mamba) environment
+fn get_conda_env(context: &Context, config: &PythonConfig) -> Option<String> {
+    let conda_env = context.get_env("CONDA_DEFAULT_ENV")?;
+    let conda_env = conda_env.trim();
+    if conda_env.is_empty() || (config.ignore_conda_base && conda_env == "base") {
+        return None;
+    }
+    Some(conda_env.to_string())
+}
+
+/// The pyenv version of `PYENV_VERSION` or the nearest `.python-version` file,
+/// without running pyenv
+fn get_pyenv_local_version(context: &Context) -> Option<String> {
+    if let Some(version) = context.get_env("PYENV_VERSION") {
+        return Some(version);
+    }
+
+    let contents = context
+        .current_dir
+        .ancestors()
+        .find_map(|dir| utils::read_file(dir.join(".python-version")).ok())?;
+    // Every line can name a version, like in `pyenv version-name`
+    let versions: Vec<&str> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    if versions.is_empty() {
+        return None;
+    }
+    Some(versions.join(":"))
+}
+
 fn get_pyenv_version(context: &Context) -> Option<String> {
     let mut version_name = context.get_env("PYENV_VERSION");
 
@@ -134,7 +183,7 @@ mod tests {
     use super::*;
     use crate::test::ModuleRenderer;
     use nu_ansi_term::Color;
-    use std::fs::{create_dir_all, File};
+    use std::fs::{self, create_dir_all, File};
     use std::io;
     use std::io::Write;
 
@@ -490,4 +539,118 @@ prompt = '(foo)'
         ));
         assert_eq!(expected, actual);
     }
+
+    fn render_envs(
+        dir: &Path,
+        envs: &[(&'static str, &str)],
+        config: toml::Value,
+    ) -> Option<String> {
+        let mut renderer = ModuleRenderer::new("python").path(dir).config(config);
+        for (key, value) in envs {
+            renderer = renderer.env(key, *value);
+        }
+        renderer.collect()
+    }
+
+    fn all_envs_config(detect_order: &[&str]) -> toml::Value {
+        let detect_order: Vec<toml::Value> = detect_order
+            .iter()
+            .map(|source| toml::Value::from(*source))
+            .collect();
+        toml::toml! {
+            [python]
+            format = "$virtualenv|$conda_env|$pyenv_version"
+            detect_order = detect_order
+        }
+    }
+
+    #[test]
+    fn with_all_environments() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".python-version"), "3.10.4\n")?;
+        let envs = [
+            ("VIRTUAL_ENV", "/foo/bar/my_venv"),
+            ("CONDA_DEFAULT_ENV", "datasci"),
+        ];
+
+        assert_eq!(
+            Some("my_venv|datasci|3.10.4".to_string()),
+            render_envs(dir.path(), &envs, all_envs_config(&["virtualenv"]))
+        );
+        assert_eq!(
+            Some("datasci|datasci|3.10.4".to_string()),
+            render_envs(dir.path(), &envs, all_envs_config(&["conda", "virtualenv"]))
+        );
+        assert_eq!(
+            Some("3.10.4|datasci|3.10.4".to_string()),
+            render_envs(dir.path(), &envs, all_envs_config(&["pyenv", "conda"]))
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn detect_order_falls_through() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".python-version"), "3.10.4\n3.9.13\n")?;
+
+        assert_eq!(
+            Some("3.10.4:3.9.13||3.10.4:3.9.13".to_string()),
+            render_envs(
+                dir.path(),
+                &[],
+                all_envs_config(&["virtualenv", "conda", "pyenv"])
+            )
+        );
+        // PYENV_VERSION takes precedence over the file
+        assert_eq!(
+            Some("|datasci|pypy3.9".to_string()),
+            render_envs(
+                dir.path(),
+                &[
+                    ("CONDA_DEFAULT_ENV", "datasci"),
+                    ("PYENV_VERSION", "pypy3.9")
+                ],
+                all_envs_config(&["virtualenv"])
+            )
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn pyenv_version_from_parent_directory() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".python-version"), "3.10.4\n")?;
+        let sub_dir = dir.path().join("src");
+        fs::create_dir(&sub_dir)?;
+        File::create(sub_dir.join("main.py"))?.sync_all()?;
+
+        assert_eq!(
+            Some("||3.10.4".to_string()),
+            render_envs(&sub_dir, &[], all_envs_config(&["virtualenv"]))
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn conda_base_env() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("main.py"))?.sync_all()?;
+        let envs = [("CONDA_DEFAULT_ENV", "base")];
+
+        assert_eq!(
+            Some("||".to_string()),
+            render_envs(dir.path(), &envs, all_envs_config(&["conda"]))
+        );
+        let config = toml::toml! {
+            [python]
+            format = "$virtualenv|$conda_env|$pyenv_version"
+            detect_order = ["conda"]
+            ignore_conda_base = false
+        };
+        assert_eq!(
+            Some("base|base|".to_string()),
+            render_envs(dir.path(), &envs, config)
+        );
+        dir.close()
+    }
 }
@@ -23,7 +23,11 @@ type ToolchainString = String;
 struct RustToolingEnvironmentInfo {
     /// Rustup settings parsed from $HOME/.rustup/settings.toml
     rustup_settings: OnceCell<RustupSettings>,
+    /// The nearest `rust-toolchain` or `rust-toolchain.toml` file
+    toolchain_file: OnceCell<Option<ToolchainFile>>,
     /// Rustc toolchain overrides as contained in the environment or files
+    configured_toolchain: OnceCell<Option<String>>,
+    /// The configured toolchain, or else the one from `rustup default`
     env_toolchain_override: OnceCell<Option<String>>,
     /// The output of `rustup rustc --version` with a fixed toolchain
     rustup_rustc_output: OnceCell<RustupRunRustcVersionOutcome>,
@@ -36,6 +40,8 @@ impl RustToolingEnvironmentInfo {
     fn new() -> Self {
         Self {
             rustup_settings: OnceCell::new(),
+            toolchain_file: OnceCell::new(),
+            configured_toolchain: OnceCell::new(),
             env_toolchain_override: OnceCell::new(),
             rustup_rustc_output: OnceCell::new(),
             rustc_verbose_output: OnceCell::new(),
@@ -47,6 +53,19 @@ impl RustToolingEnvironmentInfo {
             .get_or_init(|| RustupSettings::load(context).unwrap_or_default())
     }
 
+    fn get_toolchain_file(&self, context: &Context) -> Option<&ToolchainFile> {
+        self.toolchain_file
+            .get_or_init(|| find_rust_toolchain_file(context))
+            .as_ref()
+    }
+
+    fn get_default_host_triple(&self, context: &Context) -> Option<&str> {
+        match self.get_rustup_settings(context).default_host_triple() {
+            None => guess_host_triple(),
+            triple => triple,
+        }
+    }
+
     /// Gets any environmental toolchain overrides without downloading cargo toolchains
     fn get_env_toolchain_override(&self, context: &Context) -> Option<&str> {
         // `$CARGO_HOME/bin/rustc(.exe) --version` may attempt installing a rustup toolchain.
@@ -69,21 +88,36 @@ impl RustToolingEnvironmentInfo {
         // - `rustup which`
         self.env_toolchain_override
             .get_or_init(|| {
-                let out = env_rustup_toolchain(context)
+                let out = self
+                    .get_configured_toolchain(context)
+                    .map(str::to_owned)
+                    .or_else(|| execute_rustup_default(context));
+
+                log::debug!("Environmental toolchain override is {:?}", out);
+                out
+            })
+            .as_deref()
+    }
+
+    /// Gets the toolchain override from steps 1 through 4 of
+    /// `self.get_env_toolchain_override()`, without running `rustup`
+    fn get_configured_toolchain(&self, context: &Context) -> Option<&str> {
+        self.configured_toolchain
+            .get_or_init(|| {
+                env_rustup_toolchain(context)
                     .or_else(|| {
                         self.get_rustup_settings(context)
                             .lookup_override(context.current_dir.as_path())
                     })
-                    .or_else(|| find_rust_toolchain_file(context))
+                    .or_else(|| {
+                        self.get_toolchain_file(context)
+                            .map(|file| file.channel.clone())
+                    })
                     .or_else(|| {
                         self.get_rustup_settings(context)
                             .default_toolchain()
                             .map(std::string::ToString::to_string)
                     })
-                    .or_else(|| execute_rustup_default(context));
-
-                log::debug!("Environmental toolchain override is {:?}", out);
-                out
             })
             .as_deref()
     }
@@ -179,6 +213,10 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => Some(config.symbol),
+                "components_symbol" => rust_env_info
+                    .get_toolchain_file(context)
+                    .filter(|file| file.pins_tooling_components())
+                    .map(|_| config.components_symbol),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -189,6 +227,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 "version" => get_module_version(context, &config, &rust_env_info).map(Ok),
                 "numver" => get_module_numeric_version(context, &config, &rust_env_info).map(Ok),
                 "toolchain" => get_toolchain_version(context, &config, &rust_env_info).map(Ok),
+                "channel" => get_channel(context, &rust_env_info).map(Ok),
+                "target" => get_target(context, &rust_env_info).map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -253,14 +293,7 @@ fn get_toolchain_version(
 ) -> Option<String> {
     type Outcome = RustupRunRustcVersionOutcome;
 
-    let settings_host_triple = rust_env_info
-        .get_rustup_settings(context)
-        .default_host_triple();
-    let default_host_triple = if settings_host_triple.is_none() {
-        guess_host_triple()
-    } else {
-        settings_host_triple
-    };
+    let default_host_triple = rust_env_info.get_default_host_triple(context);
 
     match rust_env_info.get_rustup_rustc_version(context) {
         Outcome::RustcVersion(_) | Outcome::ToolchainNotInstalled(_) => {
@@ -279,6 +312,31 @@ fn get_toolchain_version(
     }
 }
 
+/// Gets the toolchain channel without running `rustc` or `rustup`
+fn get_channel(context: &Context, rust_env_info: &RustToolingEnvironmentInfo) -> Option<String> {
+    let toolchain = rust_env_info.get_configured_toolchain(context)?;
+    Some(format_toolchain(
+        toolchain,
+        rust_env_info.get_default_host_triple(context),
+    ))
+}
+
+/// Gets the first target of the toolchain file that isn't the host
+fn get_target(context: &Context, rust_env_info: &RustToolingEnvironmentInfo) -> Option<String> {
+    let file = rust_env_info.get_toolchain_file(context)?;
+    format_target(
+        &file.targets,
+        rust_env_info.get_default_host_triple(context),
+    )
+}
+
+fn format_target(targets: &[String], default_host_triple: Option<&str>) -> Option<String> {
+    targets
+        .iter()
+        .find(|target| Some(target.as_str()) != default_host_triple)
+        .cloned()
+}
+
 fn env_rustup_toolchain(context: &Context) -> Option<String> {
     log::trace!("Searching for rustup toolchain in environment.");
     let val = context.get_env("RUSTUP_TOOLCHAIN")?;
@@ -297,7 +355,24 @@ fn execute_rustup_default(context: &Context) -> Option<String> {
         .map(str::to_owned)
 }
 
-fn find_rust_toolchain_file(context: &Context) -> Option<String> {
+/// A toolchain file with a channel
+#[derive(Debug, PartialEq)]
+struct ToolchainFile {
+    channel: String,
+    targets: Vec<String>,
+    components: Vec<String>,
+}
+
+impl ToolchainFile {
+    /// Whether `clippy` or `rustfmt` are among the pinned components
+    fn pins_tooling_components(&self) -> bool {
+        self.components
+            .iter()
+            .any(|component| component == "clippy" || component == "rustfmt")
+    }
+}
+
+fn find_rust_toolchain_file(context: &Context) -> Option<ToolchainFile> {
     log::trace!("Searching for toolchain in toolchain file");
     // Look for 'rust-toolchain' or 'rust-toolchain.toml' as rustup does.
     // for more information:
@@ -313,30 +388,43 @@ fn find_rust_toolchain_file(context: &Context) -> Option<String> {
     #[derive(Deserialize)]
     struct ToolchainSection {
         channel: Option<String>,
+        #[serde(default)]
+        targets: Vec<String>,
+        #[serde(default)]
+        components: Vec<String>,
     }
 
-    fn read_channel(path: &Path, only_toml: bool) -> Option<String> {
+    fn read_toolchain_file(path: &Path, only_toml: bool) -> Option<ToolchainFile> {
         let contents = fs::read_to_string(path).ok()?;
 
-        match contents.lines().count() {
+        let section = match contents.lines().count() {
             0 => None,
-            1 if !only_toml => Some(contents),
-            _ => {
-                toml::from_str::<OverrideFile>(&contents)
-                    .ok()?
-                    .toolchain
-                    .channel
-            }
-        }
-        .filter(|c| !c.trim().is_empty())
-        .map(|c| c.trim().to_owned())
+            1 if !only_toml => Some(ToolchainSection {
+                channel: Some(contents),
+                targets: Vec::new(),
+                components: Vec::new(),
+            }),
+            _ => toml::from_str::<OverrideFile>(&contents)
+                .ok()
+                .map(|file| file.toolchain),
+        }?;
+
+        let channel = section
+            .channel
+            .filter(|c| !c.trim().is_empty())
+            .map(|c| c.trim().to_owned())?;
+        Some(ToolchainFile {
+            channel,
+            targets: section.targets,
+            components: section.components,
+        })
     }
 
     if context
         .dir_contents()
         .map_or(false, |dir| dir.has_file("rust-toolchain"))
     {
-        if let Some(toolchain) = read_channel(Path::new("rust-toolchain"), false) {
+        if let Some(toolchain) = read_toolchain_file(Path::new("rust-toolchain"), false) {
             return Some(toolchain);
         }
     }
@@ -345,17 +433,17 @@ fn find_rust_toolchain_file(context: &Context) -> Option<String> {
         .dir_contents()
         .map_or(false, |dir| dir.has_file("rust-toolchain.toml"))
     {
-        if let Some(toolchain) = read_channel(Path::new("rust-toolchain.toml"), true) {
+        if let Some(toolchain) = read_toolchain_file(Path::new("rust-toolchain.toml"), true) {
             return Some(toolchain);
         }
     }
 
     let mut dir = &*context.current_dir;
     loop {
-        if let Some(toolchain) = read_channel(&dir.join("rust-toolchain"), false) {
+        if let Some(toolchain) = read_toolchain_file(&dir.join("rust-toolchain"), false) {
             return Some(toolchain);
         }
-        if let Some(toolchain) = read_channel(&dir.join("rust-toolchain.toml"), true) {
+        if let Some(toolchain) = read_toolchain_file(&dir.join("rust-toolchain.toml"), true) {
             return Some(toolchain);
         }
         dir = dir.parent()?;
@@ -502,9 +590,11 @@ impl RustupSettings {
 #[cfg(test)]
 mod tests {
     use crate::context::{Shell, Target};
+    use crate::test::ModuleRenderer;
     use once_cell::sync::Lazy;
     use std::io;
     use std::process::{ExitStatus, Output};
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use super::*;
 
@@ -740,7 +830,7 @@ version = "12"
         );
 
         assert_eq!(
-            find_rust_toolchain_file(&context),
+            find_rust_toolchain_file(&context).map(|file| file.channel),
             Some("1.34.0".to_owned())
         );
         dir.close()?;
@@ -761,7 +851,7 @@ version = "12"
         );
 
         assert_eq!(
-            find_rust_toolchain_file(&context),
+            find_rust_toolchain_file(&context).map(|file| file.channel),
             Some("1.34.0".to_owned())
         );
         dir.close()?;
@@ -782,7 +872,7 @@ version = "12"
         );
 
         assert_eq!(
-            find_rust_toolchain_file(&context),
+            find_rust_toolchain_file(&context).map(|file| file.channel),
             Some("1.34.0".to_owned())
         );
         dir.close()?;
@@ -805,7 +895,7 @@ version = "12"
         );
 
         assert_eq!(
-            find_rust_toolchain_file(&context),
+            find_rust_toolchain_file(&context).map(|file| file.channel),
             Some("1.34.0".to_owned())
         );
         dir.close()?;
@@ -843,7 +933,7 @@ version = "12"
         );
 
         assert_eq!(
-            find_rust_toolchain_file(&context),
+            find_rust_toolchain_file(&context).map(|file| file.channel),
             Some("1.34.0".to_owned())
         );
         dir.close()?;
@@ -864,7 +954,7 @@ version = "12"
         );
 
         assert_eq!(
-            find_rust_toolchain_file(&context),
+            find_rust_toolchain_file(&context).map(|file| file.channel),
             Some("1.34.0".to_owned())
         );
         dir.close()?;
@@ -887,12 +977,97 @@ version = "12"
         );
 
         assert_eq!(
-            find_rust_toolchain_file(&context),
+            find_rust_toolchain_file(&context).map(|file| file.channel),
             Some("1.34.0".to_owned())
         );
         dir.close()
     }
 
+    fn render_toolchain_file(contents: &str, format: &str) -> io::Result<Option<String>> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+        fs::write(dir.path().join("rust-toolchain.toml"), contents)?;
+
+        let actual = ModuleRenderer::new("rust")
+            .path(dir.path())
+            .config(toml::toml! {
+                [rust]
+                format = format
+            })
+            .collect();
+        dir.close()?;
+        Ok(actual)
+    }
+
+    #[test]
+    fn channel_from_toolchain_file() -> io::Result<()> {
+        for channel in ["1.78.0", "nightly-2024-05-01"] {
+            let contents = format!("[toolchain]\nchannel = \"{}\"\n", channel);
+            assert_eq!(
+                render_toolchain_file(&contents, "$channel")?,
+                Some(channel.to_string())
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn channel_does_not_run_rustup() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("Cargo.toml"), "")?;
+        let cmd_calls = AtomicUsize::new(0);
+
+        ModuleRenderer::new("rust")
+            .path(dir.path())
+            .config(toml::toml! {
+                [rust]
+                format = "$channel"
+            })
+            .count_cmds(&cmd_calls)
+            .collect();
+
+        assert_eq!(cmd_calls.load(Ordering::Relaxed), 0);
+        dir.close()
+    }
+
+    #[test]
+    fn target_and_components_from_toolchain_file() -> io::Result<()> {
+        let contents = r#"
+[toolchain]
+channel = "1.78.0"
+components = ["rustfmt", "clippy"]
+targets = ["wasm32-unknown-unknown", "thumbv7em-none-eabihf"]
+"#;
+        assert_eq!(
+            render_toolchain_file(contents, "$components_symbol$channel $target")?,
+            Some("🔧 1.78.0 wasm32-unknown-unknown".to_string())
+        );
+
+        let contents = "[toolchain]\nchannel = \"1.78.0\"\ncomponents = [\"rust-src\"]\n";
+        assert_eq!(
+            render_toolchain_file(contents, "$components_symbol$channel( $target)")?,
+            Some("1.78.0".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_target() {
+        let targets = vec![
+            "x86_64-unknown-linux-gnu".to_string(),
+            "aarch64-unknown-linux-gnu".to_string(),
+        ];
+        assert_eq!(
+            format_target(&targets, Some("x86_64-unknown-linux-gnu")),
+            Some("aarch64-unknown-linux-gnu".to_string())
+        );
+        assert_eq!(
+            format_target(&targets, None),
+            Some("x86_64-unknown-linux-gnu".to_string())
+        );
+        assert_eq!(format_target(&targets[..1], Some(&targets[0])), None);
+    }
+
     #[test]
     fn test_format_rustc_version_verbose() {
         macro_rules! test {
@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+
+use super::utils::glob::glob_matches;
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::secrets::SecretsConfig;
+use crate::formatter::StringFormatter;
+
+/// Creates a module warning about files in the current directory that look like
+/// secrets and aren't ignored by the enclosing git repository
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("secrets");
+    let config = SecretsConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let repo = context.get_repo().ok()?;
+    let repo_root = repo.workdir.as_ref()?;
+
+    let mut candidates: Vec<String> = context
+        .dir_contents()
+        .ok()?
+        .files()
+        .filter_map(|path| path.to_str())
+        .filter(|name| config.globs.iter().any(|glob| glob_matches(glob, name)))
+        .map(String::from)
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    candidates.sort();
+
+    // `git check-ignore` prints the ignored paths, and fails when none of them are
+    let input = candidates.join("\n") + "\n";
+    let args = [
+        OsStr::new("--git-dir"),
+        repo.path.as_os_str(),
+        OsStr::new("--work-tree"),
+        repo_root.as_os_str(),
+        OsStr::new("check-ignore"),
+        OsStr::new("--stdin"),
+    ];
+    let output = context.exec_cmd_with_input("git", &args, Some(&input));
+    let ignored: HashSet<&str> = output
+        .as_ref()
+        .map(|output| output.stdout.lines().collect())
+        .unwrap_or_default();
+
+    let exposed: Vec<&str> = candidates
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !ignored.contains(name))
+        .collect();
+    if exposed.is_empty() {
+        return None;
+    }
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "symbol" => Some(config.symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "count" => Some(Ok(exposed.len().to_string())),
+                "files" => Some(Ok(format_files(
+                    &exposed,
+                    config.files_limit,
+                    config.truncation_symbol,
+                ))),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `secrets`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Joins the first `limit` file names, ending with `truncation_symbol` if there are more
+fn format_files(files: &[&str], limit: usize, truncation_symbol: &str) -> String {
+    let mut shown = files
+        .iter()
+        .take(limit)
+        .copied()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if files.len() > limit {
+        shown.push_str(truncation_symbol);
+    }
+    shown
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use nu_ansi_term::Color;
+
+    use crate::test::ModuleRenderer;
+    use crate::utils::create_command;
+
+    fn create_repo(gitignore: &str) -> io::Result<tempfile::TempDir> {
+        let repo_dir = tempfile::tempdir()?;
+        create_command("git")?
+            .args(&["init", "--quiet"])
+            .current_dir(repo_dir.path())
+            .output()?;
+        fs::write(repo_dir.path().join(".gitignore"), gitignore)?;
+        Ok(repo_dir)
+    }
+
+    fn render(path: &Path) -> Option<String> {
+        ModuleRenderer::new("secrets")
+            .path(path)
+            .config(toml::toml! {
+                [secrets]
+                disabled = false
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ignored_env_file() -> io::Result<()> {
+        let repo_dir = create_repo(".env\n")?;
+        fs::write(repo_dir.path().join(".env"), "TOKEN=secret")?;
+        fs::write(repo_dir.path().join("README.md"), "")?;
+
+        assert_eq!(None, render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn unignored_env_file() -> io::Result<()> {
+        let repo_dir = create_repo("target\n")?;
+        fs::write(repo_dir.path().join(".env"), "TOKEN=secret")?;
+
+        let expected = Some(format!("{} ", Color::Red.bold().paint("🚨 1 (.env)")));
+        assert_eq!(expected, render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn only_unignored_files_are_listed() -> io::Result<()> {
+        let repo_dir = create_repo("*.pem\n")?;
+        for file in &[".env", "id_rsa", "server.pem"] {
+            fs::write(repo_dir.path().join(file), "")?;
+        }
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Red.bold().paint("🚨 2 (.env, id_rsa)")
+        ));
+        assert_eq!(expected, render(repo_dir.path()));
+        repo_dir.close()
+    }
+
+    #[test]
+    fn truncated_files() -> io::Result<()> {
+        let repo_dir = create_repo("")?;
+        for file in &["a.pem", "b.pem", "c.pem"] {
+            fs::write(repo_dir.path().join(file), "")?;
+        }
+
+        let actual = ModuleRenderer::new("secrets")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [secrets]
+                files_limit = 2
+                disabled = false
+            })
+            .collect();
+
+        let expected = Some(format!(
+            "{} ",
+            Color::Red.bold().paint("🚨 3 (a.pem, b.pem…)")
+        ));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn not_in_repo() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join(".env"), "TOKEN=secret")?;
+
+        assert_eq!(None, render(dir.path()));
+        dir.close()
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let repo_dir = create_repo("")?;
+        fs::write(repo_dir.path().join(".env"), "TOKEN=secret")?;
+
+        let actual = ModuleRenderer::new("secrets")
+            .path(repo_dir.path())
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+}
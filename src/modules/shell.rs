@@ -12,23 +12,37 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     }
 
     let shell = &context.shell;
+    let indicator = match shell {
+        Shell::Bash => config.bash_indicator,
+        Shell::Fish => config.fish_indicator,
+        Shell::Zsh => config.zsh_indicator,
+        Shell::PowerShell => config.powershell_indicator,
+        Shell::Ion => config.ion_indicator,
+        Shell::Elvish => config.elvish_indicator,
+        Shell::Tcsh => config.tcsh_indicator,
+        Shell::Nu => config.nu_indicator,
+        Shell::Xonsh => config.xonsh_indicator,
+        Shell::Cmd => config.cmd_indicator,
+        Shell::Unknown => config.unknown_indicator,
+    };
+
+    // How deeply the shell is nested, with the base shell at 0
+    let level = context
+        .get_env("SHLVL")
+        .and_then(|shlvl| shlvl.trim().parse::<i64>().ok())
+        .map(|shlvl| (shlvl - config.level_offset).max(0));
+    let indicator = match level {
+        Some(level) if config.repeat_symbol => indicator.repeat(level.max(1) as usize),
+        _ => indicator.to_string(),
+    };
+    let level = level
+        .filter(|_| config.show_level)
+        .map(|level| level.to_string());
 
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_meta(|variable, _| match variable {
-                "indicator" => match shell {
-                    Shell::Bash => Some(config.bash_indicator),
-                    Shell::Fish => Some(config.fish_indicator),
-                    Shell::Zsh => Some(config.zsh_indicator),
-                    Shell::PowerShell => Some(config.powershell_indicator),
-                    Shell::Ion => Some(config.ion_indicator),
-                    Shell::Elvish => Some(config.elvish_indicator),
-                    Shell::Tcsh => Some(config.tcsh_indicator),
-                    Shell::Nu => Some(config.nu_indicator),
-                    Shell::Xonsh => Some(config.xonsh_indicator),
-                    Shell::Cmd => Some(config.cmd_indicator),
-                    Shell::Unknown => Some(config.unknown_indicator),
-                },
+                "indicator" => Some(indicator.as_str()),
                 _ => None,
             })
             .map_style(|variable| match variable {
@@ -46,6 +60,7 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 "xonsh_indicator" => Some(Ok(config.xonsh_indicator)),
                 "cmd_indicator" => Some(Ok(config.cmd_indicator)),
                 "unknown_indicator" => Some(Ok(config.unknown_indicator)),
+                "level" => level.as_deref().map(Ok),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -407,4 +422,49 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    fn render_level(shlvl: &str) -> Option<String> {
+        ModuleRenderer::new("shell")
+            .shell(Shell::Bash)
+            .env("SHLVL", shlvl)
+            .config(toml::toml! {
+                [shell]
+                format = "$indicator( $level)"
+                show_level = true
+                repeat_symbol = true
+                disabled = false
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_level_of_base_shell() {
+        assert_eq!(Some("bsh 0".to_string()), render_level("1"));
+    }
+
+    #[test]
+    fn test_level_of_nested_shell() {
+        assert_eq!(Some("bshbsh 2".to_string()), render_level("3"));
+    }
+
+    #[test]
+    fn test_level_with_invalid_shlvl() {
+        assert_eq!(Some("bsh".to_string()), render_level("garbage"));
+    }
+
+    #[test]
+    fn test_level_hidden_by_default() {
+        let actual = ModuleRenderer::new("shell")
+            .shell(Shell::Bash)
+            .env("SHLVL", "3")
+            .config(toml::toml! {
+                [shell]
+                format = "$indicator( $level)"
+                level_offset = 0
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(Some("bsh".to_string()), actual);
+    }
 }
@@ -107,6 +107,7 @@ fn format_exit_code<'a>(
     };
 
     let hex_status = format!("0x{:X}", exit_code_int);
+    let mapped_symbol = config.symbol_map.get(&exit_code_int.to_string()).copied();
 
     let common_meaning = status_common_meaning(exit_code_int);
 
@@ -128,6 +129,7 @@ fn format_exit_code<'a>(
         formatter
             .map_meta(|var, _| match var {
                 "symbol" => match exit_code_int {
+                    _ if mapped_symbol.is_some() => mapped_symbol,
                     0 => Some(config.success_symbol),
                     126 if config.map_symbol => Some(config.not_executable_symbol),
                     127 if config.map_symbol => Some(config.not_found_symbol),
@@ -708,4 +710,78 @@ mod tests {
             .collect();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn symbol_map() {
+        let render = |exit_code: i64| {
+            ModuleRenderer::new("status")
+                .config(toml::toml! {
+                    [status]
+                    format = "$symbol $status"
+                    disabled = false
+                    [status.symbol_map]
+                    "1" = "❗"
+                    "130" = "🛑"
+                    "137" = "💀"
+                })
+                .status(exit_code)
+                .collect()
+        };
+
+        assert_eq!(Some("❗ 1".to_string()), render(1));
+        assert_eq!(Some("🛑 130".to_string()), render(130));
+        assert_eq!(Some("💀 137".to_string()), render(137));
+        // Codes without an entry keep the usual symbol
+        assert_eq!(Some("✖ 2".to_string()), render(2));
+        assert_eq!(Some("✖ 143".to_string()), render(143));
+    }
+
+    #[test]
+    fn symbol_map_overrides_map_symbol() {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "$symbol$signal_name"
+                map_symbol = true
+                disabled = false
+                [status.symbol_map]
+                "137" = "💀"
+            })
+            .status(137)
+            .collect();
+        assert_eq!(Some("💀KILL".to_string()), actual);
+
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                format = "$symbol$signal_name"
+                map_symbol = true
+                disabled = false
+                [status.symbol_map]
+                "137" = "💀"
+            })
+            .status(143)
+            .collect();
+        assert_eq!(Some("⚡TERM".to_string()), actual);
+    }
+
+    #[test]
+    fn symbol_map_in_pipeline() {
+        let actual = ModuleRenderer::new("status")
+            .config(toml::toml! {
+                [status]
+                pipestatus = true
+                pipestatus_separator = "|"
+                pipestatus_format = "$pipestatus => $symbol$status"
+                pipestatus_segment_format = "$symbol$common_meaning$signal_name$maybe_int"
+                disabled = false
+                [status.symbol_map]
+                "0" = "✓"
+                "130" = "🛑"
+            })
+            .status(130)
+            .pipestatus(&[0, 130, 127, 3])
+            .collect();
+        assert_eq!(Some("✓|🛑INT|✖NOTFOUND|✖3 => 🛑130".to_string()), actual);
+    }
 }
@@ -1,10 +1,13 @@
 use chrono::{DateTime, FixedOffset, Local, NaiveTime, Utc};
+use std::path::Path;
 
+use super::utils::timezone;
 use super::{Context, Module, ModuleConfig};
 use crate::configs::time::TimeConfig;
 use crate::formatter::StringFormatter;
 
-/// Outputs the current time
+/// Outputs the current time, and the time in each of the `additional_timezones`
+/// as `$time_<name>`
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     let mut module = context.new_module("time");
     let config: TimeConfig = TimeConfig::try_load(module.config);
@@ -17,8 +20,8 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
 
     // Hide prompt if current time is not inside time_range
     let (display_start, display_end) = parse_time_range(config.time_range);
-    let time_now = Local::now().time();
-    if !is_inside_time_range(time_now, display_start, display_end) {
+    let now = context.get_now();
+    if !is_inside_time_range(now.time(), display_start, display_end) {
         return None;
     }
 
@@ -31,19 +34,42 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     );
 
     let formatted_time_string = if config.utc_time_offset != "local" {
-        match create_offset_time_string(Utc::now(), config.utc_time_offset, time_format) {
+        match create_offset_time_string(
+            now.with_timezone(&Utc),
+            config.utc_time_offset,
+            time_format,
+        ) {
             Ok(formatted_string) => formatted_string,
             Err(_) => {
                 log::warn!(
                     "Invalid utc_time_offset configuration provided! Falling back to \"local\"."
                 );
-                format_time(time_format, Local::now())
+                format_time(time_format, now)
             }
         }
     } else {
-        format_time(time_format, Local::now())
+        format_time(time_format, now)
     };
 
+    let tz_dir = context
+        .get_env("TZDIR")
+        .unwrap_or_else(|| timezone::DEFAULT_TZ_DIR.to_string());
+    let zone_times: Vec<(String, Option<String>)> = config
+        .additional_timezones
+        .iter()
+        .map(|(name, zone)| {
+            let time = timezone::utc_offset(Path::new(&tz_dir), zone, now.with_timezone(&Utc))
+                .map(|offset| format_time_fixed_offset(time_format, now.with_timezone(&offset)));
+            if time.is_none() {
+                log::warn!(
+                    "Unknown time zone `{}` in `time.additional_timezones`",
+                    zone
+                );
+            }
+            (format!("time_{}", name), time)
+        })
+        .collect();
+
     let parsed = StringFormatter::new(config.format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
@@ -52,7 +78,11 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
             })
             .map(|variable| match variable {
                 "time" => Some(Ok(&formatted_time_string)),
-                _ => None,
+                _ => zone_times
+                    .iter()
+                    .find(|(name, _)| name == variable)
+                    .and_then(|(_, time)| time.as_ref())
+                    .map(Ok),
             })
             .parse(None, Some(context))
     });
@@ -505,4 +535,47 @@ mod tests {
         assert!(actual.starts_with(&col_prefix));
         assert!(actual.ends_with(&col_suffix));
     }
+
+    #[test]
+    fn config_additional_timezones() -> std::io::Result<()> {
+        let tz_dir = tempfile::tempdir()?;
+        std::fs::create_dir(tz_dir.path().join("Asia"))?;
+        std::fs::write(
+            tz_dir.path().join("Asia").join("Tokyo"),
+            timezone::tests::tzif(&[], &[32400], "JST-9"),
+        )?;
+        std::fs::create_dir(tz_dir.path().join("America"))?;
+        std::fs::write(
+            tz_dir.path().join("America").join("New_York"),
+            timezone::tests::tzif(&[], &[-18000], "EST5EDT,M3.2.0,M11.1.0"),
+        )?;
+
+        let render = |now: DateTime<Utc>| {
+            ModuleRenderer::new("time")
+                .config(toml::toml! {
+                    [time]
+                    disabled = false
+                    format = "$time_utc ($time_tokyo )($time_new_york )($time_nowhere)"
+                    time_format = "%H:%M %z"
+                    [time.additional_timezones]
+                    utc = "UTC"
+                    tokyo = "Asia/Tokyo"
+                    new_york = "America/New_York"
+                    nowhere = "Nowhere/Invalid"
+                })
+                .env("TZDIR", tz_dir.path().to_string_lossy())
+                .now(now.with_timezone(&Local))
+                .collect()
+        };
+
+        assert_eq!(
+            Some("12:00 +0000 21:00 +0900 07:00 -0500 ".to_string()),
+            render(Utc.ymd(2022, 1, 15).and_hms(12, 0, 0))
+        );
+        assert_eq!(
+            Some("23:30 +0000 08:30 +0900 19:30 -0400 ".to_string()),
+            render(Utc.ymd(2022, 7, 15).and_hms(23, 30, 0))
+        );
+        tz_dir.close()
+    }
 }
@@ -0,0 +1,247 @@
+use std::ffi::OsStr;
+
+use super::{Context, Module, ModuleConfig};
+
+use crate::configs::todos::TodosConfig;
+use crate::formatter::StringFormatter;
+use crate::utils::CommandOutput;
+
+/// Creates a module with the number of TODO and FIXME markers in the tracked files of
+/// the current git repository
+pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
+    let mut module = context.new_module("todos");
+    let config = TodosConfig::try_load(module.config);
+
+    // As we default to disabled=true, we have to check here after loading our config module,
+    // before it was only checking against whatever is in the config starship.toml
+    if config.disabled {
+        return None;
+    }
+
+    let repo = context.get_repo().ok()?;
+    let repo_root = repo.workdir.as_ref()?;
+    let git = |args: &[&OsStr]| {
+        let mut git_args = vec![
+            OsStr::new("--git-dir"),
+            repo.path.as_os_str(),
+            OsStr::new("--work-tree"),
+            repo_root.as_os_str(),
+            OsStr::new("--no-optional-locks"),
+        ];
+        git_args.extend_from_slice(args);
+        // `:/` is the root of the work tree, so the whole repository is searched
+        git_args.extend_from_slice(&[OsStr::new("--"), OsStr::new(":/")]);
+        context.exec_cmd("git", &git_args)
+    };
+
+    let tracked_files = git(&[OsStr::new("ls-files")])?.stdout.lines().count();
+    if tracked_files > config.max_files {
+        log::debug!(
+            "Not counting markers in {} tracked files, `max_files` is {}",
+            tracked_files,
+            config.max_files
+        );
+        return None;
+    }
+
+    // `git grep` fails when nothing matches, so a failure counts as no markers
+    let count_markers = |patterns: &[&str]| {
+        if patterns.is_empty() {
+            return 0;
+        }
+        let mut args = vec![OsStr::new("grep"), OsStr::new("-c"), OsStr::new("-I")];
+        args.push(OsStr::new("-E"));
+        for pattern in patterns {
+            args.extend_from_slice(&[OsStr::new("-e"), OsStr::new(pattern)]);
+        }
+        git(&args).map_or(0, |output| sum_counts(&output))
+    };
+    let todo_count = count_markers(&config.todo_patterns);
+    let fixme_count = count_markers(&config.fixme_patterns);
+    if todo_count == 0 && fixme_count == 0 {
+        return None;
+    }
+
+    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+        formatter
+            .map_meta(|variable, _| match variable {
+                "todo_symbol" if todo_count > 0 => Some(config.todo_symbol),
+                "fixme_symbol" if fixme_count > 0 => Some(config.fixme_symbol),
+                _ => None,
+            })
+            .map_style(|variable| match variable {
+                "style" => Some(Ok(config.style)),
+                _ => None,
+            })
+            .map(|variable| match variable {
+                "todo_count" if todo_count > 0 => Some(Ok(todo_count.to_string())),
+                "fixme_count" if fixme_count > 0 => Some(Ok(fixme_count.to_string())),
+                _ => None,
+            })
+            .parse(None, Some(context))
+    });
+
+    module.set_segments(match parsed {
+        Ok(segments) => segments,
+        Err(error) => {
+            log::warn!("Error in module `todos`:\n{}", error);
+            return None;
+        }
+    });
+
+    Some(module)
+}
+
+/// Adds up the `<path>:<count>` lines printed by `git grep -c`
+fn sum_counts(output: &CommandOutput) -> usize {
+    output
+        .stdout
+        .lines()
+        .filter_map(|line| line.rsplit_once(':'))
+        .filter_map(|(_, count)| count.trim().parse::<usize>().ok())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+
+    use nu_ansi_term::Color;
+
+    use crate::test::ModuleRenderer;
+    use crate::utils::create_command;
+
+    fn git(args: &[&str], dir: &Path) -> io::Result<()> {
+        create_command("git")?
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .map(|_| ())
+    }
+
+    /// A repository with two TODOs and a FIXME in tracked files, and a TODO in an
+    /// untracked file
+    fn create_repo() -> io::Result<tempfile::TempDir> {
+        let repo_dir = tempfile::tempdir()?;
+        let path = repo_dir.path();
+        git(&["init", "--quiet"], path)?;
+
+        fs::create_dir(path.join("src"))?;
+        fs::write(
+            path.join("main.rs"),
+            "// TODO: handle errors\nfn main() {} // FIXME\n",
+        )?;
+        fs::write(path.join("src").join("lib.rs"), "// TODO: document\n")?;
+        git(&["add", "main.rs", "src/lib.rs"], path)?;
+        fs::write(path.join("notes.txt"), "TODO: untracked\n")?;
+        Ok(repo_dir)
+    }
+
+    #[test]
+    fn not_in_repo() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::write(dir.path().join("main.rs"), "// TODO\n")?;
+
+        let actual = ModuleRenderer::new("todos")
+            .path(dir.path())
+            .config(toml::toml! {
+                [todos]
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        dir.close()
+    }
+
+    #[test]
+    fn disabled_by_default() -> io::Result<()> {
+        let repo_dir = create_repo()?;
+
+        let actual = ModuleRenderer::new("todos").path(repo_dir.path()).collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn counts_markers_in_tracked_files() -> io::Result<()> {
+        let repo_dir = create_repo()?;
+
+        // The whole repository is searched, not just the current directory
+        let actual = ModuleRenderer::new("todos")
+            .path(repo_dir.path().join("src"))
+            .config(toml::toml! {
+                [todos]
+                disabled = false
+            })
+            .collect();
+
+        let expected = Some(format!("{}", Color::Purple.bold().paint("📝 2 🔥 1 ")));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn custom_patterns() -> io::Result<()> {
+        let repo_dir = create_repo()?;
+
+        let actual = ModuleRenderer::new("todos")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [todos]
+                todo_patterns = ["document"]
+                fixme_patterns = ["XXX", "HACK"]
+                disabled = false
+            })
+            .collect();
+
+        let expected = Some(format!("{}", Color::Purple.bold().paint("📝 1 ")));
+        assert_eq!(expected, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn too_many_files() -> io::Result<()> {
+        let repo_dir = create_repo()?;
+
+        let actual = ModuleRenderer::new("todos")
+            .path(repo_dir.path())
+            .config(toml::toml! {
+                [todos]
+                max_files = 1
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+
+    #[test]
+    fn timeout() -> io::Result<()> {
+        let repo_dir = create_repo()?;
+        let path = repo_dir.path();
+        // A command that times out has no output
+        let grep = format!(
+            "git --git-dir {} --work-tree {} --no-optional-locks grep -c -I -E -e TODO -- :/",
+            path.join(".git").display(),
+            path.display()
+        );
+
+        let actual = ModuleRenderer::new("todos")
+            .path(path)
+            .cmd(&grep, None)
+            .config(toml::toml! {
+                [todos]
+                fixme_patterns = []
+                disabled = false
+            })
+            .collect();
+
+        assert_eq!(None, actual);
+        repo_dir.close()
+    }
+}
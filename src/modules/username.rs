@@ -1,3 +1,6 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::utils::ssh;
 use super::{Context, Module, ModuleConfig};
 
 use crate::configs::username::UsernameConfig;
@@ -12,29 +15,47 @@ const USERNAME_ENV_VAR: &str = "USERNAME";
 /// Creates a module with the current user's username
 ///
 /// Will display the username if any of the following criteria are met:
-///     - The current user is root (UID = 0) [1]
+///     - The current user is root, as detected by `root_detection` [1]
 ///     - The current user isn't the same as the one that is logged in (`$LOGNAME` != `$USER`) [2]
 ///     - The user is currently connected as an SSH session (`$SSH_CONNECTION`) [3]
+///     - One of `detect_env_vars` is set, and none of the negated ones [4]
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let mut username = context.get_env(USERNAME_ENV_VAR)?;
+    let username = context.get_env(USERNAME_ENV_VAR)?;
 
     let mut module = context.new_module("username");
     let config: UsernameConfig = UsernameConfig::try_load(module.config);
 
-    let is_root = is_root_user();
-    if cfg!(target_os = "windows") && is_root {
-        username = "Administrator".to_string();
-    }
+    let is_root = is_root_user(context, &config);
+    let (mut username, elevation) = if is_windows_elevated(context) {
+        show_elevated_as(username, &config)
+    } else {
+        (username, None)
+    };
     let show_username = config.show_always
         || is_root // [1]
         || !is_login_user(context, &username) // [2]
-        || is_ssh_session(context); // [3]
+        || is_ssh_session(context, &config) // [3]
+        || context.detect_env_vars(&config.detect_env_vars); // [4]
 
     if !show_username {
         return None;
     }
 
-    let parsed = StringFormatter::new(config.format).and_then(|formatter| {
+    // Aliases are applied after the checks above, which need the real login
+    if let Some(alias) = config.aliases.get(&username) {
+        username = alias.to_string();
+    }
+    let username = truncate_username(&username, &config);
+    let sudo_user = get_sudo_user(context);
+    let ssh_connection = ssh::get_ssh_connection(context);
+
+    let format = if is_root {
+        config.format_root.unwrap_or(config.format)
+    } else {
+        config.format
+    };
+
+    let parsed = StringFormatter::new(format).and_then(|formatter| {
         formatter
             .map_style(|variable| match variable {
                 "style" => {
@@ -48,7 +69,13 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
                 _ => None,
             })
             .map(|variable| match variable {
-                "user" => Some(Ok(&username)),
+                "user" => Some(Ok(username.as_str())),
+                "sudo_user" => sudo_user.as_deref().map(Ok),
+                "elevation" => elevation.map(Ok),
+                "ssh_client_ip" => ssh_connection
+                    .as_ref()
+                    .map(|ssh| Ok(ssh.client_ip.as_str())),
+                "ssh_port" => ssh_connection.as_ref().map(|ssh| Ok(ssh.port.as_str())),
                 _ => None,
             })
             .parse(None, Some(context))
@@ -64,14 +91,79 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
+/// Shortens the username to `truncation_length` graphemes
+///
+/// The truncation symbol is only added if the username was actually truncated.
+pub fn truncate_username(username: &str, config: &UsernameConfig) -> String {
+    let len = if config.truncation_length <= 0 {
+        log::warn!(
+            "\"truncation_length\" should be a positive value, found {}",
+            config.truncation_length
+        );
+        std::usize::MAX
+    } else {
+        config.truncation_length as usize
+    };
+
+    let graphemes: Vec<&str> = username.graphemes(true).collect();
+    if graphemes.len() <= len {
+        return username.to_string();
+    }
+
+    let truncation_symbol = config
+        .truncation_symbol
+        .graphemes(true)
+        .next()
+        .unwrap_or("");
+    graphemes[..len].concat() + truncation_symbol
+}
+
+/// The name and elevation marker shown for an elevated Windows user
+///
+/// By default the username is replaced with `Administrator`. With `username`
+/// the account name is kept, and with `both` it's kept and `$elevation` is set.
+pub fn show_elevated_as<'a>(
+    username: String,
+    config: &UsernameConfig<'a>,
+) -> (String, Option<&'a str>) {
+    match config.show_elevated_as {
+        "username" => (username, None),
+        "both" => (username, Some(config.elevation_symbol)),
+        "administrator" => ("Administrator".to_string(), None),
+        unknown => {
+            log::warn!("Unknown show_elevated_as `{}`", unknown);
+            ("Administrator".to_string(), None)
+        }
+    }
+}
+
+/// The user that started the `sudo` or `doas` session
+pub fn get_sudo_user(context: &Context) -> Option<String> {
+    context
+        .get_env("SUDO_USER")
+        .or_else(|| context.get_env("DOAS_USER"))
+}
+
 fn is_login_user(context: &Context, username: &str) -> bool {
     context
         .get_env("LOGNAME")
         .map_or(true, |logname| logname == username)
 }
 
+/// Whether the user should be treated as root
+///
+/// Each entry of `root_detection` is either `euid`, which checks whether the
+/// process runs as root (or elevated on Windows), or the name of an environment
+/// variable, such as `SUDO_USER`, whose presence marks the user as root.
+pub fn is_root_user(context: &Context, config: &UsernameConfig) -> bool {
+    config.root_detection.iter().any(|&method| match method {
+        "euid" => is_elevated(context),
+        env_var => context.get_env_os(env_var).is_some(),
+    })
+}
+
 #[cfg(all(target_os = "windows", not(test)))]
-fn is_root_user() -> bool {
+fn is_elevated(_context: &Context) -> bool {
     use deelevate::{PrivilegeLevel, Token};
     let token = match Token::with_current_process() {
         Ok(token) => token,
@@ -92,27 +184,43 @@ fn is_root_user() -> bool {
     )
 }
 
-#[cfg(all(target_os = "windows", test))]
-fn is_root_user() -> bool {
+#[cfg(all(not(target_os = "windows"), not(test)))]
+fn is_elevated(_context: &Context) -> bool {
+    nix::unistd::geteuid() == nix::unistd::ROOT
+}
+
+#[cfg(test)]
+fn is_elevated(context: &Context) -> bool {
+    context.root_user
+}
+
+/// Whether the process runs elevated on Windows, which is what
+/// `show_elevated_as` applies to
+#[cfg(all(target_os = "windows", not(test)))]
+pub fn is_windows_elevated(context: &Context) -> bool {
+    is_elevated(context)
+}
+
+#[cfg(all(not(target_os = "windows"), not(test)))]
+pub fn is_windows_elevated(_context: &Context) -> bool {
     false
 }
 
-#[cfg(not(target_os = "windows"))]
-fn is_root_user() -> bool {
-    nix::unistd::geteuid() == nix::unistd::ROOT
+#[cfg(test)]
+pub fn is_windows_elevated(context: &Context) -> bool {
+    context.elevated
 }
 
-fn is_ssh_session(context: &Context) -> bool {
+pub fn is_ssh_session(context: &Context, config: &UsernameConfig) -> bool {
     let ssh_env = ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"];
-    ssh_env.iter().any(|env| context.get_env_os(env).is_some())
+    ssh::is_ssh_session(context, config.ssh_detection, &ssh_env)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::modules::utils::ssh::MockProcessTreeProvider;
     use crate::test::ModuleRenderer;
-
-    // TODO: Add tests for if root user (UID == 0)
-    // Requires mocking
+    use nu_ansi_term::Color;
 
     #[test]
     fn no_env_variables() {
@@ -123,7 +231,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
     fn no_logname_env_variable() {
         let actual = ModuleRenderer::new("username")
             .env(super::USERNAME_ENV_VAR, "astronaut")
@@ -134,7 +241,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore]
     fn logname_equals_user() {
         let actual = ModuleRenderer::new("username")
             .env("LOGNAME", "astronaut")
@@ -241,4 +347,396 @@ mod tests {
 
         assert_eq!(expected, actual.as_deref());
     }
+
+    fn render_root(env: &[(&str, &str)], root_user: bool) -> Option<String> {
+        let mut renderer = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env("LOGNAME", "astronaut")
+            .root_user(root_user)
+            .config(toml::toml! {
+                [username]
+                format = "[$user]($style)"
+                root_detection = ["euid", "SUDO_USER", "DOAS_USER"]
+            });
+        for (key, value) in env {
+            renderer = renderer.env(*key, *value);
+        }
+        renderer.collect()
+    }
+
+    #[test]
+    fn root_user() {
+        let expected = Some(format!("{}", Color::Red.bold().paint("astronaut")));
+
+        assert_eq!(expected, render_root(&[], true));
+        assert_eq!(None, render_root(&[], false));
+    }
+
+    #[test]
+    fn format_root() {
+        let render = |root_user: bool| {
+            ModuleRenderer::new("username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .root_user(root_user)
+                .config(toml::toml! {
+                    [username]
+                    show_always = true
+                    format = "[$user]($style) in "
+                    format_root = "[☠ $user]($style)"
+                })
+                .collect()
+        };
+
+        let root = Some(format!("{}", Color::Red.bold().paint("☠ astronaut")));
+        let user = Some(format!("{} in ", Color::Yellow.bold().paint("astronaut")));
+        assert_eq!(root, render(true));
+        assert_eq!(user, render(false));
+    }
+
+    #[test]
+    fn format_root_defaults_to_format() {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .root_user(true)
+            .config(toml::toml! {
+                [username]
+                format = "root: $user"
+            })
+            .collect();
+
+        assert_eq!(Some("root: astronaut"), actual.as_deref());
+    }
+
+    #[test]
+    fn sudo_user_is_root() {
+        let expected = Some(format!("{}", Color::Red.bold().paint("astronaut")));
+
+        assert_eq!(expected, render_root(&[("SUDO_USER", "astronaut")], false));
+        assert_eq!(expected, render_root(&[("DOAS_USER", "astronaut")], false));
+    }
+
+    #[test]
+    fn sudo_user_without_root_detection() {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env("SUDO_USER", "astronaut")
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                format = "[$user]($style)"
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Yellow.bold().paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn euid_not_in_root_detection() {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .root_user(true)
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                format = "[$user]($style)"
+                root_detection = ["SUDO_USER"]
+            })
+            .collect();
+        let expected = Some(format!("{}", Color::Yellow.bold().paint("astronaut")));
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn ssh_session_from_process_tree() {
+        let mut mock = MockProcessTreeProvider::new();
+        mock.expect_ancestor_names()
+            .returning(|| vec!["bash".to_string(), "sshd".to_string()]);
+        let render = |ssh_detection: &str| {
+            ModuleRenderer::new("username")
+                .env(super::USERNAME_ENV_VAR, "astronaut")
+                .env("LOGNAME", "astronaut")
+                .process_tree_provider(&mock)
+                .config(toml::toml! {
+                    [username]
+                    format = "$user"
+                    ssh_detection = ssh_detection
+                })
+                .collect()
+        };
+
+        assert_eq!(Some("astronaut"), render("process-tree").as_deref());
+        assert_eq!(Some("astronaut"), render("both").as_deref());
+        assert_eq!(None, render("env"));
+    }
+
+    fn render_ssh(key: &str, value: &str) -> Option<String> {
+        ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env(key, value)
+            .config(toml::toml! {
+                [username]
+                format = "$user( from $ssh_client_ip)( port $ssh_port)"
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ssh_client_ip_from_ssh_connection() {
+        let actual = render_ssh("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22");
+
+        assert_eq!(
+            Some("astronaut from 192.168.223.17 port 22"),
+            actual.as_deref()
+        );
+    }
+
+    #[test]
+    fn ssh_client_ip_from_ssh_client() {
+        let actual = render_ssh("SSH_CLIENT", "192.168.223.17 36673 2222");
+
+        assert_eq!(
+            Some("astronaut from 192.168.223.17 port 2222"),
+            actual.as_deref()
+        );
+    }
+
+    #[test]
+    fn ssh_client_ip_malformed() {
+        let actual = render_ssh("SSH_CONNECTION", "something");
+
+        assert_eq!(Some("astronaut"), actual.as_deref());
+    }
+
+    fn render_detect_env_vars(env: &[(&str, &str)], show_always: bool) -> Option<String> {
+        let mut renderer = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .env("LOGNAME", "astronaut")
+            .config(toml::toml! {
+                [username]
+                format = "$user"
+                show_always = show_always
+                detect_env_vars = ["TOOLBOX_PATH", "container", "!NO_USERNAME"]
+            });
+        for (key, value) in env {
+            renderer = renderer.env(*key, *value);
+        }
+        renderer.collect()
+    }
+
+    #[test]
+    fn detect_env_vars_present() {
+        let actual = render_detect_env_vars(&[("container", "podman")], false);
+
+        assert_eq!(Some("astronaut"), actual.as_deref());
+    }
+
+    #[test]
+    fn detect_env_vars_negated_present() {
+        let actual =
+            render_detect_env_vars(&[("container", "podman"), ("NO_USERNAME", "1")], false);
+
+        assert_eq!(None, actual);
+    }
+
+    #[test]
+    fn detect_env_vars_absent() {
+        assert_eq!(None, render_detect_env_vars(&[], false));
+        assert_eq!(
+            Some("astronaut"),
+            render_detect_env_vars(&[], true).as_deref()
+        );
+        assert_eq!(
+            Some("astronaut"),
+            render_detect_env_vars(&[("NO_USERNAME", "1")], true).as_deref()
+        );
+    }
+
+    fn render_truncated(username: &str, truncation: toml::Value) -> Option<String> {
+        let mut config = toml::toml! {
+            [username]
+            show_always = true
+            format = "$user"
+        };
+        config["username"]
+            .as_table_mut()
+            .unwrap()
+            .extend(truncation.as_table().unwrap().clone());
+
+        ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, username)
+            .config(config)
+            .collect()
+    }
+
+    #[test]
+    fn truncated_username() {
+        let actual = render_truncated(
+            "firstname.lastname.contractor1",
+            toml::toml! { truncation_length = 10 },
+        );
+
+        assert_eq!(Some("firstname.…"), actual.as_deref());
+    }
+
+    #[test]
+    fn truncated_username_with_symbol() {
+        let actual = render_truncated(
+            "firstname.lastname",
+            toml::toml! {
+                truncation_length = 5
+                truncation_symbol = "~"
+            },
+        );
+
+        assert_eq!(Some("first~"), actual.as_deref());
+    }
+
+    #[test]
+    fn short_username_is_not_truncated() {
+        let actual = render_truncated("astronaut", toml::toml! { truncation_length = 9 });
+
+        assert_eq!(Some("astronaut"), actual.as_deref());
+    }
+
+    #[test]
+    fn truncation_counts_graphemes() {
+        let actual = render_truncated("y̆y̆y̆y̆", toml::toml! { truncation_length = 2 });
+
+        assert_eq!(Some("y̆y̆…"), actual.as_deref());
+    }
+
+    #[test]
+    fn alias_applied() {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "jsmith2847")
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                format = "$user"
+                aliases = { "jsmith2847" = "john" }
+            })
+            .collect();
+
+        assert_eq!(Some("john"), actual.as_deref());
+    }
+
+    #[test]
+    fn alias_missing() {
+        let actual = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                format = "$user"
+                aliases = { "jsmith2847" = "john" }
+            })
+            .collect();
+
+        assert_eq!(Some("astronaut"), actual.as_deref());
+    }
+
+    #[test]
+    fn alias_is_truncated() {
+        let actual = render_truncated(
+            "jsmith2847",
+            toml::toml! {
+                truncation_length = 4
+                aliases = { "jsmith2847" = "johnny" }
+            },
+        );
+
+        assert_eq!(Some("john…"), actual.as_deref());
+    }
+
+    fn render_elevated(show_elevated_as: &str, elevated: bool) -> Option<String> {
+        ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "astronaut")
+            .root_user(elevated)
+            .elevated(elevated)
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                format = "$user( $elevation)"
+                show_elevated_as = show_elevated_as
+            })
+            .collect()
+    }
+
+    #[test]
+    fn show_elevated_as_administrator() {
+        assert_eq!(
+            Some("Administrator"),
+            render_elevated("administrator", true).as_deref()
+        );
+        assert_eq!(
+            Some("Administrator"),
+            render_elevated("unknown", true).as_deref()
+        );
+    }
+
+    #[test]
+    fn show_elevated_as_username() {
+        assert_eq!(
+            Some("astronaut"),
+            render_elevated("username", true).as_deref()
+        );
+    }
+
+    #[test]
+    fn show_elevated_as_both() {
+        assert_eq!(
+            Some("astronaut ⚡"),
+            render_elevated("both", true).as_deref()
+        );
+    }
+
+    #[test]
+    fn elevation_only_for_elevated_users() {
+        for show_elevated_as in ["administrator", "username", "both"] {
+            assert_eq!(
+                Some("astronaut"),
+                render_elevated(show_elevated_as, false).as_deref()
+            );
+        }
+    }
+
+    fn render_sudo_user(env: &[(&str, &str)]) -> Option<String> {
+        let mut renderer = ModuleRenderer::new("username")
+            .env(super::USERNAME_ENV_VAR, "root")
+            .config(toml::toml! {
+                [username]
+                show_always = true
+                format = "$user( as $sudo_user)"
+            });
+        for (key, value) in env {
+            renderer = renderer.env(*key, *value);
+        }
+        renderer.collect()
+    }
+
+    #[test]
+    fn sudo_user_present() {
+        assert_eq!(
+            Some("root as astronaut"),
+            render_sudo_user(&[("SUDO_USER", "astronaut")]).as_deref()
+        );
+        assert_eq!(
+            Some("root as astronaut"),
+            render_sudo_user(&[("DOAS_USER", "astronaut")]).as_deref()
+        );
+    }
+
+    #[test]
+    fn sudo_user_absent() {
+        assert_eq!(Some("root"), render_sudo_user(&[]).as_deref());
+    }
+
+    #[test]
+    fn invalid_truncation_length() {
+        let actual = render_truncated("astronaut", toml::toml! { truncation_length = 0 });
+
+        assert_eq!(Some("astronaut"), actual.as_deref());
+    }
 }
@@ -23,6 +23,63 @@ pub fn truncate(dir_string: &str, length: usize) -> Option<String> {
     Some(truncated_components.join("/"))
 }
 
+/// Truncate a path to only show its first `keep_first` and last `keep_last` components
+///
+/// The components in between are replaced with `symbol`, unless there are no
+/// more than `keep_first + keep_last` components. The path is returned split
+/// into the parts before, at and after the component at `root`, an index into
+/// the `/`-separated parts of `dir_string`. The root is shown even if it falls
+/// between the kept components, with `symbol` on both sides if needed. Without a
+/// `root`, the whole path is the last part.
+pub fn truncate_middle(
+    dir_string: &str,
+    keep_first: usize,
+    keep_last: usize,
+    symbol: &str,
+    root: Option<usize>,
+) -> [String; 3] {
+    let components = dir_string.split('/').collect::<Vec<&str>>();
+
+    // A leading "" is the "/" of an absolute path, which doesn't count as a component
+    let first_end = keep_first + usize::from(components[0].is_empty());
+    let last_start = components.len().saturating_sub(keep_last);
+
+    // The indices of the shown components, with `None` where components were left out
+    let shown: Vec<Option<usize>> = if last_start <= first_end {
+        (0..components.len()).map(Some).collect()
+    } else {
+        let mut shown: Vec<_> = (0..first_end).map(Some).collect();
+        shown.push(None);
+        if let Some(root) = root.filter(|root| (first_end..last_start).contains(root)) {
+            shown.push(Some(root));
+            if root + 1 < last_start {
+                shown.push(None);
+            }
+        }
+        shown.extend((last_start..components.len()).map(Some));
+        shown
+    };
+
+    let mut parts = [String::new(), String::new(), String::new()];
+    let mut part = if root.is_some() { 0 } else { 2 };
+    for (position, index) in shown.iter().enumerate() {
+        let (text, separator) = match index {
+            Some(index) if position + 1 < shown.len() => (components[*index], "/"),
+            Some(index) => (components[*index], ""),
+            None => (symbol, ""),
+        };
+        if *index == root && root.is_some() {
+            parts[1].push_str(text);
+            parts[2].push_str(separator);
+            part = 2;
+        } else {
+            parts[part].push_str(text);
+            parts[part].push_str(separator);
+        }
+    }
+    parts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,4 +125,60 @@ mod tests {
         let output = truncate(path, 3);
         assert_eq!(output.as_deref(), Some("engines/booster/rocket"));
     }
+
+    fn middle(path: &str, keep_first: usize, keep_last: usize, root: Option<usize>) -> [String; 3] {
+        truncate_middle(path, keep_first, keep_last, "…/", root)
+    }
+
+    #[test]
+    fn truncate_middle_shorter_and_same_path_as_kept_components() {
+        assert_eq!(middle("~/work/api", 2, 3, None), ["", "", "~/work/api"]);
+        assert_eq!(
+            middle("~/work/service/api/handlers", 2, 3, None),
+            ["", "", "~/work/service/api/handlers"]
+        );
+        assert_eq!(middle("/srv/www", 1, 1, None), ["", "", "/srv/www"]);
+    }
+
+    #[test]
+    fn truncate_middle_larger_path_than_kept_components() {
+        assert_eq!(
+            middle("~/work/mono/service/api/handlers", 2, 3, None),
+            ["", "", "~/work/…/service/api/handlers"]
+        );
+        assert_eq!(
+            middle("/srv/www/site/public", 1, 1, None),
+            ["", "", "/srv/…/public"]
+        );
+        assert_eq!(
+            middle("/srv/www/site/public", 0, 1, None),
+            ["", "", "/…/public"]
+        );
+    }
+
+    #[test]
+    fn truncate_middle_with_root_among_kept_components() {
+        assert_eq!(
+            middle("~/mono/service/api/src/handlers", 2, 2, Some(1)),
+            ["~/", "mono", "/…/src/handlers"]
+        );
+        assert_eq!(
+            middle("~/work/mono/service/api/src", 1, 2, Some(4)),
+            ["~/…/", "api", "/src"]
+        );
+        assert_eq!(middle("~/work/api", 2, 3, Some(2)), ["~/work/", "api", ""]);
+    }
+
+    #[test]
+    fn truncate_middle_with_root_among_left_out_components() {
+        assert_eq!(
+            middle("~/work/mono/service/api/handlers", 1, 1, Some(2)),
+            ["~/…/", "mono", "/…/handlers"]
+        );
+        assert_eq!(
+            middle("~/work/mono/service/api/handlers", 1, 2, Some(3)),
+            ["~/…/", "service", "/api/handlers"]
+        );
+        assert_eq!(middle("/a/b/c/d/e", 1, 1, Some(2)), ["/a/…/", "b", "/…/e"]);
+    }
 }
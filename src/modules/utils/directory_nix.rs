@@ -1,5 +1,6 @@
+use nix::errno::Errno;
 use nix::sys::stat::Mode;
-use nix::unistd::{Gid, Uid};
+use nix::unistd::{AccessFlags, Gid, Uid};
 use std::fs;
 use std::os::unix::fs::MetadataExt;
 use std::os::unix::fs::PermissionsExt;
@@ -33,6 +34,17 @@ pub fn is_write_allowed(folder_path: &Path) -> Result<bool, String> {
     }
 }
 
+/// Checks if the current user can write to the `folder_path` with `access(2)`.
+///
+/// Unlike `is_write_allowed` this takes ACLs and read-only file systems into account.
+pub fn is_access_allowed(folder_path: &Path) -> Result<bool, String> {
+    match nix::unistd::access(folder_path, AccessFlags::W_OK) {
+        Ok(()) => Ok(true),
+        Err(Errno::EACCES | Errno::EPERM | Errno::EROFS) => Ok(false),
+        Err(e) => Err(format!("Unable to access() directory: {e:?}")),
+    }
+}
+
 #[cfg(all(unix, not(any(target_os = "macos", target_os = "ios"))))]
 fn get_supplementary_groups() -> Vec<u32> {
     match nix::unistd::getgroups() {
@@ -60,4 +72,37 @@ mod tests {
             Err(e) => e.starts_with("Unable to stat() directory"),
         });
     }
+
+    #[test]
+    fn access_of_writable_dir() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert_eq!(is_access_allowed(dir.path()), Ok(true));
+        assert!(is_access_allowed(&dir.path().join("i_dont_exist")).is_err());
+        dir.close()
+    }
+
+    #[test]
+    fn access_of_read_only_dir() -> std::io::Result<()> {
+        // root can write to any directory on a writable file system
+        if Uid::effective().is_root() {
+            return Ok(());
+        }
+        let dir = tempfile::tempdir()?;
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o555))?;
+
+        assert_eq!(is_access_allowed(dir.path()), Ok(false));
+        assert_eq!(is_write_allowed(dir.path()), Ok(false));
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755))?;
+        dir.close()
+    }
+
+    #[test]
+    fn access_of_dir_owned_by_another_user() {
+        let root = Path::new("/");
+        let owner = fs::metadata(root).map(|meta| meta.uid()).ok();
+        if Uid::effective().is_root() || owner != Some(0) || is_write_allowed(root) != Ok(false) {
+            return;
+        }
+        assert_eq!(is_access_allowed(root), Ok(false));
+    }
 }
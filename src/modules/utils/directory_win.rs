@@ -129,3 +129,10 @@ pub fn is_write_allowed(folder_path: &Path) -> std::result::Result<bool, String>
 
     Ok(result != 0)
 }
+
+/// Checks if the current user has write access right to the `folder_path`
+///
+/// The `AccessCheck` done by `is_write_allowed` already uses the effective permissions.
+pub fn is_access_allowed(folder_path: &Path) -> std::result::Result<bool, String> {
+    is_write_allowed(folder_path)
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+use mockall::automock;
+use std::path::Path;
+
+/// The size of a filesystem and the space left on it, in bytes
+#[derive(Clone, Copy, Debug)]
+pub struct DiskSpace {
+    pub total: u64,
+    /// The space available to unprivileged users
+    pub available: u64,
+}
+
+#[cfg_attr(test, automock)]
+pub trait FilesystemStatsProvider {
+    /// The type of the filesystem containing `path`, like `ext4` or `nfs`
+    ///
+    /// This doesn't touch the filesystem itself, so it can be checked before
+    /// asking a network filesystem for its size.
+    fn filesystem_type(&self, path: &Path) -> Option<String>;
+
+    /// The size and free space of the filesystem containing `path`
+    fn disk_space(&self, path: &Path) -> Option<DiskSpace>;
+}
+
+pub struct FilesystemStatsProviderImpl;
+
+impl FilesystemStatsProvider for FilesystemStatsProviderImpl {
+    #[cfg(target_os = "linux")]
+    fn filesystem_type(&self, path: &Path) -> Option<String> {
+        let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+        mount_containing(&mounts, path)
+    }
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd"
+    ))]
+    fn filesystem_type(&self, path: &Path) -> Option<String> {
+        let stats = nix::sys::statfs::statfs(path).ok()?;
+        Some(stats.filesystem_type_name().to_string())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn filesystem_type(&self, path: &Path) -> Option<String> {
+        use std::os::windows::ffi::OsStrExt;
+        use windows::{core::PCWSTR, Win32::UI::Shell::PathIsNetworkPathW};
+
+        let wpath: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+        let is_network = unsafe { PathIsNetworkPathW(PCWSTR(wpath.as_ptr())) }.as_bool();
+        Some(if is_network { "remote" } else { "local" }.to_string())
+    }
+
+    #[cfg(not(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "windows"
+    )))]
+    fn filesystem_type(&self, _path: &Path) -> Option<String> {
+        None
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn disk_space(&self, path: &Path) -> Option<DiskSpace> {
+        let stats = nix::sys::statvfs::statvfs(path)
+            .map_err(|error| log::debug!("Unable to stat the filesystem of {:?}: {}", path, error))
+            .ok()?;
+        let fragment_size = stats.fragment_size() as u64;
+        Some(DiskSpace {
+            total: stats.blocks() as u64 * fragment_size,
+            available: stats.blocks_available() as u64 * fragment_size,
+        })
+    }
+
+    #[cfg(target_os = "windows")]
+    fn disk_space(&self, path: &Path) -> Option<DiskSpace> {
+        use std::os::windows::ffi::OsStrExt;
+        use windows::{core::PCWSTR, Win32::Storage::FileSystem::GetDiskFreeSpaceExW};
+
+        let wpath: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+        let (mut available, mut total, mut free) = (0, 0, 0);
+        let result = unsafe {
+            GetDiskFreeSpaceExW(
+                PCWSTR(wpath.as_ptr()),
+                &mut available,
+                &mut total,
+                &mut free,
+            )
+        };
+        result.as_bool().then(|| DiskSpace { total, available })
+    }
+}
+
+/// The type of the mount in `/proc/mounts` that `path` is on
+#[cfg(any(target_os = "linux", test))]
+fn mount_containing(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = unescape_mount_field(fields.nth(1)?);
+            let fs_type = fields.next()?;
+            Some((mount_point, fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        // Later mounts hide earlier ones at the same mount point
+        .max_by_key(|(mount_point, _)| Path::new(mount_point).components().count())
+        .map(|(_, fs_type)| fs_type.to_string())
+}
+
+/// Undoes the octal escapes of spaces, tabs and backslashes in `/proc/mounts`
+#[cfg(any(target_os = "linux", test))]
+fn unescape_mount_field(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(index) = rest.find('\\') {
+        unescaped.push_str(&rest[..index]);
+        let code = rest.get(index + 1..index + 4);
+        match code.and_then(|code| u8::from_str_radix(code, 8).ok()) {
+            Some(byte) => {
+                unescaped.push(char::from(byte));
+                rest = &rest[index + 4..];
+            }
+            None => {
+                unescaped.push('\\');
+                rest = &rest[index + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+proc /proc proc rw,nosuid,nodev,noexec,relatime 0 0
+/dev/nvme0n1p3 /home ext4 rw,relatime 0 0
+nas:/export /home/user/My\\040Files nfs4 rw,relatime 0 0
+";
+
+    #[test]
+    fn test_mount_containing() {
+        let fs_type = |path: &str| mount_containing(MOUNTS, Path::new(path));
+
+        assert_eq!(fs_type("/usr/bin"), Some("ext4".to_string()));
+        assert_eq!(fs_type("/home/user"), Some("ext4".to_string()));
+        assert_eq!(fs_type("/home/user/My Files/a"), Some("nfs4".to_string()));
+        assert_eq!(fs_type("/home/user/My"), Some("ext4".to_string()));
+        assert_eq!(mount_containing("", Path::new("/")), None);
+    }
+}
@@ -0,0 +1,47 @@
+/// Whether `text` matches the glob `pattern`, where `*` matches any number of
+/// characters and `?` exactly one
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The last `*` and the position in the text it was tried at
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    backtrack = Some((star, star_t + 1));
+                    p = star + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("prod-*", "prod-"));
+        assert!(glob_matches("*-production", "app-production"));
+        assert!(glob_matches("*a*b?", "xxaybbz"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("prod-*", "preprod-1"));
+        assert!(!glob_matches("db-?", "db-"));
+        assert!(!glob_matches("a*c", "abcd"));
+    }
+}
@@ -6,4 +6,14 @@ pub mod directory_win;
 #[cfg(not(target_os = "windows"))]
 pub mod directory_nix;
 
+pub mod filesystem;
+
+pub mod glob;
+
+pub mod network;
+
 pub mod path;
+
+pub mod ssh;
+
+pub mod timezone;
@@ -0,0 +1,146 @@
+#[cfg(test)]
+use mockall::automock;
+use std::net::Ipv4Addr;
+
+/// A network interface of the machine
+#[derive(Clone, Debug)]
+pub struct NetworkInterface {
+    pub name: String,
+    /// Whether the interface is up and running
+    pub is_up: bool,
+    /// The first IPv4 address of the interface
+    pub ipv4: Option<Ipv4Addr>,
+}
+
+#[cfg_attr(test, automock)]
+pub trait NetworkInterfaceProvider {
+    /// The network interfaces of the machine
+    fn interfaces(&self) -> Vec<NetworkInterface>;
+}
+
+pub struct NetworkInterfaceProviderImpl;
+
+impl NetworkInterfaceProvider for NetworkInterfaceProviderImpl {
+    // `/proc/net/route` only lists the main routing table, while WireGuard and
+    // Tailscale route through tables of their own, so the state of the
+    // interfaces is read from `getifaddrs` on every Unix
+    #[cfg(not(target_os = "windows"))]
+    fn interfaces(&self) -> Vec<NetworkInterface> {
+        use nix::ifaddrs::getifaddrs;
+        use nix::net::if_::InterfaceFlags;
+
+        let addresses = match getifaddrs() {
+            Ok(addresses) => addresses,
+            Err(error) => {
+                log::debug!("Unable to list the network interfaces: {}", error);
+                return Vec::new();
+            }
+        };
+
+        let mut interfaces: Vec<NetworkInterface> = Vec::new();
+        for address in addresses {
+            let ipv4 = address
+                .address
+                .as_ref()
+                .and_then(|address| address.as_sockaddr_in())
+                .map(|address| Ipv4Addr::from(address.ip()));
+            let is_up = address
+                .flags
+                .contains(InterfaceFlags::IFF_UP | InterfaceFlags::IFF_RUNNING);
+
+            // `getifaddrs` lists an interface once per address
+            match interfaces
+                .iter_mut()
+                .find(|interface| interface.name == address.interface_name)
+            {
+                Some(interface) => interface.ipv4 = interface.ipv4.or(ipv4),
+                None => interfaces.push(NetworkInterface {
+                    name: address.interface_name,
+                    is_up,
+                    ipv4,
+                }),
+            }
+        }
+        log::trace!("Network interfaces: {:?}", interfaces);
+        interfaces
+    }
+
+    #[cfg(target_os = "windows")]
+    fn interfaces(&self) -> Vec<NetworkInterface> {
+        use windows::Win32::Foundation::{ERROR_BUFFER_OVERFLOW, NO_ERROR};
+        use windows::Win32::NetworkManagement::IpHelper::{
+            GetAdaptersAddresses, GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER,
+            GAA_FLAG_SKIP_MULTICAST, IP_ADAPTER_ADDRESSES_LH,
+        };
+        use windows::Win32::NetworkManagement::Ndis::IfOperStatusUp;
+        use windows::Win32::Networking::WinSock::{AF_INET, AF_UNSPEC, SOCKADDR_IN};
+
+        let flags = GAA_FLAG_SKIP_ANYCAST | GAA_FLAG_SKIP_MULTICAST | GAA_FLAG_SKIP_DNS_SERVER;
+        // The recommended initial size, which is grown if the adapters don't fit
+        let mut size: u32 = 15_000;
+        let mut buffer: Vec<u64>;
+        let mut attempts = 0;
+        loop {
+            // A `u64` buffer keeps the adapters aligned
+            buffer = vec![0; (size as usize + 7) / 8];
+            let result = unsafe {
+                GetAdaptersAddresses(
+                    AF_UNSPEC,
+                    flags,
+                    std::ptr::null_mut(),
+                    buffer.as_mut_ptr().cast(),
+                    &mut size,
+                )
+            };
+            attempts += 1;
+            if result == ERROR_BUFFER_OVERFLOW.0 && attempts < 3 {
+                continue;
+            }
+            if result != NO_ERROR.0 {
+                log::debug!("Unable to list the network adapters: {}", result);
+                return Vec::new();
+            }
+            break;
+        }
+
+        let mut interfaces = Vec::new();
+        let mut adapter = buffer.as_ptr().cast::<IP_ADAPTER_ADDRESSES_LH>();
+        while let Some(current) = unsafe { adapter.as_ref() } {
+            let mut ipv4 = None;
+            let mut unicast = current.FirstUnicastAddress;
+            while let Some(address) = unsafe { unicast.as_ref() } {
+                let sockaddr = address.Address.lpSockaddr;
+                if !sockaddr.is_null() && unsafe { (*sockaddr).sa_family } == AF_INET.0 as u16 {
+                    let sockaddr = unsafe { &*sockaddr.cast::<SOCKADDR_IN>() };
+                    // The address is stored in network byte order
+                    let octets = unsafe { sockaddr.sin_addr.S_un.S_addr }.to_ne_bytes();
+                    ipv4 = Some(Ipv4Addr::from(octets));
+                    break;
+                }
+                unicast = address.Next;
+            }
+
+            interfaces.push(NetworkInterface {
+                name: unsafe { wide_to_string(current.FriendlyName.0) },
+                is_up: current.OperStatus == IfOperStatusUp,
+                ipv4,
+            });
+            adapter = current.Next;
+        }
+        log::trace!("Network interfaces: {:?}", interfaces);
+        interfaces
+    }
+}
+
+/// Reads a NUL-terminated UTF-16 string
+#[cfg(target_os = "windows")]
+unsafe fn wide_to_string(ptr: *const u16) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len))
+}
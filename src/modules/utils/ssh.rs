@@ -0,0 +1,413 @@
+use crate::context::Context;
+#[cfg(test)]
+use mockall::automock;
+#[cfg(any(all(unix, not(target_os = "linux")), test))]
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// How far up the process tree to look for an `sshd` ancestor
+const MAX_ANCESTORS: usize = 64;
+
+/// Whether the user is connected through SSH
+///
+/// With `ssh_detection = "env"` any of the `env_vars` marks the session as
+/// remote. With `process-tree` an `sshd` ancestor process does, which still
+/// works after `sudo` or a later `tmux attach` stripped the environment, and
+/// `both` accepts either.
+pub fn is_ssh_session(context: &Context, ssh_detection: &str, env_vars: &[&str]) -> bool {
+    let from_env = || env_vars.iter().any(|var| context.get_env_os(var).is_some());
+    match ssh_detection {
+        "env" => from_env(),
+        "process-tree" => context.has_sshd_ancestor(),
+        "both" => from_env() || context.has_sshd_ancestor(),
+        unknown => {
+            log::warn!("Unknown ssh_detection `{}`", unknown);
+            from_env()
+        }
+    }
+}
+
+/// The client end and server port of the current SSH connection
+#[derive(Debug, PartialEq, Eq)]
+pub struct SshConnection {
+    pub client_ip: String,
+    pub port: String,
+}
+
+/// Parses `SSH_CONNECTION` (`client_ip client_port server_ip server_port`), or
+/// `SSH_CLIENT` (`client_ip client_port server_port`) if it isn't set
+///
+/// Returns `None` if neither is set or the value is malformed.
+pub fn get_ssh_connection(context: &Context) -> Option<SshConnection> {
+    let (value, tokens) = match context.get_env("SSH_CONNECTION") {
+        Some(value) => (value, 4),
+        None => (context.get_env("SSH_CLIENT")?, 3),
+    };
+
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    if parts.len() != tokens {
+        log::debug!("Malformed SSH connection: {:?}", value);
+        return None;
+    }
+    let client_ip: IpAddr = parts[0].parse().ok()?;
+    let ports = [parts[1], parts[tokens - 1]];
+    if ports.iter().any(|port| port.parse::<u16>().is_err()) {
+        log::debug!("Malformed SSH connection: {:?}", value);
+        return None;
+    }
+
+    Some(SshConnection {
+        client_ip: client_ip.to_string(),
+        port: ports[1].to_string(),
+    })
+}
+
+/// Whether one of the process names belongs to the SSH daemon
+pub fn is_sshd(name: &str) -> bool {
+    let name = name.strip_suffix(".exe").unwrap_or(name);
+    // OpenSSH 9.8 and later run sessions as `sshd-session`
+    name == "sshd" || name.starts_with("sshd-")
+}
+
+/// Whether the current process, or the client attached to the tmux session it
+/// runs in, has an `sshd` ancestor
+///
+/// A tmux server detaches from the terminal it was started in, so a shell in a
+/// session attached over SSH only finds `sshd` above the tmux client.
+pub fn has_sshd_ancestor(context: &Context) -> bool {
+    let has_sshd = |names: Vec<String>| names.iter().any(|name| is_sshd(name));
+    has_sshd(context.process_tree_provider.ancestor_names())
+        || tmux_client_pid(context).map_or(false, |pid| {
+            has_sshd(context.process_tree_provider.process_names(pid))
+        })
+}
+
+/// The PID of the client attached to the current tmux session
+fn tmux_client_pid(context: &Context) -> Option<u32> {
+    context.get_env_os("TMUX")?;
+    let output = context.exec_cmd("tmux", &["display-message", "-p", "#{client_pid}"])?;
+    output.stdout.trim().parse().ok()
+}
+
+#[cfg_attr(test, automock)]
+pub trait ProcessTreeProvider {
+    /// The names of the ancestors of the current process, parent first
+    fn ancestor_names(&self) -> Vec<String>;
+
+    /// The names of process `pid` and its ancestors
+    fn process_names(&self, pid: u32) -> Vec<String>;
+}
+
+pub struct ProcessTreeProviderImpl;
+
+impl ProcessTreeProvider for ProcessTreeProviderImpl {
+    #[cfg(not(target_os = "windows"))]
+    fn ancestor_names(&self) -> Vec<String> {
+        self.process_names(std::os::unix::process::parent_id())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn ancestor_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn process_names(&self, pid: u32) -> Vec<String> {
+        walk_ancestors(pid, process_info)
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    fn process_names(&self, pid: u32) -> Vec<String> {
+        let processes = list_processes().unwrap_or_default();
+        walk_ancestors(pid, |pid| processes.get(&pid).cloned())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn process_names(&self, _pid: u32) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// The names of `pid` and its ancestors, up to but excluding `init`
+///
+/// `process_info` gives the name and parent PID of a process.
+#[cfg(any(unix, test))]
+fn walk_ancestors<F>(mut pid: u32, process_info: F) -> Vec<String>
+where
+    F: Fn(u32) -> Option<(String, u32)>,
+{
+    let mut names = Vec::new();
+    while pid > 1 && names.len() < MAX_ANCESTORS {
+        match process_info(pid) {
+            Some((name, parent)) => {
+                names.push(name);
+                pid = parent;
+            }
+            None => break,
+        }
+    }
+    log::trace!("Process ancestors: {:?}", names);
+    names
+}
+
+/// The name and parent PID of a process, read from `/proc`
+#[cfg(target_os = "linux")]
+fn process_info(pid: u32) -> Option<(String, u32)> {
+    let name = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The name in `stat` may contain spaces, so the fields are read after it
+    let parent = stat
+        .rsplit_once(')')?
+        .1
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()?;
+    Some((name.trim_end().to_string(), parent))
+}
+
+/// The name and parent PID of every process, from a single `ps` call
+#[cfg(all(unix, not(target_os = "linux")))]
+fn list_processes() -> Option<HashMap<u32, (String, u32)>> {
+    let output = crate::utils::create_command("ps")
+        .ok()?
+        .args(["-A", "-o", "pid=", "-o", "ppid=", "-o", "comm="])
+        .output()
+        .ok()?;
+    Some(parse_processes(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Parses the `pid ppid comm` lines listed by `ps`
+#[cfg(any(all(unix, not(target_os = "linux")), test))]
+fn parse_processes(output: &str) -> HashMap<u32, (String, u32)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (pid, rest) = line.trim().split_once(char::is_whitespace)?;
+            let (parent, command) = rest.trim_start().split_once(char::is_whitespace)?;
+            // macOS reports the full path of the executable
+            let name = command.trim().rsplit('/').next()?;
+            Some((pid.parse().ok()?, (name.to_string(), parent.parse().ok()?)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::default_context;
+    use crate::utils::CommandOutput;
+
+    fn mock_tree(names: &'static [&'static str]) -> MockProcessTreeProvider {
+        let mut mock = MockProcessTreeProvider::new();
+        mock.expect_ancestor_names()
+            .returning(move || names.iter().map(|name| name.to_string()).collect());
+        mock
+    }
+
+    fn detect(ssh_detection: &str, env: bool, names: &'static [&'static str]) -> bool {
+        let mock = mock_tree(names);
+        let mut context = default_context();
+        context.process_tree_provider = &mock;
+        if env {
+            context
+                .env
+                .insert("SSH_CONNECTION", "something".to_string());
+        }
+        is_ssh_session(&context, ssh_detection, &["SSH_CONNECTION"])
+    }
+
+    #[test]
+    fn env_detection() {
+        assert!(detect("env", true, &[]));
+        assert!(!detect("env", false, &["bash", "sshd"]));
+    }
+
+    #[test]
+    fn process_tree_detection() {
+        assert!(detect(
+            "process-tree",
+            false,
+            &["bash", "tmux: server", "sshd"]
+        ));
+        assert!(!detect("process-tree", false, &["bash", "login"]));
+        assert!(!detect("process-tree", true, &["bash"]));
+    }
+
+    #[test]
+    fn daemonized_tmux_server() {
+        /// The process tree of a shell in a tmux session that was attached
+        /// over SSH. The detached server was reparented to `init`.
+        struct TmuxOverSsh(HashMap<u32, (String, u32)>);
+
+        impl ProcessTreeProvider for TmuxOverSsh {
+            fn ancestor_names(&self) -> Vec<String> {
+                self.process_names(300)
+            }
+
+            fn process_names(&self, pid: u32) -> Vec<String> {
+                walk_ancestors(pid, |pid| self.0.get(&pid).cloned())
+            }
+        }
+
+        let tree = TmuxOverSsh(parse_processes(
+            "    1     0 /sbin/launchd
+                80     1 /usr/sbin/sshd
+                90    80 -bash
+                95    90 tmux
+               110     1 -zsh
+               115   110 tmux
+               200     1 tmux
+               300   200 -bash",
+        ));
+        let detect = |tmux: bool, client_pid: &str| {
+            let mut context = default_context();
+            context.process_tree_provider = &tree;
+            if tmux {
+                context
+                    .env
+                    .insert("TMUX", "/tmp/tmux-501/default,200,0".to_string());
+            }
+            context.cmd.insert(
+                "tmux display-message -p #{client_pid}",
+                Some(CommandOutput {
+                    stdout: format!("{}\n", client_pid),
+                    stderr: String::new(),
+                }),
+            );
+            is_ssh_session(&context, "process-tree", &[])
+        };
+
+        assert_eq!(tree.ancestor_names(), ["-bash", "tmux"]);
+        assert!(detect(true, "95"));
+        assert!(!detect(false, "95"));
+        // A client attached locally
+        assert!(!detect(true, "115"));
+        assert!(!detect(true, "not-a-pid"));
+    }
+
+    #[test]
+    fn process_table() {
+        let processes = parse_processes(
+            "  1     0 /sbin/launchd
+              812     1 /usr/sbin/sshd
+              815   812 sshd: astronaut@ttys000
+            ",
+        );
+        assert_eq!(processes.len(), 3);
+        assert_eq!(processes[&812], ("sshd".to_string(), 1));
+        assert_eq!(
+            processes[&815],
+            ("sshd: astronaut@ttys000".to_string(), 812)
+        );
+        assert_eq!(
+            walk_ancestors(815, |pid| processes.get(&pid).cloned()),
+            ["sshd: astronaut@ttys000", "sshd"]
+        );
+    }
+
+    #[test]
+    fn both_detection() {
+        assert!(detect("both", true, &["bash"]));
+        assert!(detect("both", false, &["bash", "sshd-session"]));
+        assert!(!detect("both", false, &["bash"]));
+    }
+
+    #[test]
+    fn unknown_detection_uses_env() {
+        assert!(detect("telepathy", true, &["bash"]));
+        assert!(!detect("telepathy", false, &["sshd"]));
+    }
+
+    #[test]
+    fn process_tree_is_walked_once() {
+        let mut mock = MockProcessTreeProvider::new();
+        mock.expect_ancestor_names()
+            .times(1)
+            .returning(|| vec!["sshd".to_string()]);
+        let mut context = default_context();
+        context.process_tree_provider = &mock;
+
+        assert!(is_ssh_session(&context, "process-tree", &[]));
+        assert!(is_ssh_session(&context, "both", &[]));
+        assert!(context.has_sshd_ancestor());
+    }
+
+    fn connection(env: &[(&'static str, &str)]) -> Option<SshConnection> {
+        let mut context = default_context();
+        for (key, value) in env {
+            context.env.insert(key, value.to_string());
+        }
+        get_ssh_connection(&context)
+    }
+
+    #[test]
+    fn ssh_connection() {
+        let expected = Some(SshConnection {
+            client_ip: "192.168.223.17".to_string(),
+            port: "22".to_string(),
+        });
+        assert_eq!(
+            expected,
+            connection(&[("SSH_CONNECTION", "192.168.223.17 36673 192.168.223.229 22")])
+        );
+        assert_eq!(
+            expected,
+            connection(&[("SSH_CLIENT", "192.168.223.17 36673 22")])
+        );
+    }
+
+    #[test]
+    fn ssh_connection_ipv6() {
+        let actual = connection(&[("SSH_CONNECTION", "fe80::1 36673 fe80::2 2222")]);
+        assert_eq!(
+            Some(SshConnection {
+                client_ip: "fe80::1".to_string(),
+                port: "2222".to_string(),
+            }),
+            actual
+        );
+    }
+
+    #[test]
+    fn ssh_connection_malformed() {
+        assert_eq!(None, connection(&[]));
+        assert_eq!(None, connection(&[("SSH_CONNECTION", "something")]));
+        assert_eq!(
+            None,
+            connection(&[("SSH_CONNECTION", "192.168.223.17 36673 22")])
+        );
+        assert_eq!(None, connection(&[("SSH_CLIENT", "not-an-ip 36673 22")]));
+        assert_eq!(
+            None,
+            connection(&[("SSH_CLIENT", "192.168.223.17 36673 99999")])
+        );
+    }
+
+    #[test]
+    fn ssh_connection_takes_precedence() {
+        let actual = connection(&[
+            ("SSH_CONNECTION", "10.0.0.1 36673 10.0.0.2 22"),
+            ("SSH_CLIENT", "192.168.223.17 36673 2222"),
+        ]);
+        assert_eq!(Some("10.0.0.1".to_string()), actual.map(|c| c.client_ip));
+    }
+
+    #[test]
+    fn sshd_names() {
+        assert!(is_sshd("sshd"));
+        assert!(is_sshd("sshd-session"));
+        assert!(is_sshd("sshd.exe"));
+        assert!(!is_sshd("ssh"));
+        assert!(!is_sshd("sshdaemon"));
+        assert!(!is_sshd("tmux: server"));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn reads_own_process() {
+        let (name, parent) = process_info(std::process::id()).unwrap();
+        assert!(!name.is_empty());
+        assert_eq!(std::os::unix::process::parent_id(), parent);
+    }
+}
@@ -0,0 +1,396 @@
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+use std::convert::TryInto;
+use std::path::Path;
+
+/// The directory the IANA time zone database is read from, unless `TZDIR` is set
+pub const DEFAULT_TZ_DIR: &str = "/usr/share/zoneinfo";
+
+/// The offset from UTC of the time zone `name` (e.g. `Asia/Tokyo`) at `time`
+///
+/// Zones are read from the compiled time zone database in `tz_dir`, which only
+/// exists on Unix-like systems. `UTC` is always available.
+pub fn utc_offset(tz_dir: &Path, name: &str, time: DateTime<Utc>) -> Option<FixedOffset> {
+    if matches!(name, "UTC" | "Etc/UTC" | "GMT" | "Etc/GMT") {
+        return Some(FixedOffset::east(0));
+    }
+    // Zone names are relative paths inside the database
+    let is_valid = !name.is_empty()
+        && name
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..");
+    if !is_valid {
+        return None;
+    }
+
+    let data = std::fs::read(tz_dir.join(name)).ok()?;
+    let offset = TzFile::parse(&data)?.offset_at(time.timestamp())?;
+    FixedOffset::east_opt(offset)
+}
+
+/// A time zone read from a TZif file, as described by RFC 8536
+struct TzFile {
+    /// The times the offset changes at, with the index of the offset from then on
+    transitions: Vec<(i64, usize)>,
+    /// The offsets from UTC in seconds
+    offsets: Vec<i32>,
+    /// The POSIX TZ rule for the times after the last transition
+    rule: Option<PosixRule>,
+}
+
+impl TzFile {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let (version, counts, body) = parse_header(data)?;
+        let tz = if version == 0 {
+            Self::parse_body(body, counts, 4)?.0
+        } else {
+            // Version 2 and later repeat the data with 64-bit times, followed by the rule
+            let (_, counts, body) = parse_header(body.get(counts.body_len(4)..)?)?;
+            let (mut tz, rest) = Self::parse_body(body, counts, 8)?;
+            tz.rule = rest
+                .strip_prefix(b"\n")
+                .and_then(|rest| rest.split(|&byte| byte == b'\n').next())
+                .and_then(|rule| std::str::from_utf8(rule).ok())
+                .and_then(PosixRule::parse);
+            tz
+        };
+        (!tz.offsets.is_empty()).then(|| tz)
+    }
+
+    /// Parses a data block, returning the rest of the file after it
+    fn parse_body(body: &[u8], counts: Counts, time_size: usize) -> Option<(Self, &[u8])> {
+        let (times, body) = split(body, counts.transitions * time_size)?;
+        let (indices, body) = split(body, counts.transitions)?;
+        let (types, body) = split(body, counts.types * 6)?;
+        let rest = body.get(
+            counts.chars
+                + counts.leap_seconds * (time_size + 4)
+                + counts.std_indicators
+                + counts.utc_indicators..,
+        )?;
+
+        let transitions = times
+            .chunks(time_size)
+            .zip(indices)
+            .map(|(time, &index)| {
+                let time = match time_size {
+                    4 => i64::from(i32::from_be_bytes(time.try_into().ok()?)),
+                    _ => i64::from_be_bytes(time.try_into().ok()?),
+                };
+                Some((time, usize::from(index)))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let offsets = types
+            .chunks(6)
+            .map(|info| Some(i32::from_be_bytes(info[..4].try_into().ok()?)))
+            .collect::<Option<Vec<_>>>()?;
+        if transitions.iter().any(|(_, index)| *index >= offsets.len()) {
+            return None;
+        }
+
+        let tz = Self {
+            transitions,
+            offsets,
+            rule: None,
+        };
+        Some((tz, rest))
+    }
+
+    fn offset_at(&self, timestamp: i64) -> Option<i32> {
+        let last = self.transitions.last().map(|(time, _)| *time);
+        match &self.rule {
+            Some(rule) if last.map_or(true, |last| timestamp >= last) => {
+                Some(rule.offset_at(timestamp))
+            }
+            _ => {
+                // Before the first transition the first offset applies
+                let index = self
+                    .transitions
+                    .iter()
+                    .take_while(|(time, _)| *time <= timestamp)
+                    .last()
+                    .map_or(0, |(_, index)| *index);
+                self.offsets.get(index).copied()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Counts {
+    utc_indicators: usize,
+    std_indicators: usize,
+    leap_seconds: usize,
+    transitions: usize,
+    types: usize,
+    chars: usize,
+}
+
+impl Counts {
+    /// The length of the data block that follows the header
+    fn body_len(&self, time_size: usize) -> usize {
+        self.transitions * (time_size + 1)
+            + self.types * 6
+            + self.chars
+            + self.leap_seconds * (time_size + 4)
+            + self.std_indicators
+            + self.utc_indicators
+    }
+}
+
+/// Parses the header of a TZif file into its version and counts, and the rest of the file
+fn parse_header(data: &[u8]) -> Option<(u8, Counts, &[u8])> {
+    let (header, body) = split(data, 44)?;
+    if &header[..4] != b"TZif" {
+        return None;
+    }
+    let version = match header[4] {
+        0 => 0,
+        version => version.checked_sub(b'0')?,
+    };
+    let count = |index: usize| {
+        let start = 20 + index * 4;
+        u32::from_be_bytes(header[start..start + 4].try_into().ok()?)
+            .try_into()
+            .ok()
+    };
+    let counts = Counts {
+        utc_indicators: count(0)?,
+        std_indicators: count(1)?,
+        leap_seconds: count(2)?,
+        transitions: count(3)?,
+        types: count(4)?,
+        chars: count(5)?,
+    };
+    Some((version, counts, body))
+}
+
+fn split(data: &[u8], at: usize) -> Option<(&[u8], &[u8])> {
+    (at <= data.len()).then(|| data.split_at(at))
+}
+
+/// A POSIX TZ rule like `EST5EDT,M3.2.0,M11.1.0`
+struct PosixRule {
+    std_offset: i32,
+    dst: Option<DstRule>,
+}
+
+struct DstRule {
+    offset: i32,
+    start: (u32, u32, u32, i32),
+    end: (u32, u32, u32, i32),
+}
+
+impl PosixRule {
+    fn parse(rule: &str) -> Option<Self> {
+        let mut rest = rule;
+        skip_name(&mut rest)?;
+        let std_offset = -parse_offset(&mut rest)?;
+        if rest.is_empty() {
+            return Some(Self {
+                std_offset,
+                dst: None,
+            });
+        }
+
+        skip_name(&mut rest)?;
+        let offset = if rest.starts_with(',') {
+            std_offset + 3600
+        } else {
+            -parse_offset(&mut rest)?
+        };
+        let mut dates = rest.strip_prefix(',')?.split(',');
+        let start = parse_date(dates.next()?)?;
+        let end = parse_date(dates.next()?)?;
+        Some(Self {
+            std_offset,
+            dst: Some(DstRule { offset, start, end }),
+        })
+    }
+
+    fn offset_at(&self, timestamp: i64) -> i32 {
+        let dst = match &self.dst {
+            Some(dst) => dst,
+            None => return self.std_offset,
+        };
+        let year = match chrono::NaiveDateTime::from_timestamp_opt(
+            timestamp + i64::from(self.std_offset),
+            0,
+        ) {
+            Some(local) => local.year(),
+            None => return self.std_offset,
+        };
+
+        // The start is given in standard time, the end in daylight saving time
+        let start = transition_time(year, dst.start).map(|time| time - i64::from(self.std_offset));
+        let end = transition_time(year, dst.end).map(|time| time - i64::from(dst.offset));
+        let is_dst = match (start, end) {
+            (Some(start), Some(end)) if start < end => start <= timestamp && timestamp < end,
+            // Daylight saving time spans the new year in the southern hemisphere
+            (Some(start), Some(end)) => timestamp < end || start <= timestamp,
+            _ => false,
+        };
+        if is_dst {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+}
+
+/// Skips a zone abbreviation, either alphabetic (`EST`) or quoted (`<+09>`)
+fn skip_name(rest: &mut &str) -> Option<()> {
+    let len = if let Some(quoted) = rest.strip_prefix('<') {
+        quoted.find('>')? + 2
+    } else {
+        rest.find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len())
+    };
+    if len < 3 {
+        return None;
+    }
+    *rest = &rest[len..];
+    Some(())
+}
+
+/// Parses `[+-]hh[:mm[:ss]]` into seconds
+fn parse_offset(rest: &mut &str) -> Option<i32> {
+    let len = rest
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | ':')))
+        .unwrap_or(rest.len());
+    let (offset, remaining) = rest.split_at(len);
+    *rest = remaining;
+
+    let (sign, offset) = match offset.strip_prefix('-') {
+        Some(offset) => (-1, offset),
+        None => (1, offset.strip_prefix('+').unwrap_or(offset)),
+    };
+    let mut seconds = 0;
+    for (part, unit) in offset.split(':').zip([3600, 60, 1]) {
+        seconds += part.parse::<i32>().ok()? * unit;
+    }
+    Some(sign * seconds)
+}
+
+/// Parses a date of the form `Mm.w.d[/time]` into the month, week, weekday and time
+fn parse_date(date: &str) -> Option<(u32, u32, u32, i32)> {
+    let (date, mut time) = match date.split_once('/') {
+        Some((date, time)) => (date, time),
+        None => (date, "2"),
+    };
+    let mut parts = date.strip_prefix('M')?.split('.');
+    let month = parts.next()?.parse().ok()?;
+    let week = parts.next()?.parse().ok()?;
+    let weekday = parts.next()?.parse().ok()?;
+    let time = parse_offset(&mut time)?;
+    Some((month, week, weekday, time))
+}
+
+/// The local time the transition `(month, week, weekday, time)` happens at in `year`,
+/// in seconds since the epoch
+fn transition_time(year: i32, (month, week, weekday, time): (u32, u32, u32, i32)) -> Option<i64> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let first_weekday = first.weekday().num_days_from_sunday();
+    let mut day = 1 + (weekday + 7 - first_weekday) % 7 + (week.clamp(1, 5) - 1) * 7;
+    // Week 5 means the last such weekday of the month
+    while NaiveDate::from_ymd_opt(year, month, day).is_none() {
+        day -= 7;
+    }
+    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+    Some(date.and_hms(0, 0, 0).timestamp() + i64::from(time))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Builds a version 2 TZif file with the given transitions, offsets and rule
+    pub fn tzif(transitions: &[(i64, u8)], offsets: &[i32], rule: &str) -> Vec<u8> {
+        let header = |transitions: usize, types: usize| {
+            let mut header = b"TZif2".to_vec();
+            header.extend([0; 15]);
+            for count in [0, 0, 0, transitions, types, 1] {
+                header.extend((count as u32).to_be_bytes());
+            }
+            header
+        };
+
+        let mut data = header(0, 0);
+        data.push(0);
+        data.extend(header(transitions.len(), offsets.len()));
+        for (time, _) in transitions {
+            data.extend(time.to_be_bytes());
+        }
+        data.extend(transitions.iter().map(|(_, index)| index));
+        for offset in offsets {
+            data.extend(offset.to_be_bytes());
+            data.extend([0, 0]);
+        }
+        data.push(0);
+        data.extend(format!("\n{}\n", rule).as_bytes());
+        data
+    }
+
+    fn offset(data: &[u8], time: DateTime<Utc>) -> Option<i32> {
+        TzFile::parse(data)?.offset_at(time.timestamp())
+    }
+
+    #[test]
+    fn fixed_offset_zone() {
+        let data = tzif(&[(-577_962_000, 0)], &[32400], "JST-9");
+        let now = Utc.ymd(2022, 10, 1).and_hms(12, 0, 0);
+        assert_eq!(Some(32400), offset(&data, now));
+    }
+
+    #[test]
+    fn transitions_before_the_rule() {
+        let data = tzif(&[(0, 0), (1000, 1)], &[3600, 7200], "<+03>-3");
+        assert_eq!(Some(3600), offset(&data, Utc.timestamp(-10, 0)));
+        assert_eq!(Some(3600), offset(&data, Utc.timestamp(999, 0)));
+        assert_eq!(Some(10800), offset(&data, Utc.timestamp(1000, 0)));
+    }
+
+    #[test]
+    fn daylight_saving_time_rules() {
+        let data = tzif(&[], &[-18000], "EST5EDT,M3.2.0,M11.1.0");
+        assert_eq!(
+            Some(-18000),
+            offset(&data, Utc.ymd(2022, 1, 15).and_hms(12, 0, 0))
+        );
+        assert_eq!(
+            Some(-14400),
+            offset(&data, Utc.ymd(2022, 7, 15).and_hms(12, 0, 0))
+        );
+        // The clocks move forward at 2:00 EST on the 13th of March 2022
+        assert_eq!(
+            Some(-18000),
+            offset(&data, Utc.ymd(2022, 3, 13).and_hms(6, 59, 59))
+        );
+        assert_eq!(
+            Some(-14400),
+            offset(&data, Utc.ymd(2022, 3, 13).and_hms(7, 0, 0))
+        );
+
+        // In the southern hemisphere daylight saving time spans the new year
+        let data = tzif(&[], &[36000], "AEST-10AEDT,M10.1.0,M4.1.0/3");
+        assert_eq!(
+            Some(39600),
+            offset(&data, Utc.ymd(2022, 1, 15).and_hms(12, 0, 0))
+        );
+        assert_eq!(
+            Some(36000),
+            offset(&data, Utc.ymd(2022, 7, 15).and_hms(12, 0, 0))
+        );
+    }
+
+    #[test]
+    fn invalid_zones() {
+        let dir = Path::new("/nonexistent");
+        let now = Utc.ymd(2022, 10, 1).and_hms(12, 0, 0);
+        assert_eq!(Some(FixedOffset::east(0)), utc_offset(dir, "UTC", now));
+        assert_eq!(None, utc_offset(dir, "Asia/Tokyo", now));
+        assert_eq!(None, utc_offset(Path::new("/"), "../etc/passwd", now));
+        assert!(TzFile::parse(b"not a time zone").is_none());
+        assert!(TzFile::parse(&tzif(&[(0, 1)], &[0], "")).is_none());
+    }
+}
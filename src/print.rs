@@ -1,22 +1,33 @@
 use clap::{PossibleValue, ValueEnum};
 use nu_ansi_term::AnsiStrings;
 use rayon::prelude::*;
-use std::collections::BTreeSet;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::{self, Debug, Write as FmtWrite};
 use std::io::{self, Write};
+use std::iter;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use std::thread;
 use std::time::Duration;
 use terminal_size::terminal_size;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthChar;
 
+use crate::config::{Either, ModuleConfig};
+use crate::configs::custom::CustomConfig;
 use crate::configs::PROMPT_ORDER;
 use crate::context::{Context, Properties, Shell, Target};
-use crate::formatter::{StringFormatter, VariableHolder};
+use crate::formatter::{gradient, StringFormatter, VariableHolder};
 use crate::module::Module;
 use crate::module::ALL_MODULES;
 use crate::modules;
+use crate::modules::gradient::GradientTable;
 use crate::segment::Segment;
 use crate::shadow;
+use crate::utils::file_url_path;
 
 pub struct Grapheme<'a>(pub &'a str);
 
@@ -55,14 +66,32 @@ fn test_grapheme_aware_width() {
     assert_eq!(11, "normal text".width_graphemes());
 }
 
-pub fn prompt(args: Properties, target: Target) {
-    let context = Context::new(args, target);
+pub fn prompt(args: Properties, target: Target, transient: bool) {
+    let mut context = Context::new(args, target);
+    context.transient = transient;
     let stdout = io::stdout();
     let mut handle = stdout.lock();
     write!(handle, "{}", get_prompt(context)).unwrap();
 }
 
-pub fn get_prompt(context: Context) -> String {
+/// Prints the OSC 7 sequence that reports the current directory to the terminal,
+/// for the init scripts with `osc7`
+pub fn osc7(args: Properties) {
+    let context = Context::new(args, Target::Main);
+    let host = gethostname::gethostname();
+    print!(
+        "{}",
+        osc7_sequence(&host.to_string_lossy(), &context.logical_dir)
+    );
+}
+
+fn osc7_sequence(host: &str, path: &Path) -> String {
+    format!("\u{1b}]7;file://{}{}\u{1b}\\", host, file_url_path(path))
+}
+
+pub fn get_prompt(context: Context<'static>) -> String {
+    let prompt_context = PromptContext::new(context);
+    let context: &Context = &prompt_context;
     let config = &context.root_config;
     let mut buf = String::new();
 
@@ -81,38 +110,100 @@ pub fn get_prompt(context: Context) -> String {
         buf.push_str("\x1b[J"); // An ASCII control code to clear screen
     }
 
-    let (formatter, modules) = load_formatter_and_modules(&context);
+    let (_, modules) = load_formatter_and_modules(context);
 
-    let formatter = formatter.map_variables_to_segments(|module| {
-        // Make $all display all modules not explicitly referenced
-        if module == "all" {
-            Some(Ok(all_modules_uniq(&modules)
-                .par_iter()
-                .flat_map(|module| {
-                    handle_module(module, &context, &modules)
-                        .into_iter()
-                        .flat_map(|module| module.segments)
-                        .collect::<Vec<Segment>>()
-                })
-                .collect::<Vec<_>>()))
-        } else if context.is_module_disabled_in_config(module) {
-            None
-        } else {
-            // Get segments from module
-            Some(Ok(handle_module(module, &context, &modules)
-                .into_iter()
-                .flat_map(|module| module.segments)
-                .collect::<Vec<Segment>>()))
+    // Modules are only computed once, even if the prompt has to be rendered
+    // again without some of them to fit in `max_prompt_width`
+    let computed: Mutex<HashMap<String, Vec<Segment>>> = Mutex::default();
+    let module_segments = |module: &str| {
+        if let Some(segments) = computed.lock().unwrap().get(module) {
+            return segments.clone();
         }
-    });
+        let segments: Vec<Segment> = handle_module_with_timeout(module, &prompt_context, &modules)
+            .into_iter()
+            .flat_map(|module| module.segments)
+            .collect();
+        computed
+            .lock()
+            .unwrap()
+            .insert(module.to_string(), segments.clone());
+        segments
+    };
+    let render = |dropped: &BTreeSet<String>| {
+        let (formatter, _) = load_formatter_and_modules(context);
+        let formatter = formatter.map_variables_to_segments(|module| {
+            // Make $all display all modules not explicitly referenced
+            if module == "all" {
+                Some(Ok(all_modules_uniq(&modules)
+                    .par_iter()
+                    .filter(|module| !dropped.contains(*module))
+                    .flat_map(|module| module_segments(module))
+                    .collect::<Vec<_>>()))
+            } else if context.is_module_disabled_in_config(module) || dropped.contains(module) {
+                None
+            } else {
+                // Get segments from module
+                Some(Ok(module_segments(module)))
+            }
+        });
+        let segments = formatter
+            .parse(None, Some(context))
+            .expect("Unexpected error returned in root format variables");
+        resolve_line_breaks(segments, context.width)
+    };
+
+    let mut dropped = BTreeSet::new();
+    let mut segments = with_parallelism(context, || render(&dropped));
+    if let Some(max_width) = max_prompt_width(context) {
+        let mut overflow = prompt_overflow(&segments, max_width);
+        let mut candidates: Vec<String> = computed
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(module, segments)| {
+                !UNDROPPABLE_MODULES.contains(&module.as_str()) && !segments.is_empty()
+            })
+            .map(|(module, _)| module.clone())
+            .collect();
+        candidates.sort_by_key(|module| {
+            let order = PROMPT_ORDER.iter().position(|name| name == module);
+            (
+                module_priority(context, module),
+                Reverse(order.unwrap_or(PROMPT_ORDER.len())),
+                Reverse(module.clone()),
+            )
+        });
+
+        for module in candidates {
+            if overflow == 0 {
+                break;
+            }
+            dropped.insert(module.clone());
+            let reduced = render(&dropped);
+            let reduced_overflow = prompt_overflow(&reduced, max_width);
+            if reduced_overflow < overflow {
+                log::debug!("Dropped module `{}` to fit the prompt width", module);
+                segments = reduced;
+                overflow = reduced_overflow;
+            } else {
+                dropped.remove(&module);
+            }
+        }
+    }
 
     // Creates a root module and prints it.
     let mut root_module = Module::new("Starship Root", "The root module", None);
-    root_module.set_segments(
-        formatter
-            .parse(None, Some(&context))
-            .expect("Unexpected error returned in root format variables"),
-    );
+    let mut segments = apply_line_gradient(context, segments);
+    if config.semantic_prompt && context.target == Target::Main {
+        segments.insert(0, Segment::escape(PROMPT_START_MARK));
+        segments.push(Segment::escape(PROMPT_END_MARK));
+    }
+    if config.set_title && context.target == Target::Main && !context.transient {
+        if let Some(title) = render_title(context, &module_segments) {
+            segments.insert(0, Segment::escape(format!("\u{1b}]0;{}\u{7}", title)));
+        }
+    }
+    root_module.set_segments(segments);
 
     let module_strings = root_module.ansi_strings_for_shell(context.shell, Some(context.width));
     if config.add_newline && context.target != Target::Continuation {
@@ -136,6 +227,156 @@ pub fn get_prompt(context: Context) -> String {
     buf
 }
 
+/// Keeps the conditional line breaks of `line_break.min_width` if fewer than
+/// `min_width` columns are left after the line before them, and removes the others
+///
+/// Fills are left out of the width of the line, as they only take up the space
+/// that is left.
+fn resolve_line_breaks(segments: Vec<Segment>, term_width: usize) -> Vec<Segment> {
+    let mut line_width = 0;
+    segments
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::ConditionalLineTerm(min_width) if line_width + min_width <= term_width => None,
+            Segment::LineTerm | Segment::ConditionalLineTerm(_) => {
+                line_width = 0;
+                Some(Segment::LineTerm)
+            }
+            Segment::Fill(_) => Some(segment),
+            _ => {
+                line_width += segment.width_graphemes();
+                Some(segment)
+            }
+        })
+        .collect()
+}
+
+/// The OSC 133 marks around the prompt with `semantic_prompt`, for terminals with
+/// shell integration. The init scripts mark the output of commands.
+const PROMPT_START_MARK: &str = "\u{1b}]133;A\u{7}";
+const PROMPT_END_MARK: &str = "\u{1b}]133;B\u{7}";
+
+/// Renders `title_format`, or `title_format_ssh` in SSH sessions, as plain text
+/// for the window title
+///
+/// Control characters are left out, as they could end the title sequence early.
+fn render_title<F>(context: &Context, module_segments: F) -> Option<String>
+where
+    F: Fn(&str) -> Vec<Segment> + Sync,
+{
+    let config = &context.root_config;
+    let is_ssh = modules::is_ssh_session(context);
+    let (key, format) = if is_ssh && !config.title_format_ssh.is_empty() {
+        ("title_format_ssh", &config.title_format_ssh)
+    } else {
+        ("title_format", &config.title_format)
+    };
+
+    let segments = StringFormatter::new(format).and_then(|formatter| {
+        formatter
+            .map_variables_to_segments(|module| {
+                if context.is_module_disabled_in_config(module) {
+                    None
+                } else {
+                    Some(Ok(module_segments(module)))
+                }
+            })
+            .parse(None, Some(context))
+    });
+    match segments {
+        Ok(segments) => Some(
+            segments
+                .iter()
+                .filter_map(|segment| match segment {
+                    Segment::Text(text) => Some(text.value.as_str()),
+                    _ => None,
+                })
+                .flat_map(str::chars)
+                .filter(|c| !c.is_control())
+                .collect(),
+        ),
+        Err(error) => {
+            log::warn!("Error in `{}`:\n{}", key, error);
+            None
+        }
+    }
+}
+
+/// Modules that are never dropped to fit the prompt in `max_prompt_width`
+const UNDROPPABLE_MODULES: &[&str] = &["character", "line_break"];
+
+/// The width that the lines of the prompt have to fit in, if limited
+fn max_prompt_width(context: &Context) -> Option<usize> {
+    match context.root_config.max_prompt_width.as_ref()? {
+        Either::First(width) => Some(*width),
+        Either::Second(width) if width == "auto" => Some(context.width),
+        Either::Second(width) => {
+            log::warn!(
+                "Invalid `max_prompt_width` {:?}, expected a number or `auto`",
+                width
+            );
+            None
+        }
+    }
+}
+
+/// The `priority` of a module, where modules with lower priorities are dropped
+/// first to fit the prompt in `max_prompt_width`
+fn module_priority(context: &Context, module: &str) -> i64 {
+    let config = match module.strip_prefix("custom.") {
+        Some(custom) => context.config.get_custom_module_config(custom),
+        None => context.config.get_module_config(module),
+    };
+    config
+        .and_then(|config| config.get("priority")?.as_integer())
+        .unwrap_or(0)
+}
+
+/// The number of columns by which the lines of the prompt exceed `max_width`
+///
+/// Fills are left out, as they only take up the space that is left.
+fn prompt_overflow(segments: &[Segment], max_width: usize) -> usize {
+    let mut overflow = 0;
+    let mut line_width: usize = 0;
+    for segment in segments.iter().chain(iter::once(&Segment::LineTerm)) {
+        match segment {
+            Segment::LineTerm | Segment::ConditionalLineTerm(_) => {
+                overflow += line_width.saturating_sub(max_width);
+                line_width = 0;
+            }
+            Segment::Fill(_) => {}
+            _ => line_width += segment.width_graphemes(),
+        }
+    }
+    overflow
+}
+
+/// Lays a single gradient over the whole prompt if `[gradient]` is set to
+/// `scope = "line"`
+fn apply_line_gradient(context: &Context, segments: Vec<Segment>) -> Vec<Segment> {
+    let config = match modules::gradient::load_config(context.config.get_module_config("gradient"))
+    {
+        GradientTable::Modules(config) => config,
+        GradientTable::Directory(_) => return segments,
+    };
+    match config.scope {
+        "line" => gradient::gradientify_segments(
+            context,
+            &segments,
+            &gradient::build_gradient(&config.gradient),
+            &config.gradient,
+        ),
+        "module" => segments,
+        scope => {
+            log::warn!(
+                "Unknown gradient scope `{}`, expected one of `module` or `line`",
+                scope
+            );
+            segments
+        }
+    }
+}
+
 pub fn module(module_name: &str, args: Properties) {
     let context = Context::new(args, Target::Main);
     let module = get_module(module_name, context).unwrap_or_default();
@@ -146,32 +387,80 @@ pub fn get_module(module_name: &str, context: Context) -> Option<String> {
     modules::handle(module_name, &context).map(|m| m.to_string())
 }
 
-pub fn timings(args: Properties) {
-    let context = Context::new(args, Target::Main);
+/// The output format of `starship timings`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TimingsFormat {
+    Text,
+    Json,
+}
+
+/// The order of the modules in `starship timings`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum TimingsSort {
+    /// Slowest first
+    Duration,
+    Name,
+}
+
+/// The timing of a module in `starship timings --format json`
+#[derive(Serialize)]
+struct JsonTiming<'a> {
+    module: &'a str,
+    duration_us: u128,
+    rendered_len: usize,
+}
+
+pub fn timings(args: Properties, format: TimingsFormat, sort: TimingsSort, threshold: Option<u64>) {
+    let context = PromptContext::new(Context::new(args, Target::Main));
 
     struct ModuleTiming {
         name: String,
         name_len: usize,
         value: String,
+        value_len: usize,
         duration: Duration,
+        duration_text: String,
         duration_len: usize,
     }
 
     let mut modules = compute_modules(&context)
         .iter()
         .filter(|module| !module.is_empty() || module.duration.as_millis() > 0)
+        .filter(|module| {
+            threshold.map_or(true, |threshold| {
+                module.duration.as_millis() >= threshold.into()
+            })
+        })
         .map(|module| ModuleTiming {
             name: String::from(module.get_name().as_str()),
             name_len: module.get_name().width_graphemes(),
             value: nu_ansi_term::AnsiStrings(&module.ansi_strings())
                 .to_string()
                 .replace('\n', "\\n"),
+            value_len: module.get_segments().join("").width_graphemes(),
             duration: module.duration,
-            duration_len: format_duration(&module.duration).width_graphemes(),
+            duration_text: format_module_duration(module),
+            duration_len: format_module_duration(module).width_graphemes(),
         })
         .collect::<Vec<ModuleTiming>>();
 
-    modules.sort_by(|a, b| b.duration.cmp(&a.duration));
+    match sort {
+        TimingsSort::Duration => modules.sort_by(|a, b| b.duration.cmp(&a.duration)),
+        TimingsSort::Name => modules.sort_by(|a, b| a.name.cmp(&b.name)),
+    }
+
+    if format == TimingsFormat::Json {
+        let timings: Vec<_> = modules
+            .iter()
+            .map(|timing| JsonTiming {
+                module: &timing.name,
+                duration_us: timing.duration.as_micros(),
+                rendered_len: timing.value_len,
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&timings).unwrap());
+        return;
+    }
 
     let max_name_width = modules.iter().map(|i| i.name_len).max().unwrap_or(0);
     let max_duration_width = modules.iter().map(|i| i.duration_len).max().unwrap_or(0);
@@ -186,20 +475,21 @@ pub fn timings(args: Properties) {
             timing.name,
             " ".repeat(max_name_width - (timing.name_len)),
             " ".repeat(max_duration_width - (timing.duration_len)),
-            format_duration(&timing.duration),
+            timing.duration_text,
             timing.value
         );
     }
 }
 
-pub fn explain(args: Properties) {
-    let context = Context::new(args, Target::Main);
+pub fn explain(args: Properties, verbose: bool) {
+    let context = PromptContext::new(Context::new(args, Target::Main));
 
     struct ModuleInfo {
         value: String,
         value_len: usize,
         desc: String,
         duration: String,
+        details: Vec<String>,
     }
 
     static DONT_PRINT: &[&str] = &["line_break"];
@@ -217,6 +507,11 @@ pub fn explain(args: Properties) {
                     + format_duration(&module.duration).width_graphemes(),
                 desc: module.get_description().clone(),
                 duration: format_duration(&module.duration),
+                details: if verbose {
+                    module_details(&module)
+                } else {
+                    Vec::new()
+                },
             }
         })
         .collect::<Vec<ModuleInfo>>();
@@ -283,32 +578,162 @@ pub fn explain(args: Properties) {
                 " ".repeat(max_module_width - info.value_len),
                 info.desc,
             );
+        }
+        for detail in info.details {
+            println!("     {}", detail);
+        }
+    }
+}
+
+/// The style of each segment of a module and the gradient it was colored with,
+/// as shown by `starship explain --verbose`
+fn module_details(module: &Module) -> Vec<String> {
+    let mut details: Vec<String> = module
+        .segments
+        .iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(format!("{:?}: {}", text.value, style_string(text.style))),
+            Segment::Fill(fill) => Some(format!(
+                "fill {:?}: {}",
+                fill.value,
+                style_string(fill.style)
+            )),
+            Segment::Escape(sequence) => Some(format!("escape {:?}", sequence)),
+            Segment::LineTerm | Segment::ConditionalLineTerm(_) => None,
+        })
+        .collect();
+
+    if let Some(info) = &module.gradient {
+        let rgb = |color: Option<(u8, u8, u8)>| {
+            color.map_or_else(
+                || "none".to_string(),
+                |(r, g, b)| format!("#{:02x}{:02x}{:02x}", r, g, b),
+            )
         };
+        details.push(format!(
+            "gradient: {}, domain: {:?}, interpolation: {}, first: {}, last: {}",
+            info.gradient.join(","),
+            info.domain,
+            info.interpolation,
+            rgb(info.first_color),
+            rgb(info.last_color),
+        ));
+    }
+    details
+}
+
+/// A style in the syntax of style strings, e.g. `bold fg:#ff0000`
+fn style_string(style: Option<nu_ansi_term::Style>) -> String {
+    let style = match style {
+        Some(style) if style != nu_ansi_term::Style::default() => style,
+        _ => return "none".to_string(),
+    };
+
+    let mut parts = Vec::new();
+    for (enabled, name) in [
+        (style.is_bold, "bold"),
+        (style.is_dimmed, "dimmed"),
+        (style.is_italic, "italic"),
+        (style.is_underline, "underline"),
+        (style.is_blink, "blink"),
+        (style.is_reverse, "inverted"),
+        (style.is_hidden, "hidden"),
+        (style.is_strikethrough, "strikethrough"),
+    ] {
+        if enabled {
+            parts.push(name.to_string());
+        }
+    }
+    if let Some(color) = style.foreground {
+        parts.push(format!("fg:{}", color_string(color)));
+    }
+    if let Some(color) = style.background {
+        parts.push(format!("bg:{}", color_string(color)));
     }
+    parts.join(" ")
 }
 
-fn compute_modules<'a>(context: &'a Context) -> Vec<Module<'a>> {
-    let mut prompt_order: Vec<Module<'a>> = Vec::new();
+fn color_string(color: nu_ansi_term::Color) -> String {
+    use nu_ansi_term::Color;
+
+    match color {
+        Color::Black => "black".to_string(),
+        Color::Red => "red".to_string(),
+        Color::Green => "green".to_string(),
+        Color::Yellow => "yellow".to_string(),
+        Color::Blue => "blue".to_string(),
+        Color::Purple | Color::Magenta => "purple".to_string(),
+        Color::Cyan => "cyan".to_string(),
+        Color::White => "white".to_string(),
+        Color::DarkGray => "bright-black".to_string(),
+        Color::LightRed => "bright-red".to_string(),
+        Color::LightGreen => "bright-green".to_string(),
+        Color::LightYellow => "bright-yellow".to_string(),
+        Color::LightBlue => "bright-blue".to_string(),
+        Color::LightPurple | Color::LightMagenta => "bright-purple".to_string(),
+        Color::LightCyan => "bright-cyan".to_string(),
+        Color::LightGray => "bright-white".to_string(),
+        Color::Fixed(n) => n.to_string(),
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        Color::Default => "default".to_string(),
+    }
+}
 
+fn compute_modules(prompt_context: &PromptContext) -> Vec<Module> {
+    let context: &Context = prompt_context;
     let (_formatter, modules) = load_formatter_and_modules(context);
 
-    for module in &modules {
-        // Manually add all modules if `$all` is encountered
-        if module == "all" {
-            for module in all_modules_uniq(&modules) {
-                let modules = handle_module(&module, context, &modules);
-                prompt_order.extend(modules);
+    let prompt_order: Vec<String> = modules
+        .iter()
+        .flat_map(|module| {
+            // Manually add all modules if `$all` is encountered
+            if module == "all" {
+                all_modules_uniq(&modules)
+            } else {
+                vec![module.clone()]
             }
-        } else {
-            let modules = handle_module(module, context, &modules);
-            prompt_order.extend(modules);
-        }
-    }
+        })
+        .collect();
+
+    // Each module measures its own duration, so the timings stay per module
+    with_parallelism(context, || {
+        prompt_order
+            .par_iter()
+            .flat_map(|module| handle_module_with_timeout(module, prompt_context, &modules))
+            .collect()
+    })
+}
 
-    prompt_order
+/// Runs `op` on a thread pool with `parallelism` threads, or on the global
+/// pool if the option is unset
+fn with_parallelism<R, OP>(context: &Context, op: OP) -> R
+where
+    R: Send,
+    OP: FnOnce() -> R + Send,
+{
+    with_threads(context.root_config.parallelism, op)
+}
+
+/// Runs `op` on a thread pool with `threads` threads, or on the current pool
+/// if `threads` is 0
+fn with_threads<R, OP>(threads: usize, op: OP) -> R
+where
+    R: Send,
+    OP: FnOnce() -> R + Send,
+{
+    match threads {
+        0 => op(),
+        threads => match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            Ok(pool) => pool.install(op),
+            Err(error) => {
+                log::warn!("Unable to create a pool of {} threads: {}", threads, error);
+                op()
+            }
+        },
+    }
 }
 
-fn handle_module<'a>(
+pub(crate) fn handle_module<'a>(
     module: &str,
     context: &'a Context,
     module_list: &BTreeSet<String>,
@@ -331,14 +756,21 @@ fn handle_module<'a>(
     } else if module == "custom" {
         // Write out all custom modules, except for those that are explicitly set
         if let Some(custom_modules) = context.config.get_custom_modules() {
-            let custom_modules = custom_modules.iter().filter_map(|(custom_module, config)| {
-                if should_add_implicit_custom_module(custom_module, config, module_list) {
-                    modules::custom::module(custom_module, context)
-                } else {
-                    None
-                }
-            });
-            modules.extend(custom_modules);
+            let custom_modules: Vec<&String> = custom_modules
+                .iter()
+                .filter(|(custom_module, config)| {
+                    should_add_implicit_custom_module(custom_module, config, module_list)
+                })
+                .map(|(custom_module, _)| custom_module)
+                .collect();
+            // Their commands are independent, so they run concurrently, in the order
+            // they were defined
+            modules.extend(with_threads(context.root_config.custom_parallelism, || {
+                custom_modules
+                    .par_iter()
+                    .filter_map(|custom_module| modules::custom::module(custom_module, context))
+                    .collect::<Vec<_>>()
+            }));
         }
     } else if let Some(module) = module.strip_prefix("custom.") {
         // Write out a custom module if it isn't disabled (and it exists...)
@@ -368,6 +800,109 @@ fn handle_module<'a>(
     modules
 }
 
+/// The context a prompt is rendered with
+///
+/// Modules that time out keep running on their own thread, so if any module has
+/// a timeout the context is leaked to outlive the prompt. Otherwise it's owned.
+enum PromptContext {
+    Owned(Box<Context<'static>>),
+    Shared(&'static Context<'static>),
+}
+
+impl PromptContext {
+    fn new(context: Context<'static>) -> Self {
+        let config = &context.root_config;
+        let has_timeouts = config.module_timeout_ms != 0
+            || config.module_timeouts.values().any(|timeout| *timeout != 0);
+        if has_timeouts {
+            PromptContext::Shared(Box::leak(Box::new(context)))
+        } else {
+            PromptContext::Owned(Box::new(context))
+        }
+    }
+}
+
+impl Deref for PromptContext {
+    type Target = Context<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            PromptContext::Owned(context) => context,
+            PromptContext::Shared(context) => context,
+        }
+    }
+}
+
+/// Computes a module like `handle_module`, but stops waiting for it once it
+/// exceeds its `module_timeout_ms`
+///
+/// The abandoned module keeps running on its worker thread and is replaced by
+/// `timeout_symbol`.
+fn handle_module_with_timeout<'a>(
+    module: &str,
+    prompt_context: &'a PromptContext,
+    module_list: &BTreeSet<String>,
+) -> Vec<Module<'a>> {
+    let config = &prompt_context.root_config;
+    let timeout = config
+        .module_timeouts
+        .get(module)
+        .unwrap_or(&config.module_timeout_ms);
+    let (context, timeout) = match (prompt_context, timeout) {
+        (PromptContext::Shared(context), timeout) if *timeout != 0 => {
+            (*context, Duration::from_millis(*timeout))
+        }
+        _ => return handle_module(module, prompt_context, module_list),
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let name = module.to_string();
+    let worker_module_list = module_list.clone();
+    let worker = thread::Builder::new()
+        .name(format!("module-{}", module))
+        .spawn(move || {
+            // The receiver is gone if the module timed out
+            let _ = sender.send(handle_module(&name, context, &worker_module_list));
+        });
+    if let Err(error) = worker {
+        log::warn!(
+            "Unable to spawn a thread for module `{}`: {}",
+            module,
+            error
+        );
+        return handle_module(module, context, module_list);
+    }
+
+    match receiver.recv_timeout(timeout) {
+        Ok(modules) => modules,
+        Err(_) => {
+            log::warn!("Module `{}` timed out after {:?}", module, timeout);
+            let mut timed_out = timed_out_module(module, context);
+            timed_out.duration = timeout;
+            timed_out.timed_out = true;
+            match StringFormatter::new(&config.timeout_symbol)
+                .and_then(|formatter| formatter.parse(None, Some(context)))
+            {
+                Ok(segments) => timed_out.set_segments(segments),
+                Err(error) => log::warn!("Error in `timeout_symbol`:\n{}", error),
+            }
+            vec![timed_out]
+        }
+    }
+}
+
+/// An empty module with the name the module would have had
+fn timed_out_module<'a>(module: &str, context: &'a Context) -> Module<'a> {
+    match module.strip_prefix("custom.") {
+        Some(name) => {
+            let config = context.config.get_custom_module_config(name);
+            let description = CustomConfig::try_load(config).description;
+            Module::new(name, description, config)
+        }
+        None => context.new_module(module),
+    }
+}
+
 fn should_add_implicit_custom_module(
     custom_module: &str,
     config: &toml::Value,
@@ -390,6 +925,15 @@ fn should_add_implicit_custom_module(
         .unwrap_or(false)
 }
 
+/// The duration of a module for `starship timings`, marking timed out ones
+fn format_module_duration(module: &Module) -> String {
+    if module.timed_out {
+        format!("{} (timed out)", format_duration(&module.duration))
+    } else {
+        format_duration(&module.duration)
+    }
+}
+
 pub fn format_duration(duration: &Duration) -> String {
     let milis = duration.as_millis();
     if milis == 0 {
@@ -411,19 +955,43 @@ fn all_modules_uniq(module_list: &BTreeSet<String>) -> Vec<String> {
     prompt_order
 }
 
+/// The modules that can be used in `continuation_prompt`, as the shells only
+/// render it once at startup
+pub const CONTINUATION_MODULES: &[&str] = &["character"];
+
+/// The variables of a `continuation_prompt` that aren't `CONTINUATION_MODULES`
+pub fn unsupported_continuation_modules(variables: &BTreeSet<String>) -> Vec<String> {
+    variables
+        .iter()
+        .filter(|variable| !CONTINUATION_MODULES.contains(&variable.as_str()))
+        .cloned()
+        .collect()
+}
+
 /// Load the correct formatter for the context (ie left prompt or right prompt)
 /// and the list of all modules used in a format string
 fn load_formatter_and_modules<'a>(context: &'a Context) -> (StringFormatter<'a>, BTreeSet<String>) {
     let config = &context.root_config;
 
-    let lformatter = StringFormatter::new(&config.format);
-    let rformatter = StringFormatter::new(&config.right_format);
+    let ((format_key, format), (right_format_key, right_format)) = if context.transient {
+        (
+            ("transient_format", &config.transient_format),
+            ("transient_right_format", &config.transient_right_format),
+        )
+    } else {
+        (
+            ("format", &config.format),
+            ("right_format", &config.right_format),
+        )
+    };
+    let lformatter = StringFormatter::new(format);
+    let rformatter = StringFormatter::new(right_format);
     let cformatter = StringFormatter::new(&config.continuation_prompt);
     if lformatter.is_err() {
-        log::error!("Error parsing `format`")
+        log::error!("Error parsing `{}`", format_key)
     }
     if rformatter.is_err() {
-        log::error!("Error parsing `right_format`")
+        log::error!("Error parsing `{}`", right_format_key)
     }
     if cformatter.is_err() {
         log::error!("Error parsing `continuation_prompt`")
@@ -436,10 +1004,26 @@ fn load_formatter_and_modules<'a>(context: &'a Context) -> (StringFormatter<'a>,
                 modules.extend(lf.get_variables());
                 modules.extend(rf.get_variables());
             }
+            if modules.contains("gradient") {
+                // Modules wrapped by the gradient module are part of the prompt too
+                modules.extend(modules::gradient::wrapped_modules(context));
+            }
             match context.target {
                 Target::Main => (lf, modules),
                 Target::Right => (rf, modules),
-                Target::Continuation => (cf, modules),
+                Target::Continuation => {
+                    let unsupported = unsupported_continuation_modules(&cf.get_variables());
+                    if unsupported.is_empty() {
+                        (cf, modules)
+                    } else {
+                        log::error!(
+                            "`continuation_prompt` can only contain the modules {:?}, but contains {:?}",
+                            CONTINUATION_MODULES,
+                            unsupported
+                        );
+                        (StringFormatter::raw(">"), BTreeSet::new())
+                    }
+                }
             }
         }
         _ => (StringFormatter::raw(">"), BTreeSet::new()),
@@ -452,6 +1036,45 @@ pub fn print_schema() {
     println!("{}", serde_json::to_string_pretty(&schema).unwrap());
 }
 
+/// Prints `text` and a swatch of `blocks` blocks colored with the gradient of
+/// one of the gradient modules, as configured
+pub fn gradient_preview(module_name: &str, text: &str, blocks: usize) {
+    let context = Context::new(Properties::default(), Target::Main);
+    match preview_gradient(&context, module_name, text, blocks) {
+        Some(preview) => print!("{}", preview),
+        None => {
+            eprintln!(
+                "Error: `{}` is not a gradient module, expected one of: {}",
+                module_name,
+                modules::gradient::GRADIENT_MODULES.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+fn preview_gradient(
+    context: &Context,
+    module_name: &str,
+    text: &str,
+    blocks: usize,
+) -> Option<String> {
+    let (gradient, config) = modules::gradient::module_gradient(context, module_name)?;
+
+    let mut preview = String::new();
+    for line in [text.to_string(), "█".repeat(blocks)] {
+        let segments = gradient::gradientify_segments(
+            context,
+            &Segment::from_text(None, line),
+            &gradient,
+            &config,
+        );
+        let strings: Vec<_> = segments.iter().map(Segment::ansi_string).collect();
+        writeln!(preview, "{}", AnsiStrings(&strings)).unwrap();
+    }
+    Some(preview)
+}
+
 #[derive(Clone, Debug)]
 pub struct Preset(pub &'static str);
 
@@ -486,6 +1109,7 @@ mod test {
     use super::*;
     use crate::config::StarshipConfig;
     use crate::test::default_context;
+    use nu_ansi_term::Color;
 
     #[test]
     fn right_prompt() {
@@ -521,6 +1145,357 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn transient_prompt() {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [character]
+                format = "> "
+            }),
+        };
+        context.root_config.format = "full $character".to_string();
+        context.root_config.add_newline = false;
+        context.transient = true;
+
+        let expected = String::from("> ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn transient_right_prompt() {
+        let mut context = default_context();
+        context.root_config.right_format = "full".to_string();
+        context.root_config.transient_right_format = "transient".to_string();
+        context.target = Target::Right;
+        context.transient = true;
+
+        let expected = String::from("transient");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn continuation_prompt_with_character() {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [character]
+                continuation_symbol = "…"
+            }),
+        };
+        context.root_config.continuation_prompt = "$character| ".to_string();
+        context.target = Target::Continuation;
+
+        let expected = String::from("… | ");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn continuation_prompt_rejects_other_modules() {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [directory]
+                format = "dir"
+            }),
+        };
+        context.root_config.continuation_prompt = "$directory$character".to_string();
+        context.target = Target::Continuation;
+
+        let expected = String::from(">");
+        let actual = get_prompt(context);
+        assert_eq!(expected, actual);
+    }
+
+    /// Renders a first line of 50 columns followed by a line break that needs 20
+    /// columns to be left
+    fn render_with_line_break(width: usize, line_break: toml::Value) -> String {
+        let mut context = default_context();
+        let mut config = toml::value::Table::new();
+        config.insert("line_break".to_string(), line_break);
+        context.config = StarshipConfig {
+            config: Some(toml::Value::Table(config)),
+        };
+        context.root_config.format = format!("{}$line_break>", "x".repeat(50));
+        context.root_config.add_newline = false;
+        context.width = width;
+        get_prompt(context)
+    }
+
+    #[test]
+    fn conditional_line_break_on_narrow_terminal() {
+        let actual = render_with_line_break(60, toml::toml! { min_width = 20 });
+        assert_eq!(format!("{}\n>", "x".repeat(50)), actual);
+    }
+
+    #[test]
+    fn conditional_line_break_on_wide_terminal() {
+        let actual = render_with_line_break(200, toml::toml! { min_width = 20 });
+        assert_eq!(format!("{}>", "x".repeat(50)), actual);
+    }
+
+    #[test]
+    fn conditional_line_break_at_the_margin() {
+        // Exactly `min_width` columns are left
+        let actual = render_with_line_break(70, toml::toml! { min_width = 20 });
+        assert_eq!(format!("{}>", "x".repeat(50)), actual);
+    }
+
+    #[test]
+    fn line_break_without_min_width() {
+        let actual = render_with_line_break(200, toml::value::Table::new().into());
+        assert_eq!(format!("{}\n>", "x".repeat(50)), actual);
+    }
+
+    #[test]
+    fn conditional_line_break_measures_its_own_line() {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [line_break]
+                min_width = 20
+            }),
+        };
+        // Only the 30 columns after the first line break count
+        context.root_config.format = format!("{}\n{}$line_break>", "x".repeat(50), "y".repeat(30));
+        context.root_config.add_newline = false;
+        context.width = 60;
+        let expected = format!("{}\n{}>", "x".repeat(50), "y".repeat(30));
+        assert_eq!(expected, get_prompt(context));
+    }
+
+    fn render_with_gradient_scope(scope: &str) -> String {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [gradient]
+                scope = scope
+                gradient = "#ff0000,#0000ff"
+                [character]
+                format = "[>>](bold)"
+                [fill]
+                symbol = "-"
+            }),
+        };
+        context.root_config.format = "$character$fill$character".to_string();
+        context.root_config.add_newline = false;
+        context.width = 20;
+        get_prompt(context)
+    }
+
+    #[test]
+    fn line_gradient() {
+        let ansi_escape = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        let per_module = render_with_gradient_scope("module");
+        let line = render_with_gradient_scope("line");
+
+        let expected = format!(">>{}>>", "-".repeat(16));
+        assert_eq!(expected, ansi_escape.replace_all(&per_module, ""));
+        assert_eq!(expected, ansi_escape.replace_all(&line, ""));
+
+        // Every column, including those taken by the fill, gets its own color
+        let colors: Vec<_> = regex::Regex::new("38;2;([0-9;]*)m")
+            .unwrap()
+            .captures_iter(&line)
+            .map(|captures| captures[1].to_owned())
+            .collect();
+        assert_eq!(20, colors.len());
+        assert_eq!("255;0;0", colors[0]);
+        assert_eq!("0;0;255", colors[19]);
+        assert!(!per_module.contains("38;2;"));
+    }
+
+    #[test]
+    fn unknown_gradient_scope_is_ignored() {
+        assert_eq!(
+            render_with_gradient_scope("module"),
+            render_with_gradient_scope("sideways")
+        );
+    }
+
+    #[cfg(not(windows))]
+    fn context_with_sleeping_modules(dir: &std::path::Path) -> Context<'static> {
+        let mut context = default_context();
+        context.current_dir = dir.to_path_buf();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [custom.short]
+                command = "sleep 0.4; echo short"
+                when = true
+                shell = ["sh"]
+                format = "$output"
+                ignore_timeout = true
+                [custom.long]
+                command = "sleep 0.6; echo long"
+                when = true
+                shell = ["sh"]
+                format = "$output"
+                ignore_timeout = true
+            }),
+        };
+        context.root_config.format = "${custom.short}${custom.long}".to_string();
+        context.root_config.add_newline = false;
+        context.root_config.parallelism = 2;
+        context
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn modules_render_in_parallel() {
+        let dir = tempfile::tempdir().unwrap();
+        let start = std::time::Instant::now();
+        let actual = get_prompt(context_with_sleeping_modules(dir.path()));
+        let elapsed = start.elapsed();
+
+        assert_eq!("shortlong", actual);
+        // Close to the slower module, not the sum of both
+        assert!(elapsed < Duration::from_millis(900), "{elapsed:?}");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn implicit_custom_modules_render_in_parallel() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = context_with_sleeping_modules(dir.path());
+        context.root_config.format = "$custom".to_string();
+        context.root_config.parallelism = 0;
+        context.root_config.custom_parallelism = 2;
+
+        let start = std::time::Instant::now();
+        let actual = get_prompt(context);
+        let elapsed = start.elapsed();
+
+        // In the order the modules are defined, not the order they finished in
+        assert_eq!("shortlong", actual);
+        assert!(elapsed < Duration::from_millis(900), "{elapsed:?}");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn timings_are_per_module() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = PromptContext::new(context_with_sleeping_modules(dir.path()));
+        let start = std::time::Instant::now();
+        let modules = compute_modules(&context);
+        let elapsed = start.elapsed();
+
+        let durations: Vec<_> = modules
+            .iter()
+            .map(|module| (module.get_name().as_str(), module.duration))
+            .collect();
+        assert_eq!(2, durations.len());
+        assert_eq!("long", durations[0].0);
+        assert_eq!("short", durations[1].0);
+        assert!(durations[0].1 >= Duration::from_millis(600));
+        assert!(durations[1].1 >= Duration::from_millis(400));
+        assert!(durations[1].1 < Duration::from_millis(600));
+        assert!(elapsed < Duration::from_millis(900), "{elapsed:?}");
+    }
+
+    #[cfg(not(windows))]
+    fn context_with_hanging_module(dir: &std::path::Path) -> Context<'static> {
+        let mut context = default_context();
+        context.current_dir = dir.to_path_buf();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [custom.fast]
+                command = "echo fast"
+                when = true
+                shell = ["sh"]
+                format = "$output"
+                [custom.hang]
+                command = "sleep 5; echo hang"
+                when = true
+                shell = ["sh"]
+                format = "$output"
+                ignore_timeout = true
+            }),
+        };
+        context.root_config.format = "${custom.fast}${custom.hang}".to_string();
+        context.root_config.add_newline = false;
+        context
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn module_timeout_renders_timeout_symbol() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = context_with_hanging_module(dir.path());
+        context.root_config.module_timeout_ms = 100;
+        context.root_config.timeout_symbol = "[…](red)".to_string();
+
+        let start = std::time::Instant::now();
+        let actual = get_prompt(context);
+        let elapsed = start.elapsed();
+
+        let expected = format!("fast{}", Color::Red.paint("…"));
+        assert_eq!(expected, actual);
+        assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn per_module_timeout_overrides_global() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = context_with_hanging_module(dir.path());
+        context.root_config.module_timeout_ms = 10_000;
+        context
+            .root_config
+            .module_timeouts
+            .insert("custom.hang".to_string(), 100);
+
+        let start = std::time::Instant::now();
+        let actual = get_prompt(context);
+        let elapsed = start.elapsed();
+
+        assert_eq!("fast", actual);
+        assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    }
+
+    #[test]
+    fn context_is_only_shared_with_timeouts() {
+        let context = PromptContext::new(default_context());
+        assert!(matches!(context, PromptContext::Owned(_)));
+
+        let mut context = default_context();
+        context
+            .root_config
+            .module_timeouts
+            .insert("character".to_string(), 100);
+        let context = PromptContext::new(context);
+        assert!(matches!(context, PromptContext::Shared(_)));
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn timed_out_modules_are_marked() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = context_with_hanging_module(dir.path());
+        context
+            .root_config
+            .module_timeouts
+            .insert("custom.hang".to_string(), 100);
+        let context = PromptContext::new(context);
+
+        let modules = compute_modules(&context);
+        let hang = modules
+            .iter()
+            .find(|module| module.get_name() == "hang")
+            .unwrap();
+        let fast = modules
+            .iter()
+            .find(|module| module.get_name() == "fast")
+            .unwrap();
+
+        assert!(hang.timed_out);
+        assert!(hang.is_empty());
+        assert_eq!("100ms (timed out)", format_module_duration(hang));
+        assert!(!fast.timed_out);
+    }
+
     #[test]
     fn preset_list_returns_one_or_more_items() {
         assert!(preset_list().trim().split('\n').count() > 0);
@@ -534,9 +1509,398 @@ mod test {
             .for_each(|v| preset_command(Some(v.clone()), false));
     }
 
+    fn render_gradient_for_shell(shell: Shell) -> String {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [gradient_username]
+                show_always = true
+                format = "[$user]($style)!"
+                style_user = "bold"
+                gradient = "#ff0000,#0000ff"
+                color_mode = "truecolor"
+            }),
+        };
+        context.env.insert("USER", "abc".to_string());
+        context.root_config.format = "$gradient_username".to_string();
+        context.root_config.add_newline = false;
+        context.shell = shell;
+        get_prompt(context)
+    }
+
+    #[test]
+    fn gradient_escapes_are_wrapped_per_shell() {
+        let expected = |begin: &str, end: &str| {
+            format!(
+                "{b}\u{1b}[1;38;2;255;0;0m{e}a{b}\u{1b}[38;2;103;0;23m{e}b{b}\u{1b}[38;2;23;0;103m{e}c\
+                 {b}\u{1b}[0m\u{1b}[38;2;0;0;255m{e}!{b}\u{1b}[0m{e}",
+                b = begin,
+                e = end
+            )
+        };
+        assert_eq!(
+            expected("\\[", "\\]"),
+            render_gradient_for_shell(Shell::Bash)
+        );
+        assert_eq!(expected("%{", "%}"), render_gradient_for_shell(Shell::Zsh));
+        // tcsh escapes the `!` on its own
+        assert_eq!(
+            expected("%{", "%}").replace('!', "\\!"),
+            render_gradient_for_shell(Shell::Tcsh)
+        );
+        // fish measures the prompt itself, after the workaround that clears the screen
+        assert_eq!(
+            format!("\u{1b}[J{}", expected("", "")),
+            render_gradient_for_shell(Shell::Fish)
+        );
+        assert_eq!(expected("", ""), render_gradient_for_shell(Shell::Unknown));
+    }
+
+    #[test]
+    fn osc7_percent_encodes_the_path() {
+        assert_eq!(
+            "\u{1b}]7;file://host/home/user\u{1b}\\",
+            osc7_sequence("host", Path::new("/home/user"))
+        );
+        assert_eq!(
+            "\u{1b}]7;file://host/tmp/my%20dir/%E6%97%A5%E6%9C%AC/a%23b%3F%25/v1.0_x~y-z\u{1b}\\",
+            osc7_sequence("host", Path::new("/tmp/my dir/日本/a#b?%/v1.0_x~y-z"))
+        );
+    }
+
+    fn title_context(
+        dir: &std::path::Path,
+        shell: Shell,
+        env: &[(&'static str, &str)],
+    ) -> Context<'static> {
+        let mut context = default_context();
+        context.current_dir = dir.to_path_buf();
+        context.config = StarshipConfig {
+            config: Some(
+                toml::from_str(
+                    r#"
+                    [custom.dir]
+                    when = true
+                    format = "[~/src](bold cyan)"
+                    [custom.branch]
+                    when = true
+                    format = "on [main](purple)"
+                    [custom.control]
+                    when = true
+                    format = "a\u0007\u001b\\]0;b\nc"
+                    "#,
+                )
+                .unwrap(),
+            ),
+        };
+        for (key, value) in env {
+            context.env.insert(key, value.to_string());
+        }
+        context.shell = shell;
+        context.root_config.format = "${custom.dir}>".to_string();
+        context.root_config.add_newline = false;
+        context.root_config.set_title = true;
+        context.root_config.title_format = "${custom.dir} — ${custom.branch}".to_string();
+        context.root_config.title_format_ssh = "ssh ${custom.dir} ${custom.control}".to_string();
+        context
+    }
+
+    fn render_title_prompt(shell: Shell, env: &[(&'static str, &str)]) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        get_prompt(title_context(dir.path(), shell, env))
+    }
+
+    #[test]
+    fn title_is_the_plain_rendering_of_title_format() {
+        let prompt = "\u{1b}[1;36m~/src\u{1b}[0m>";
+        assert_eq!(
+            format!("\u{1b}]0;~/src — on main\u{7}{}", prompt),
+            render_title_prompt(Shell::Unknown, &[])
+        );
+        assert_eq!(
+            format!("%{{\u{1b}]0;~/src — on main\u{7}\u{1b}[1;36m%}}~/src%{{\u{1b}[0m%}}>"),
+            render_title_prompt(Shell::Zsh, &[])
+        );
+    }
+
+    #[test]
+    fn title_format_ssh_strips_control_characters() {
+        let actual = render_title_prompt(Shell::Unknown, &[("SSH_TTY", "/dev/pts/0")]);
+        assert!(
+            actual.starts_with("\u{1b}]0;ssh ~/src a]0;bc\u{7}\u{1b}[1;36m"),
+            "{:?}",
+            actual
+        );
+    }
+
+    #[test]
+    fn title_format_ssh_follows_ssh_detection() {
+        struct SshdParent;
+
+        impl modules::ProcessTreeProvider for SshdParent {
+            fn ancestor_names(&self) -> Vec<String> {
+                vec!["bash".to_string(), "sshd".to_string()]
+            }
+
+            fn process_names(&self, _pid: u32) -> Vec<String> {
+                Vec::new()
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let render = |module: &str| {
+            let mut context = title_context(dir.path(), Shell::Unknown, &[]);
+            context.process_tree_provider = &SshdParent;
+            if let Some(config) = context
+                .config
+                .config
+                .as_mut()
+                .and_then(toml::Value::as_table_mut)
+            {
+                config.insert(
+                    module.to_string(),
+                    toml::toml! { ssh_detection = "process-tree" },
+                );
+            }
+            get_prompt(context)
+        };
+
+        assert!(render("hostname").starts_with("\u{1b}]0;ssh ~/src"));
+        assert!(render("username").starts_with("\u{1b}]0;ssh ~/src"));
+        assert!(render("directory").starts_with("\u{1b}]0;~/src — on main"));
+    }
+
+    #[test]
+    fn title_is_not_set_by_default() {
+        let mut context = default_context();
+        context.root_config.format = ">".to_string();
+        context.root_config.add_newline = false;
+        assert_eq!(">", get_prompt(context));
+    }
+
+    fn render_semantic_prompt(shell: Shell, target: Target) -> String {
+        let mut context = default_context();
+        context.target = target;
+        context.shell = shell;
+        context.root_config.format = ">".to_string();
+        context.root_config.right_format = "<".to_string();
+        context.root_config.add_newline = false;
+        context.root_config.semantic_prompt = true;
+        get_prompt(context)
+    }
+
+    #[test]
+    fn semantic_prompt_marks() {
+        assert_eq!(
+            "\\[\u{1b}]133;A\u{7}\\]>\\[\u{1b}]133;B\u{7}\\]",
+            render_semantic_prompt(Shell::Bash, Target::Main)
+        );
+        assert_eq!(
+            "%{\u{1b}]133;A\u{7}%}>%{\u{1b}]133;B\u{7}%}",
+            render_semantic_prompt(Shell::Zsh, Target::Main)
+        );
+        assert_eq!(
+            "\u{1b}[J\u{1b}]133;A\u{7}>\u{1b}]133;B\u{7}",
+            render_semantic_prompt(Shell::Fish, Target::Main)
+        );
+    }
+
+    #[test]
+    fn semantic_prompt_only_marks_the_main_prompt() {
+        assert_eq!("<", render_semantic_prompt(Shell::Zsh, Target::Right));
+    }
+
+    #[test]
+    fn semantic_prompt_is_disabled_by_default() {
+        let mut context = default_context();
+        context.shell = Shell::Zsh;
+        context.root_config.format = ">".to_string();
+        context.root_config.add_newline = false;
+        assert_eq!(">", get_prompt(context));
+    }
+
+    fn render_with_priorities(width: usize) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = default_context();
+        context.current_dir = dir.path().to_path_buf();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [character]
+                format = "> "
+                [fill]
+                symbol = " "
+                style = ""
+                [custom.a]
+                when = true
+                format = "aaaaaaaaaaaaaaaaaaaa"
+                priority = 5
+                [custom.b]
+                when = true
+                format = "bbbbbbbbbbbbbbbbbbbb"
+                priority = 1
+                [custom.c]
+                when = true
+                format = "cccccccccccccccccccc"
+                [custom.d]
+                when = true
+                format = "dddddddddd"
+                priority = 2
+            }),
+        };
+        context.root_config.format =
+            "${custom.a}${custom.b}${custom.c}$fill${custom.d}$character".to_string();
+        context.root_config.max_prompt_width = Some(Either::Second("auto".to_string()));
+        context.root_config.add_newline = false;
+        context.width = width;
+        get_prompt(context)
+    }
+
+    #[test]
+    fn prompt_within_max_width() {
+        let actual = render_with_priorities(200);
+        let expected = format!(
+            "{}{}{}{}{}> ",
+            "a".repeat(20),
+            "b".repeat(20),
+            "c".repeat(20),
+            " ".repeat(128),
+            "d".repeat(10)
+        );
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn lowest_priority_modules_are_dropped() {
+        // `custom.c` has the default priority of 0
+        let actual = render_with_priorities(60);
+        let expected = format!(
+            "{}{}{}{}> ",
+            "a".repeat(20),
+            "b".repeat(20),
+            " ".repeat(8),
+            "d".repeat(10)
+        );
+        assert_eq!(expected, actual);
+
+        let actual = render_with_priorities(40);
+        let expected = format!("{}{}{}> ", "a".repeat(20), " ".repeat(8), "d".repeat(10));
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn character_is_never_dropped() {
+        // Without any space left, the fill is a single symbol wide
+        assert_eq!(" > ", render_with_priorities(1));
+    }
+
+    #[test]
+    fn max_prompt_width_measures_each_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut context = default_context();
+        context.current_dir = dir.path().to_path_buf();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [character]
+                format = "> "
+                [custom.a]
+                when = true
+                format = "aaaaaaaaaa"
+                [custom.b]
+                when = true
+                format = "bbbbbbbbbb"
+                priority = 1
+            }),
+        };
+        context.root_config.format = "${custom.a}${custom.b}$line_break$character".to_string();
+        context.root_config.max_prompt_width = Some(Either::First(15));
+        context.root_config.add_newline = false;
+        context.width = 200;
+        let actual = get_prompt(context);
+        assert_eq!(format!("{}\n> ", "b".repeat(10)), actual);
+    }
+
+    #[test]
+    fn equal_priorities_drop_the_later_modules_first() {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(toml::toml! {
+                [username]
+                show_always = true
+                format = "$user"
+                [directory]
+                format = "$path"
+            }),
+        };
+        context.env.insert("USER", "abcdefghij".to_string());
+        context.current_dir = std::path::PathBuf::from("/");
+        context.root_config.format = "$directory$username".to_string();
+        context.root_config.max_prompt_width = Some(Either::First(10));
+        context.root_config.add_newline = false;
+        // `directory` comes after `username` in the default prompt order
+        assert_eq!("abcdefghij", get_prompt(context));
+    }
+
     #[test]
     #[cfg(feature = "config-schema")]
     fn print_schema_does_not_panic() {
         print_schema();
     }
+
+    fn gradient_username_details(config: toml::Value) -> Vec<String> {
+        let mut context = default_context();
+        context.config = StarshipConfig {
+            config: Some(config),
+        };
+        context.env.insert("USER", "abc".to_string());
+        let module = modules::handle("gradient_username", &context).unwrap();
+        module_details(&module)
+    }
+
+    #[test]
+    fn explain_verbose_gradient_username() {
+        let details = gradient_username_details(toml::toml! {
+            [gradient_username]
+            show_always = true
+            format = "[$user]($style)"
+            style_user = "bold yellow"
+            gradient = "#ff0000,#0000ff"
+            color_mode = "truecolor"
+        });
+
+        assert_eq!(
+            details,
+            vec![
+                "\"a\": bold fg:#ff0000",
+                "\"b\": bold fg:#370037",
+                "\"c\": bold fg:#0000ff",
+                "gradient: #ff0000,#0000ff, domain: [], interpolation: linear-rgb, first: #ff0000, last: #0000ff",
+            ]
+        );
+    }
+
+    #[test]
+    fn explain_verbose_gradient_username_preset() {
+        let details = gradient_username_details(toml::toml! {
+            [gradient_username]
+            show_always = true
+            format = "[$user]($style) on"
+            style_user = "italic"
+            gradient = "viridis"
+            gradient_variables = ["user"]
+            domain = [1.0, 0.0]
+            interpolation = "oklab"
+            color_mode = "ansi256"
+        });
+
+        assert_eq!(
+            details,
+            vec![
+                "\"a\": italic fg:232",
+                "\"b\": italic fg:23",
+                "\"c\": italic fg:220",
+                "\" on\": none",
+                "gradient: viridis, domain: [1.0, 0.0], interpolation: oklab, first: #0f0017, last: #fdce05",
+            ]
+        );
+    }
 }
@@ -1,10 +1,11 @@
 use crate::print::{Grapheme, UnicodeWidthGraphemes};
 use nu_ansi_term::{AnsiString, Style};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// Type that holds text with an associated style
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct TextSegment {
     /// The segment's style. If None, will inherit the style of the module containing it.
     pub style: Option<Style>,
@@ -24,34 +25,85 @@ impl TextSegment {
 }
 
 /// Type that holds fill text with an associated style
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct FillSegment {
     /// The segment's style. If None, will inherit the style of the module containing it.
     pub style: Option<Style>,
 
     /// The string value of the current segment.
     pub value: String,
+
+    /// The styles of a gradient across the fill, sampled for a fill as wide as the
+    /// terminal. If empty, the whole fill has `style`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gradient: Vec<Style>,
 }
 
 impl FillSegment {
+    // Returns the segment value repeated to fill the given width
+    //
+    // If the last repetition of a wide grapheme doesn't fit, the remaining columns
+    // are filled with spaces, so the fill always takes up the whole width.
+    pub fn expanded_value(&self, width: Option<usize>) -> String {
+        match width {
+            // A value without width would be repeated forever
+            Some(w) if self.value.width_graphemes() > 0 => {
+                let mut used = 0;
+                let mut expanded = String::new();
+                for g in self.value.graphemes(true).cycle() {
+                    let g_width = Grapheme(g).width();
+                    if used + g_width > w {
+                        break;
+                    }
+                    used += g_width;
+                    expanded.push_str(g);
+                }
+                expanded.extend(std::iter::repeat(' ').take(w - used));
+                expanded
+            }
+            _ => String::from(&self.value),
+        }
+    }
+
+    // Returns the segment value repeated to fill the given width as text segments,
+    // a single one, or one per grapheme if the fill has a gradient
+    pub fn expanded_text(&self, width: Option<usize>) -> Vec<TextSegment> {
+        let value = self.expanded_value(width);
+        if self.gradient.is_empty() || value.is_empty() {
+            return vec![TextSegment {
+                style: self.style,
+                value,
+            }];
+        }
+
+        // The gradient is stretched over the graphemes of the fill
+        let graphemes: Vec<&str> = value.graphemes(true).collect();
+        let last = graphemes.len().saturating_sub(1).max(1);
+        let last_style = self.gradient.len() - 1;
+        graphemes
+            .iter()
+            .enumerate()
+            .map(|(i, g)| TextSegment {
+                style: Some(self.gradient[(i * last_style + last / 2) / last]),
+                value: (*g).to_string(),
+            })
+            .collect()
+    }
+
+    // Returns the AnsiStrings of the segment value, not including its prefix and suffix
+    pub fn ansi_strings(&self, width: Option<usize>) -> Vec<AnsiString> {
+        self.expanded_text(width)
+            .into_iter()
+            .map(|text| match text.style {
+                Some(style) => style.paint(text.value),
+                None => AnsiString::from(text.value),
+            })
+            .collect()
+    }
+
     // Returns the AnsiString of the segment value, not including its prefix and suffix
     pub fn ansi_string(&self, width: Option<usize>) -> AnsiString {
-        let s = match width {
-            Some(w) => self
-                .value
-                .graphemes(true)
-                .cycle()
-                .scan(0usize, |len, g| {
-                    *len += Grapheme(g).width();
-                    if *len <= w {
-                        Some(g)
-                    } else {
-                        None
-                    }
-                })
-                .collect::<String>(),
-            None => String::from(&self.value),
-        };
+        let s = self.expanded_value(width);
         match self.style {
             Some(style) => style.paint(s),
             None => AnsiString::from(s),
@@ -75,25 +127,61 @@ mod fill_seg_tests {
             ("-:-", "-:--:--:--"),
             ("🟦", "🟦🟦🟦🟦🟦"),
             ("🟢🔵🟡", "🟢🔵🟡🟢🔵"),
+            ("·—", "·—·—·—·—·—"),
+            ("🟦-", "🟦-🟦-🟦- "),
         ];
 
         for (text, expected) in &inputs {
             let f = FillSegment {
                 value: String::from(*text),
                 style: Some(style),
+                gradient: Vec::new(),
             };
             let actual = f.ansi_string(Some(width));
             assert_eq!(style.paint(*expected), actual);
         }
     }
+
+    #[test]
+    fn gradient_is_stretched_over_the_fill() {
+        let styles = vec![
+            Color::Red.normal(),
+            Color::Green.normal(),
+            Color::Blue.normal(),
+        ];
+        let f = FillSegment {
+            value: String::from("-="),
+            style: None,
+            gradient: styles,
+        };
+
+        let actual: Vec<_> = f
+            .expanded_text(Some(5))
+            .into_iter()
+            .map(|text| (text.value, text.style.and_then(|style| style.foreground)))
+            .collect();
+        let expected = vec![
+            ("-".to_string(), Some(Color::Red)),
+            ("=".to_string(), Some(Color::Green)),
+            ("-".to_string(), Some(Color::Green)),
+            ("=".to_string(), Some(Color::Blue)),
+            ("-".to_string(), Some(Color::Blue)),
+        ];
+        assert_eq!(expected, actual);
+    }
 }
 
 /// A segment is a styled text chunk ready for printing.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Segment {
     Text(TextSegment),
     Fill(FillSegment),
     LineTerm,
+    /// A line break that is only kept if fewer than this many columns are left
+    /// on the line before it, see `print::resolve_line_breaks`
+    ConditionalLineTerm(usize),
+    /// A terminal escape sequence that takes up no space in the prompt
+    Escape(String),
 }
 
 impl Segment {
@@ -123,14 +211,23 @@ impl Segment {
         Self::Fill(FillSegment {
             style,
             value: value.into(),
+            gradient: Vec::new(),
         })
     }
 
+    /// Creates a new escape segment
+    pub fn escape<T>(value: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::Escape(value.into())
+    }
+
     pub fn style(&self) -> Option<Style> {
         match self {
             Self::Fill(fs) => fs.style,
             Self::Text(ts) => ts.style,
-            Self::LineTerm => None,
+            Self::LineTerm | Self::ConditionalLineTerm(_) | Self::Escape(_) => None,
         }
     }
 
@@ -146,7 +243,7 @@ impl Segment {
                     ts.style = style
                 }
             }
-            Self::LineTerm => {}
+            Self::LineTerm | Self::ConditionalLineTerm(_) | Self::Escape(_) => {}
         }
     }
 
@@ -154,7 +251,8 @@ impl Segment {
         match self {
             Self::Fill(fs) => &fs.value,
             Self::Text(ts) => &ts.value,
-            Self::LineTerm => LINE_TERMINATOR_STRING,
+            Self::LineTerm | Self::ConditionalLineTerm(_) => LINE_TERMINATOR_STRING,
+            Self::Escape(_) => "",
         }
     }
 
@@ -163,7 +261,10 @@ impl Segment {
         match self {
             Self::Fill(fs) => fs.ansi_string(None),
             Self::Text(ts) => ts.ansi_string(),
-            Self::LineTerm => AnsiString::from(LINE_TERMINATOR_STRING),
+            Self::LineTerm | Self::ConditionalLineTerm(_) => {
+                AnsiString::from(LINE_TERMINATOR_STRING)
+            }
+            Self::Escape(sequence) => AnsiString::from(sequence),
         }
     }
 
@@ -171,7 +272,7 @@ impl Segment {
         match self {
             Self::Fill(fs) => fs.value.width_graphemes(),
             Self::Text(ts) => ts.value.width_graphemes(),
-            Self::LineTerm => 0,
+            Self::LineTerm | Self::ConditionalLineTerm(_) | Self::Escape(_) => 0,
         }
     }
 }
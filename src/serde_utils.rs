@@ -6,6 +6,10 @@ use serde::de::{
 use std::{cmp::Ordering, fmt};
 use toml::Value;
 
+/// Keys in the config of every module that are read by the prompt itself, rather
+/// than by the module
+pub const MODULE_KEYS: &[&str] = &["priority"];
+
 /// A helper struct for deserializing a TOML value references with serde.
 /// This also prints a warning and suggestions if a key is unknown.
 #[derive(Debug)]
@@ -130,6 +134,14 @@ impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
         {
             return visitor.visit_none();
         }
+        if self
+            .info
+            .filter(|StructInfo { name, .. }| name != &"StarshipRootConfig")
+            .and(self.current_key)
+            .map_or(false, |key| MODULE_KEYS.contains(&key))
+        {
+            return visitor.visit_none();
+        }
 
         let did_you_mean = match (self.current_key, self.info) {
             (Some(key), Some(StructInfo { fields, .. })) => fields
@@ -331,6 +343,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_deserialize_module_keys() {
+        let value = toml::toml! {
+            foo = "bar"
+            priority = 3
+        };
+        let deserializer = ValueDeserializer::new(&value);
+
+        #[derive(Debug, Deserialize)]
+        struct Sample {
+            foo: String,
+        }
+
+        let result = Sample::deserialize(deserializer).unwrap();
+        assert_eq!(result.foo, "bar");
+
+        let value = toml::toml! {
+            priority = 3
+        };
+        let result = StarshipRootConfig::deserialize(ValueDeserializer::new(&value));
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_deserialize_unknown_root_module() {
         let value = toml::toml! {
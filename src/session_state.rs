@@ -0,0 +1,261 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::utils;
+
+/// Session state files that weren't written to for this long are removed
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The directory the state of shell sessions is kept in
+pub fn sessions_dir() -> PathBuf {
+    utils::cache_dir().join("sessions")
+}
+
+/// Small values that persist between the prompts of a shell session
+///
+/// The state of each session is a JSON object in its own file, named after
+/// `STARSHIP_SESSION_KEY`. Every change is written to a temporary file that
+/// then replaces the state, so prompts rendered at the same time (e.g. the left
+/// and the right prompt) never read a partially written file.
+pub struct SessionState {
+    path: PathBuf,
+    values: Mutex<Map<String, Value>>,
+}
+
+impl SessionState {
+    /// Opens the state of the session `key` in `dir`, removing the state of
+    /// sessions that ended long ago
+    pub fn open(dir: &Path, key: &str) -> Self {
+        let file_name: String = key
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        let path = dir.join(format!("{}.json", file_name));
+        remove_stale(dir, &path, MAX_AGE);
+
+        Self {
+            values: Mutex::new(read(&path)),
+            path,
+        }
+    }
+
+    /// The value stored at `key`, or `None` if there is none or it has another type
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let values = self.values.lock().ok()?;
+        let value = values.get(key)?.clone();
+        serde_json::from_value(value)
+            .map_err(|error| log::debug!("Invalid session state at {:?}: {}", key, error))
+            .ok()
+    }
+
+    /// Stores `value` at `key` and writes the state to disk
+    pub fn set<T: Serialize>(&self, key: &str, value: &T) {
+        let value = match serde_json::to_value(value) {
+            Ok(value) => value,
+            Err(error) => {
+                log::debug!("Unable to serialize session state {:?}: {}", key, error);
+                return;
+            }
+        };
+        let mut values = match self.values.lock() {
+            Ok(values) => values,
+            Err(_) => return,
+        };
+
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+        if let Err(error) = fs::create_dir_all(dir) {
+            log::debug!(
+                "Unable to create session state directory {:?}: {}",
+                dir,
+                error
+            );
+            return;
+        }
+
+        // Keep the values other prompts stored since the state was opened
+        let _lock = FileLock::acquire(self.path.with_extension("lock"));
+        let mut stored = read(&self.path);
+        stored.insert(key.to_string(), value);
+        *values = stored;
+
+        let temp_path = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        let result = serde_json::to_string(&*values)
+            .map_err(io::Error::from)
+            .and_then(|content| fs::write(&temp_path, content))
+            .and_then(|_| fs::rename(&temp_path, &self.path));
+        if let Err(error) = result {
+            log::debug!("Unable to write session state {:?}: {}", self.path, error);
+        }
+    }
+}
+
+/// A lock file, held while a session state is read, updated and written back
+///
+/// Prompts that can't get the lock in time write without it, since replacing
+/// the state with a temporary file already keeps it from being corrupted.
+struct FileLock(PathBuf);
+
+impl FileLock {
+    /// How long to wait for another prompt to release the lock
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    fn acquire(path: PathBuf) -> Option<Self> {
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Some(Self(path)),
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+                    if start.elapsed() >= Self::TIMEOUT {
+                        // A prompt that was killed while holding the lock leaves it behind
+                        log::debug!("Timed out waiting for the lock {:?}", path);
+                        let _ = fs::remove_file(&path);
+                        return None;
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Err(error) => {
+                    log::debug!("Unable to create the lock {:?}: {}", path, error);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn read(path: &Path) -> Map<String, Value> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| {
+            serde_json::from_str(&content)
+                .map_err(|error| log::debug!("Invalid session state {:?}: {}", path, error))
+                .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// Removes the state files in `dir` other than `current` that are older than `max_age`
+fn remove_stale(dir: &Path, current: &Path, max_age: Duration) {
+    let now = SystemTime::now();
+    let entries = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok);
+    for entry in entries {
+        let path = entry.path();
+        let is_stale = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .map_or(false, |modified| {
+                now.duration_since(modified).unwrap_or_default() >= max_age
+            });
+        if path != current && path.extension().map_or(false, |ext| ext == "json") && is_stale {
+            log::trace!("Removing stale session state {:?}", path);
+            if let Err(error) = fs::remove_file(&path) {
+                log::debug!("Unable to remove session state {:?}: {}", path, error);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::default_context;
+
+    #[test]
+    fn values_persist_between_prompts() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let state = SessionState::open(dir.path(), "session");
+        state.set("branch", &"main");
+        state.set("exit_codes", &vec![0, 1]);
+
+        let state = SessionState::open(dir.path(), "session");
+        assert_eq!(Some("main".to_string()), state.get("branch"));
+        assert_eq!(Some(vec![0, 1]), state.get::<Vec<i32>>("exit_codes"));
+        assert_eq!(None, state.get::<u64>("branch"));
+        assert_eq!(None, state.get::<String>("missing"));
+        assert_eq!(
+            None,
+            SessionState::open(dir.path(), "other").get::<String>("branch")
+        );
+        dir.close()
+    }
+
+    #[test]
+    fn concurrent_prompts_keep_the_file_valid() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+
+        let prompts: Vec<_> = ["left", "right"]
+            .iter()
+            .map(|prompt| {
+                let dir = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    let state = SessionState::open(&dir, "session");
+                    for i in 0..50 {
+                        state.set(prompt, &i);
+                    }
+                })
+            })
+            .collect();
+        for prompt in prompts {
+            prompt.join().unwrap();
+        }
+
+        let content = fs::read_to_string(dir.path().join("session.json"))?;
+        let values: Map<String, Value> = serde_json::from_str(&content)?;
+        assert_eq!(Some(&Value::from(49)), values.get("left"));
+        assert_eq!(Some(&Value::from(49)), values.get("right"));
+        dir.close()
+    }
+
+    #[test]
+    fn stale_sessions_are_removed() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        SessionState::open(dir.path(), "old").set("key", &1);
+        SessionState::open(dir.path(), "current").set("key", &2);
+        fs::write(dir.path().join("notes.txt"), "")?;
+
+        remove_stale(dir.path(), &dir.path().join("current.json"), Duration::ZERO);
+
+        let mut remaining: Vec<_> = fs::read_dir(dir.path())?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<io::Result<_>>()?;
+        remaining.sort();
+        assert_eq!(vec!["current.json", "notes.txt"], remaining);
+        dir.close()
+    }
+
+    #[test]
+    fn context_uses_the_session_key() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut context = default_context();
+        context.session_dir = Some(dir.path().to_path_buf());
+        assert!(context.session_state().is_none());
+
+        let mut context = default_context();
+        context.session_dir = Some(dir.path().to_path_buf());
+        context
+            .env
+            .insert("STARSHIP_SESSION_KEY", "1234567890".to_string());
+        context.session_state().unwrap().set("key", &true);
+
+        assert!(dir.path().join("1234567890.json").exists());
+        dir.close()
+    }
+}
@@ -1,10 +1,11 @@
 use crate::context::{Context, Shell, Target};
 use crate::logger::StarshipLogger;
 use crate::{
-    config::{ModuleConfig, StarshipConfig},
+    config::{apply_profile, interpolate_env, ModuleConfig, StarshipConfig},
     configs::StarshipRootConfig,
     utils::{create_command, CommandOutput},
 };
+use chrono::{DateTime, Local};
 use log::{Level, LevelFilter};
 use once_cell::sync::Lazy;
 use std::io;
@@ -103,6 +104,58 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    /// Sets the time the underlying context reports as the current time
+    pub fn now(mut self, now: DateTime<Local>) -> Self {
+        self.context.now = Some(now);
+        self
+    }
+
+    /// Sets whether the underlying context reports the current user as root
+    pub fn root_user(mut self, root_user: bool) -> Self {
+        self.context.root_user = root_user;
+        self
+    }
+
+    /// Sets whether the underlying context reports the process as elevated on Windows
+    pub fn elevated(mut self, elevated: bool) -> Self {
+        self.context.elevated = elevated;
+        self
+    }
+
+    /// Enables the module cache in the given directory
+    pub fn cache_dir<T>(mut self, path: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        self.context.cache_dir = Some(path.into());
+        self
+    }
+
+    /// Keeps the session state in the given directory
+    pub fn session_dir<T>(mut self, path: T) -> Self
+    where
+        T: Into<PathBuf>,
+    {
+        self.context.session_dir = Some(path.into());
+        self
+    }
+
+    /// Counts the commands executed while rendering the module
+    pub fn count_cmds(mut self, cmd_calls: &'a std::sync::atomic::AtomicUsize) -> Self {
+        self.context.cmd_calls = Some(cmd_calls);
+        self
+    }
+
+    pub fn width(mut self, width: usize) -> Self {
+        self.context.width = width;
+        self
+    }
+
+    pub fn target(mut self, target: Target) -> Self {
+        self.context.target = target;
+        self
+    }
+
     pub fn shell(mut self, shell: Shell) -> Self {
         self.context.shell = shell;
         self
@@ -113,6 +166,12 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    pub fn jobs_names(mut self, names: &[&str]) -> Self {
+        self.context.properties.jobs_names =
+            Some(names.iter().map(|name| name.to_string()).collect());
+        self
+    }
+
     pub fn cmd_duration(mut self, duration: u64) -> Self {
         self.context.properties.cmd_duration = Some(duration.to_string());
         self
@@ -140,6 +199,32 @@ impl<'a> ModuleRenderer<'a> {
         self
     }
 
+    pub fn process_tree_provider(
+        mut self,
+        process_tree_provider: &'a (dyn crate::modules::ProcessTreeProvider + Send + Sync),
+    ) -> Self {
+        self.context.process_tree_provider = process_tree_provider;
+        self
+    }
+
+    pub fn network_interface_provider(
+        mut self,
+        network_interface_provider: &'a (dyn crate::modules::NetworkInterfaceProvider
+                 + Send
+                 + Sync),
+    ) -> Self {
+        self.context.network_interface_provider = network_interface_provider;
+        self
+    }
+
+    pub fn filesystem_stats_provider(
+        mut self,
+        filesystem_stats_provider: &'a (dyn crate::modules::FilesystemStatsProvider + Send + Sync),
+    ) -> Self {
+        self.context.filesystem_stats_provider = filesystem_stats_provider;
+        self
+    }
+
     pub fn pipestatus(mut self, status: &[i64]) -> Self {
         self.context.properties.pipestatus = Some(
             status
@@ -151,7 +236,14 @@ impl<'a> ModuleRenderer<'a> {
     }
 
     /// Renders the module returning its output
-    pub fn collect(self) -> Option<String> {
+    pub fn collect(mut self) -> Option<String> {
+        // Apply the profile and environment variables the way the config loader does at startup
+        let env = self.context.env.clone();
+        if let Some(config) = self.context.config.config.as_mut() {
+            apply_profile(config, env.get("STARSHIP_PROFILE").map(String::as_str));
+            interpolate_env(config, &|name| env.get(name).cloned());
+            self.context.root_config = StarshipRootConfig::load(config);
+        }
         let ret = crate::print::get_module(self.name, self.context);
         // all tests rely on the fact that an empty module produces None as output as the
         // convention was that there would be no module but None. This is nowadays not anymore
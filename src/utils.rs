@@ -2,7 +2,7 @@ use process_control::{ChildExt, Control};
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fs::read_to_string;
-use std::io::{Error, ErrorKind, Result};
+use std::io::{Error, ErrorKind, Result, Write};
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
@@ -390,57 +390,69 @@ CMake suite maintained and supported by Kitware (kitware.com/cmake).\n",
     Some(out)
 }
 
-/// Wraps ANSI color escape sequences in the shell-appropriate wrappers.
-pub fn wrap_colorseq_for_shell(ansi: String, shell: Shell) -> String {
-    const ESCAPE_BEGIN: char = '\u{1b}';
-    const ESCAPE_END: char = 'm';
-    wrap_seq_for_shell(ansi, shell, ESCAPE_BEGIN, ESCAPE_END)
+/// Many shells cannot deal with raw unprintable characters and miscompute the cursor position,
+/// leading to strange visual bugs like duplicated/missing chars. This function wraps every
+/// escape sequence in `ansi` in shell-specific escapes to avoid these problems, with adjacent
+/// sequences sharing a single pair of them.
+pub fn wrap_escapes_for_shell(ansi: &str, shell: Shell) -> String {
+    let (begin, end) = match shell {
+        Shell::Bash => ("\\[", "\\]"),
+        Shell::Zsh | Shell::Tcsh => ("%{", "%}"),
+        _ => return ansi.to_string(),
+    };
+
+    let mut wrapped = String::with_capacity(ansi.len());
+    let mut rest = ansi;
+    while let Some(start) = rest.find('\u{1b}') {
+        wrapped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        // Escape sequences that directly follow each other are wrapped together
+        let mut length = 0;
+        while rest[length..].starts_with('\u{1b}') {
+            length += escape_sequence_len(&rest[length..]);
+        }
+        wrapped.push_str(begin);
+        wrapped.push_str(&rest[..length]);
+        wrapped.push_str(end);
+        rest = &rest[length..];
+    }
+    wrapped.push_str(rest);
+    wrapped
 }
 
-/// Many shells cannot deal with raw unprintable characters and miscompute the cursor position,
-/// leading to strange visual bugs like duplicated/missing chars. This function wraps a specified
-/// sequence in shell-specific escapes to avoid these problems.
-pub fn wrap_seq_for_shell(
-    ansi: String,
-    shell: Shell,
-    escape_begin: char,
-    escape_end: char,
-) -> String {
-    const BASH_BEG: &str = "\u{5c}\u{5b}"; // \[
-    const BASH_END: &str = "\u{5c}\u{5d}"; // \]
-    const ZSH_BEG: &str = "\u{25}\u{7b}"; // %{
-    const ZSH_END: &str = "\u{25}\u{7d}"; // %}
-    const TCSH_BEG: &str = "\u{25}\u{7b}"; // %{
-    const TCSH_END: &str = "\u{25}\u{7d}"; // %}
-
-    // ANSI escape codes cannot be nested, so we can keep track of whether we're
-    // in an escape or not with a single boolean variable
-    let mut escaped = false;
-    let final_string: String = ansi
-        .chars()
-        .map(|x| {
-            if x == escape_begin && !escaped {
-                escaped = true;
-                match shell {
-                    Shell::Bash => format!("{}{}", BASH_BEG, escape_begin),
-                    Shell::Zsh => format!("{}{}", ZSH_BEG, escape_begin),
-                    Shell::Tcsh => format!("{}{}", TCSH_BEG, escape_begin),
-                    _ => x.to_string(),
-                }
-            } else if x == escape_end && escaped {
-                escaped = false;
-                match shell {
-                    Shell::Bash => format!("{}{}", escape_end, BASH_END),
-                    Shell::Zsh => format!("{}{}", escape_end, ZSH_END),
-                    Shell::Tcsh => format!("{}{}", escape_end, TCSH_END),
-                    _ => x.to_string(),
-                }
-            } else {
-                x.to_string()
-            }
-        })
-        .collect();
-    final_string
+/// The length in bytes of the escape sequence at the start of `text`
+///
+/// Control sequences (`ESC [`) end with a byte in `@` to `~`, while operating system
+/// commands (`ESC ]`) and device control strings (`ESC P`) end with a bell or with
+/// `ESC \`. Any other escape is followed by a single character.
+fn escape_sequence_len(text: &str) -> usize {
+    let body = &text[1..];
+    let mut chars = body.char_indices();
+    let len = match chars.next() {
+        Some((_, '[')) => chars
+            .find(|(_, c)| ('@'..='~').contains(c))
+            .map_or(body.len(), |(index, c)| index + c.len_utf8()),
+        Some((_, ']' | 'P')) => match (body.find('\u{7}'), body.find("\u{1b}\\")) {
+            (Some(bell), Some(terminator)) if terminator < bell => terminator + 2,
+            (Some(bell), _) => bell + 1,
+            (None, Some(terminator)) => terminator + 2,
+            (None, None) => body.len(),
+        },
+        Some((_, c)) => c.len_utf8(),
+        None => 0,
+    };
+    1 + len
+}
+
+/// The path of a `file://` URL that points to `path`, with each of its components
+/// percent-encoded
+pub fn file_url_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .split('/')
+        .map(urlencoding::encode)
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 fn internal_exec_cmd<T: AsRef<OsStr> + Debug, U: AsRef<OsStr> + Debug>(
@@ -454,14 +466,36 @@ fn internal_exec_cmd<T: AsRef<OsStr> + Debug, U: AsRef<OsStr> + Debug>(
 }
 
 pub fn exec_timeout(cmd: &mut Command, time_limit: Duration) -> Option<CommandOutput> {
+    exec_timeout_with_input(cmd, None, time_limit)
+}
+
+/// Like `exec_timeout`, but writes `input` to the stdin of the command
+pub fn exec_timeout_with_input(
+    cmd: &mut Command,
+    input: Option<&str>,
+    time_limit: Duration,
+) -> Option<CommandOutput> {
     let start = Instant::now();
-    let process = match cmd.spawn() {
+    if input.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+    let mut process = match cmd.spawn() {
         Ok(process) => process,
         Err(error) => {
             log::info!("Unable to run {:?}, {:?}", cmd.get_program(), error);
             return None;
         }
     };
+    if let (Some(input), Some(mut stdin)) = (input, process.stdin.take()) {
+        // Dropping stdin closes it, so the command knows the input ended
+        if let Err(error) = stdin.write_all(input.as_bytes()) {
+            log::info!(
+                "Unable to write to the stdin of {:?}, {:?}",
+                cmd.get_program(),
+                error
+            );
+        }
+    }
     match process
         .controlled_with_output()
         .time_limit(time_limit)
@@ -519,43 +553,105 @@ pub fn exec_timeout(cmd: &mut Command, time_limit: Duration) -> Option<CommandOu
 
 // Render the time into a nice human-readable string
 pub fn render_time(raw_millis: u128, show_millis: bool) -> String {
-    // Make sure it renders something if the time equals zero instead of an empty string
-    if raw_millis == 0 {
-        return "0ms".into();
-    }
+    render_duration(raw_millis, DurationStyle::Compact, show_millis, 0)
+}
+
+/// How `render_duration` lays out a duration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DurationStyle {
+    /// `2m13s`, with milliseconds only below a second unless they are requested
+    Compact,
+    /// `2m 13s 412ms`
+    Precise,
+    /// `00:02:13.412`
+    Digital,
+}
 
+/// Render a duration in the given style, with at most `max_units` units
+///
+/// The smallest units are left out beyond `max_units`, and `0` shows all of
+/// them. Digital durations always show every unit.
+pub fn render_duration(
+    raw_millis: u128,
+    style: DurationStyle,
+    show_millis: bool,
+    max_units: usize,
+) -> String {
     // Calculate a simple breakdown into days/hours/minutes/seconds/milliseconds
     let (millis, raw_seconds) = (raw_millis % 1000, raw_millis / 1000);
     let (seconds, raw_minutes) = (raw_seconds % 60, raw_seconds / 60);
     let (minutes, raw_hours) = (raw_minutes % 60, raw_minutes / 60);
     let (hours, days) = (raw_hours % 24, raw_hours / 24);
 
-    let components = [days, hours, minutes, seconds];
-    let suffixes = ["d", "h", "m", "s"];
-
-    let mut rendered_components: Vec<String> = components
-        .iter()
-        .zip(&suffixes)
-        .map(render_time_component)
-        .collect();
-    if show_millis || raw_millis < 1000 {
-        rendered_components.push(render_time_component((&millis, &"ms")));
+    if style == DurationStyle::Digital {
+        return format!(
+            "{:02}:{:02}:{:02}.{:03}",
+            raw_hours, minutes, seconds, millis
+        );
     }
-    rendered_components.join("")
-}
-
-/// Render a single component of the time string, giving an empty string if component is zero
-fn render_time_component((component, suffix): (&u128, &&str)) -> String {
-    match component {
-        0 => String::new(),
-        n => format!("{}{}", n, suffix),
+    // Make sure it renders something if the time equals zero instead of an empty string
+    if raw_millis == 0 {
+        return "0ms".into();
     }
+
+    let components = [
+        (days, "d"),
+        (hours, "h"),
+        (minutes, "m"),
+        (seconds, "s"),
+        (millis, "ms"),
+    ];
+    let (components, separator): (Vec<_>, _) = match style {
+        // Only the units that aren't zero
+        DurationStyle::Compact => (
+            components
+                .iter()
+                .filter(|(component, suffix)| {
+                    *component != 0 && (*suffix != "ms" || show_millis || raw_millis < 1000)
+                })
+                .collect(),
+            "",
+        ),
+        // Every unit from the largest to the smallest one that isn't zero
+        _ => {
+            let first = components.iter().position(|(component, _)| *component != 0);
+            let last = components
+                .iter()
+                .rposition(|(component, _)| *component != 0);
+            match (first, last) {
+                (Some(first), Some(last)) => (components[first..=last].iter().collect(), " "),
+                _ => (Vec::new(), " "),
+            }
+        }
+    };
+
+    let units = match max_units {
+        0 => components.len(),
+        max_units => max_units.min(components.len()),
+    };
+    components[..units]
+        .iter()
+        .map(|(component, suffix)| format!("{}{}", component, suffix))
+        .collect::<Vec<_>>()
+        .join(separator)
 }
 
 pub fn home_dir() -> Option<PathBuf> {
     dirs_next::home_dir()
 }
 
+/// The directory starship keeps its logs and caches in, `STARSHIP_CACHE` or
+/// `~/.cache/starship`
+pub fn cache_dir() -> PathBuf {
+    std::env::var_os("STARSHIP_CACHE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            home_dir()
+                .expect("Unable to find home directory")
+                .join(".cache/starship")
+        })
+}
+
 const HEXTABLE: &[char] = &[
     '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f',
 ];
@@ -600,6 +696,50 @@ mod tests {
         assert_eq!(render_time(86_400_000_u128, true), "1d")
     }
 
+    #[test]
+    fn test_render_duration_precise() {
+        let precise = |millis| render_duration(millis, DurationStyle::Precise, false, 0);
+        assert_eq!(precise(0), "0ms");
+        assert_eq!(precise(999), "999ms");
+        assert_eq!(precise(1_000), "1s");
+        assert_eq!(precise(59_999), "59s 999ms");
+        assert_eq!(precise(60_000), "1m");
+        assert_eq!(precise(133_412), "2m 13s 412ms");
+        assert_eq!(precise(3_605_000), "1h 0m 5s");
+        assert_eq!(precise(90_000_001), "1d 1h 0m 0s 1ms");
+    }
+
+    #[test]
+    fn test_render_duration_digital() {
+        let digital = |millis| render_duration(millis, DurationStyle::Digital, false, 2);
+        assert_eq!(digital(0), "00:00:00.000");
+        assert_eq!(digital(999), "00:00:00.999");
+        assert_eq!(digital(60_000), "00:01:00.000");
+        assert_eq!(digital(133_412), "00:02:13.412");
+        assert_eq!(digital(3_605_000), "01:00:05.000");
+        assert_eq!(digital(90_000_001), "25:00:00.001");
+    }
+
+    #[test]
+    fn test_render_duration_max_units() {
+        assert_eq!(
+            render_duration(3_605_000, DurationStyle::Compact, true, 1),
+            "1h"
+        );
+        assert_eq!(
+            render_duration(3_605_412, DurationStyle::Compact, true, 2),
+            "1h5s"
+        );
+        assert_eq!(
+            render_duration(3_605_412, DurationStyle::Precise, false, 2),
+            "1h 0m"
+        );
+        assert_eq!(
+            render_duration(999, DurationStyle::Precise, false, 2),
+            "999ms"
+        );
+    }
+
     #[test]
     fn exec_mocked_command() {
         let result = exec_cmd(
@@ -695,40 +835,73 @@ mod tests {
 
     #[test]
     fn test_color_sequence_wrappers() {
-        let test0 = "\x1b2mhellomynamekeyes\x1b2m"; // BEGIN: \x1b     END: m
-        let test1 = "\x1b]330;mlol\x1b]0m"; // BEGIN: \x1b     END: m
-        let test2 = "\u{1b}J"; // BEGIN: \x1b     END: J
-        let test3 = "OH NO"; // BEGIN: O    END: O
+        let test0 = "\x1b[2mhellomynamekeyes\x1b[0m";
+        let test1 = "\x1b]8;;https://starship.rs\x1b\\lol\x1b]8;;\x1b\\";
+        let test2 = "\u{1b}J";
+        let test3 = "\x1b]9;notification\x07done";
         let test4 = "herpaderp";
         let test5 = "";
 
-        let zresult0 = wrap_seq_for_shell(test0.to_string(), Shell::Zsh, '\x1b', 'm');
-        let zresult1 = wrap_seq_for_shell(test1.to_string(), Shell::Zsh, '\x1b', 'm');
-        let zresult2 = wrap_seq_for_shell(test2.to_string(), Shell::Zsh, '\x1b', 'J');
-        let zresult3 = wrap_seq_for_shell(test3.to_string(), Shell::Zsh, 'O', 'O');
-        let zresult4 = wrap_seq_for_shell(test4.to_string(), Shell::Zsh, '\x1b', 'm');
-        let zresult5 = wrap_seq_for_shell(test5.to_string(), Shell::Zsh, '\x1b', 'm');
+        let zresult0 = wrap_escapes_for_shell(test0, Shell::Zsh);
+        let zresult1 = wrap_escapes_for_shell(test1, Shell::Zsh);
+        let zresult2 = wrap_escapes_for_shell(test2, Shell::Zsh);
+        let zresult3 = wrap_escapes_for_shell(test3, Shell::Zsh);
+        let zresult4 = wrap_escapes_for_shell(test4, Shell::Zsh);
+        let zresult5 = wrap_escapes_for_shell(test5, Shell::Zsh);
 
-        assert_eq!(&zresult0, "%{\x1b2m%}hellomynamekeyes%{\x1b2m%}");
-        assert_eq!(&zresult1, "%{\x1b]330;m%}lol%{\x1b]0m%}");
+        assert_eq!(&zresult0, "%{\x1b[2m%}hellomynamekeyes%{\x1b[0m%}");
+        assert_eq!(
+            &zresult1,
+            "%{\x1b]8;;https://starship.rs\x1b\\%}lol%{\x1b]8;;\x1b\\%}"
+        );
         assert_eq!(&zresult2, "%{\x1bJ%}");
-        assert_eq!(&zresult3, "%{OH NO%}");
+        assert_eq!(&zresult3, "%{\x1b]9;notification\x07%}done");
         assert_eq!(&zresult4, "herpaderp");
         assert_eq!(&zresult5, "");
 
-        let bresult0 = wrap_seq_for_shell(test0.to_string(), Shell::Bash, '\x1b', 'm');
-        let bresult1 = wrap_seq_for_shell(test1.to_string(), Shell::Bash, '\x1b', 'm');
-        let bresult2 = wrap_seq_for_shell(test2.to_string(), Shell::Bash, '\x1b', 'J');
-        let bresult3 = wrap_seq_for_shell(test3.to_string(), Shell::Bash, 'O', 'O');
-        let bresult4 = wrap_seq_for_shell(test4.to_string(), Shell::Bash, '\x1b', 'm');
-        let bresult5 = wrap_seq_for_shell(test5.to_string(), Shell::Bash, '\x1b', 'm');
+        let bresult0 = wrap_escapes_for_shell(test0, Shell::Bash);
+        let bresult1 = wrap_escapes_for_shell(test1, Shell::Bash);
+        let bresult2 = wrap_escapes_for_shell(test2, Shell::Bash);
+        let bresult3 = wrap_escapes_for_shell(test3, Shell::Bash);
+        let bresult4 = wrap_escapes_for_shell(test4, Shell::Bash);
+        let bresult5 = wrap_escapes_for_shell(test5, Shell::Bash);
 
-        assert_eq!(&bresult0, "\\[\x1b2m\\]hellomynamekeyes\\[\x1b2m\\]");
-        assert_eq!(&bresult1, "\\[\x1b]330;m\\]lol\\[\x1b]0m\\]");
+        assert_eq!(&bresult0, "\\[\x1b[2m\\]hellomynamekeyes\\[\x1b[0m\\]");
+        assert_eq!(
+            &bresult1,
+            "\\[\x1b]8;;https://starship.rs\x1b\\\\]lol\\[\x1b]8;;\x1b\\\\]"
+        );
         assert_eq!(&bresult2, "\\[\x1bJ\\]");
-        assert_eq!(&bresult3, "\\[OH NO\\]");
+        assert_eq!(&bresult3, "\\[\x1b]9;notification\x07\\]done");
         assert_eq!(&bresult4, "herpaderp");
         assert_eq!(&bresult5, "");
+
+        let tresult0 = wrap_escapes_for_shell(test0, Shell::Tcsh);
+        assert_eq!(&tresult0, "%{\x1b[2m%}hellomynamekeyes%{\x1b[0m%}");
+        let fresult0 = wrap_escapes_for_shell(test0, Shell::Fish);
+        assert_eq!(&fresult0, test0);
+    }
+
+    #[test]
+    fn test_adjacent_sequences_are_wrapped_together() {
+        let ansi = "\x1b[0m\x1b[1;38;2;255;0;0ma\x1b[38;5;12m\x1b]8;;x\x07b\x1b[K";
+        assert_eq!(
+            wrap_escapes_for_shell(ansi, Shell::Zsh),
+            "%{\x1b[0m\x1b[1;38;2;255;0;0m%}a%{\x1b[38;5;12m\x1b]8;;x\x07%}b%{\x1b[K%}"
+        );
+    }
+
+    #[test]
+    fn test_unterminated_sequences_are_wrapped() {
+        assert_eq!(
+            wrap_escapes_for_shell("a\x1b[38;2", Shell::Zsh),
+            "a%{\x1b[38;2%}"
+        );
+        assert_eq!(
+            wrap_escapes_for_shell("a\x1b]8;;x", Shell::Bash),
+            "a\\[\x1b]8;;x\\]"
+        );
+        assert_eq!(wrap_escapes_for_shell("a\x1b", Shell::Zsh), "a%{\x1b%}");
     }
 
     #[test]
@@ -0,0 +1,58 @@
+use std::io::Write;
+use std::process::Command;
+
+fn preview(config: &str, args: &[&str], env: &[(&str, &str)]) -> std::process::Output {
+    let mut config_file = tempfile::NamedTempFile::new().unwrap();
+    config_file.write_all(config.as_bytes()).unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    // Cargo provides the absolute path of the binary, so nothing is looked up
+    // in the current directory
+    #[allow(clippy::disallowed_methods)]
+    Command::new(env!("CARGO_BIN_EXE_starship"))
+        .args(["gradient", "preview"])
+        .args(args)
+        .env("STARSHIP_CONFIG", config_file.path())
+        .env("STARSHIP_CACHE", cache_dir.path())
+        .env_remove("NO_COLOR")
+        .envs(env.iter().copied())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn prints_text_and_swatch_in_truecolor() {
+    let output = preview(
+        "[gradient_username]\ngradient = \"#ff0000,#0000ff\"\n",
+        &["--text", "abc", "--blocks", "5"],
+        &[("COLORTERM", "truecolor")],
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success());
+    assert!(
+        stdout.starts_with("\x1b[38;2;255;0;0ma\x1b[38;2;55;0;55mb\x1b[38;2;0;0;255mc"),
+        "{stdout:?}"
+    );
+    let swatch = stdout.lines().nth(1).unwrap();
+    assert_eq!(5, swatch.matches('█').count());
+    assert!(swatch.starts_with("\x1b[38;2;255;0;0m█"), "{swatch:?}");
+}
+
+#[test]
+fn honors_no_color() {
+    let output = preview(
+        "[gradient]\ngradient = \"#ff0000,#0000ff\"\n",
+        &["--module", "gradient", "--text", "abc", "--blocks", "3"],
+        &[("NO_COLOR", "1")],
+    );
+
+    assert_eq!("abc\n███\n", String::from_utf8(output.stdout).unwrap());
+}
+
+#[test]
+fn unknown_module_fails() {
+    let output = preview("", &["--module", "username"], &[]);
+
+    assert!(!output.status.success());
+}
@@ -0,0 +1,39 @@
+use std::process::Command;
+
+/// The output of `starship init <shell>`, with the path of the binary replaced
+/// by `starship`
+fn init(shell: &str) -> String {
+    let config_file = tempfile::NamedTempFile::new().unwrap();
+
+    // Cargo provides the absolute path of the binary, so nothing is looked up
+    // in the current directory
+    #[allow(clippy::disallowed_methods)]
+    let output = Command::new(env!("CARGO_BIN_EXE_starship"))
+        .args(&["init", shell])
+        .env("STARSHIP_CONFIG", config_file.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .replace(env!("CARGO_BIN_EXE_starship"), "starship")
+}
+
+#[test]
+fn nu_init_script() {
+    assert_eq!(include_str!("golden/init.nu"), init("nu"));
+}
+
+#[test]
+fn unknown_shells_are_rejected() {
+    #[allow(clippy::disallowed_methods)]
+    let output = Command::new(env!("CARGO_BIN_EXE_starship"))
+        .args(&["init", "murex"])
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("murex is not yet supported by starship."));
+    assert!(stderr.contains("* nu\n* xonsh\n* cmd\n"));
+}
@@ -0,0 +1,69 @@
+use std::io::Write;
+use std::process::Command;
+
+fn timings(args: &[&str]) -> serde_json::Value {
+    let mut config_file = tempfile::NamedTempFile::new().unwrap();
+    config_file.write_all(CONFIG.as_bytes()).unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    // Cargo provides the absolute path of the binary, so nothing is looked up
+    // in the current directory
+    #[allow(clippy::disallowed_methods)]
+    let output = Command::new(env!("CARGO_BIN_EXE_starship"))
+        .args(&["timings", "--format", "json"])
+        .args(args)
+        .env("STARSHIP_CONFIG", config_file.path())
+        .env("STARSHIP_CACHE", cache_dir.path())
+        .env("STARSHIP_SHELL", "")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+/// The names of the modules, in the order they are printed
+fn names(timings: &serde_json::Value) -> Vec<&str> {
+    timings
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|timing| timing["module"].as_str().unwrap())
+        .collect()
+}
+
+const CONFIG: &str = r#"
+format = "$custom"
+
+[custom.fast]
+command = "echo fast"
+when = true
+shell = ["sh"]
+
+[custom.slow]
+command = "sleep 0.2; echo slow module"
+when = true
+shell = ["sh"]
+"#;
+
+#[test]
+fn json_has_a_timing_per_module() {
+    let timings = timings(&[]);
+
+    assert_eq!(vec!["slow", "fast"], names(&timings));
+    // The default format of custom modules adds a space after the output
+    let slow = &timings[0];
+    assert_eq!(Some(12), slow["rendered_len"].as_u64());
+    assert!(slow["duration_us"].as_u64().unwrap() >= 200_000);
+    assert_eq!(Some(5), timings[1]["rendered_len"].as_u64());
+}
+
+#[test]
+fn sort_by_name() {
+    assert_eq!(vec!["fast", "slow"], names(&timings(&["--sort", "name"])));
+}
+
+#[test]
+fn threshold_hides_fast_modules() {
+    assert_eq!(vec!["slow"], names(&timings(&["--threshold", "100"])));
+}
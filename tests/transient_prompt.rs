@@ -0,0 +1,55 @@
+use std::io::Write;
+use std::process::Command;
+
+fn prompt(config: &str, args: &[&str]) -> String {
+    let mut config_file = tempfile::NamedTempFile::new().unwrap();
+    config_file.write_all(config.as_bytes()).unwrap();
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    // Cargo provides the absolute path of the binary, so nothing is looked up
+    // in the current directory
+    #[allow(clippy::disallowed_methods)]
+    let output = Command::new(env!("CARGO_BIN_EXE_starship"))
+        .arg("prompt")
+        .args(args)
+        .env("STARSHIP_CONFIG", config_file.path())
+        .env("STARSHIP_CACHE", cache_dir.path())
+        .env("STARSHIP_SHELL", "")
+        .env_remove("TERM")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+const CONFIG: &str = r#"
+add_newline = false
+format = "full left"
+right_format = "full right"
+transient_format = "transient left"
+transient_right_format = "transient right"
+"#;
+
+#[test]
+fn transient_prompt_uses_transient_format() {
+    assert_eq!("full left", prompt(CONFIG, &[]));
+    assert_eq!("transient left", prompt(CONFIG, &["--transient"]));
+}
+
+#[test]
+fn transient_right_prompt_uses_transient_right_format() {
+    assert_eq!("full right", prompt(CONFIG, &["--right"]));
+    assert_eq!(
+        "transient right",
+        prompt(CONFIG, &["--right", "--transient"])
+    );
+}
+
+#[test]
+fn transient_prompt_defaults_to_character() {
+    let config =
+        "add_newline = false\n[character]\nsuccess_symbol = \"❯\"\nformat = \"$symbol \"\n";
+
+    assert_eq!("❯ ", prompt(config, &["--transient"]));
+}